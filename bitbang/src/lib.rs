@@ -0,0 +1,213 @@
+//! Generic bit-banged SPI interface for display drivers
+//!
+//! The clock and data lines are driven in software through `OutputPin`s, which makes it possible
+//! to attach a display when no hardware SPI peripheral is available. Data is shifted out MSB
+//! first in SPI mode 0 (or mode 2 with an idle-high clock), and the `DataFormat` handling is
+//! shared with `display-interface-spi` so the resulting byte stream is identical.
+
+#![no_std]
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_spi::SpiInterface;
+pub use display_interface_spi::{NoCs, NoDelay};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::OutputPin,
+    spi::{self, ErrorKind, Operation, Polarity, SpiDevice},
+};
+
+/// Error returned by the bit-banged SPI device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BitBangError {
+    /// Unable to drive one of the clock, data or chip select lines
+    Pin,
+    /// Reading is not possible since there is no MISO line
+    ReadNotSupported,
+}
+
+impl spi::Error for BitBangError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Software SPI device shifting bytes out MSB first over GPIO pins
+///
+/// Chip select is asserted (pulled low) for the duration of each transaction.
+pub struct BitBangSpiDevice<SCK, MOSI, CS, D> {
+    sck: SCK,
+    mosi: MOSI,
+    cs: CS,
+    delay: D,
+    polarity: Polarity,
+    half_period_ns: u32,
+}
+
+impl<SCK, MOSI, CS, D> BitBangSpiDevice<SCK, MOSI, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Create a new bit-banged SPI device. This does not change the state of the pins.
+    ///
+    /// The clock idles low and no delay is inserted between clock edges by default.
+    pub fn new(sck: SCK, mosi: MOSI, cs: CS, delay: D) -> Self {
+        Self {
+            sck,
+            mosi,
+            cs,
+            delay,
+            polarity: Polarity::IdleLow,
+            half_period_ns: 0,
+        }
+    }
+
+    /// Consume the device and return the pins and delay used by it
+    pub fn release(self) -> (SCK, MOSI, CS, D) {
+        (self.sck, self.mosi, self.cs, self.delay)
+    }
+
+    fn set_sck(&mut self, active: bool) -> Result<(), BitBangError> {
+        let high = active ^ (self.polarity == Polarity::IdleHigh);
+        if high {
+            self.sck.set_high()
+        } else {
+            self.sck.set_low()
+        }
+        .map_err(|_| BitBangError::Pin)
+    }
+
+    fn wait(&mut self) {
+        if self.half_period_ns > 0 {
+            self.delay.delay_ns(self.half_period_ns);
+        }
+    }
+
+    fn write_byte(&mut self, byte: u8) -> Result<(), BitBangError> {
+        for bit in (0..8).rev() {
+            if byte & (1 << bit) != 0 {
+                self.mosi.set_high()
+            } else {
+                self.mosi.set_low()
+            }
+            .map_err(|_| BitBangError::Pin)?;
+
+            self.wait();
+            // Data is sampled by the display on the leading clock edge
+            self.set_sck(true)?;
+            self.wait();
+            self.set_sck(false)?;
+        }
+
+        Ok(())
+    }
+
+    fn run(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), BitBangError> {
+        for op in operations {
+            match op {
+                Operation::Write(words) => {
+                    for &byte in words.iter() {
+                        self.write_byte(byte)?;
+                    }
+                }
+                Operation::DelayNs(ns) => self.delay.delay_ns(*ns),
+                _ => return Err(BitBangError::ReadNotSupported),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<SCK, MOSI, CS, D> spi::ErrorType for BitBangSpiDevice<SCK, MOSI, CS, D> {
+    type Error = BitBangError;
+}
+
+impl<SCK, MOSI, CS, D> SpiDevice for BitBangSpiDevice<SCK, MOSI, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        self.set_sck(false)?;
+        self.cs.set_low().map_err(|_| BitBangError::Pin)?;
+
+        let result = self.run(operations);
+
+        // Always try to release the chip select, but report the first error
+        let deassert = self.cs.set_high().map_err(|_| BitBangError::Pin);
+        result.and(deassert)
+    }
+}
+
+/// Bit-banged SPI display interface.
+///
+/// This combines the clock, data and optional chip select pins with a data/command pin. Use
+/// [NoCs] if chip select is hard-wired and [NoDelay] if the target is slow enough to not
+/// exceed the display's maximum clock rate.
+pub struct BitBangSpiInterface<SCK, MOSI, DC, CS, D> {
+    iface: SpiInterface<BitBangSpiDevice<SCK, MOSI, CS, D>, DC>,
+}
+
+impl<SCK, MOSI, DC, CS, D> BitBangSpiInterface<SCK, MOSI, DC, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Create new bit-banged SPI interface for communication with a display driver
+    pub fn new(sck: SCK, mosi: MOSI, dc: DC, cs: CS, delay: D) -> Self {
+        Self {
+            iface: SpiInterface::new(BitBangSpiDevice::new(sck, mosi, cs, delay), dc),
+        }
+    }
+
+    /// Set the clock polarity, i.e. the level of the clock line while idle
+    pub fn with_polarity(self, polarity: Polarity) -> Self {
+        let (mut spi, dc) = self.iface.release();
+        spi.polarity = polarity;
+        Self {
+            iface: SpiInterface::new(spi, dc),
+        }
+    }
+
+    /// Set the time to wait between two clock edges, in nanoseconds
+    pub fn with_half_period_ns(self, half_period_ns: u32) -> Self {
+        let (mut spi, dc) = self.iface.release();
+        spi.half_period_ns = half_period_ns;
+        Self {
+            iface: SpiInterface::new(spi, dc),
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the GPIO pins and delay used by it
+    pub fn release(self) -> (SCK, MOSI, DC, CS, D) {
+        let (spi, dc) = self.iface.release();
+        let (sck, mosi, cs, delay) = spi.release();
+        (sck, mosi, dc, cs, delay)
+    }
+}
+
+impl<SCK, MOSI, DC, CS, D> WriteOnlyDataCommand for BitBangSpiInterface<SCK, MOSI, DC, CS, D>
+where
+    SCK: OutputPin,
+    MOSI: OutputPin,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.iface.send_commands(cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.iface.send_data(buf)
+    }
+}
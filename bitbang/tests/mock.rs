@@ -0,0 +1,161 @@
+//! Pin levels driven for the slice formats, checked with `embedded-hal-mock`
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_bitbang::{BitBangSpiInterface, NoCs, NoDelay};
+use display_interface_spi::SpiInterface;
+use embedded_hal::spi::Polarity;
+use embedded_hal_mock::eh1::{
+    delay::{CheckedDelay, Transaction as DelayTransaction},
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+const WORDS: [u16; 3] = [0x1234, 0xa5c3, 0x00ff];
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    U8,
+    U16BE,
+    U16LE,
+}
+
+const KINDS: [Kind; 3] = [Kind::U8, Kind::U16BE, Kind::U16LE];
+
+/// The bytes expected on the wire for [WORDS] sent as `kind`
+fn bytes(kind: Kind) -> Vec<u8> {
+    match kind {
+        Kind::U8 => WORDS.iter().map(|&w| w as u8).collect(),
+        Kind::U16BE => WORDS.iter().flat_map(|w| w.to_be_bytes()).collect(),
+        Kind::U16LE => WORDS.iter().flat_map(|w| w.to_le_bytes()).collect(),
+    }
+}
+
+/// Call `f` with [WORDS] as `kind`
+fn with_format<R>(kind: Kind, f: impl FnOnce(DataFormat<'_>) -> R) -> R {
+    let mut words = WORDS;
+    match kind {
+        Kind::U8 => f(DataFormat::U8(&bytes(Kind::U8))),
+        Kind::U16BE => f(DataFormat::U16BE(&mut words)),
+        Kind::U16LE => f(DataFormat::U16LE(&mut words)),
+    }
+}
+
+fn send(iface: &mut impl WriteOnlyDataCommand, data: bool, kind: Kind) {
+    with_format(kind, |format| {
+        if data {
+            iface.send_data(format)
+        } else {
+            iface.send_commands(format)
+        }
+    })
+    .unwrap();
+}
+
+fn level(high: bool) -> PinTransaction {
+    PinTransaction::set(if high { State::High } else { State::Low })
+}
+
+/// Data line levels shifting out `bytes` MSB first
+fn mosi_levels(bytes: &[u8]) -> Vec<PinTransaction> {
+    bytes
+        .iter()
+        .flat_map(|&byte| (0..8).rev().map(move |bit| level(byte & (1 << bit) != 0)))
+        .collect()
+}
+
+/// Clock levels of one transaction of `len` bytes: the idle level, then a pulse per bit
+fn sck_levels(len: usize, polarity: Polarity) -> Vec<PinTransaction> {
+    let idle = polarity == Polarity::IdleHigh;
+    let mut levels = vec![level(idle)];
+    for _ in 0..len * 8 {
+        levels.extend([level(!idle), level(idle)]);
+    }
+    levels
+}
+
+/// The hardware SPI interface writes the same bytes for `kind` as expected of the bit-banged one
+fn check_spi_interface(data: bool, kind: Kind) {
+    let spi = [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(bytes(kind)),
+        SpiTransaction::transaction_end(),
+    ];
+    let mut iface = SpiInterface::new(SpiMock::new(&spi), PinMock::new(&[level(data)]));
+    send(&mut iface, data, kind);
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+fn check(data: bool, kind: Kind, polarity: Polarity) {
+    let bytes = bytes(kind);
+
+    // Chip select framing the transaction
+    let mut iface = BitBangSpiInterface::new(
+        PinMock::new(&sck_levels(bytes.len(), polarity)),
+        PinMock::new(&mosi_levels(&bytes)),
+        PinMock::new(&[level(data)]),
+        PinMock::new(&[level(false), level(true)]),
+        NoDelay,
+    )
+    .with_polarity(polarity);
+    send(&mut iface, data, kind);
+    let (mut sck, mut mosi, mut dc, mut cs, _) = iface.release();
+    sck.done();
+    mosi.done();
+    dc.done();
+    cs.done();
+
+    // Hard-wired chip select
+    let mut iface = BitBangSpiInterface::new(
+        PinMock::new(&sck_levels(bytes.len(), polarity)),
+        PinMock::new(&mosi_levels(&bytes)),
+        PinMock::new(&[level(data)]),
+        NoCs,
+        NoDelay,
+    )
+    .with_polarity(polarity);
+    send(&mut iface, data, kind);
+    let (mut sck, mut mosi, mut dc, _, _) = iface.release();
+    sck.done();
+    mosi.done();
+    dc.done();
+}
+
+#[test]
+fn idle_low_clock() {
+    for kind in KINDS {
+        for data in [false, true] {
+            check_spi_interface(data, kind);
+            check(data, kind, Polarity::IdleLow);
+        }
+    }
+}
+
+#[test]
+fn idle_high_clock() {
+    for kind in KINDS {
+        for data in [false, true] {
+            check(data, kind, Polarity::IdleHigh);
+        }
+    }
+}
+
+#[test]
+fn half_period_waits_around_every_edge() {
+    let delays: Vec<_> = (0..16).map(|_| DelayTransaction::delay_ns(50)).collect();
+    let mut iface = BitBangSpiInterface::new(
+        PinMock::new(&sck_levels(1, Polarity::IdleLow)),
+        PinMock::new(&mosi_levels(&[0x81])),
+        PinMock::new(&[level(true)]),
+        NoCs,
+        CheckedDelay::new(&delays),
+    )
+    .with_half_period_ns(50);
+    iface.send_data(DataFormat::U8(&[0x81])).unwrap();
+    let (mut sck, mut mosi, mut dc, _, mut delay) = iface.release();
+    sck.done();
+    mosi.done();
+    dc.done();
+    delay.done();
+}
@@ -0,0 +1,124 @@
+//! Memory-mapped 8080 interface for display drivers
+//!
+//! External memory controllers like the FSMC/FMC of STM32 microcontrollers can drive the 8080
+//! bus of a display controller by themselves: the data lines are wired to the controller's data
+//! bus and the D/C line to one of its address lines, so the display shows up as two memory
+//! addresses, one for commands and one for data. Writing a word to either address produces the
+//! whole bus cycle in hardware, including the write strobe and chip select.
+//!
+//! With D/C on address line A16 of a 16-bit wide bank 1, for example, the command address is
+//! `0x6000_0000` and the data address `0x6002_0000`, the address lines of a 16-bit bank being
+//! shifted by one:
+//!
+//! ```no_run
+//! use display_interface_fsmc::MmioInterface;
+//!
+//! let iface = unsafe { MmioInterface::new(0x6000_0000 as *mut u16, 0x6002_0000 as *mut u16) };
+//! ```
+
+#![no_std]
+
+use core::ptr::write_volatile;
+
+pub use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+type Result = core::result::Result<(), DisplayError>;
+
+/// Memory-mapped display interface
+///
+/// Words are written with volatile stores to the command or the data address, `W` being [u8] for
+/// 8-bit and [u16] for 16-bit wide memory banks. On an 8-bit bank 16-bit formats are split into
+/// two bytes in the requested byte order, while bytes are zero-extended on a 16-bit bank.
+pub struct MmioInterface<W> {
+    command: *mut W,
+    data: *mut W,
+}
+
+// SAFETY: The addresses belong to the memory controller rather than to the current thread, and
+// the interface is their only user as required by `MmioInterface::new`
+unsafe impl<W: Send> Send for MmioInterface<W> {}
+
+impl<W> MmioInterface<W> {
+    /// Create new memory-mapped interface for communication with a display driver
+    ///
+    /// # Safety
+    ///
+    /// `command` and `data` must be valid for volatile writes of `W` for as long as the interface
+    /// is used, i.e. the memory controller has to be set up with a bank mapping the display
+    /// before calling any method, and must stay so. Writing to either address must have no
+    /// effect beyond the bus cycle to the display, and no other code may access the bank while
+    /// the interface exists.
+    pub unsafe fn new(command: *mut W, data: *mut W) -> Self {
+        Self { command, data }
+    }
+
+    /// Consume the display interface and return
+    /// the command and data addresses used by it
+    pub fn release(self) -> (*mut W, *mut W) {
+        (self.command, self.data)
+    }
+}
+
+/// Write every word of `iter` to `address`
+fn write_iter<W>(address: *mut W, iter: impl Iterator<Item = W>) {
+    for word in iter {
+        // SAFETY: Guaranteed by the caller of `MmioInterface::new`
+        unsafe { write_volatile(address, word) };
+    }
+}
+
+fn write_u8(address: *mut u8, data: DataFormat<'_>) -> Result {
+    match data {
+        DataFormat::U8(slice) => write_iter(address, slice.iter().copied()),
+        DataFormat::U8Iter(iter) => write_iter(address, iter),
+        DataFormat::U16(slice) => {
+            write_iter(address, slice.iter().copied().flat_map(u16::to_ne_bytes))
+        }
+        DataFormat::U16BE(slice) => {
+            write_iter(address, slice.iter().copied().flat_map(u16::to_be_bytes))
+        }
+        DataFormat::U16LE(slice) => {
+            write_iter(address, slice.iter().copied().flat_map(u16::to_le_bytes))
+        }
+        DataFormat::U16BEIter(iter) => write_iter(address, iter.flat_map(u16::to_be_bytes)),
+        DataFormat::U16LEIter(iter) => write_iter(address, iter.flat_map(u16::to_le_bytes)),
+        _ => return Err(DisplayError::DataFormatNotImplemented),
+    }
+
+    Ok(())
+}
+
+fn write_u16(address: *mut u16, data: DataFormat<'_>) -> Result {
+    match data {
+        DataFormat::U8(slice) => write_iter(address, slice.iter().copied().map(u16::from)),
+        DataFormat::U8Iter(iter) => write_iter(address, iter.map(u16::from)),
+        DataFormat::U16(slice) => write_iter(address, slice.iter().copied()),
+        DataFormat::U16BE(slice) | DataFormat::U16LE(slice) => {
+            write_iter(address, slice.iter().copied())
+        }
+        DataFormat::U16BEIter(iter) | DataFormat::U16LEIter(iter) => write_iter(address, iter),
+        _ => return Err(DisplayError::DataFormatNotImplemented),
+    }
+
+    Ok(())
+}
+
+impl WriteOnlyDataCommand for MmioInterface<u8> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        write_u8(self.command, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        write_u8(self.data, buf)
+    }
+}
+
+impl WriteOnlyDataCommand for MmioInterface<u16> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        write_u16(self.command, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        write_u16(self.data, buf)
+    }
+}
@@ -0,0 +1,34 @@
+//! Stores to the command and data addresses, backed by plain memory
+//!
+//! Memory only keeps the last word written, so every check writes a single word or looks at the
+//! last one.
+
+use display_interface_fsmc::{DataFormat, MmioInterface, WriteOnlyDataCommand};
+
+#[test]
+fn commands_and_data_go_to_their_addresses() {
+    let (mut command, mut data) = (0u16, 0u16);
+    let mut iface = unsafe { MmioInterface::new(&mut command as *mut u16, &mut data) };
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.send_data(DataFormat::U16BE(&mut [0xf800])).unwrap();
+
+    assert_eq!((command, data), (0x2c, 0xf800));
+}
+
+#[test]
+fn eight_bit_banks_get_the_requested_byte_order() {
+    let (mut command, mut data) = (0u8, 0u8);
+    let mut iface = unsafe { MmioInterface::new(&mut command as *mut u8, &mut data) };
+
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+    let (_, address) = iface.release();
+    assert_eq!(unsafe { address.read_volatile() }, 0x34);
+
+    let mut iface = unsafe { MmioInterface::new(&mut command as *mut u8, &mut data) };
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    let (_, address) = iface.release();
+    assert_eq!(unsafe { address.read_volatile() }, 0x12);
+}
@@ -0,0 +1,51 @@
+//! Bytes per second through `I2cInterface` for every supported `DataFormat`
+//!
+//! The interface writes to a `NullI2c`, so this measures the overhead of the interface itself:
+//! prefixing control bytes, byte swapping and chunking. Run with `cargo bench --bench throughput`.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::{I2cInterface, NullI2c};
+
+/// One 128x64 monochrome frame, or half as many 16-bit words
+const BYTES: usize = 128 * 64 / 8;
+const WORDS: usize = BYTES / 2;
+
+fn formats(c: &mut Criterion) {
+    let mut iface = I2cInterface::new(NullI2c::new(), 0x3c, 0x40);
+    let bytes = [0x5au8; BYTES];
+    let words = [0xa55au16; WORDS];
+    let mut swapped = words;
+
+    let mut group = c.benchmark_group("i2c");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    group.bench_function("U8", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8(black_box(&bytes))))
+    });
+    group.bench_function("U16", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16(black_box(&words))))
+    });
+    group.bench_function("U16BE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BE(black_box(&mut swapped))))
+    });
+    group.bench_function("U16LE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LE(black_box(&mut swapped))))
+    });
+    group.bench_function("U8Iter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8Iter(&mut black_box(bytes).into_iter())))
+    });
+    group.bench_function("U16BEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BEIter(&mut black_box(words).into_iter())))
+    });
+    group.bench_function("U16LEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LEIter(&mut black_box(words).into_iter())))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, formats);
+criterion_main!(benches);
@@ -1,85 +1,116 @@
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use display_interface::{
+    AsyncReadData, AsyncWordWriteOnlyDataCommand, AsyncWriteOnlyDataCommand, DataFormat,
+    DisplayError,
+};
+use embedded_hal_async::i2c::{AddressMode, I2c};
 
-use crate::I2cInterface;
+use crate::{transfer::asynch as transfer, I2cInterface, I2cRegisterInterface};
 
-impl<I2C> AsyncWriteOnlyDataCommand for I2cInterface<I2C>
+impl<I2C, A, const N: usize> AsyncWriteOnlyDataCommand for I2cInterface<I2C, A, N>
 where
-    I2C: embedded_hal_async::i2c::I2c,
+    I2C: I2c<A>,
+    A: AddressMode + Copy,
 {
     async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
-        // Copy over given commands to new aray to prefix with command identifier
-        match cmds {
-            DataFormat::U8(slice) => {
-                let mut writebuf: [u8; 8] = [0; 8];
-                writebuf[1..=slice.len()].copy_from_slice(&slice[0..slice.len()]);
-
-                self.i2c
-                    .write(self.addr, &writebuf[..=slice.len()])
-                    .await
-                    .map_err(|_| DisplayError::BusWriteError)
-            }
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+        transfer::send_commands(self, cmds)
+            .await
+            .map_err(Into::into)
     }
 
     async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
-        match buf {
-            DataFormat::U8(slice) => {
-                // No-op if the data buffer is empty
-                if slice.is_empty() {
-                    return Ok(());
-                }
-
-                let mut writebuf = [0; 17];
+        transfer::send_data(self, buf).await.map_err(Into::into)
+    }
+}
 
-                // Data mode
-                writebuf[0] = self.data_byte;
+impl<I2C, A, const N: usize> AsyncWordWriteOnlyDataCommand for I2cInterface<I2C, A, N>
+where
+    I2C: I2c<A>,
+    A: AddressMode + Copy,
+{
+    type Word = u8;
 
-                for chunk in slice.chunks(16) {
-                    let chunk_len = chunk.len();
+    async fn send_command_iter(
+        &mut self,
+        cmds: impl IntoIterator<Item = u8>,
+    ) -> Result<(), DisplayError> {
+        transfer::write_commands(self, cmds.into_iter())
+            .await
+            .map_err(Into::into)
+    }
 
-                    // Copy over all data from buffer, leaving the data command byte intact
-                    writebuf[1..=chunk_len].copy_from_slice(chunk);
+    async fn send_data_iter(
+        &mut self,
+        data: impl IntoIterator<Item = u8>,
+    ) -> Result<(), DisplayError> {
+        let data_byte = Some(self.data_byte);
+        transfer::write_chunked(self, data_byte, false, data.into_iter())
+            .await
+            .map_err(Into::into)
+    }
 
-                    self.i2c
-                        .write(self.addr, &writebuf[0..=chunk_len])
-                        .await
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                }
+    async fn send_data_slice(&mut self, data: &[u8]) -> Result<(), DisplayError> {
+        transfer::write_data_slice(self, data)
+            .await
+            .map_err(Into::into)
+    }
+}
 
-                Ok(())
-            }
-            DataFormat::U8Iter(iter) => {
-                let mut writebuf = [0; 17];
-                let mut i = 1;
-                let len = writebuf.len();
+impl<I2C, A, const N: usize> AsyncReadData for I2cInterface<I2C, A, N>
+where
+    I2C: I2c<A>,
+    A: AddressMode + Copy,
+{
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), DisplayError> {
+        let data_byte = self.data_byte;
+        transfer::read(self, data_byte, buf).await
+    }
+}
 
-                // Data mode
-                writebuf[0] = self.data_byte;
+impl<I2C, A, const N: usize> I2cInterface<I2C, A, N>
+where
+    I2C: I2c<A>,
+    A: AddressMode + Copy,
+{
+    /// Send `cmds` immediately followed by `data`, without a STOP condition in between
+    ///
+    /// See [I2cInterface::send_commands_and_data].
+    pub async fn send_commands_and_data_async(
+        &mut self,
+        cmds: &[u8],
+        data: &[u8],
+    ) -> Result<(), DisplayError> {
+        transfer::send_commands_and_data(self, cmds, data).await
+    }
 
-                for byte in iter.into_iter() {
-                    writebuf[i] = byte;
-                    i += 1;
+    /// Read the status register
+    ///
+    /// See [I2cInterface::read_status].
+    pub async fn read_status_async(&mut self) -> Result<u8, DisplayError> {
+        let mut status = [0];
+        let command_byte = self.command_byte;
+        transfer::read(self, command_byte, &mut status).await?;
+        Ok(status[0])
+    }
 
-                    if i == len {
-                        self.i2c
-                            .write(self.addr, &writebuf[0..=len])
-                            .await
-                            .map_err(|_| DisplayError::BusWriteError)?;
-                        i = 1;
-                    }
-                }
+    /// Send `buf[1..]` as data in a single write without copying
+    ///
+    /// See [I2cInterface::send_data_prefixed].
+    pub async fn send_data_prefixed_async(&mut self, buf: &mut [u8]) -> Result<(), DisplayError> {
+        transfer::send_data_prefixed(self, buf).await
+    }
+}
 
-                if i > 1 {
-                    self.i2c
-                        .write(self.addr, &writebuf[0..=i])
-                        .await
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                }
+impl<I2C> AsyncWriteOnlyDataCommand for I2cRegisterInterface<I2C>
+where
+    I2C: I2c,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        let register = self.command_register;
+        transfer::send_registers(self, register, cmds).await
+    }
 
-                Ok(())
-            }
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let register = self.data_register;
+        transfer::send_registers(self, register, buf).await
     }
 }
@@ -0,0 +1,97 @@
+//! Builder for [I2cInterface] with presets for common display controllers
+
+use embedded_hal::i2c::SevenBitAddress;
+
+use crate::{CommandFraming, I2cInterface};
+
+/// Builder for an [I2cInterface]
+///
+/// Start from the preset for your controller and override what differs, e.g. the address of a
+/// module with its address pin pulled high:
+///
+/// ```
+/// # use display_interface_i2c::I2cInterfaceBuilder;
+/// # fn f<I2C>(i2c: I2C) {
+/// let iface = I2cInterfaceBuilder::ssd1306().address(0x3d).build(i2c);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct I2cInterfaceBuilder<const N: usize = 17> {
+    address: u8,
+    command_byte: u8,
+    data_byte: u8,
+    framing: CommandFraming,
+}
+
+impl I2cInterfaceBuilder {
+    /// Create a builder for a controller at `address` expecting `data_byte` before data and
+    /// `0x00` before commands
+    pub fn new(address: u8, data_byte: u8) -> Self {
+        Self {
+            address,
+            command_byte: 0x00,
+            data_byte,
+            framing: CommandFraming::SingleControlByte,
+        }
+    }
+
+    /// Preset for the SSD1306: address `0x3c`, command byte `0x00` and data byte `0x40`
+    ///
+    /// Modules with the SA0 pin pulled high answer at `0x3d` instead.
+    pub fn ssd1306() -> Self {
+        Self::new(0x3c, 0x40)
+    }
+
+    /// Preset for the SH1106: address `0x3c`, command byte `0x00` and data byte `0x40`
+    ///
+    /// Modules with the SA0 pin pulled high answer at `0x3d` instead. Some clones need
+    /// [CommandFraming::PerByteContinuation] to accept commands.
+    pub fn sh1106() -> Self {
+        Self::new(0x3c, 0x40)
+    }
+}
+
+impl<const N: usize> I2cInterfaceBuilder<N> {
+    /// Set the 7-bit address of the display
+    pub fn address(mut self, address: u8) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Set the control byte sent before commands
+    pub fn command_byte(mut self, command_byte: u8) -> Self {
+        self.command_byte = command_byte;
+        self
+    }
+
+    /// Set the control byte sent before data
+    pub fn data_byte(mut self, data_byte: u8) -> Self {
+        self.data_byte = data_byte;
+        self
+    }
+
+    /// Set how command bytes are framed
+    pub fn framing(mut self, framing: CommandFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Use a write buffer of `M` bytes, including the control byte
+    ///
+    /// See [I2cInterface::with_buffer_size].
+    pub fn buffer<const M: usize>(self) -> I2cInterfaceBuilder<M> {
+        I2cInterfaceBuilder {
+            address: self.address,
+            command_byte: self.command_byte,
+            data_byte: self.data_byte,
+            framing: self.framing,
+        }
+    }
+
+    /// Create the configured interface on top of `i2c`
+    pub fn build<I2C>(self, i2c: I2C) -> I2cInterface<I2C, SevenBitAddress, N> {
+        I2cInterface::new_with_control_bytes(i2c, self.address, self.command_byte, self.data_byte)
+            .with_command_framing(self.framing)
+            .with_buffer_size::<N>()
+    }
+}
@@ -0,0 +1,64 @@
+//! Commands followed by data without a STOP condition in between
+
+use display_interface::DisplayError;
+use embedded_hal::i2c::{AddressMode, I2c, Operation};
+
+use crate::{transfer, I2cInterface};
+
+/// Maximum number of commands sent with [I2cInterface::send_commands_and_data]
+pub const MAX_COMBINED_COMMANDS: usize = 16;
+
+/// Number of operations needed for the longest combined write
+const MAX_COMBINED_OPS: usize = 2 * MAX_COMBINED_COMMANDS + 2;
+
+/// The operations writing `cmds` and `data` as one continuous write, and how many of them are
+/// used
+///
+/// Every command is preceded by `command_byte`, which has the continuation bit set, the data by
+/// `data_byte`, which has it cleared.
+pub(crate) fn combined_ops<'a>(
+    cmds: &'a [u8],
+    command_byte: &'a [u8; 1],
+    data_byte: &'a [u8; 1],
+    data: &'a [u8],
+) -> Result<([Operation<'a>; MAX_COMBINED_OPS], usize), DisplayError> {
+    if cmds.len() > MAX_COMBINED_COMMANDS {
+        return Err(DisplayError::InvalidFormatError);
+    }
+
+    let mut ops: [Operation<'a>; MAX_COMBINED_OPS] =
+        core::array::from_fn(|_| Operation::Write(&[]));
+    let mut n = 0;
+    for cmd in cmds.chunks(1) {
+        ops[n] = Operation::Write(command_byte);
+        ops[n + 1] = Operation::Write(cmd);
+        n += 2;
+    }
+    ops[n] = Operation::Write(data_byte);
+    ops[n + 1] = Operation::Write(data);
+
+    Ok((ops, n + 2))
+}
+
+impl<I2C, A, const N: usize> I2cInterface<I2C, A, N>
+where
+    I2C: I2c<A>,
+    A: AddressMode + Copy,
+{
+    /// Send `cmds` immediately followed by `data`, without a STOP condition in between
+    ///
+    /// Some controllers reset their data pointer on a STOP after a command block. This sends
+    /// everything as one continuous write within a single `I2c::transaction`, using the
+    /// continuation bit of the control bytes to switch from commands to data, as supported by
+    /// SSD1306 and SH1106 controllers. Every command is preceded by the configured command byte
+    /// with the continuation bit set, or by `0x80` in
+    /// [PerByteContinuation](crate::CommandFraming::PerByteContinuation) mode. More than
+    /// [MAX_COMBINED_COMMANDS] commands result in an
+    /// [InvalidFormatError](DisplayError::InvalidFormatError).
+    ///
+    /// For HALs with broken transaction support, [I2cInterface::with_combined_writes] falls back
+    /// to sending commands and data as separate writes.
+    pub fn send_commands_and_data(&mut self, cmds: &[u8], data: &[u8]) -> Result<(), DisplayError> {
+        transfer::blocking::send_commands_and_data(self, cmds, data)
+    }
+}
@@ -0,0 +1,70 @@
+//! Shims for HALs which only implement embedded-hal 0.2
+
+use core::fmt::Debug;
+
+use eh02::blocking::i2c::Write;
+use embedded_hal::i2c::{self, ErrorKind, ErrorType, I2c, Operation, SevenBitAddress};
+
+use crate::I2cInterface;
+
+/// Error of the embedded-hal 0.2 shim
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eh02Error<E> {
+    /// Error returned by the wrapped 0.2 implementation
+    Hal(E),
+    /// The transaction has no counterpart in `blocking::i2c::Write`, i.e. it reads or consists
+    /// of more than one write
+    Unsupported,
+}
+
+impl<E: Debug> i2c::Error for Eh02Error<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// I2C bus implementing only the embedded-hal 0.2 `blocking::i2c::Write`
+///
+/// Every transaction has to be a single write, which is what [I2cInterface] does unless
+/// [SliceStrategy::SingleTransaction](crate::SliceStrategy::SingleTransaction) or combined writes
+/// are selected. Everything else, including reads, fails with [Eh02Error::Unsupported].
+pub struct Eh02I2c<I2C>(pub I2C);
+
+impl<I2C> ErrorType for Eh02I2c<I2C>
+where
+    I2C: Write,
+    I2C::Error: Debug,
+{
+    type Error = Eh02Error<I2C::Error>;
+}
+
+impl<I2C> I2c<SevenBitAddress> for Eh02I2c<I2C>
+where
+    I2C: Write,
+    I2C::Error: Debug,
+{
+    fn transaction(
+        &mut self,
+        address: SevenBitAddress,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        match operations {
+            [Operation::Write(bytes)] => self.write(address, bytes),
+            _ => Err(Eh02Error::Unsupported),
+        }
+    }
+
+    fn write(&mut self, address: SevenBitAddress, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(address, bytes).map_err(Eh02Error::Hal)
+    }
+}
+
+impl<I2C> I2cInterface<Eh02I2c<I2C>> {
+    /// Create new I2C interface from an embedded-hal 0.2 implementation
+    ///
+    /// See [I2cInterface::new]. Combined writes are disabled, as they need transactions of
+    /// several writes.
+    pub fn new_eh02(i2c: I2C, addr: u8, data_byte: u8) -> Self {
+        Self::new(Eh02I2c(i2c), addr, data_byte).with_combined_writes(false)
+    }
+}
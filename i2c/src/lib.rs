@@ -1,25 +1,255 @@
 //! Generic I2C interface for display drivers
+//!
+//! The easiest way to get the control bytes and address right is to start from a controller
+//! preset of [I2cInterfaceBuilder]:
+//!
+//! ```
+//! # use display_interface_i2c::I2cInterfaceBuilder;
+//! # fn f<I2C>(i2c: I2C) {
+//! let iface = I2cInterfaceBuilder::ssd1306().build(i2c);
+//! # }
+//! ```
 
 #![no_std]
 
 mod asynch;
+mod builder;
+mod combined;
+#[cfg(feature = "eh02")]
+mod compat;
+mod null;
+mod recover;
+mod register;
+mod scan;
+mod transfer;
 
-use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+pub use builder::I2cInterfaceBuilder;
+pub use combined::MAX_COMBINED_COMMANDS;
+#[cfg(feature = "eh02")]
+pub use compat::{Eh02Error, Eh02I2c};
+pub use null::NullI2c;
+pub use recover::recover_bus;
+pub use register::I2cRegisterInterface;
+pub use scan::scan;
+
+use display_interface::{
+    DataFormat, DisplayError, ReadData, TypedWriteOnlyDataCommand, WordWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+use embedded_hal::i2c::{AddressMode, SevenBitAddress, TenBitAddress};
+
+/// Splits a byte stream into I2C writes prefixed with a control byte
+pub(crate) struct Chunks<I> {
+    iter: I,
+    pairs: bool,
+}
+
+impl<I: Iterator<Item = u8>> Chunks<I> {
+    /// Split `iter` into chunks, keeping pairs of bytes in the same chunk if `pairs` is set
+    pub(crate) fn new(iter: I, pairs: bool) -> Self {
+        Self { iter, pairs }
+    }
+
+    /// Fill `writebuf` with `control_byte`, if any, followed by the next chunk of bytes,
+    /// returning the number of bytes to write or `None` once the stream is exhausted
+    pub(crate) fn fill(&mut self, writebuf: &mut [u8], control_byte: Option<u8>) -> Option<usize> {
+        let start = match control_byte {
+            Some(byte) => {
+                writebuf[0] = byte;
+                1
+            }
+            None => 0,
+        };
+
+        let mut end = writebuf.len();
+        if self.pairs {
+            end -= (end - start) % 2;
+        }
+
+        let mut len = start;
+        for (slot, byte) in writebuf[start..end].iter_mut().zip(&mut self.iter) {
+            *slot = byte;
+            len += 1;
+        }
+
+        (len > start).then_some(len)
+    }
+}
+
+/// Control byte prefixed to every command byte in [CommandFraming::PerByteContinuation] mode
+pub(crate) const CONTINUATION_BYTE: u8 = 0x80;
+
+/// How command bytes are framed on the bus
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CommandFraming {
+    /// A single command control byte followed by all command bytes
+    #[default]
+    SingleControlByte,
+    /// Every command byte is preceded by its own `0x80` control byte, with the continuation bit
+    /// set, as required by some SSD1306/SH1106 clones
+    PerByteContinuation,
+}
+
+/// How [DataFormat::U8] data slices are put on the bus
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SliceStrategy {
+    /// Copy the slice into the write buffer and send it in writes of up to `N` bytes
+    #[default]
+    Chunked,
+    /// Send the data byte and the whole slice as one write, using a single `I2c::transaction`
+    /// with two adjacent write operations
+    ///
+    /// This avoids both the copy and the per-chunk overhead, but relies on the HAL merging
+    /// adjacent writes as required by `embedded-hal`; not every implementation does.
+    SingleTransaction,
+}
 
 /// I2C communication interface
-pub struct I2cInterface<I2C> {
+///
+/// Commands and data are written in chunks of up to `N` bytes including the control byte, so
+/// by default a 1 KiB framebuffer takes 64 I2C writes. A buffer large enough for a whole frame,
+/// selected with [I2cInterface::with_buffer_size], flushes it in a single write with one control
+/// byte instead, at the cost of `N` bytes of stack during every call.
+///
+/// The interface talks to 7-bit addresses by default; use [I2cInterface::new_ten_bit] for
+/// devices strapped into the 10-bit range.
+///
+/// Unlike the SPI and parallel interfaces, it doesn't implement
+/// [LowPower](display_interface::LowPower): it caches no state and owns no pins besides the bus,
+/// which is parked, if needed, through the HAL's I2C driver before entering a low-power mode.
+pub struct I2cInterface<I2C, A = SevenBitAddress, const N: usize = 17> {
     i2c: I2C,
-    addr: u8,
+    addr: A,
+    command_byte: u8,
     data_byte: u8,
+    command_framing: CommandFraming,
+    slice_strategy: SliceStrategy,
+    combined_writes: bool,
 }
 
 impl<I2C> I2cInterface<I2C> {
     /// Create new I2C interface for communication with a display driver
+    ///
+    /// Commands are prefixed with the control byte `0x00`.
     pub fn new(i2c: I2C, addr: u8, data_byte: u8) -> Self {
+        Self::new_with_control_bytes(i2c, addr, 0x00, data_byte)
+    }
+
+    /// Create new I2C interface for a display driver expecting a different command control byte
+    ///
+    /// Every command write is prefixed with `command_byte` and every data write with `data_byte`.
+    pub fn new_with_control_bytes(i2c: I2C, addr: u8, command_byte: u8, data_byte: u8) -> Self {
+        Self::with_address(i2c, addr, command_byte, data_byte)
+    }
+}
+
+impl<I2C> I2cInterface<I2C, TenBitAddress> {
+    /// Create new I2C interface for a display driver with a 10-bit address
+    ///
+    /// Commands are prefixed with the control byte `0x00`.
+    pub fn new_ten_bit(i2c: I2C, addr: u16, data_byte: u8) -> Self {
+        Self::new_ten_bit_with_control_bytes(i2c, addr, 0x00, data_byte)
+    }
+
+    /// Create new I2C interface for a display driver with a 10-bit address, expecting a
+    /// different command control byte
+    ///
+    /// See [I2cInterface::new_with_control_bytes].
+    pub fn new_ten_bit_with_control_bytes(
+        i2c: I2C,
+        addr: u16,
+        command_byte: u8,
+        data_byte: u8,
+    ) -> Self {
+        Self::with_address(i2c, addr, command_byte, data_byte)
+    }
+}
+
+impl<I2C, A> I2cInterface<I2C, A> {
+    fn with_address(i2c: I2C, addr: A, command_byte: u8, data_byte: u8) -> Self {
         Self {
             i2c,
             addr,
+            command_byte,
             data_byte,
+            command_framing: CommandFraming::default(),
+            slice_strategy: SliceStrategy::default(),
+            combined_writes: true,
+        }
+    }
+}
+
+impl<I2C, A, const N: usize> I2cInterface<I2C, A, N> {
+    /// At least the control byte and one 16-bit word have to fit into the buffer
+    const VALID_SIZE: () = assert!(N >= 3, "the I2C write buffer must hold at least 3 bytes");
+
+    /// Use a write buffer of `M` bytes, including the control byte
+    ///
+    /// ```
+    /// # use display_interface_i2c::I2cInterface;
+    /// # fn f<I2C>(i2c: I2C) {
+    /// // Send a 128x64 monochrome framebuffer in a single write
+    /// let iface = I2cInterface::new(i2c, 0x3c, 0x40).with_buffer_size::<1025>();
+    /// # }
+    /// ```
+    pub fn with_buffer_size<const M: usize>(self) -> I2cInterface<I2C, A, M> {
+        #[allow(clippy::let_unit_value)]
+        let () = I2cInterface::<I2C, A, M>::VALID_SIZE;
+
+        I2cInterface {
+            i2c: self.i2c,
+            addr: self.addr,
+            command_byte: self.command_byte,
+            data_byte: self.data_byte,
+            command_framing: self.command_framing,
+            slice_strategy: self.slice_strategy,
+            combined_writes: self.combined_writes,
+        }
+    }
+
+    /// Talk to the display at `addr` from now on
+    ///
+    /// This allows driving several identical displays on one bus, see [scan] to find them.
+    pub fn set_address(&mut self, addr: A) {
+        self.addr = addr;
+    }
+
+    /// Select how command bytes are framed
+    ///
+    /// Data framing is not affected.
+    pub fn with_command_framing(mut self, command_framing: CommandFraming) -> Self {
+        self.command_framing = command_framing;
+        self
+    }
+
+    /// Select how data slices are put on the bus
+    pub fn with_slice_strategy(mut self, slice_strategy: SliceStrategy) -> Self {
+        self.slice_strategy = slice_strategy;
+        self
+    }
+
+    /// Select whether [I2cInterface::send_commands_and_data] uses a single transaction
+    ///
+    /// This is enabled by default; disable it for HALs which don't implement
+    /// `I2c::transaction` correctly, so commands and data are sent as separate writes.
+    pub fn with_combined_writes(mut self, combined_writes: bool) -> Self {
+        self.combined_writes = combined_writes;
+        self
+    }
+
+    /// Return how data slices are put on the bus
+    pub fn slice_strategy(&self) -> SliceStrategy {
+        self.slice_strategy
+    }
+
+    /// Return the maximum number of data bytes of a [DataFormat::U8] slice sent in one I2C
+    /// write, or `None` if any slice is sent in a single write
+    ///
+    /// Drivers can use this to size their batches so every batch is a single write.
+    pub fn max_slice_write_len(&self) -> Option<usize> {
+        match self.slice_strategy {
+            SliceStrategy::Chunked => Some(N - 1),
+            SliceStrategy::SingleTransaction => None,
         }
     }
 
@@ -30,79 +260,116 @@ impl<I2C> I2cInterface<I2C> {
     }
 }
 
-impl<I2C> WriteOnlyDataCommand for I2cInterface<I2C>
+/// Error of [I2cInterface], keeping the error of the I2C bus
+///
+/// Converts into [DisplayError] for drivers written against [WriteOnlyDataCommand].
+#[derive(Debug)]
+pub enum I2cInterfaceError<E> {
+    /// The I2C bus failed, e.g. because the display didn't acknowledge
+    I2c(E),
+    /// The data couldn't be sent, e.g. because of an unsupported format
+    Format(DisplayError),
+}
+
+impl<E> From<I2cInterfaceError<E>> for DisplayError {
+    fn from(e: I2cInterfaceError<E>) -> Self {
+        match e {
+            I2cInterfaceError::I2c(_) => DisplayError::BusWriteError,
+            I2cInterfaceError::Format(e) => e,
+        }
+    }
+}
+
+impl<I2C, A, const N: usize> TypedWriteOnlyDataCommand for I2cInterface<I2C, A, N>
+where
+    I2C: embedded_hal::i2c::I2c<A>,
+    A: AddressMode + Copy,
+{
+    type Error = I2cInterfaceError<I2C::Error>;
+
+    fn send_commands_typed(&mut self, cmds: DataFormat<'_>) -> Result<(), Self::Error> {
+        transfer::blocking::send_commands(self, cmds)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> Result<(), Self::Error> {
+        transfer::blocking::send_data(self, buf)
+    }
+}
+
+impl<I2C, A, const N: usize> WriteOnlyDataCommand for I2cInterface<I2C, A, N>
 where
-    I2C: embedded_hal::i2c::I2c,
+    I2C: embedded_hal::i2c::I2c<A>,
+    A: AddressMode + Copy,
 {
     fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
-        // Copy over given commands to new aray to prefix with command identifier
-        match cmds {
-            DataFormat::U8(slice) => {
-                let mut writebuf: [u8; 8] = [0; 8];
-                writebuf[1..=slice.len()].copy_from_slice(&slice[0..slice.len()]);
-
-                self.i2c
-                    .write(self.addr, &writebuf[..=slice.len()])
-                    .map_err(|_| DisplayError::BusWriteError)
-            }
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+        self.send_commands_typed(cmds).map_err(Into::into)
     }
 
     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
-        match buf {
-            DataFormat::U8(slice) => {
-                // No-op if the data buffer is empty
-                if slice.is_empty() {
-                    return Ok(());
-                }
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
 
-                let mut writebuf = [0; 17];
+impl<I2C, A, const N: usize> WordWriteOnlyDataCommand for I2cInterface<I2C, A, N>
+where
+    I2C: embedded_hal::i2c::I2c<A>,
+    A: AddressMode + Copy,
+{
+    type Word = u8;
 
-                // Data mode
-                writebuf[0] = self.data_byte;
+    fn send_command_iter(
+        &mut self,
+        cmds: impl IntoIterator<Item = u8>,
+    ) -> Result<(), DisplayError> {
+        transfer::blocking::write_commands(self, cmds.into_iter()).map_err(Into::into)
+    }
 
-                slice
-                    .chunks(16)
-                    .try_for_each(|c| {
-                        let chunk_len = c.len();
+    fn send_data_iter(&mut self, data: impl IntoIterator<Item = u8>) -> Result<(), DisplayError> {
+        let data_byte = Some(self.data_byte);
+        transfer::blocking::write_chunked(self, data_byte, false, data.into_iter())
+            .map_err(Into::into)
+    }
 
-                        // Copy over all data from buffer, leaving the data command byte intact
-                        writebuf[1..=chunk_len].copy_from_slice(c);
+    fn send_data_slice(&mut self, data: &[u8]) -> Result<(), DisplayError> {
+        transfer::blocking::write_data_slice(self, data).map_err(Into::into)
+    }
+}
 
-                        self.i2c.write(self.addr, &writebuf[0..=chunk_len])
-                    })
-                    .map_err(|_| DisplayError::BusWriteError)
-            }
-            DataFormat::U8Iter(iter) => {
-                let mut writebuf = [0; 17];
-                let mut i = 1;
-                let len = writebuf.len();
-
-                // Data mode
-                writebuf[0] = self.data_byte;
-
-                for byte in iter.into_iter() {
-                    writebuf[i] = byte;
-                    i += 1;
-
-                    if i == len {
-                        self.i2c
-                            .write(self.addr, &writebuf[0..=len])
-                            .map_err(|_| DisplayError::BusWriteError)?;
-                        i = 1;
-                    }
-                }
-
-                if i > 1 {
-                    self.i2c
-                        .write(self.addr, &writebuf[0..=i])
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                }
-
-                Ok(())
-            }
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+impl<I2C, A, const N: usize> ReadData for I2cInterface<I2C, A, N>
+where
+    I2C: embedded_hal::i2c::I2c<A>,
+    A: AddressMode + Copy,
+{
+    /// Read data from the display, writing the data byte first to select data mode
+    fn read_data(&mut self, buf: &mut [u8]) -> Result<(), DisplayError> {
+        let data_byte = self.data_byte;
+        transfer::blocking::read(self, data_byte, buf)
+    }
+}
+
+impl<I2C, A, const N: usize> I2cInterface<I2C, A, N>
+where
+    I2C: embedded_hal::i2c::I2c<A>,
+    A: AddressMode + Copy,
+{
+    /// Read the status register, e.g. the busy and display on/off flags of an SSD1306 or SH1106
+    ///
+    /// The command byte is written to select command mode, immediately followed by a repeated
+    /// start and the read of a single byte.
+    pub fn read_status(&mut self) -> Result<u8, DisplayError> {
+        let mut status = [0];
+        let command_byte = self.command_byte;
+        transfer::blocking::read(self, command_byte, &mut status)?;
+        Ok(status[0])
+    }
+
+    /// Send `buf[1..]` as data in a single write without copying
+    ///
+    /// The first byte of `buf` is reserved for the interface: it is overwritten with the data
+    /// byte and the whole buffer is written as is. Keeping one spare byte in front of a
+    /// framebuffer thus allows flushing it with a single write regardless of the buffer size.
+    /// Returns [DisplayError::InvalidFormatError] if `buf` is empty.
+    pub fn send_data_prefixed(&mut self, buf: &mut [u8]) -> Result<(), DisplayError> {
+        transfer::blocking::send_data_prefixed(self, buf)
     }
 }
@@ -0,0 +1,77 @@
+//! I2C bus discarding everything written to it, for benchmarks
+
+use core::{convert::Infallible, hint::black_box};
+
+use embedded_hal::i2c::{AddressMode, ErrorType, I2c, Operation};
+
+/// [I2c] bus accepting every transaction at once and only counting the bytes written
+///
+/// It measures the time an interface, or a driver rendering through it, spends on its own,
+/// without any bus in the way. Control bytes count like any other byte, the address doesn't. The
+/// written bytes are passed through [black_box], so the work producing them isn't optimized away.
+/// Reads leave the buffers untouched.
+///
+/// ```
+/// use display_interface_i2c::{I2cInterface, NullI2c};
+/// use display_interface::{DataFormat, WriteOnlyDataCommand};
+///
+/// let mut iface = I2cInterface::new(NullI2c::new(), 0x3c, 0x40);
+/// iface.send_data(DataFormat::U8(&[0; 10])).unwrap();
+/// // The data is prefixed with the 0x40 control byte
+/// assert_eq!(iface.release().bytes(), 11);
+/// ```
+#[derive(Debug, Default)]
+pub struct NullI2c {
+    bytes: usize,
+}
+
+impl NullI2c {
+    /// Create a bus which hasn't seen any bytes yet
+    pub const fn new() -> Self {
+        Self { bytes: 0 }
+    }
+
+    /// Number of bytes written since creation or the last [reset](Self::reset)
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        self.bytes = 0;
+    }
+
+    fn count(&mut self, operations: &mut [Operation<'_>]) {
+        for op in operations {
+            if let Operation::Write(buf) = op {
+                self.bytes += black_box(*buf).len();
+            }
+        }
+    }
+}
+
+impl ErrorType for NullI2c {
+    type Error = Infallible;
+}
+
+impl<A: AddressMode> I2c<A> for NullI2c {
+    fn transaction(
+        &mut self,
+        _address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.count(operations);
+        Ok(())
+    }
+}
+
+impl<A: AddressMode> embedded_hal_async::i2c::I2c<A> for NullI2c {
+    async fn transaction(
+        &mut self,
+        _address: A,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.count(operations);
+        Ok(())
+    }
+}
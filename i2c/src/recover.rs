@@ -0,0 +1,42 @@
+//! Recovering a bus which is held by a device after an interrupted transfer
+
+use display_interface::DisplayError;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{InputPin, OutputPin},
+};
+
+/// Half of an SCL period at 100 kHz
+const HALF_PERIOD_US: u32 = 5;
+
+/// Clock SCL until a device holding SDA low releases it, returning whether SDA is high
+///
+/// A device which was interrupted in the middle of a transfer, e.g. by a reset of the
+/// microcontroller, keeps driving SDA low while waiting for the rest of its clock pulses, which
+/// makes every following transfer fail. This clocks SCL at 100 kHz up to nine times, until the
+/// device has shifted out its byte and sees the missing acknowledge as the end of the read.
+///
+/// The pins have to be switched to GPIO mode temporarily: `scl` as an (open drain) output and
+/// `sda_in` as an input. SCL is left high; as SDA can't be driven here, no STOP condition is
+/// generated, the START of the next transfer resets all devices on the bus instead.
+pub fn recover_bus(
+    scl: &mut impl OutputPin,
+    sda_in: &mut impl InputPin,
+    delay: &mut impl DelayNs,
+) -> Result<bool, DisplayError> {
+    scl.set_high().map_err(|_| DisplayError::BusWriteError)?;
+    delay.delay_us(HALF_PERIOD_US);
+
+    for _ in 0..9 {
+        if sda_in.is_high().map_err(|_| DisplayError::BusReadError)? {
+            return Ok(true);
+        }
+
+        scl.set_low().map_err(|_| DisplayError::BusWriteError)?;
+        delay.delay_us(HALF_PERIOD_US);
+        scl.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        delay.delay_us(HALF_PERIOD_US);
+    }
+
+    sda_in.is_high().map_err(|_| DisplayError::BusReadError)
+}
@@ -0,0 +1,68 @@
+//! I2C interface for register-file oriented display controllers
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+use crate::{transfer, Chunks};
+
+/// Size of the write buffer, including the register address
+pub(crate) const REGISTER_BUFFER_SIZE: usize = 17;
+
+/// I2C communication interface for controllers addressed through auto-incrementing registers
+///
+/// Unlike the control byte scheme of [I2cInterface](crate::I2cInterface), every write starts
+/// with a register address, e.g. for HT16K33 LED matrix drivers. Commands are written starting
+/// at the command register and data starting at the data register. Long payloads are split into
+/// several writes, each starting with the register address advanced by the number of bytes
+/// already written.
+pub struct I2cRegisterInterface<I2C> {
+    pub(crate) i2c: I2C,
+    pub(crate) addr: u8,
+    pub(crate) command_register: u8,
+    pub(crate) data_register: u8,
+}
+
+impl<I2C> I2cRegisterInterface<I2C> {
+    /// Create new interface for the controller at `addr`, writing commands starting at
+    /// `command_register` and data starting at `data_register`
+    pub fn new(i2c: I2C, addr: u8, command_register: u8, data_register: u8) -> Self {
+        Self {
+            i2c,
+            addr,
+            command_register,
+            data_register,
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+/// Fill `writebuf` with `register` followed by the next chunk from `chunks`, advancing
+/// `register` past the chunk
+pub(crate) fn fill_registers<I: Iterator<Item = u8>>(
+    chunks: &mut Chunks<I>,
+    writebuf: &mut [u8; REGISTER_BUFFER_SIZE],
+    register: &mut u8,
+) -> Option<usize> {
+    let len = chunks.fill(writebuf, Some(*register))?;
+    *register = register.wrapping_add((len - 1) as u8);
+    Some(len)
+}
+
+impl<I2C> WriteOnlyDataCommand for I2cRegisterInterface<I2C>
+where
+    I2C: embedded_hal::i2c::I2c,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        let register = self.command_register;
+        transfer::blocking::send_registers(self, register, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let register = self.data_register;
+        transfer::blocking::send_registers(self, register, buf)
+    }
+}
@@ -0,0 +1,33 @@
+//! Probing the bus for devices
+
+use embedded_hal::i2c::I2c;
+
+/// Return whether `addr` is one of the 7-bit addresses reserved by the I2C specification
+fn is_reserved(addr: u8) -> bool {
+    !(0x08..=0x77).contains(&addr)
+}
+
+/// Probe the addresses in `range` with empty writes and return the ones acknowledging them
+///
+/// Addresses reserved by the I2C specification (`0x00..=0x07` and `0x78..=0x7f`) and values
+/// outside the 7-bit range are skipped without touching the bus. Any failed write, usually a
+/// NACK, means the address is not present.
+///
+/// ```
+/// # use display_interface_i2c::{scan, I2cInterface};
+/// # fn f<I2C: embedded_hal::i2c::I2c>(mut i2c: I2C) {
+/// // Find the first of the two possible SSD1306 addresses which responds
+/// let addr = scan(&mut i2c, 0x3c..=0x3d).next();
+/// # }
+/// ```
+pub fn scan<'a, I2C, R>(i2c: &'a mut I2C, range: R) -> impl Iterator<Item = u8> + 'a
+where
+    I2C: I2c,
+    R: IntoIterator<Item = u8>,
+    R::IntoIter: 'a,
+{
+    range
+        .into_iter()
+        .filter(|&addr| !is_reserved(addr))
+        .filter(move |&addr| i2c.write(addr, &[]).is_ok())
+}
@@ -0,0 +1,269 @@
+//! Bus transfers shared by the blocking and `async` implementations
+//!
+//! The functions are written once and expanded into the `blocking` and `asynch` variants, so a
+//! fix to the framing or chunking always applies to both.
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    use display_interface::{DataFormat, DisplayError};
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::i2c::I2c;
+    use embedded_hal::i2c::{AddressMode, Operation};
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::i2c::I2c;
+
+    use crate::{
+        combined::combined_ops,
+        register::{fill_registers, REGISTER_BUFFER_SIZE},
+        Chunks, CommandFraming, I2cInterface, I2cInterfaceError, I2cRegisterInterface,
+        SliceStrategy, CONTINUATION_BYTE,
+    };
+
+    type Result<T = ()> = core::result::Result<T, DisplayError>;
+
+    /// Result of a write keeping the error of the I2C bus
+    type WriteResult<E> = core::result::Result<(), I2cInterfaceError<E>>;
+
+    pub(crate) async fn send_commands<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        cmds: DataFormat<'_>,
+    ) -> WriteResult<I2C::Error>
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        match cmds {
+            DataFormat::U8(slice) => write_commands(iface, slice.iter().copied()).await,
+            DataFormat::U8Iter(iter) => write_commands(iface, iter).await,
+            _ => Err(I2cInterfaceError::Format(
+                DisplayError::DataFormatNotImplemented,
+            )),
+        }
+    }
+
+    pub(crate) async fn send_data<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        buf: DataFormat<'_>,
+    ) -> WriteResult<I2C::Error>
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        let data_byte = Some(iface.data_byte);
+        match buf {
+            DataFormat::U8(slice) => write_data_slice(iface, slice).await,
+            DataFormat::U8Iter(iter) => write_chunked(iface, data_byte, false, iter).await,
+            DataFormat::U16(slice) => {
+                let bytes = slice.iter().flat_map(|w| w.to_ne_bytes());
+                write_chunked(iface, data_byte, true, bytes).await
+            }
+            DataFormat::U16BE(slice) => {
+                let bytes = slice.iter().flat_map(|w| w.to_be_bytes());
+                write_chunked(iface, data_byte, true, bytes).await
+            }
+            DataFormat::U16LE(slice) => {
+                let bytes = slice.iter().flat_map(|w| w.to_le_bytes());
+                write_chunked(iface, data_byte, true, bytes).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                let bytes = iter.flat_map(u16::to_be_bytes);
+                write_chunked(iface, data_byte, true, bytes).await
+            }
+            DataFormat::U16LEIter(iter) => {
+                let bytes = iter.flat_map(u16::to_le_bytes);
+                write_chunked(iface, data_byte, true, bytes).await
+            }
+            _ => Err(I2cInterfaceError::Format(
+                DisplayError::DataFormatNotImplemented,
+            )),
+        }
+    }
+
+    /// Write the command bytes from `cmds`, framed as configured
+    pub(crate) async fn write_commands<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        cmds: impl Iterator<Item = u8>,
+    ) -> WriteResult<I2C::Error>
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        match iface.command_framing {
+            CommandFraming::SingleControlByte => {
+                let command_byte = Some(iface.command_byte);
+                write_chunked(iface, command_byte, false, cmds).await
+            }
+            CommandFraming::PerByteContinuation => {
+                let pairs = cmds.flat_map(|cmd| [CONTINUATION_BYTE, cmd]);
+                write_chunked(iface, None, true, pairs).await
+            }
+        }
+    }
+
+    /// Write `slice` prefixed with the data byte, according to the slice strategy
+    pub(crate) async fn write_data_slice<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        slice: &[u8],
+    ) -> WriteResult<I2C::Error>
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        // Slices fitting into the write buffer are sent in a single write either way
+        if iface.slice_strategy == SliceStrategy::Chunked || slice.len() < N {
+            let data_byte = Some(iface.data_byte);
+            return write_chunked(iface, data_byte, false, slice.iter().copied()).await;
+        }
+
+        iface
+            .i2c
+            .transaction(
+                iface.addr,
+                &mut [
+                    Operation::Write(&[iface.data_byte]),
+                    Operation::Write(slice),
+                ],
+            )
+            .await
+            .map_err(I2cInterfaceError::I2c)
+    }
+
+    /// Write the bytes from `iter` in chunks of up to `N` bytes, each starting with
+    /// `control_byte` if given
+    ///
+    /// With `pairs` set every chunk holds an even number of bytes after the control byte, so
+    /// 16-bit words and continuation byte pairs are never split between writes.
+    pub(crate) async fn write_chunked<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        control_byte: Option<u8>,
+        pairs: bool,
+        iter: impl Iterator<Item = u8>,
+    ) -> WriteResult<I2C::Error>
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        let mut writebuf = [0; N];
+        let mut chunks = Chunks::new(iter, pairs);
+
+        while let Some(len) = chunks.fill(&mut writebuf, control_byte) {
+            iface
+                .i2c
+                .write(iface.addr, &writebuf[..len])
+                .await
+                .map_err(I2cInterfaceError::I2c)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send `cmds` immediately followed by `data`, without a STOP condition in between
+    pub(crate) async fn send_commands_and_data<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        cmds: &[u8],
+        data: &[u8],
+    ) -> Result
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        if !iface.combined_writes {
+            send_commands(iface, DataFormat::U8(cmds)).await?;
+            return send_data(iface, DataFormat::U8(data))
+                .await
+                .map_err(Into::into);
+        }
+
+        let command_byte = [match iface.command_framing {
+            CommandFraming::SingleControlByte => iface.command_byte | CONTINUATION_BYTE,
+            CommandFraming::PerByteContinuation => CONTINUATION_BYTE,
+        }];
+        let data_byte = [iface.data_byte];
+        let (mut ops, n) = combined_ops(cmds, &command_byte, &data_byte, data)?;
+
+        iface
+            .i2c
+            .transaction(iface.addr, &mut ops[..n])
+            .await
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Overwrite the first byte of `buf` with the data byte and write the whole buffer
+    pub(crate) async fn send_data_prefixed<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        buf: &mut [u8],
+    ) -> Result
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        let Some(first) = buf.first_mut() else {
+            return Err(DisplayError::InvalidFormatError);
+        };
+        *first = iface.data_byte;
+
+        iface
+            .i2c
+            .write(iface.addr, buf)
+            .await
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Write `control_byte` and read the response into `buf` after a repeated start
+    pub(crate) async fn read<I2C, A, const N: usize>(
+        iface: &mut I2cInterface<I2C, A, N>,
+        control_byte: u8,
+        buf: &mut [u8],
+    ) -> Result
+    where
+        I2C: I2c<A>,
+        A: AddressMode + Copy,
+    {
+        iface
+            .i2c
+            .write_read(iface.addr, &[control_byte], buf)
+            .await
+            .map_err(|_| DisplayError::BusReadError)
+    }
+
+    pub(crate) async fn send_registers<I2C>(
+        iface: &mut I2cRegisterInterface<I2C>,
+        register: u8,
+        format: DataFormat<'_>,
+    ) -> Result
+    where
+        I2C: I2c,
+    {
+        match format {
+            DataFormat::U8(slice) => write_registers(iface, register, slice.iter().copied()).await,
+            DataFormat::U8Iter(iter) => write_registers(iface, register, iter).await,
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    /// Write the bytes from `iter` to consecutive registers, starting at `register`
+    async fn write_registers<I2C>(
+        iface: &mut I2cRegisterInterface<I2C>,
+        mut register: u8,
+        iter: impl Iterator<Item = u8>,
+    ) -> Result
+    where
+        I2C: I2c,
+    {
+        let mut writebuf = [0; REGISTER_BUFFER_SIZE];
+        let mut chunks = Chunks::new(iter, false);
+
+        while let Some(len) = fill_registers(&mut chunks, &mut writebuf, &mut register) {
+            iface
+                .i2c
+                .write(iface.addr, &writebuf[..len])
+                .await
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+
+        Ok(())
+    }
+}
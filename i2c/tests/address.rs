@@ -0,0 +1,89 @@
+//! Both 7-bit and 10-bit addressed buses can drive the interface
+
+mod common;
+
+use core::convert::Infallible;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::I2cInterface;
+use embedded_hal::i2c::{ErrorType, Operation, SevenBitAddress, TenBitAddress};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+/// 10-bit addressed I2C bus recording every write
+#[derive(Default)]
+struct TenBitRecorder(Vec<(u16, Vec<u8>)>);
+
+impl ErrorType for TenBitRecorder {
+    type Error = Infallible;
+}
+
+impl TenBitRecorder {
+    fn record(&mut self, address: u16, operations: &mut [Operation<'_>]) {
+        for op in operations {
+            if let Operation::Write(bytes) = op {
+                self.0.push((address, bytes.to_vec()));
+            }
+        }
+    }
+}
+
+impl embedded_hal::i2c::I2c<TenBitAddress> for TenBitRecorder {
+    fn transaction(
+        &mut self,
+        address: u16,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.record(address, operations);
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::i2c::I2c<TenBitAddress> for TenBitRecorder {
+    async fn transaction(
+        &mut self,
+        address: u16,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.record(address, operations);
+        Ok(())
+    }
+}
+
+#[test]
+fn seven_bit_address() {
+    let expected = [
+        I2cTransaction::write(0x3c, vec![0x00, 0xaf]),
+        I2cTransaction::write(0x3c, vec![0x40, 0x01]),
+    ];
+    let mut iface: I2cInterface<I2cMock, SevenBitAddress> =
+        I2cInterface::new(I2cMock::new(&expected), 0x3c, 0x40);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xaf])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x01])).unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn ten_bit_address() {
+    let expected = vec![(0x2a5, vec![0x00, 0xaf]), (0x2a5, vec![0x40, 0x01])];
+
+    let mut iface = I2cInterface::new_ten_bit(TenBitRecorder::default(), 0x2a5, 0x40);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xaf])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x01])).unwrap();
+    assert_eq!(iface.release().0, expected);
+
+    let mut iface: I2cInterface<_, TenBitAddress> =
+        I2cInterface::new_ten_bit(TenBitRecorder::default(), 0x2a5, 0x40);
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&[0xaf]),
+    ))
+    .unwrap();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&[0x01]),
+    ))
+    .unwrap();
+    assert_eq!(iface.release().0, expected);
+}
@@ -0,0 +1,71 @@
+//! Interfaces created from the builder presets put the conventional bytes on the bus
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::{CommandFraming, I2cInterfaceBuilder};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+fn check(builder: I2cInterfaceBuilder, expected: &[I2cTransaction]) {
+    let mut iface = builder.build(I2cMock::new(expected));
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xae, 0xaf])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x01, 0x02])).unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn ssd1306() {
+    check(
+        I2cInterfaceBuilder::ssd1306(),
+        &[
+            I2cTransaction::write(0x3c, vec![0x00, 0xae, 0xaf]),
+            I2cTransaction::write(0x3c, vec![0x40, 0x01, 0x02]),
+        ],
+    );
+}
+
+#[test]
+fn sh1106() {
+    check(
+        I2cInterfaceBuilder::sh1106(),
+        &[
+            I2cTransaction::write(0x3c, vec![0x00, 0xae, 0xaf]),
+            I2cTransaction::write(0x3c, vec![0x40, 0x01, 0x02]),
+        ],
+    );
+}
+
+#[test]
+fn overrides() {
+    check(
+        I2cInterfaceBuilder::ssd1306()
+            .address(0x3d)
+            .command_byte(0x80)
+            .data_byte(0xc0),
+        &[
+            I2cTransaction::write(0x3d, vec![0x80, 0xae, 0xaf]),
+            I2cTransaction::write(0x3d, vec![0xc0, 0x01, 0x02]),
+        ],
+    );
+
+    check(
+        I2cInterfaceBuilder::new(0x3c, 0x40).framing(CommandFraming::PerByteContinuation),
+        &[
+            I2cTransaction::write(0x3c, vec![0x80, 0xae, 0x80, 0xaf]),
+            I2cTransaction::write(0x3c, vec![0x40, 0x01, 0x02]),
+        ],
+    );
+}
+
+#[test]
+fn buffer() {
+    let frame = [0x55; 64];
+    let mut bytes = vec![0x40];
+    bytes.extend_from_slice(&frame);
+    let expected = [I2cTransaction::write(0x3c, bytes)];
+
+    let mut iface = I2cInterfaceBuilder::ssd1306()
+        .buffer::<65>()
+        .build(I2cMock::new(&expected));
+    assert_eq!(iface.max_slice_write_len(), Some(64));
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&frame)).unwrap();
+    iface.release().done();
+}
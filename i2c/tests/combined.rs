@@ -0,0 +1,131 @@
+//! Commands and data sent without a STOP condition in between
+
+mod common;
+
+use display_interface::DisplayError;
+use display_interface_i2c::{CommandFraming, I2cInterface, MAX_COMBINED_COMMANDS};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+const ADDR: u8 = 0x3c;
+
+#[test]
+fn single_transaction() {
+    let expected = [
+        I2cTransaction::transaction_start(ADDR),
+        I2cTransaction::write(ADDR, vec![0x80]),
+        I2cTransaction::write(ADDR, vec![0xb0]),
+        I2cTransaction::write(ADDR, vec![0x80]),
+        I2cTransaction::write(ADDR, vec![0x10]),
+        I2cTransaction::write(ADDR, vec![0x40]),
+        I2cTransaction::write(ADDR, vec![1, 2, 3]),
+        I2cTransaction::transaction_end(ADDR),
+    ];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    iface
+        .send_commands_and_data(&[0xb0, 0x10], &[1, 2, 3])
+        .unwrap();
+    iface.release().done();
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    block_on(iface.send_commands_and_data_async(&[0xb0, 0x10], &[1, 2, 3])).unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn discrete_writes() {
+    let expected = [
+        I2cTransaction::write(ADDR, vec![0x00, 0xb0, 0x10]),
+        I2cTransaction::write(ADDR, vec![0x40, 1, 2, 3]),
+    ];
+    let interface =
+        || I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40).with_combined_writes(false);
+
+    let mut iface = interface();
+    iface
+        .send_commands_and_data(&[0xb0, 0x10], &[1, 2, 3])
+        .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(iface.send_commands_and_data_async(&[0xb0, 0x10], &[1, 2, 3])).unwrap();
+    iface.release().done();
+}
+
+/// Send `0xb0, 0x10` and `1, 2, 3` with a command byte of `0x20`, framed as `framing`, through
+/// the combined and the fallback path, blocking and `async`
+fn both_paths(framing: CommandFraming, combined: &[I2cTransaction], fallback: &[I2cTransaction]) {
+    for (combined_writes, expected) in [(true, combined), (false, fallback)] {
+        let interface = || {
+            I2cInterface::new_with_control_bytes(I2cMock::new(expected), ADDR, 0x20, 0x40)
+                .with_command_framing(framing)
+                .with_combined_writes(combined_writes)
+        };
+
+        let mut iface = interface();
+        iface
+            .send_commands_and_data(&[0xb0, 0x10], &[1, 2, 3])
+            .unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        block_on(iface.send_commands_and_data_async(&[0xb0, 0x10], &[1, 2, 3])).unwrap();
+        iface.release().done();
+    }
+}
+
+#[test]
+fn configured_command_byte() {
+    // The command byte keeps its bits in both paths, combined it also has the continuation bit
+    both_paths(
+        CommandFraming::SingleControlByte,
+        &[
+            I2cTransaction::transaction_start(ADDR),
+            I2cTransaction::write(ADDR, vec![0xa0]),
+            I2cTransaction::write(ADDR, vec![0xb0]),
+            I2cTransaction::write(ADDR, vec![0xa0]),
+            I2cTransaction::write(ADDR, vec![0x10]),
+            I2cTransaction::write(ADDR, vec![0x40]),
+            I2cTransaction::write(ADDR, vec![1, 2, 3]),
+            I2cTransaction::transaction_end(ADDR),
+        ],
+        &[
+            I2cTransaction::write(ADDR, vec![0x20, 0xb0, 0x10]),
+            I2cTransaction::write(ADDR, vec![0x40, 1, 2, 3]),
+        ],
+    );
+}
+
+#[test]
+fn per_byte_continuation() {
+    // Both paths put the same control bytes in front of the commands
+    both_paths(
+        CommandFraming::PerByteContinuation,
+        &[
+            I2cTransaction::transaction_start(ADDR),
+            I2cTransaction::write(ADDR, vec![0x80]),
+            I2cTransaction::write(ADDR, vec![0xb0]),
+            I2cTransaction::write(ADDR, vec![0x80]),
+            I2cTransaction::write(ADDR, vec![0x10]),
+            I2cTransaction::write(ADDR, vec![0x40]),
+            I2cTransaction::write(ADDR, vec![1, 2, 3]),
+            I2cTransaction::transaction_end(ADDR),
+        ],
+        &[
+            I2cTransaction::write(ADDR, vec![0x80, 0xb0, 0x80, 0x10]),
+            I2cTransaction::write(ADDR, vec![0x40, 1, 2, 3]),
+        ],
+    );
+}
+
+#[test]
+fn too_many_commands() {
+    let mut iface = I2cInterface::new(I2cMock::new(&[]), ADDR, 0x40);
+    assert!(matches!(
+        iface.send_commands_and_data(&[0; MAX_COMBINED_COMMANDS + 1], &[]),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    iface.release().done();
+}
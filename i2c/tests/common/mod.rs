@@ -0,0 +1,94 @@
+//! Helpers shared by the integration tests
+
+use core::{
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use embedded_hal::i2c::{ErrorType, Operation};
+
+/// I2C bus recording every transaction as the list of its operations
+///
+/// Writes are recorded with `true`, reads with `false` and answered with `0x5a` bytes.
+#[allow(dead_code)]
+#[derive(Debug, Default, PartialEq)]
+pub struct Recorder(pub Vec<Vec<(bool, Vec<u8>)>>);
+
+impl ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+#[allow(dead_code)]
+impl Recorder {
+    fn record(&mut self, operations: &mut [Operation<'_>]) {
+        let ops = operations
+            .iter_mut()
+            .map(|op| match op {
+                Operation::Write(bytes) => (true, bytes.to_vec()),
+                Operation::Read(buf) => {
+                    buf.fill(0x5a);
+                    (false, buf.to_vec())
+                }
+            })
+            .collect();
+        self.0.push(ops);
+    }
+
+    /// The bytes written in every transaction
+    ///
+    /// Adjacent writes within a transaction are merged on the wire, so they are joined here.
+    pub fn writes(&self) -> Vec<Vec<u8>> {
+        self.0
+            .iter()
+            .map(|ops| {
+                ops.iter()
+                    .filter(|(write, _)| *write)
+                    .flat_map(|(_, bytes)| bytes.iter().copied())
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl embedded_hal::i2c::I2c for Recorder {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for Recorder {
+    async fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+/// Poll `future` to completion without an executor
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    let waker = unsafe { Waker::from_raw(clone(core::ptr::null())) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
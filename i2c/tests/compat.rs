@@ -0,0 +1,62 @@
+//! Interfaces built from embedded-hal 0.2 implementations
+
+#![cfg(feature = "eh02")]
+
+use display_interface::{DataFormat, DisplayError, ReadData, WriteOnlyDataCommand};
+use display_interface_i2c::I2cInterface;
+use embedded_hal_mock::eh0::{
+    i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+    MockError,
+};
+
+const ADDR: u8 = 0x3c;
+const DATA_BYTE: u8 = 0x40;
+
+#[test]
+fn commands_and_data_are_written() {
+    let expected = [
+        I2cTransaction::write(ADDR, vec![0x00, 0xaf]),
+        I2cTransaction::write(ADDR, vec![DATA_BYTE, 0x12, 0x34]),
+    ];
+    let mut iface = I2cInterface::new_eh02(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    iface.send_commands(DataFormat::U8(&[0xaf])).unwrap();
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+
+    iface.release().0.done();
+}
+
+#[test]
+fn commands_and_data_are_sent_separately() {
+    let expected = [
+        I2cTransaction::write(ADDR, vec![0x00, 0x2c]),
+        I2cTransaction::write(ADDR, vec![DATA_BYTE, 0xff]),
+    ];
+    let mut iface = I2cInterface::new_eh02(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    iface.send_commands_and_data(&[0x2c], &[0xff]).unwrap();
+
+    iface.release().0.done();
+}
+
+#[test]
+fn bus_errors_are_mapped() {
+    let expected = [I2cTransaction::write(ADDR, vec![0x00, 0xaf])
+        .with_error(MockError::Io(std::io::ErrorKind::Other))];
+    let mut iface = I2cInterface::new_eh02(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    let result = iface.send_commands(DataFormat::U8(&[0xaf]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    iface.release().0.done();
+}
+
+#[test]
+fn reads_are_unsupported() {
+    let mut iface = I2cInterface::new_eh02(I2cMock::new(&[]), ADDR, DATA_BYTE);
+
+    let result = iface.read_data(&mut [0; 2]);
+
+    assert!(matches!(result, Err(DisplayError::BusReadError)));
+    iface.release().0.done();
+}
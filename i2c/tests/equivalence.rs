@@ -0,0 +1,183 @@
+//! Slice formats and their iterator counterparts put identical writes on the bus
+//!
+//! Both are copied into the write buffer in chunks, so even the split into writes has to match.
+//! The one exception is [SliceStrategy::SingleTransaction], which sends long `U8` slices without
+//! chunking them; only the payload matches there.
+
+mod common;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::{CommandFraming, I2cInterface, SliceStrategy};
+use proptest::{collection::vec, prelude::*};
+
+use common::{block_on, Recorder};
+
+const DATA_BYTE: u8 = 0x40;
+
+/// The settings every pair of formats is checked with
+#[derive(Clone, Copy, Debug)]
+enum Setup {
+    Default,
+    Async,
+    /// A write buffer of an odd size, so 16-bit words have to be kept together
+    SmallBuffer,
+    PerByteContinuation,
+}
+
+const SETUPS: [Setup; 4] = [
+    Setup::Default,
+    Setup::Async,
+    Setup::SmallBuffer,
+    Setup::PerByteContinuation,
+];
+
+/// The outcome of a call, with the error as text since `DisplayError` can't be compared
+type Outcome = (Result<(), String>, Vec<Vec<u8>>);
+
+/// Send `format` as commands or data, with the error as text
+fn call(
+    iface: &mut impl WriteOnlyDataCommand,
+    commands: bool,
+    format: DataFormat<'_>,
+) -> Result<(), String> {
+    let result = if commands {
+        iface.send_commands(format)
+    } else {
+        iface.send_data(format)
+    };
+    result.map_err(|e| format!("{e:?}"))
+}
+
+/// Send `format` as commands or data through an interface set up as `setup` and capture the bus
+fn send(setup: Setup, commands: bool, format: DataFormat<'_>) -> Outcome {
+    let iface = I2cInterface::new(Recorder::default(), 0x3c, DATA_BYTE);
+
+    match setup {
+        Setup::Default => {
+            let mut iface = iface;
+            let result = call(&mut iface, commands, format);
+            (result, iface.release().writes())
+        }
+        Setup::Async => {
+            let mut iface = iface;
+            let result = if commands {
+                block_on(AsyncWriteOnlyDataCommand::send_commands(&mut iface, format))
+            } else {
+                block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, format))
+            };
+            (
+                result.map_err(|e| format!("{e:?}")),
+                iface.release().writes(),
+            )
+        }
+        Setup::SmallBuffer => {
+            let mut iface = iface.with_buffer_size::<5>();
+            let result = call(&mut iface, commands, format);
+            (result, iface.release().writes())
+        }
+        Setup::PerByteContinuation => {
+            let mut iface = iface.with_command_framing(CommandFraming::PerByteContinuation);
+            let result = call(&mut iface, commands, format);
+            (result, iface.release().writes())
+        }
+    }
+}
+
+/// Run `check` with every setup, for commands and for data
+fn each_setup(
+    mut check: impl FnMut(Setup, bool) -> Result<(), TestCaseError>,
+) -> Result<(), TestCaseError> {
+    for setup in SETUPS {
+        for commands in [false, true] {
+            check(setup, commands)?;
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn u8_slice_and_iter_match(bytes in vec(any::<u8>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U8(&bytes));
+            let iter = send(setup, commands, DataFormat::U8Iter(&mut bytes.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16be_slice_and_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16BE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16BEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16le_slice_and_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16LE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16LEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// `U16` is in native byte order, so it matches the iterator of the native order
+    #[test]
+    fn u16_slice_and_native_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16(&words));
+            let mut iter = words.iter().copied();
+            let iter = if cfg!(target_endian = "big") {
+                send(setup, commands, DataFormat::U16BEIter(&mut iter))
+            } else {
+                send(setup, commands, DataFormat::U16LEIter(&mut iter))
+            };
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// Long slices go out in a single write with one data byte, iterators are still chunked
+    #[test]
+    fn single_transaction_slices_match_in_payload_only(bytes in vec(any::<u8>(), 17..300)) {
+        let payload = |writes: Vec<Vec<u8>>| -> Vec<u8> {
+            writes.into_iter().flat_map(|w| w.into_iter().skip(1)).collect()
+        };
+        let mut slice = I2cInterface::new(Recorder::default(), 0x3c, DATA_BYTE)
+            .with_slice_strategy(SliceStrategy::SingleTransaction);
+        call(&mut slice, false, DataFormat::U8(&bytes)).unwrap();
+        let slice = slice.release().writes();
+
+        let mut iter = I2cInterface::new(Recorder::default(), 0x3c, DATA_BYTE)
+            .with_slice_strategy(SliceStrategy::SingleTransaction);
+        call(&mut iter, false, DataFormat::U8Iter(&mut bytes.iter().copied())).unwrap();
+        let iter = iter.release().writes();
+
+        prop_assert_eq!(slice.len(), 1);
+        prop_assert!(iter.len() > 1);
+        prop_assert_eq!(payload(slice), payload(iter));
+    }
+}
+
+/// 24-bit words aren't supported over I2C, whether in a slice or an iterator
+#[test]
+fn u24_is_unsupported_either_way() {
+    let unsupported = (Err("DataFormatNotImplemented".into()), vec![]);
+    each_setup(|setup, commands| {
+        let slice = send(setup, commands, DataFormat::U24(&[0x123456]));
+        let iter = send(
+            setup,
+            commands,
+            DataFormat::U24Iter(&mut [0x123456].into_iter()),
+        );
+        prop_assert_eq!(&slice, &unsupported, "{:?}", setup);
+        prop_assert_eq!(&iter, &unsupported, "{:?}", setup);
+        Ok(())
+    })
+    .unwrap();
+}
@@ -0,0 +1,155 @@
+//! Checks that the blocking and `async` implementations issue the same transactions
+
+mod common;
+
+use display_interface::{
+    AsyncReadData, AsyncWriteOnlyDataCommand, DataFormat, ReadData, WriteOnlyDataCommand,
+};
+use display_interface_i2c::{
+    CommandFraming, I2cInterface, I2cInterfaceBuilder, I2cRegisterInterface, SliceStrategy,
+};
+
+use common::{block_on, Recorder};
+
+fn words() -> Vec<u16> {
+    (0..50u16)
+        .map(|i| i.wrapping_mul(0x0101) ^ 0x1234)
+        .collect()
+}
+
+/// Run `calls` through both implementations and compare the recorded transactions
+fn check_both<T>(
+    make: impl Fn() -> T,
+    release: impl Fn(T) -> Recorder,
+    calls: impl Fn(&mut T, bool),
+) {
+    let mut iface = make();
+    calls(&mut iface, false);
+    let blocking = release(iface);
+
+    let mut iface = make();
+    calls(&mut iface, true);
+    let asynch = release(iface);
+
+    assert!(!blocking.0.is_empty());
+    assert_eq!(blocking, asynch);
+}
+
+/// Send `format` through the blocking or `async` implementation
+fn send<T: WriteOnlyDataCommand + AsyncWriteOnlyDataCommand>(
+    iface: &mut T,
+    data: bool,
+    format: DataFormat<'_>,
+    asynch: bool,
+) {
+    match (data, asynch) {
+        (false, false) => WriteOnlyDataCommand::send_commands(iface, format),
+        (true, false) => WriteOnlyDataCommand::send_data(iface, format),
+        (false, true) => block_on(AsyncWriteOnlyDataCommand::send_commands(iface, format)),
+        (true, true) => block_on(AsyncWriteOnlyDataCommand::send_data(iface, format)),
+    }
+    .unwrap();
+}
+
+fn send_all_formats<T: WriteOnlyDataCommand + AsyncWriteOnlyDataCommand>(
+    iface: &mut T,
+    asynch: bool,
+) {
+    let mut buf = words();
+    let bytes: Vec<u8> = buf.iter().map(|&w| w as u8).collect();
+    send(iface, false, DataFormat::U8(&bytes), asynch);
+    send(
+        iface,
+        false,
+        DataFormat::U8Iter(&mut bytes.iter().copied()),
+        asynch,
+    );
+    send(iface, true, DataFormat::U8(&bytes), asynch);
+    send(
+        iface,
+        true,
+        DataFormat::U8Iter(&mut bytes.iter().copied()),
+        asynch,
+    );
+    send(iface, true, DataFormat::U16(&buf), asynch);
+    send(iface, true, DataFormat::U16BE(&mut buf), asynch);
+    send(iface, true, DataFormat::U16LE(&mut buf), asynch);
+    send(
+        iface,
+        true,
+        DataFormat::U16BEIter(&mut words().into_iter()),
+        asynch,
+    );
+    send(
+        iface,
+        true,
+        DataFormat::U16LEIter(&mut words().into_iter()),
+        asynch,
+    );
+}
+
+#[test]
+fn formats_match() {
+    check_both(
+        || I2cInterface::new(Recorder::default(), 0x3c, 0x40),
+        I2cInterface::release,
+        send_all_formats,
+    );
+    check_both(
+        || {
+            I2cInterfaceBuilder::ssd1306()
+                .framing(CommandFraming::PerByteContinuation)
+                .buffer::<7>()
+                .build(Recorder::default())
+        },
+        I2cInterface::release,
+        send_all_formats,
+    );
+    check_both(
+        || {
+            I2cInterface::new(Recorder::default(), 0x3c, 0x40)
+                .with_slice_strategy(SliceStrategy::SingleTransaction)
+        },
+        I2cInterface::release,
+        send_all_formats,
+    );
+    check_both(
+        || I2cRegisterInterface::new(Recorder::default(), 0x70, 0x80, 0x00),
+        I2cRegisterInterface::release,
+        |iface, asynch| {
+            let bytes: Vec<u8> = (0..40).collect();
+            send(iface, false, DataFormat::U8(&bytes), asynch);
+            send(
+                iface,
+                true,
+                DataFormat::U8Iter(&mut bytes.iter().copied()),
+                asynch,
+            );
+        },
+    );
+}
+
+#[test]
+fn extras_match() {
+    check_both(
+        || I2cInterface::new(Recorder::default(), 0x3c, 0x40),
+        I2cInterface::release,
+        |iface, asynch| {
+            let mut frame = [0; 33];
+            let mut buf = [0; 2];
+            if asynch {
+                block_on(iface.send_commands_and_data_async(&[0xb0, 0x10], &[1, 2, 3])).unwrap();
+                block_on(iface.send_data_prefixed_async(&mut frame)).unwrap();
+                block_on(iface.read_status_async()).unwrap();
+                block_on(AsyncReadData::read_data(iface, &mut buf)).unwrap();
+            } else {
+                iface
+                    .send_commands_and_data(&[0xb0, 0x10], &[1, 2, 3])
+                    .unwrap();
+                iface.send_data_prefixed(&mut frame).unwrap();
+                iface.read_status().unwrap();
+                ReadData::read_data(iface, &mut buf).unwrap();
+            }
+        },
+    );
+}
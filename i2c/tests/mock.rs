@@ -0,0 +1,456 @@
+//! Exact I2C writes for the supported `DataFormat`s, checked with `embedded-hal-mock`
+
+mod common;
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, TypedWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+use display_interface_i2c::{CommandFraming, I2cInterface, I2cInterfaceError, SliceStrategy};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+const ADDR: u8 = 0x3c;
+const DATA_BYTE: u8 = 0x40;
+
+/// Writes of up to 16 bytes, each prefixed with `control_byte`
+fn expected(control_byte: u8, data: &[u8]) -> Vec<I2cTransaction> {
+    data.chunks(16)
+        .map(|chunk| {
+            let mut bytes = vec![control_byte];
+            bytes.extend_from_slice(chunk);
+            I2cTransaction::write(ADDR, bytes)
+        })
+        .collect()
+}
+
+/// Send `data` as slice and iterator through the blocking and `async` implementations
+fn check_data(data: &[u8]) {
+    let expected = expected(DATA_BYTE, data);
+    let interface = || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(data)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8Iter(&mut data.iter().copied()))
+        .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(data),
+    ))
+    .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8Iter(&mut data.iter().copied()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn data_chunk_boundaries() {
+    for len in [0, 1, 15, 16, 17, 32, 33, 1024] {
+        let data: Vec<u8> = (0..len).map(|i| i as u8).collect();
+        check_data(&data);
+    }
+}
+
+#[test]
+fn iterator_data_crosses_small_chunks() {
+    // Four payload bytes per write
+    for len in 0..=9 {
+        let data: Vec<u8> = (0..len).collect();
+        let expected: Vec<_> = data
+            .chunks(4)
+            .map(|chunk| I2cTransaction::write(ADDR, [&[DATA_BYTE], chunk].concat()))
+            .collect();
+        let interface =
+            || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE).with_buffer_size::<5>();
+
+        let mut iface = interface();
+        WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8Iter(&mut data.iter().copied()))
+            .unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        block_on(AsyncWriteOnlyDataCommand::send_data(
+            &mut iface,
+            DataFormat::U8Iter(&mut data.iter().copied()),
+        ))
+        .unwrap();
+        iface.release().done();
+    }
+}
+
+/// Send `cmds` as slice and iterator through the blocking and `async` implementations
+fn check_commands(cmds: &[u8]) {
+    let expected = expected(0x00, cmds);
+    let interface = || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(cmds)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8Iter(&mut cmds.iter().copied()))
+        .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(cmds),
+    ))
+    .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8Iter(&mut cmds.iter().copied()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn command_batches_of_every_length() {
+    for len in 0..=30 {
+        let cmds: Vec<u8> = (0xa0..0xa0 + len).collect();
+        check_commands(&cmds);
+    }
+}
+
+#[test]
+fn custom_command_control_byte() {
+    let cmds: Vec<u8> = (0..20).collect();
+    let expected = expected(0x80, &cmds);
+    let interface =
+        || I2cInterface::new_with_control_bytes(I2cMock::new(&expected), ADDR, 0x80, DATA_BYTE);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&cmds)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&cmds),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+fn check_framing(framing: CommandFraming, expected: &[I2cTransaction]) {
+    let cmds = [0xa8, 0x3f, 0xd3];
+    let interface =
+        || I2cInterface::new(I2cMock::new(expected), ADDR, DATA_BYTE).with_command_framing(framing);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&cmds)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&cmds),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn single_control_byte_framing() {
+    check_framing(
+        CommandFraming::SingleControlByte,
+        &[I2cTransaction::write(ADDR, vec![0x00, 0xa8, 0x3f, 0xd3])],
+    );
+}
+
+#[test]
+fn per_byte_continuation_framing() {
+    check_framing(
+        CommandFraming::PerByteContinuation,
+        &[I2cTransaction::write(
+            ADDR,
+            vec![0x80, 0xa8, 0x80, 0x3f, 0x80, 0xd3],
+        )],
+    );
+}
+
+#[test]
+fn per_byte_continuation_never_splits_pairs() {
+    let cmds: Vec<u8> = (0..9).collect();
+    let pairs: Vec<u8> = cmds.iter().flat_map(|&cmd| [0x80, cmd]).collect();
+    let expected: Vec<_> = pairs
+        .chunks(16)
+        .map(|chunk| I2cTransaction::write(ADDR, chunk.to_vec()))
+        .collect();
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE)
+        .with_command_framing(CommandFraming::PerByteContinuation);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&cmds)).unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn buffer_size_sets_transactions_per_frame() {
+    let frame: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+
+    let expected = expected(DATA_BYTE, &frame);
+    assert_eq!(expected.len(), 64);
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&frame)).unwrap();
+    iface.release().done();
+
+    let mut bytes = vec![DATA_BYTE];
+    bytes.extend_from_slice(&frame);
+    let expected = [I2cTransaction::write(ADDR, bytes)];
+    let interface =
+        || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE).with_buffer_size::<1025>();
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&frame)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8Iter(&mut frame.iter().copied()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+fn u16_words() -> Vec<u16> {
+    (0..20u16)
+        .map(|i| i.wrapping_mul(0x0101) ^ 0x1234)
+        .collect()
+}
+
+/// Send the 16-bit format built from `buf` through both implementations, expecting `bytes`
+macro_rules! check_u16 {
+    ($bytes:expr, $buf:ident => $format:expr) => {{
+        let expected = expected(DATA_BYTE, &$bytes);
+        let interface = || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+        let mut iface = interface();
+        #[allow(unused_mut)]
+        let mut $buf = u16_words();
+        WriteOnlyDataCommand::send_data(&mut iface, $format).unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        #[allow(unused_mut)]
+        let mut $buf = u16_words();
+        block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, $format)).unwrap();
+        iface.release().done();
+    }};
+}
+
+#[test]
+fn u16_formats() {
+    let be: Vec<u8> = u16_words().iter().flat_map(|w| w.to_be_bytes()).collect();
+    let le: Vec<u8> = u16_words().iter().flat_map(|w| w.to_le_bytes()).collect();
+    let ne: Vec<u8> = u16_words().iter().flat_map(|w| w.to_ne_bytes()).collect();
+    assert_eq!(&be[..2], &[0x12, 0x34]);
+
+    check_u16!(be, buf => DataFormat::U16BE(&mut buf));
+    check_u16!(le, buf => DataFormat::U16LE(&mut buf));
+    check_u16!(ne, buf => DataFormat::U16(&buf));
+    check_u16!(be, buf => DataFormat::U16BEIter(&mut buf.iter().copied()));
+    check_u16!(le, buf => DataFormat::U16LEIter(&mut buf.iter().copied()));
+}
+
+#[test]
+fn u16_words_are_never_split() {
+    // Room for the control byte and three more bytes, of which only two are used
+    let words = [0x1234u16, 0x5678, 0x9abc];
+    let expected = [
+        I2cTransaction::write(ADDR, vec![DATA_BYTE, 0x12, 0x34]),
+        I2cTransaction::write(ADDR, vec![DATA_BYTE, 0x56, 0x78]),
+        I2cTransaction::write(ADDR, vec![DATA_BYTE, 0x9a, 0xbc]),
+    ];
+
+    let interface =
+        || I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE).with_buffer_size::<4>();
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16BEIter(&mut words.iter().copied()),
+    )
+    .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16BEIter(&mut words.iter().copied()),
+    ))
+    .unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut words.clone())).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16BE(&mut words.clone()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn command_iterators_span_chunks() {
+    let cmds: Vec<u8> = (0x10..0x10 + 20).collect();
+    let pairs: Vec<u8> = cmds.iter().flat_map(|&cmd| [0x80, cmd]).collect();
+    let per_byte: Vec<_> = pairs
+        .chunks(16)
+        .map(|chunk| I2cTransaction::write(ADDR, chunk.to_vec()))
+        .collect();
+
+    for (framing, expected) in [
+        (CommandFraming::SingleControlByte, expected(0x00, &cmds)),
+        (CommandFraming::PerByteContinuation, per_byte),
+    ] {
+        let interface = || {
+            I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE)
+                .with_command_framing(framing)
+        };
+
+        let mut iface = interface();
+        WriteOnlyDataCommand::send_commands(
+            &mut iface,
+            DataFormat::U8Iter(&mut cmds.iter().copied()),
+        )
+        .unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        block_on(AsyncWriteOnlyDataCommand::send_commands(
+            &mut iface,
+            DataFormat::U8Iter(&mut cmds.iter().copied()),
+        ))
+        .unwrap();
+        iface.release().done();
+    }
+}
+
+#[test]
+fn single_transaction_slices() {
+    let frame: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+    let expected = [
+        I2cTransaction::transaction_start(ADDR),
+        I2cTransaction::write(ADDR, vec![DATA_BYTE]),
+        I2cTransaction::write(ADDR, frame.clone()),
+        I2cTransaction::transaction_end(ADDR),
+    ];
+    let interface = || {
+        I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE)
+            .with_slice_strategy(SliceStrategy::SingleTransaction)
+    };
+
+    let mut iface = interface();
+    assert_eq!(iface.max_slice_write_len(), None);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&frame)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&frame),
+    ))
+    .unwrap();
+    iface.release().done();
+
+    // Small slices still go through the write buffer
+    let expected = [I2cTransaction::write(ADDR, vec![DATA_BYTE, 1, 2, 3])];
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE)
+        .with_slice_strategy(SliceStrategy::SingleTransaction);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[1, 2, 3])).unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn chunked_slice_write_len() {
+    let mock = I2cMock::new(&[]);
+    let iface = I2cInterface::new(mock, ADDR, DATA_BYTE);
+    assert_eq!(iface.slice_strategy(), SliceStrategy::Chunked);
+    assert_eq!(iface.max_slice_write_len(), Some(16));
+
+    let iface = iface.with_buffer_size::<1025>();
+    assert_eq!(iface.max_slice_write_len(), Some(1024));
+    iface.release().done();
+}
+
+#[test]
+fn prefixed_buffer() {
+    let mut bytes = vec![DATA_BYTE];
+    bytes.extend((0..1024).map(|i| i as u8));
+    let expected = [I2cTransaction::write(ADDR, bytes.clone())];
+
+    let mut buf = bytes.clone();
+    buf[0] = 0xff;
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+    iface.send_data_prefixed(&mut buf).unwrap();
+    assert_eq!(buf, bytes);
+    iface.release().done();
+
+    let mut buf = bytes.clone();
+    buf[0] = 0xff;
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+    block_on(iface.send_data_prefixed_async(&mut buf)).unwrap();
+    assert_eq!(buf, bytes);
+    iface.release().done();
+
+    let mut iface = I2cInterface::new(I2cMock::new(&[]), ADDR, DATA_BYTE);
+    assert!(matches!(
+        iface.send_data_prefixed(&mut []),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    iface.release().done();
+}
+
+#[test]
+fn typed_errors_keep_the_bus_error() {
+    let nack = ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address);
+    let expected = [
+        I2cTransaction::write(ADDR, vec![0x00, 0xaf]).with_error(nack),
+        I2cTransaction::write(ADDR, vec![0x00, 0xaf]).with_error(nack),
+    ];
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, DATA_BYTE);
+
+    let result = iface.send_commands_typed(DataFormat::U8(&[0xaf]));
+    assert!(matches!(
+        result,
+        Err(I2cInterfaceError::I2c(ErrorKind::NoAcknowledge(
+            NoAcknowledgeSource::Address
+        )))
+    ));
+
+    let result = iface.send_commands_typed(DataFormat::U16(&[0]));
+    assert!(matches!(
+        result,
+        Err(I2cInterfaceError::Format(
+            DisplayError::DataFormatNotImplemented
+        ))
+    ));
+
+    let result = WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xaf]));
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    iface.release().done();
+}
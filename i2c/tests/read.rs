@@ -0,0 +1,55 @@
+//! Exact I2C transactions for reading back from the display
+
+mod common;
+
+use display_interface::{AsyncReadData, DisplayError, ReadData};
+use display_interface_i2c::I2cInterface;
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+const ADDR: u8 = 0x3c;
+
+#[test]
+fn read_status() {
+    let expected = [I2cTransaction::write_read(ADDR, vec![0x00], vec![0x40])];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    assert_eq!(iface.read_status().unwrap(), 0x40);
+    iface.release().done();
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    assert_eq!(block_on(iface.read_status_async()).unwrap(), 0x40);
+    iface.release().done();
+}
+
+#[test]
+fn read_data() {
+    let expected = [I2cTransaction::write_read(ADDR, vec![0x40], vec![1, 2, 3])];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    let mut buf = [0; 3];
+    ReadData::read_data(&mut iface, &mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    iface.release().done();
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    let mut buf = [0; 3];
+    block_on(AsyncReadData::read_data(&mut iface, &mut buf)).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    iface.release().done();
+}
+
+#[test]
+fn read_errors() {
+    let expected =
+        [I2cTransaction::write_read(ADDR, vec![0x00], vec![0]).with_error(ErrorKind::Other)];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40);
+    assert!(matches!(
+        iface.read_status(),
+        Err(DisplayError::BusReadError)
+    ));
+    iface.release().done();
+}
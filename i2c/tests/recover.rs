@@ -0,0 +1,52 @@
+//! Clocking a stuck bus free
+
+use display_interface_i2c::recover_bus;
+use embedded_hal_mock::eh1::{
+    delay::NoopDelay,
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+};
+
+/// The SCL transactions of `pulses` clock pulses after releasing SCL
+fn scl_pulses(pulses: usize) -> Vec<PinTransaction> {
+    let mut expected = vec![PinTransaction::set(State::High)];
+    for _ in 0..pulses {
+        expected.push(PinTransaction::set(State::Low));
+        expected.push(PinTransaction::set(State::High));
+    }
+    expected
+}
+
+#[test]
+fn idle_bus_is_not_clocked() {
+    let mut scl = PinMock::new(&scl_pulses(0));
+    let mut sda = PinMock::new(&[PinTransaction::get(State::High)]);
+
+    assert!(recover_bus(&mut scl, &mut sda, &mut NoopDelay::new()).unwrap());
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn sda_released_after_three_pulses() {
+    let mut scl = PinMock::new(&scl_pulses(3));
+    let mut sda = PinMock::new(&[
+        PinTransaction::get(State::Low),
+        PinTransaction::get(State::Low),
+        PinTransaction::get(State::Low),
+        PinTransaction::get(State::High),
+    ]);
+
+    assert!(recover_bus(&mut scl, &mut sda, &mut NoopDelay::new()).unwrap());
+    scl.done();
+    sda.done();
+}
+
+#[test]
+fn sda_stuck_after_nine_pulses() {
+    let mut scl = PinMock::new(&scl_pulses(9));
+    let mut sda = PinMock::new(&vec![PinTransaction::get(State::Low); 10]);
+
+    assert!(!recover_bus(&mut scl, &mut sda, &mut NoopDelay::new()).unwrap());
+    scl.done();
+    sda.done();
+}
@@ -0,0 +1,61 @@
+//! Exact I2C writes of the register-addressed interface
+
+mod common;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::I2cRegisterInterface;
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+const ADDR: u8 = 0x70;
+
+#[test]
+fn commands_and_data() {
+    let expected = [
+        I2cTransaction::write(ADDR, vec![0x80, 0x21]),
+        I2cTransaction::write(ADDR, vec![0x00, 0x01, 0x02, 0x03]),
+    ];
+    let interface = || I2cRegisterInterface::new(I2cMock::new(&expected), ADDR, 0x80, 0x00);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x21])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[1, 2, 3])).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8Iter(&mut [0x21].into_iter()),
+    ))
+    .unwrap();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&[1, 2, 3]),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn long_data_advances_the_register() {
+    let data: Vec<u8> = (0..40).collect();
+    let expected = [
+        I2cTransaction::write(ADDR, [&[0x10][..], &data[..16]].concat()),
+        I2cTransaction::write(ADDR, [&[0x20][..], &data[16..32]].concat()),
+        I2cTransaction::write(ADDR, [&[0x30][..], &data[32..]].concat()),
+    ];
+    let interface = || I2cRegisterInterface::new(I2cMock::new(&expected), ADDR, 0x80, 0x10);
+
+    let mut iface = interface();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&data)).unwrap();
+    iface.release().done();
+
+    let mut iface = interface();
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8Iter(&mut data.iter().copied()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
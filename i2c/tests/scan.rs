@@ -0,0 +1,42 @@
+//! Finding displays on the bus and switching between them
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_i2c::{scan, I2cInterface};
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+fn nack(addr: u8) -> I2cTransaction {
+    I2cTransaction::write(addr, vec![])
+        .with_error(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address))
+}
+
+#[test]
+fn scan_reports_acknowledging_addresses() {
+    let mut expected = vec![];
+    for addr in 0x08..=0x77 {
+        expected.push(match addr {
+            0x3c | 0x3d => I2cTransaction::write(addr, vec![]),
+            _ => nack(addr),
+        });
+    }
+
+    // Reserved addresses never reach the bus
+    let mut i2c = I2cMock::new(&expected);
+    let found: Vec<u8> = scan(&mut i2c, 0..=0xff).collect();
+    assert_eq!(found, [0x3c, 0x3d]);
+    i2c.done();
+}
+
+#[test]
+fn set_address() {
+    let expected = [
+        I2cTransaction::write(0x3c, vec![0x00, 0xaf]),
+        I2cTransaction::write(0x3d, vec![0x00, 0xaf]),
+    ];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), 0x3c, 0x40);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xaf])).unwrap();
+    iface.set_address(0x3d);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0xaf])).unwrap();
+    iface.release().done();
+}
@@ -0,0 +1,109 @@
+//! The word-based traits put the same bytes on the bus as the `DataFormat`-based ones
+
+mod common;
+
+use display_interface::{
+    AsyncWordWriteOnlyDataCommand, AsyncWriteOnlyDataCommand, DataFormat, WordWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+use display_interface_i2c::{CommandFraming, I2cInterface, SliceStrategy};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+use common::block_on;
+
+const ADDR: u8 = 0x3c;
+
+/// Record the writes of a `DataFormat` based command and data call
+fn reference(framing: CommandFraming, cmds: &[u8], data: &[u8]) -> Vec<I2cTransaction> {
+    let mut recorded = vec![];
+    let mut record = |bytes: &[u8]| recorded.push(I2cTransaction::write(ADDR, bytes.to_vec()));
+
+    let prefixed = |control: u8, chunk: &[u8]| [&[control][..], chunk].concat();
+    match framing {
+        CommandFraming::SingleControlByte => cmds
+            .chunks(16)
+            .for_each(|chunk| record(&prefixed(0x00, chunk))),
+        CommandFraming::PerByteContinuation => {
+            let pairs: Vec<u8> = cmds.iter().flat_map(|&cmd| [0x80, cmd]).collect();
+            pairs.chunks(16).for_each(&mut record);
+        }
+    }
+    data.chunks(16)
+        .for_each(|chunk| record(&prefixed(0x40, chunk)));
+
+    // Make sure the reference matches the `DataFormat` based path
+    let mut iface =
+        I2cInterface::new(I2cMock::new(&recorded), ADDR, 0x40).with_command_framing(framing);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(cmds)).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(data)).unwrap();
+    iface.release().done();
+
+    recorded
+}
+
+#[test]
+fn word_and_format_paths_match() {
+    let cmds: Vec<u8> = (0xa0..0xb4).collect();
+    let data: Vec<u8> = (0..40).collect();
+
+    for framing in [
+        CommandFraming::SingleControlByte,
+        CommandFraming::PerByteContinuation,
+    ] {
+        let expected = reference(framing, &cmds, &data);
+        let interface =
+            || I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40).with_command_framing(framing);
+
+        let mut iface = interface();
+        WordWriteOnlyDataCommand::send_command_slice(&mut iface, &cmds).unwrap();
+        WordWriteOnlyDataCommand::send_data_slice(&mut iface, &data).unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        WordWriteOnlyDataCommand::send_command_iter(&mut iface, cmds.iter().copied()).unwrap();
+        WordWriteOnlyDataCommand::send_data_iter(&mut iface, data.iter().copied()).unwrap();
+        iface.release().done();
+
+        let mut iface = interface();
+        block_on(AsyncWordWriteOnlyDataCommand::send_command_slice(
+            &mut iface, &cmds,
+        ))
+        .unwrap();
+        block_on(AsyncWordWriteOnlyDataCommand::send_data_iter(
+            &mut iface,
+            data.iter().copied(),
+        ))
+        .unwrap();
+        iface.release().done();
+
+        // The async `DataFormat` path for completeness
+        let mut iface = interface();
+        block_on(AsyncWriteOnlyDataCommand::send_commands(
+            &mut iface,
+            DataFormat::U8(&cmds),
+        ))
+        .unwrap();
+        block_on(AsyncWriteOnlyDataCommand::send_data(
+            &mut iface,
+            DataFormat::U8(&data),
+        ))
+        .unwrap();
+        iface.release().done();
+    }
+}
+
+#[test]
+fn data_slices_use_single_transaction() {
+    let data: Vec<u8> = (0..40).collect();
+    let expected = [
+        I2cTransaction::transaction_start(ADDR),
+        I2cTransaction::write(ADDR, vec![0x40]),
+        I2cTransaction::write(ADDR, data.clone()),
+        I2cTransaction::transaction_end(ADDR),
+    ];
+
+    let mut iface = I2cInterface::new(I2cMock::new(&expected), ADDR, 0x40)
+        .with_slice_strategy(SliceStrategy::SingleTransaction);
+    WordWriteOnlyDataCommand::send_data_slice(&mut iface, &data).unwrap();
+    iface.release().done();
+}
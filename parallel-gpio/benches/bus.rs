@@ -0,0 +1,99 @@
+//! Time per word of `BsrrBus8` versus `Generic8BitBus`, and of solid fills through
+//! `PGpio8BitInterface` with and without the strobe-only path for runs of identical words
+//!
+//! All buses write to plain memory here instead of GPIO registers, with every pin of the
+//! generic bus doing a volatile write of its own like a HAL's `OutputPin` would. Run with
+//! `cargo bench --bench bus --features cortex-m`; the numbers are only meaningful relative to
+//! each other.
+
+use core::{convert::Infallible, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use display_interface_parallel_gpio::{
+    BsrrBus8, DataFormat, DisplayError, Generic8BitBus, OutputBus, PGpio8BitInterface,
+    WriteOnlyDataCommand,
+};
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+/// Words of a solid fill
+const WORDS: usize = 64 * 64;
+
+struct Pin(*mut u32, u32);
+
+impl ErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        unsafe { core::ptr::write_volatile(self.0, 1 << (self.1 + 16)) };
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        unsafe { core::ptr::write_volatile(self.0, 1 << self.1) };
+        Ok(())
+    }
+}
+
+fn set_values(c: &mut Criterion, name: &str, bus: &mut impl OutputBus<Word = u8>) {
+    c.benchmark_group("set_value")
+        .throughput(Throughput::Elements(2))
+        .bench_function(name, |b| {
+            // Alternate between values so every pin changes
+            b.iter(|| {
+                bus.set_value(black_box(0x55)).unwrap();
+                bus.set_value(black_box(0xaa)).unwrap();
+            })
+        });
+}
+
+/// Bus forwarding only [OutputBus::set_value], so every word of a run goes through it
+struct PerWord<BUS>(BUS);
+
+impl<BUS: OutputBus> OutputBus for PerWord<BUS> {
+    type Word = BUS::Word;
+
+    fn set_value(&mut self, value: BUS::Word) -> Result<(), DisplayError> {
+        self.0.set_value(value)
+    }
+}
+
+fn fill(c: &mut Criterion, name: &str, iface: &mut impl WriteOnlyDataCommand) {
+    c.benchmark_group("solid_fill")
+        .throughput(Throughput::Elements(WORDS as u64))
+        .bench_function(name, |b| {
+            b.iter(|| {
+                iface.send_data(DataFormat::U8Iter(
+                    &mut core::iter::repeat(black_box(0x55)).take(WORDS),
+                ))
+            })
+        });
+}
+
+fn generic_bus(bsrr: *mut u32) -> Generic8BitBus<Pin, Pin, Pin, Pin, Pin, Pin, Pin, Pin> {
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = core::array::from_fn(|n| Pin(bsrr, n as u32));
+    Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7))
+}
+
+fn buses(c: &mut Criterion) {
+    let mut bsrr = 0u32;
+    let bsrr: *mut u32 = &mut bsrr;
+
+    set_values(c, "Generic8BitBus", &mut generic_bus(bsrr));
+    set_values(c, "BsrrBus8", &mut unsafe { BsrrBus8::new(bsrr, 0, 8) });
+
+    fill(
+        c,
+        "per word",
+        &mut PGpio8BitInterface::new(PerWord(generic_bus(bsrr)), Pin(bsrr, 8), Pin(bsrr, 9)),
+    );
+    fill(
+        c,
+        "strobe only",
+        &mut PGpio8BitInterface::new(generic_bus(bsrr), Pin(bsrr, 8), Pin(bsrr, 9)),
+    );
+}
+
+criterion_group!(benches, buses);
+criterion_main!(benches);
@@ -0,0 +1,61 @@
+//! Bytes per second through the 8-bit and 16-bit interfaces for every supported `DataFormat`
+//!
+//! The interfaces write to a `NullOutputBus` with `NullPin`s, so this measures the overhead of
+//! the interfaces themselves: splitting and swapping words and driving the strobe through its
+//! trait. Run with `cargo bench --bench throughput`. See `write_pairs` for timing on a target.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use display_interface_parallel_gpio::{
+    DataFormat, NullOutputBus, NullPin, PGpio16BitInterface, PGpio8BitInterface,
+    WriteOnlyDataCommand,
+};
+
+/// One 64x64 tile of RGB565 pixels
+const PIXELS: usize = 64 * 64;
+const BYTES: usize = PIXELS * 2;
+
+fn send_all_formats(c: &mut Criterion, group: &str, iface: &mut impl WriteOnlyDataCommand) {
+    let bytes = [0x5au8; BYTES];
+    let pixels = [0xa55au16; PIXELS];
+    let mut swapped = pixels;
+
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    group.bench_function("U8", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8(black_box(&bytes))))
+    });
+    group.bench_function("U16", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16(black_box(&pixels))))
+    });
+    group.bench_function("U16BE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BE(black_box(&mut swapped))))
+    });
+    group.bench_function("U16LE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LE(black_box(&mut swapped))))
+    });
+    group.bench_function("U8Iter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8Iter(&mut black_box(bytes).into_iter())))
+    });
+    group.bench_function("U16BEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BEIter(&mut black_box(pixels).into_iter())))
+    });
+    group.bench_function("U16LEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LEIter(&mut black_box(pixels).into_iter())))
+    });
+
+    group.finish();
+}
+
+fn formats(c: &mut Criterion) {
+    let mut eight = PGpio8BitInterface::new(NullOutputBus::<u8>::new(), NullPin, NullPin);
+    send_all_formats(c, "pgpio8", &mut eight);
+
+    let mut sixteen = PGpio16BitInterface::new(NullOutputBus::<u16>::new(), NullPin, NullPin);
+    send_all_formats(c, "pgpio16", &mut sixteen);
+}
+
+criterion_group!(benches, formats);
+criterion_main!(benches);
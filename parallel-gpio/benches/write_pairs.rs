@@ -0,0 +1,79 @@
+//! Time per 16-bit word sent through `PGpio8BitInterface`, i.e. the byte splitting hot path
+//!
+//! The bus only counts the bytes set and the pins do nothing, so this measures the overhead of
+//! the interface itself. Run with `cargo bench --bench write_pairs`; criterion compares every run
+//! with the previous one.
+//!
+//! To measure on a target instead, enable the DWT cycle counter (`DCB::enable_trace` and
+//! `DWT::enable_cycle_counter` with the `cortex-m` crate) and read `DWT::cycle_count` around a
+//! `send_data` of a full frame, using the board's real bus and pins. The Cortex-M0+ has no DWT
+//! cycle counter; use SysTick counting down from its reload value there.
+
+use core::{convert::Infallible, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, OutputBus, PGpio8BitInterface, WriteOnlyDataCommand,
+};
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+const WORDS: usize = 1 << 16;
+
+/// Bus counting the bytes set, summing them so the values can't be optimized away
+#[derive(Default)]
+struct CountingBus(usize, u8);
+
+impl OutputBus for CountingBus {
+    type Word = u8;
+
+    fn set_value(&mut self, value: u8) -> Result<(), DisplayError> {
+        self.0 += 1;
+        self.1 = self.1.wrapping_add(value);
+        Ok(())
+    }
+}
+
+struct Pin;
+
+impl ErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+fn pairs(c: &mut Criterion) {
+    let mut words: Vec<u16> = (0..=u16::MAX).collect();
+    let mut iface = PGpio8BitInterface::new(CountingBus::default(), Pin, Pin);
+
+    let mut group = c.benchmark_group("write_pairs");
+    group.throughput(Throughput::Elements(WORDS as u64));
+    group.bench_function("U16", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16(black_box(&words))))
+    });
+    group.bench_function("U16BE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BE(black_box(&mut words))))
+    });
+    group.bench_function("U16LE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LE(black_box(&mut words))))
+    });
+    group.bench_function("U16BEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BEIter(&mut black_box(0..=u16::MAX))))
+    });
+    group.finish();
+
+    // Every word went out as two bytes
+    let (bus, _, _) = iface.release();
+    assert_eq!(bus.0 % (2 * WORDS), 0);
+    black_box(bus.1);
+}
+
+criterion_group!(benches, pairs);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Measure how fast a parallel display can be driven through the Linux GPIO character device
+//!
+//! The wiring below matches a common 8-bit ILI9341 hat on a Raspberry Pi; adjust the line
+//! offsets to your board. Run with
+//!
+//! ```sh
+//! cargo run --release --example cdev_throughput --features linux -- /dev/gpiochip0
+//! ```
+
+use std::time::Instant;
+
+use display_interface_parallel_gpio::{
+    CdevBus8, CdevPin, DataFormat, PGpio8BitInterface, WriteOnlyDataCommand,
+};
+use gpio_cdev::Chip;
+
+/// Line offsets of D0 to D7
+const DATA: [u32; 8] = [5, 6, 12, 13, 16, 19, 20, 21];
+const DC: u32 = 24;
+const WR: u32 = 25;
+
+/// Words per run, a 320x240 frame at 16 bits per pixel
+const WORDS: usize = 320 * 240 * 2;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args().nth(1).unwrap_or("/dev/gpiochip0".into());
+    let mut chip = Chip::new(path)?;
+
+    let bus = CdevBus8::request(&mut chip, &DATA, "cdev_throughput")?;
+    let dc = CdevPin::request(&mut chip, DC, true, "cdev_throughput")?;
+    let wr = CdevPin::request(&mut chip, WR, true, "cdev_throughput")?;
+    let mut iface = PGpio8BitInterface::new(bus, dc, wr);
+
+    // Memory write, then a pattern changing on every word so no write is skipped
+    iface
+        .send_commands(DataFormat::U8(&[0x2c]))
+        .map_err(|e| format!("{e:?}"))?;
+    let start = Instant::now();
+    iface
+        .send_data(DataFormat::U8Iter(&mut (0..WORDS).map(|i| i as u8)))
+        .map_err(|e| format!("{e:?}"))?;
+    let elapsed = start.elapsed();
+
+    println!(
+        "{WORDS} words in {elapsed:?}: {:.0} words/s, {:.2} frames/s",
+        WORDS as f64 / elapsed.as_secs_f64(),
+        1.0 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! `async` implementations of the parallel interfaces
+//!
+//! The pins are still driven by blocking `OutputPin` calls, so a single word is written just as
+//! fast as with the blocking implementation. To keep a long framebuffer push from monopolizing
+//! the executor, the interfaces yield to it every `yield_every` words, see
+//! [PGpio8BitInterface::with_yield_every]. Each yield lets other tasks run, at the cost of a
+//! pause in the transfer and a round trip through the executor; the default of 0 never yields.
+
+use core::{
+    future::Future,
+    iter::Peekable,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    format::{Bytes, Words},
+    release_on_error, set_dc, OutputBus, PGpio16BitInterface, PGpio8BitInterface, Result,
+    U8Packing, WriteEnable,
+};
+
+/// Future which returns `Pending` once, giving the executor a chance to run other tasks
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Write `iter` in batches of `yield_every` words with `write`, yielding to the executor
+/// between batches
+async fn write_batched<I: Iterator>(
+    iter: I,
+    yield_every: usize,
+    mut write: impl FnMut(&mut Peekable<I>, usize) -> Result,
+) -> Result {
+    let mut iter = iter.peekable();
+    if yield_every == 0 {
+        return write(&mut iter, usize::MAX);
+    }
+
+    loop {
+        write(&mut iter, yield_every)?;
+        if iter.peek().is_none() {
+            return Ok(());
+        }
+        YieldNow(false).await;
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn write_iter(&mut self, iter: impl Iterator<Item = u8>) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = bus.write_words_iter(iter, &mut strobe);
+        release_on_error(&mut strobe, result)
+    }
+
+    async fn write_iter_async(&mut self, iter: impl Iterator<Item = u8>) -> Result {
+        write_batched(iter, self.yield_every, |iter, n| {
+            self.write_iter(iter.take(n))
+        })
+        .await
+    }
+
+    async fn write_data_async(&mut self, data: DataFormat<'_>) -> Result {
+        self.write_iter_async(Bytes::new(data)?).await
+    }
+}
+
+impl<BUS, DC, WR, D> AsyncWriteOnlyDataCommand for PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.write_data_async(cmds).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, true)?;
+        self.write_data_async(buf).await
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn write_iter(&mut self, iter: impl Iterator<Item = u16>) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = bus.write_words_iter(iter, &mut strobe);
+        release_on_error(&mut strobe, result)
+    }
+
+    async fn write_iter_async(&mut self, iter: impl Iterator<Item = u16>) -> Result {
+        write_batched(iter, self.yield_every, |iter, n| {
+            self.write_iter(iter.take(n))
+        })
+        .await
+    }
+
+    async fn write_data_async(&mut self, data: DataFormat<'_>, packing: U8Packing) -> Result {
+        let mut words = Words::new(data, packing)?;
+        self.write_iter_async(&mut words).await?;
+        words.finish()
+    }
+}
+
+impl<BUS, DC, WR, D> AsyncWriteOnlyDataCommand for PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.write_data_async(cmds, U8Packing::ZeroExtend).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, true)?;
+        self.write_data_async(buf, self.u8_packing).await
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{OutputBus, Result};
+
+macro_rules! bsrr_bus {
+    ($(#[$meta:meta])* $BsrrBus:ident { type Word = $Word:ident; }) => {
+        $(#[$meta])*
+        pub struct $BsrrBus {
+            bsrr: *mut u32,
+            offset: u32,
+            width: u32,
+        }
+
+        impl $BsrrBus {
+            /// Creates a new bus for the `width` pins starting at pin `offset` of the port
+            /// whose `BSRR` register is at `bsrr`. This does not change the state of the pins.
+            ///
+            /// # Safety
+            ///
+            /// `bsrr` must point to the bit set/reset register of a GPIO port for as long as the
+            /// bus is used, with pins `offset` to `offset + width - 1` of that port configured as
+            /// outputs. Nothing else may drive these pins while the bus exists; other pins of the
+            /// port are never touched.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `width` is 0 or exceeds the word size, or if the pins don't fit into a
+            /// 16-pin port.
+            pub unsafe fn new(bsrr: *mut u32, offset: u32, width: u32) -> Self {
+                assert!(
+                    width > 0 && width <= $Word::BITS && offset + width <= 16,
+                    "the bus pins must fit into the word and the port"
+                );
+                Self {
+                    bsrr,
+                    offset,
+                    width,
+                }
+            }
+        }
+
+        impl OutputBus for $BsrrBus {
+            type Word = $Word;
+
+            /// Bits of `value` beyond the width of the bus are ignored
+            fn set_value(&mut self, value: Self::Word) -> Result {
+                let mask = ((1 << self.width) - 1) << self.offset;
+                let set = (u32::from(value) << self.offset) & mask;
+
+                // Safety: guaranteed by the contract of `new`
+                unsafe { core::ptr::write_volatile(self.bsrr, ((mask & !set) << 16) | set) };
+                Ok(())
+            }
+        }
+    };
+}
+
+bsrr_bus! {
+    /// An implementation of [OutputBus] writing the `BSRR` register of a GPIO port, for buses of
+    /// up to 8 bits
+    ///
+    /// STM32 and similar Cortex-M parts have a write-only `BSRR` register per GPIO port, which
+    /// sets the pins whose bits are written as 1 in its lower half and resets those written as 1
+    /// in its upper half. Every value thus takes a single volatile write, instead of one
+    /// `OutputPin` call per changed pin.
+    BsrrBus8 {
+        type Word = u8;
+    }
+}
+
+bsrr_bus! {
+    /// An implementation of [OutputBus] writing the `BSRR` register of a GPIO port, for buses of
+    /// up to 16 bits
+    ///
+    /// See [BsrrBus8] for details.
+    BsrrBus16 {
+        type Word = u16;
+    }
+}
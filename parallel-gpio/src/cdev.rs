@@ -0,0 +1,155 @@
+//! Buses and pins on the Linux GPIO character device
+//!
+//! See [CdevBus8] for the expected throughput.
+
+use embedded_hal::digital::{self, ErrorKind, ErrorType, OutputPin};
+use gpio_cdev::{Chip, LineHandle, LineRequestFlags, MultiLineHandle};
+
+use crate::{DisplayError, OutputBus, Result};
+
+macro_rules! cdev_bus {
+    ($(#[$meta:meta])* $CdevBus:ident { type Word = $Word:ident; }) => {
+        $(#[$meta])*
+        pub struct $CdevBus {
+            lines: MultiLineHandle,
+            last: Option<$Word>,
+        }
+
+        impl $CdevBus {
+            /// Creates a new bus from `lines`, which must have been requested as outputs
+            ///
+            /// The first line is bit 0 of the bus. This does not change the state of the lines.
+            ///
+            /// # Panics
+            ///
+            /// Panics if there are more lines than bits in the word.
+            pub fn new(lines: MultiLineHandle) -> Self {
+                assert!(
+                    lines.num_lines() <= $Word::BITS as usize,
+                    "too many lines for the bus width"
+                );
+                Self { lines, last: None }
+            }
+
+            /// Requests the lines at `offsets` of `chip` as outputs and creates a bus from them
+            ///
+            /// The lines are driven low. See [Self::new] for the order of the lines and when this
+            /// panics.
+            pub fn request(
+                chip: &mut Chip,
+                offsets: &[u32],
+                consumer: &str,
+            ) -> core::result::Result<Self, gpio_cdev::Error> {
+                assert!(
+                    offsets.len() <= $Word::BITS as usize,
+                    "too many lines for the bus width"
+                );
+                let lines = chip.get_lines(offsets)?.request(
+                    LineRequestFlags::OUTPUT,
+                    &[0; $Word::BITS as usize][..offsets.len()],
+                    consumer,
+                )?;
+                Ok(Self::new(lines))
+            }
+
+            /// Consumes the bus and returns the line handle. This does not change the state of
+            /// the lines.
+            pub fn release(self) -> MultiLineHandle {
+                self.lines
+            }
+        }
+
+        impl OutputBus for $CdevBus {
+            type Word = $Word;
+
+            fn set_value(&mut self, value: Self::Word) -> Result {
+                // Repeated words are common when filling, and cost a whole syscall otherwise
+                if self.last == Some(value) {
+                    return Ok(());
+                }
+                self.last = None;
+
+                let mut levels = [0; $Word::BITS as usize];
+                let levels = &mut levels[..self.lines.num_lines()];
+                for (bit, level) in levels.iter_mut().enumerate() {
+                    *level = ((value >> bit) & 1) as u8;
+                }
+                self.lines
+                    .set_values(levels)
+                    .map_err(|_| DisplayError::BusWriteError)?;
+
+                self.last = Some(value);
+                Ok(())
+            }
+
+            fn invalidate(&mut self) {
+                self.last = None;
+            }
+        }
+    };
+}
+
+cdev_bus! {
+    /// An implementation of [OutputBus] setting up to 8 lines of a GPIO character device at once
+    ///
+    /// Every value put on the bus is a single `ioctl` on a multi-line handle, instead of one per
+    /// changed pin. Each `ioctl` costs in the order of a microsecond or two on typical single
+    /// board computers, and the interfaces spend three of them per word: asserting WR, setting
+    /// the bus and releasing WR. That's a few hundred thousand words per second, i.e. several
+    /// frames per second for a 320x240 display on a 16-bit bus, compared to a fraction of a frame
+    /// with one `ioctl` per pin. The `cdev_throughput` example measures the actual rate.
+    CdevBus8 {
+        type Word = u8;
+    }
+}
+
+cdev_bus! {
+    /// An implementation of [OutputBus] setting up to 16 lines of a GPIO character device at once
+    ///
+    /// See [CdevBus8] for details.
+    CdevBus16 {
+        type Word = u16;
+    }
+}
+
+/// Error of a [CdevPin]
+#[derive(Debug)]
+pub struct CdevError(pub gpio_cdev::Error);
+
+impl digital::Error for CdevError {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// `OutputPin` on a single line of a GPIO character device, e.g. for the D/C and WR pins
+pub struct CdevPin(pub LineHandle);
+
+impl CdevPin {
+    /// Requests the line at `offset` of `chip` as an output, driven to `high`
+    pub fn request(
+        chip: &mut Chip,
+        offset: u32,
+        high: bool,
+        consumer: &str,
+    ) -> core::result::Result<Self, gpio_cdev::Error> {
+        let line =
+            chip.get_line(offset)?
+                .request(LineRequestFlags::OUTPUT, high.into(), consumer)?;
+        Ok(Self(line))
+    }
+}
+
+impl ErrorType for CdevPin {
+    type Error = CdevError;
+}
+
+impl OutputPin for CdevPin {
+    fn set_low(&mut self) -> core::result::Result<(), CdevError> {
+        self.0.set_value(0).map_err(CdevError)
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), CdevError> {
+        self.0.set_value(1).map_err(CdevError)
+    }
+}
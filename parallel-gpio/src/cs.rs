@@ -0,0 +1,179 @@
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError, LowPower, ReadData, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{NoDelay, OutputBus, PGpio16BitInterface, PGpio8BitInterface, Result, WriteEnable};
+
+/// Parallel communication interface with a chip select pin
+///
+/// This wraps any of the parallel interfaces and pulls the low-active chip select pin low around
+/// every call, releasing it high again afterwards even if the call failed. Several displays can
+/// thus share the data bus, each selected by its own chip select pin.
+///
+/// ```
+/// # use display_interface_parallel_gpio::{OutputBus, PGpio8BitInterface, PGpio8BitInterfaceWithCs};
+/// # use embedded_hal::digital::OutputPin;
+/// # fn f<BUS: OutputBus<Word = u8>, P: OutputPin>(bus: BUS, dc: P, wr: P, cs: P) {
+/// let iface = PGpio8BitInterfaceWithCs::new(PGpio8BitInterface::new(bus, dc, wr), cs);
+/// # }
+/// ```
+pub struct PGpioInterfaceWithCs<IFACE, CS> {
+    iface: IFACE,
+    cs: CS,
+}
+
+/// [PGpio8BitInterface] with a chip select pin
+pub type PGpio8BitInterfaceWithCs<BUS, DC, WR, CS, D = NoDelay> =
+    PGpioInterfaceWithCs<PGpio8BitInterface<BUS, DC, WR, D>, CS>;
+
+/// [PGpio16BitInterface] with a chip select pin
+pub type PGpio16BitInterfaceWithCs<BUS, DC, WR, CS, D = NoDelay> =
+    PGpioInterfaceWithCs<PGpio16BitInterface<BUS, DC, WR, D>, CS>;
+
+impl<IFACE, CS> PGpioInterfaceWithCs<IFACE, CS>
+where
+    CS: OutputPin,
+{
+    /// Create new parallel GPIO interface selecting the display with `cs`
+    ///
+    /// The chip select pin is expected to be high, i.e. the display deselected.
+    pub fn new(iface: IFACE, cs: CS) -> Self {
+        Self { iface, cs }
+    }
+
+    /// Consume the display interface and return
+    /// the wrapped interface and chip select pin
+    pub fn release(self) -> (IFACE, CS) {
+        (self.iface, self.cs)
+    }
+
+    /// Run `f` on the wrapped interface with chip select asserted
+    fn selected<T>(&mut self, f: impl FnOnce(&mut IFACE) -> Result<T>) -> Result<T> {
+        let cs = CsGuard::assert(&mut self.cs)?;
+        let result = f(&mut self.iface);
+        let deselected = cs.deassert();
+        let value = result?;
+        deselected.map(|_| value)
+    }
+}
+
+/// Asserted chip select which is deasserted again when dropped
+///
+/// This keeps chip select from being left asserted when an `async` send is cancelled half way.
+pub(crate) struct CsGuard<'a, CS: OutputPin>(Option<&'a mut CS>);
+
+impl<'a, CS: OutputPin> CsGuard<'a, CS> {
+    /// Assert chip select
+    pub(crate) fn assert(cs: &'a mut CS) -> Result<Self> {
+        cs.set_low().map_err(|_| DisplayError::CSError)?;
+        Ok(Self(Some(cs)))
+    }
+
+    /// Deassert chip select, reporting failure to do so
+    pub(crate) fn deassert(mut self) -> Result {
+        match self.0.take() {
+            Some(cs) => cs.set_high().map_err(|_| DisplayError::CSError),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<CS: OutputPin> Drop for CsGuard<'_, CS> {
+    fn drop(&mut self) {
+        if let Some(cs) = self.0.take() {
+            cs.set_high().ok();
+        }
+    }
+}
+
+impl<IFACE, CS> WriteOnlyDataCommand for PGpioInterfaceWithCs<IFACE, CS>
+where
+    IFACE: WriteOnlyDataCommand,
+    CS: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.selected(|iface| iface.send_commands(cmds))
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.selected(|iface| iface.send_data(buf))
+    }
+}
+
+impl<IFACE, CS> ReadData for PGpioInterfaceWithCs<IFACE, CS>
+where
+    IFACE: ReadData,
+    CS: OutputPin,
+{
+    fn read_data(&mut self, buf: &mut [u8]) -> Result {
+        self.selected(|iface| iface.read_data(buf))
+    }
+}
+
+impl<BUS, DC, WR, CS, D> PGpio8BitInterfaceWithCs<BUS, DC, WR, CS, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Deassert chip select, then [resync](PGpio8BitInterface::resync) the wrapped interface
+    pub fn resync(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.iface.resync()
+    }
+}
+
+impl<BUS, DC, WR, CS, D> PGpio16BitInterfaceWithCs<BUS, DC, WR, CS, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Deassert chip select, then [resync](PGpio16BitInterface::resync) the wrapped interface
+    pub fn resync(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.iface.resync()
+    }
+}
+
+/// The chip select pin is deasserted high before the wrapped interface is suspended, and driven
+/// high again when resuming.
+impl<IFACE, CS> LowPower for PGpioInterfaceWithCs<IFACE, CS>
+where
+    IFACE: LowPower,
+    CS: OutputPin,
+{
+    fn suspend(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.iface.suspend()
+    }
+
+    fn resume(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.iface.resume()
+    }
+}
+
+#[cfg(feature = "async")]
+impl<IFACE, CS> AsyncWriteOnlyDataCommand for PGpioInterfaceWithCs<IFACE, CS>
+where
+    IFACE: AsyncWriteOnlyDataCommand,
+    CS: OutputPin,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        let cs = CsGuard::assert(&mut self.cs)?;
+        let result = self.iface.send_commands(cmds).await;
+        result.and(cs.deassert())
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        let cs = CsGuard::assert(&mut self.cs)?;
+        let result = self.iface.send_data(buf).await;
+        result.and(cs.deassert())
+    }
+}
@@ -0,0 +1,206 @@
+#[cfg(feature = "async")]
+use display_interface::AsyncWriteOnlyDataCommand;
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    cs::CsGuard, NoDelay, OutputBus, PGpio16BitInterface, PGpio8BitInterface, Result, WriteEnable,
+};
+
+/// Controllers addressed by a [PGpioDualInterface]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Target {
+    /// Only the controller selected by the first chip select pin
+    #[default]
+    First,
+    /// Only the controller selected by the second chip select pin
+    Second,
+    /// Both controllers at once, e.g. for sending the same initialization sequence
+    Both,
+}
+
+impl Target {
+    /// Whether the first and the second chip select pin are asserted
+    fn selects(self) -> (bool, bool) {
+        match self {
+            Target::First => (true, false),
+            Target::Second => (false, true),
+            Target::Both => (true, true),
+        }
+    }
+}
+
+/// Parallel communication interface to two controllers sharing one data bus
+///
+/// Some large modules contain two display controllers each driving half of the panel, connected
+/// to the same data bus, D/C and write-enable lines but with a low-active chip select pin each.
+/// This wraps any of the parallel interfaces and pulls the chip select pins of the controllers
+/// chosen with [PGpioDualInterface::select] low around every call, releasing them high again
+/// afterwards even if the call failed. Selecting [Target::Both] writes to both controllers at
+/// once.
+///
+/// ```
+/// # use display_interface_parallel_gpio::{OutputBus, PGpio8BitInterface, PGpio8BitDualInterface, Target};
+/// # use embedded_hal::digital::OutputPin;
+/// # fn f<BUS: OutputBus<Word = u8>, P: OutputPin>(bus: BUS, dc: P, wr: P, cs1: P, cs2: P) {
+/// let mut iface = PGpio8BitDualInterface::new(PGpio8BitInterface::new(bus, dc, wr), cs1, cs2);
+/// iface.select(Target::Both);
+/// # }
+/// ```
+pub struct PGpioDualInterface<IFACE, CS1, CS2> {
+    iface: IFACE,
+    cs1: CS1,
+    cs2: CS2,
+    target: Target,
+}
+
+/// [PGpio8BitInterface] driving two controllers
+pub type PGpio8BitDualInterface<BUS, DC, WR, CS1, CS2, D = NoDelay> =
+    PGpioDualInterface<PGpio8BitInterface<BUS, DC, WR, D>, CS1, CS2>;
+
+/// [PGpio16BitInterface] driving two controllers
+pub type PGpio16BitDualInterface<BUS, DC, WR, CS1, CS2, D = NoDelay> =
+    PGpioDualInterface<PGpio16BitInterface<BUS, DC, WR, D>, CS1, CS2>;
+
+impl<IFACE, CS1, CS2> PGpioDualInterface<IFACE, CS1, CS2>
+where
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    /// Create new parallel GPIO interface selecting the controllers with `cs1` and `cs2`
+    ///
+    /// Both chip select pins are expected to be high, i.e. the controllers deselected. The
+    /// first controller is targeted until [PGpioDualInterface::select] is called.
+    pub fn new(iface: IFACE, cs1: CS1, cs2: CS2) -> Self {
+        Self {
+            iface,
+            cs1,
+            cs2,
+            target: Target::default(),
+        }
+    }
+
+    /// Address the controllers chosen by `target` with all following calls
+    pub fn select(&mut self, target: Target) {
+        self.target = target;
+    }
+
+    /// Return the controllers currently addressed
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    /// Consume the display interface and return
+    /// the wrapped interface and chip select pins
+    pub fn release(self) -> (IFACE, CS1, CS2) {
+        (self.iface, self.cs1, self.cs2)
+    }
+
+    /// Deassert both chip select pins, whatever the target
+    fn deselect_all(&mut self) -> Result {
+        let first = self.cs1.set_high().map_err(|_| DisplayError::CSError);
+        let second = self.cs2.set_high().map_err(|_| DisplayError::CSError);
+        first.and(second)
+    }
+
+    /// Run `f` on the wrapped interface with the chip select pins of the target asserted
+    fn selected(&mut self, f: impl FnOnce(&mut IFACE) -> Result) -> Result {
+        let cs = Selection::assert(self.target, &mut self.cs1, &mut self.cs2)?;
+        let result = f(&mut self.iface);
+        result.and(cs.deassert())
+    }
+}
+
+/// Asserted chip select pins of a [Target], deasserted again when dropped
+struct Selection<'a, CS1: OutputPin, CS2: OutputPin> {
+    first: Option<CsGuard<'a, CS1>>,
+    second: Option<CsGuard<'a, CS2>>,
+}
+
+impl<'a, CS1: OutputPin, CS2: OutputPin> Selection<'a, CS1, CS2> {
+    /// Assert the chip select pins of `target`
+    ///
+    /// If the second one fails, the first one is deasserted again rather than left selected.
+    fn assert(target: Target, cs1: &'a mut CS1, cs2: &'a mut CS2) -> Result<Self> {
+        let (first, second) = target.selects();
+        let first = first.then(|| CsGuard::assert(cs1)).transpose()?;
+        let second = second.then(|| CsGuard::assert(cs2)).transpose()?;
+        Ok(Self { first, second })
+    }
+
+    /// Deassert both chip select pins, reporting failure of either
+    fn deassert(self) -> Result {
+        let first = self.first.map_or(Ok(()), CsGuard::deassert);
+        let second = self.second.map_or(Ok(()), CsGuard::deassert);
+        first.and(second)
+    }
+}
+
+impl<BUS, DC, WR, CS1, CS2, D> PGpio8BitDualInterface<BUS, DC, WR, CS1, CS2, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    CS1: OutputPin,
+    CS2: OutputPin,
+    D: DelayNs,
+{
+    /// Deassert both chip selects, then [resync](PGpio8BitInterface::resync) the wrapped
+    /// interface
+    pub fn resync(&mut self) -> Result {
+        self.deselect_all()?;
+        self.iface.resync()
+    }
+}
+
+impl<BUS, DC, WR, CS1, CS2, D> PGpio16BitDualInterface<BUS, DC, WR, CS1, CS2, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    CS1: OutputPin,
+    CS2: OutputPin,
+    D: DelayNs,
+{
+    /// Deassert both chip selects, then [resync](PGpio16BitInterface::resync) the wrapped
+    /// interface
+    pub fn resync(&mut self) -> Result {
+        self.deselect_all()?;
+        self.iface.resync()
+    }
+}
+
+impl<IFACE, CS1, CS2> WriteOnlyDataCommand for PGpioDualInterface<IFACE, CS1, CS2>
+where
+    IFACE: WriteOnlyDataCommand,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.selected(|iface| iface.send_commands(cmds))
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.selected(|iface| iface.send_data(buf))
+    }
+}
+
+#[cfg(feature = "async")]
+impl<IFACE, CS1, CS2> AsyncWriteOnlyDataCommand for PGpioDualInterface<IFACE, CS1, CS2>
+where
+    IFACE: AsyncWriteOnlyDataCommand,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        let cs = Selection::assert(self.target, &mut self.cs1, &mut self.cs2)?;
+        let result = self.iface.send_commands(cmds).await;
+        result.and(cs.deassert())
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        let cs = Selection::assert(self.target, &mut self.cs1, &mut self.cs2)?;
+        let result = self.iface.send_data(buf).await;
+        result.and(cs.deassert())
+    }
+}
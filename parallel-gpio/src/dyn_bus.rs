@@ -0,0 +1,113 @@
+use display_interface::DisplayError;
+use embedded_hal::digital::{Error, OutputPin};
+
+use crate::{OutputBus, Result};
+
+macro_rules! dyn_bus {
+    ($(#[$meta:meta])* $DynBus:ident { type Word = $Word:ident; }) => {
+        $(#[$meta])*
+        pub struct $DynBus<'a, E, const N: usize> {
+            pins: [&'a mut dyn OutputPin<Error = E>; N],
+            last: Option<$Word>,
+            caching: bool,
+        }
+
+        impl<'a, E: Error, const N: usize> $DynBus<'a, E, N> {
+            /// Creates a new bus. This does not change the state of the pins.
+            ///
+            /// The first pin in the array is the least significant bit.
+            ///
+            /// # Panics
+            ///
+            /// Panics if there are more pins than bits in a word.
+            pub fn new(pins: [&'a mut dyn OutputPin<Error = E>; N]) -> Self {
+                assert!(N <= $Word::BITS as usize, "too many pins for the bus width");
+                Self {
+                    pins,
+                    last: None,
+                    caching: true,
+                }
+            }
+
+            /// Remember the last value set to skip unchanged pins, which is the default
+            ///
+            /// Without caching, every pin is set for every value.
+            pub fn with_caching(mut self, caching: bool) -> Self {
+                self.caching = caching;
+                self.last = None;
+                self
+            }
+
+            /// Forget the last value set, so all pins are set for the next value
+            ///
+            /// Call this after something else has driven the pins.
+            pub fn invalidate(&mut self) {
+                self.last = None;
+            }
+
+            /// Consumes the bus and returns the pins. This does not change the state of the pins.
+            pub fn release(self) -> [&'a mut dyn OutputPin<Error = E>; N] {
+                self.pins
+            }
+        }
+
+        impl<E: Error, const N: usize> OutputBus for $DynBus<'_, E, N> {
+            type Word = $Word;
+
+            fn set_value(&mut self, value: Self::Word) -> Result {
+                if self.last == Some(value) {
+                    return Ok(());
+                }
+
+                // Only known again once all pins have been set successfully
+                let changed = match self.last.take() {
+                    Some(old_value) => value ^ old_value,
+                    None => !0,
+                };
+
+                for (bit, pin) in self.pins.iter_mut().enumerate() {
+                    let mask = 1 << bit;
+                    if changed & mask != 0 {
+                        if value & mask != 0 {
+                            pin.set_high()
+                        } else {
+                            pin.set_low()
+                        }
+                        .map_err(|_| DisplayError::BusWriteError)?;
+                    }
+                }
+
+                if self.caching {
+                    self.last = Some(value);
+                }
+                Ok(())
+            }
+
+            fn invalidate(&mut self) {
+                self.last = None;
+            }
+        }
+    };
+}
+
+dyn_bus! {
+    /// An implementation of [OutputBus] using an array of `dyn` [OutputPin]s
+    ///
+    /// Unlike [Generic8BitBus](crate::Generic8BitBus), the pins can be picked at runtime, e.g.
+    /// from a configuration block, without monomorphizing the driver for every combination of
+    /// pin types. Setting a pin costs a dynamic call. Like on the generic buses, only the pins
+    /// of bits differing from the previous value are set, unless the cache is invalidated or
+    /// turned off.
+    DynBus {
+        type Word = u8;
+    }
+}
+
+dyn_bus! {
+    /// An implementation of [OutputBus] with [u16] words using an array of `dyn` [OutputPin]s
+    ///
+    /// See [DynBus].
+    DynBus16 {
+        type Word = u16;
+    }
+}
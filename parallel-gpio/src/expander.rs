@@ -0,0 +1,266 @@
+use core::cell::RefCell;
+
+use display_interface::DisplayError;
+use embedded_hal::{
+    digital::{ErrorKind, ErrorType, OutputPin},
+    i2c::I2c,
+};
+
+use crate::{OutputBus, Result};
+
+/// MCP23017 `IODIRA` register address, with `IOCON.BANK` cleared as after power-on
+const MCP23017_IODIRA: u8 = 0x00;
+/// MCP23017 `OLATA` register address, with `IOCON.BANK` cleared as after power-on
+const MCP23017_OLATA: u8 = 0x14;
+
+/// An 8-bit output port of an I2C port expander
+///
+/// The port also determines the register protocol used to write it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpanderPort {
+    /// The port of a PCF8574 or PCF8574A at the given address, written without a register
+    /// address
+    Pcf8574(u8),
+    /// Port A of an MCP23017 at the given address, written through `OLATA`
+    Mcp23017A(u8),
+    /// Port B of an MCP23017 at the given address, written through `OLATB`
+    Mcp23017B(u8),
+}
+
+impl ExpanderPort {
+    /// Value of the output register after power-on
+    fn reset_value(self) -> u8 {
+        match self {
+            ExpanderPort::Pcf8574(_) => 0xff,
+            ExpanderPort::Mcp23017A(_) | ExpanderPort::Mcp23017B(_) => 0x00,
+        }
+    }
+
+    /// Switch all pins of the port to outputs
+    fn configure<I2C: I2c>(self, i2c: &mut I2C) -> Result {
+        match self {
+            // The PCF8574 has quasi-bidirectional pins, which are always outputs
+            ExpanderPort::Pcf8574(_) => Ok(()),
+            ExpanderPort::Mcp23017A(address) => i2c.write(address, &[MCP23017_IODIRA, 0x00]),
+            ExpanderPort::Mcp23017B(address) => i2c.write(address, &[MCP23017_IODIRA + 1, 0x00]),
+        }
+        .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Write `value` to the output register
+    fn write<I2C: I2c>(self, i2c: &mut I2C, value: u8) -> Result {
+        match self {
+            ExpanderPort::Pcf8574(address) => i2c.write(address, &[value]),
+            ExpanderPort::Mcp23017A(address) => i2c.write(address, &[MCP23017_OLATA, value]),
+            ExpanderPort::Mcp23017B(address) => i2c.write(address, &[MCP23017_OLATA + 1, value]),
+        }
+        .map_err(|_| DisplayError::BusWriteError)
+    }
+}
+
+/// An implementation of [OutputBus] using one port of an I2C port expander
+///
+/// Every new value takes one I2C write; values identical to the previous one are skipped. Use
+/// [ExpanderPins] for the control pins; they can live on the other port of an MCP23017 or on a
+/// second expander, sharing the bus through e.g. `embedded-hal-bus`.
+pub struct ExpanderBus8<I2C> {
+    i2c: I2C,
+    port: ExpanderPort,
+    last: Option<u8>,
+}
+
+impl<I2C> ExpanderBus8<I2C>
+where
+    I2C: I2c,
+{
+    /// Creates a new bus. This does not change the state of the port.
+    pub fn new(i2c: I2C, port: ExpanderPort) -> Self {
+        Self {
+            i2c,
+            port,
+            last: None,
+        }
+    }
+
+    /// Switches all pins of the port to outputs, which is required once after power-on for an
+    /// MCP23017
+    pub fn configure(&mut self) -> Result {
+        self.port.configure(&mut self.i2c)
+    }
+
+    /// Consumes the bus and returns the I2C bus. This does not change the state of the port.
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C> OutputBus for ExpanderBus8<I2C>
+where
+    I2C: I2c,
+{
+    type Word = u8;
+
+    fn set_value(&mut self, value: u8) -> Result {
+        if self.last == Some(value) {
+            return Ok(());
+        }
+
+        self.last = None;
+        self.port.write(&mut self.i2c, value)?;
+        self.last = Some(value);
+        Ok(())
+    }
+
+    fn invalidate(&mut self) {
+        self.last = None;
+    }
+}
+
+/// An implementation of [OutputBus] using two ports of I2C port expanders for 16 bits
+///
+/// The ports can be the two ports of an MCP23017 or two separate expanders on the same bus. Only
+/// ports whose byte changed are written, so a new value takes one or two I2C writes.
+pub struct ExpanderBus16<I2C> {
+    i2c: I2C,
+    ports: [ExpanderPort; 2],
+    last: [Option<u8>; 2],
+}
+
+impl<I2C> ExpanderBus16<I2C>
+where
+    I2C: I2c,
+{
+    /// Creates a new bus with the low byte on `low` and the high byte on `high`. This does not
+    /// change the state of the ports.
+    pub fn new(i2c: I2C, low: ExpanderPort, high: ExpanderPort) -> Self {
+        Self {
+            i2c,
+            ports: [low, high],
+            last: [None; 2],
+        }
+    }
+
+    /// Switches all pins of both ports to outputs, which is required once after power-on for an
+    /// MCP23017
+    pub fn configure(&mut self) -> Result {
+        for port in self.ports {
+            port.configure(&mut self.i2c)?;
+        }
+        Ok(())
+    }
+
+    /// Consumes the bus and returns the I2C bus. This does not change the state of the ports.
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+}
+
+impl<I2C> OutputBus for ExpanderBus16<I2C>
+where
+    I2C: I2c,
+{
+    type Word = u16;
+
+    fn set_value(&mut self, value: u16) -> Result {
+        for ((port, last), byte) in self
+            .ports
+            .iter()
+            .zip(&mut self.last)
+            .zip(value.to_le_bytes())
+        {
+            if *last != Some(byte) {
+                *last = None;
+                port.write(&mut self.i2c, byte)?;
+                *last = Some(byte);
+            }
+        }
+        Ok(())
+    }
+
+    fn invalidate(&mut self) {
+        self.last = [None; 2];
+    }
+}
+
+/// The individual pins of one port of an I2C port expander
+///
+/// Hands out [ExpanderPin]s implementing `OutputPin`, e.g. for the D/C and write-enable pins of
+/// a parallel interface. Setting a pin writes the whole port, so the pins share a copy of the
+/// output register, starting from its power-on value.
+pub struct ExpanderPins<I2C> {
+    port: ExpanderPort,
+    state: RefCell<(I2C, u8)>,
+}
+
+impl<I2C> ExpanderPins<I2C>
+where
+    I2C: I2c,
+{
+    /// Creates new pins for `port`. This does not change the state of the port.
+    pub fn new(i2c: I2C, port: ExpanderPort) -> Self {
+        Self {
+            port,
+            state: RefCell::new((i2c, port.reset_value())),
+        }
+    }
+
+    /// Switches all pins of the port to outputs, which is required once after power-on for an
+    /// MCP23017
+    pub fn configure(&self) -> Result {
+        self.port.configure(&mut self.state.borrow_mut().0)
+    }
+
+    /// Returns pin `bit` of the port
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bit` is not in `0..8`.
+    pub fn pin(&self, bit: u8) -> ExpanderPin<'_, I2C> {
+        assert!(bit < 8, "expander ports have 8 pins");
+        ExpanderPin {
+            pins: self,
+            mask: 1 << bit,
+        }
+    }
+
+    /// Consumes the pins and returns the I2C bus. This does not change the state of the port.
+    pub fn release(self) -> I2C {
+        self.state.into_inner().0
+    }
+
+    fn update(&self, mask: u8, high: bool) -> core::result::Result<(), ErrorKind> {
+        let (i2c, output) = &mut *self.state.borrow_mut();
+        let value = if high {
+            *output | mask
+        } else {
+            *output & !mask
+        };
+
+        self.port.write(i2c, value).map_err(|_| ErrorKind::Other)?;
+        *output = value;
+        Ok(())
+    }
+}
+
+/// A single pin of an I2C port expander, see [ExpanderPins]
+pub struct ExpanderPin<'a, I2C> {
+    pins: &'a ExpanderPins<I2C>,
+    mask: u8,
+}
+
+impl<I2C> ErrorType for ExpanderPin<'_, I2C> {
+    type Error = ErrorKind;
+}
+
+impl<I2C> OutputPin for ExpanderPin<'_, I2C>
+where
+    I2C: I2c,
+{
+    fn set_low(&mut self) -> core::result::Result<(), Self::Error> {
+        self.pins.update(self.mask, false)
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Self::Error> {
+        self.pins.update(self.mask, true)
+    }
+}
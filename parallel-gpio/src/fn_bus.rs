@@ -0,0 +1,69 @@
+use crate::{OutputBus, Result};
+
+macro_rules! fn_bus {
+    ($(#[$meta:meta])* $FnBus:ident { type Word = $Word:ident; }) => {
+        $(#[$meta])*
+        pub struct $FnBus<F> {
+            f: F,
+        }
+
+        impl<F> $FnBus<F>
+        where
+            F: FnMut($Word) -> Result,
+        {
+            /// Creates a new bus writing every value with `f`
+            pub fn new(f: F) -> Self {
+                Self { f }
+            }
+
+            /// Consumes the bus and returns the closure
+            pub fn release(self) -> F {
+                self.f
+            }
+        }
+
+        impl<F> OutputBus for $FnBus<F>
+        where
+            F: FnMut($Word) -> Result,
+        {
+            type Word = $Word;
+
+            fn set_value(&mut self, value: Self::Word) -> Result {
+                (self.f)(value)
+            }
+        }
+    };
+}
+
+fn_bus! {
+    /// An implementation of [OutputBus] delegating to a closure
+    ///
+    /// If all data lines are on a single GPIO port, the closure can set them with a single
+    /// port register write, which is much faster than setting the pins one by one. The closure
+    /// is called with every value and must set exactly the eight bus bits of the port to it,
+    /// leaving all other pins of the port untouched, before returning.
+    ///
+    /// For example with the PAC of a typical STM32 HAL, for data lines on `PB0` to `PB7`:
+    ///
+    /// ```ignore
+    /// let gpiob = unsafe { &*pac::GPIOB::ptr() };
+    /// let bus = FnBus::new(|value: u8| {
+    ///     // The upper half of BSRR resets the pins, the lower half sets them
+    ///     gpiob.bsrr.write(|w| unsafe { w.bits(0x00ff_0000 | u32::from(value)) });
+    ///     Ok(())
+    /// });
+    /// ```
+    FnBus {
+        type Word = u8;
+    }
+}
+
+fn_bus! {
+    /// An implementation of [OutputBus] delegating to a closure, for 16-bit buses
+    ///
+    /// The closure must set exactly the sixteen bus bits to the value it is called with; see
+    /// [FnBus] for details.
+    FnBus16 {
+        type Word = u16;
+    }
+}
@@ -0,0 +1,229 @@
+use core::{iter::Map, slice};
+
+use display_interface::{DataFormat, DisplayError};
+
+use crate::{OutputBus, Result, WriteStrobe};
+
+/// How bytes are put on a 16-bit bus
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum U8Packing {
+    /// Every byte is zero-extended to a word of its own
+    #[default]
+    ZeroExtend,
+    /// Every two bytes are combined into one word, the first byte being the high byte
+    ///
+    /// An odd number of bytes is rejected with [DisplayError::InvalidFormatError]; slices are
+    /// checked before anything is written, while iterators are only found to be odd after all
+    /// complete words have been written.
+    BigEndian,
+    /// Like [U8Packing::BigEndian], but a final odd byte is padded with a zero low byte
+    BigEndianPadded,
+}
+
+/// Combines pairs of bytes into big endian words
+pub(crate) struct Packed<I> {
+    iter: I,
+    pad: bool,
+    odd: bool,
+}
+
+impl<I: Iterator<Item = u8>> Packed<I> {
+    pub(crate) fn new(iter: I, pad: bool) -> Self {
+        Self {
+            iter,
+            pad,
+            odd: false,
+        }
+    }
+
+    /// Return an error if a final odd byte was dropped
+    pub(crate) fn finish(self) -> Result {
+        if self.odd {
+            Err(DisplayError::InvalidFormatError)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<I: Iterator<Item = u8>> Iterator for Packed<I> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        let high = self.iter.next()?;
+        match self.iter.next() {
+            Some(low) => Some(u16::from_be_bytes([high, low])),
+            None if self.pad => Some(u16::from_be_bytes([high, 0])),
+            None => {
+                self.odd = true;
+                None
+            }
+        }
+    }
+}
+
+/// Words of a slice or an iterator format
+pub(crate) enum Source<'a, T> {
+    Slice(slice::Iter<'a, T>),
+    Iter(&'a mut dyn Iterator<Item = T>),
+}
+
+impl<T: Copy> Iterator for Source<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match self {
+            Source::Slice(iter) => iter.next().copied(),
+            Source::Iter(iter) => iter.next(),
+        }
+    }
+}
+
+/// The bytes of a format for an 8-bit bus
+///
+/// 16-bit formats are split into two bytes in the requested byte order, [DataFormat::U16] being
+/// in native byte order.
+pub(crate) enum Bytes<'a> {
+    U8(Source<'a, u8>),
+    U16 {
+        words: Source<'a, u16>,
+        split: fn(u16) -> [u8; 2],
+        second: Option<u8>,
+    },
+}
+
+impl<'a> Bytes<'a> {
+    pub(crate) fn new(format: DataFormat<'a>) -> Result<Self> {
+        let split = |words, split| Bytes::U16 {
+            words,
+            split,
+            second: None,
+        };
+
+        Ok(match format {
+            DataFormat::U8(slice) => Bytes::U8(Source::Slice(slice.iter())),
+            DataFormat::U8Iter(iter) => Bytes::U8(Source::Iter(iter)),
+            DataFormat::U16(slice) => split(Source::Slice(slice.iter()), u16::to_ne_bytes),
+            DataFormat::U16BE(slice) => split(Source::Slice(slice.iter()), u16::to_be_bytes),
+            DataFormat::U16LE(slice) => split(Source::Slice(slice.iter()), u16::to_le_bytes),
+            DataFormat::U16BEIter(iter) => split(Source::Iter(iter), u16::to_be_bytes),
+            DataFormat::U16LEIter(iter) => split(Source::Iter(iter), u16::to_le_bytes),
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        })
+    }
+}
+
+impl Iterator for Bytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        match self {
+            Bytes::U8(bytes) => bytes.next(),
+            Bytes::U16 {
+                words,
+                split,
+                second,
+            } => second.take().or_else(|| {
+                let [first, next] = split(words.next()?);
+                *second = Some(next);
+                Some(first)
+            }),
+        }
+    }
+}
+
+/// The words of a format for a 16-bit bus
+///
+/// 16-bit formats are taken one word at a time, while bytes are put on the bus as selected by
+/// the [U8Packing]. Call [Words::finish] once written to catch a final odd byte.
+pub(crate) enum Words<'a> {
+    U8(Map<Source<'a, u8>, fn(u8) -> u16>),
+    Packed(Packed<Source<'a, u8>>),
+    U16(Source<'a, u16>),
+}
+
+impl<'a> Words<'a> {
+    /// Return the words of `format`, rejecting odd byte slices up front
+    pub(crate) fn new(format: DataFormat<'a>, packing: U8Packing) -> Result<Self> {
+        let bytes = |bytes: Source<'a, u8>| match packing {
+            U8Packing::ZeroExtend => Words::U8(bytes.map(u16::from as fn(u8) -> u16)),
+            _ => Words::Packed(Packed::new(bytes, packing == U8Packing::BigEndianPadded)),
+        };
+
+        Ok(match format {
+            DataFormat::U8(slice) if packing == U8Packing::BigEndian && slice.len() % 2 != 0 => {
+                return Err(DisplayError::InvalidFormatError)
+            }
+            DataFormat::U8(slice) => bytes(Source::Slice(slice.iter())),
+            DataFormat::U8Iter(iter) => bytes(Source::Iter(iter)),
+            DataFormat::U16(slice) => Words::U16(Source::Slice(slice.iter())),
+            DataFormat::U16BE(slice) => Words::U16(Source::Slice(slice.iter())),
+            DataFormat::U16LE(slice) => Words::U16(Source::Slice(slice.iter())),
+            DataFormat::U16BEIter(iter) => Words::U16(Source::Iter(iter)),
+            DataFormat::U16LEIter(iter) => Words::U16(Source::Iter(iter)),
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        })
+    }
+
+    /// Return an error if a final odd byte was dropped
+    pub(crate) fn finish(self) -> Result {
+        match self {
+            Words::Packed(packed) => packed.finish(),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Iterator for Words<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        match self {
+            Words::U8(words) => words.next(),
+            Words::Packed(words) => words.next(),
+            Words::U16(words) => words.next(),
+        }
+    }
+}
+
+/// Write `format` to the 8-bit `bus`, latching every byte with `strobe`
+///
+/// This is the format handling of [PGpio8BitInterface](crate::PGpio8BitInterface) for
+/// interfaces which need to strobe the bus differently, e.g. with an extra latch pulse per word.
+/// 16-bit formats are split into two bytes in the requested byte order, [DataFormat::U16] being
+/// in native byte order. Bytes are written with [OutputBus::write_words] and
+/// [OutputBus::write_words_iter].
+pub fn write_format_8bit(
+    bus: &mut impl OutputBus<Word = u8>,
+    strobe: &mut impl WriteStrobe,
+    format: DataFormat<'_>,
+) -> Result {
+    match format {
+        DataFormat::U8(slice) => bus.write_words(slice, strobe),
+        format => bus.write_words_iter(Bytes::new(format)?, strobe),
+    }
+}
+
+/// Write `format` to the 16-bit `bus`, latching every word with `strobe`
+///
+/// This is the format handling of [PGpio16BitInterface](crate::PGpio16BitInterface) for
+/// interfaces which need to strobe the bus differently. 16-bit formats are written one word at
+/// a time, while bytes are put on the bus as selected by `packing`. Words are written with
+/// [OutputBus::write_words] and [OutputBus::write_words_iter].
+pub fn write_format_16bit(
+    bus: &mut impl OutputBus<Word = u16>,
+    strobe: &mut impl WriteStrobe,
+    format: DataFormat<'_>,
+    packing: U8Packing,
+) -> Result {
+    match format {
+        DataFormat::U16(slice) => bus.write_words(slice, strobe),
+        DataFormat::U16BE(slice) => bus.write_words(slice, strobe),
+        DataFormat::U16LE(slice) => bus.write_words(slice, strobe),
+        format => {
+            let mut words = Words::new(format, packing)?;
+            bus.write_words_iter(&mut words, strobe)?;
+            words.finish()
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use display_interface::{
+    DataFormat, DcPolarity, DisplayError, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    release_on_error, select, set_dc, write_format_8bit, NoDelay, OutputBus, PGpioInterfaceError,
+    Result, StrobePolarity, WriteStrobe,
+};
+
+/// Parallel 4 Bit communication interface
+///
+/// This interface implements the 4-Bit "nibble mode" of character and small graphic LCD
+/// controllers like the HD44780 or ST7066, using any [OutputBus] implementation with
+/// [u8] words of which only the low 4 bits are significant, e.g.
+/// [Generic4BitBus](crate::Generic4BitBus), as well as one `OutputPin` for the data/command
+/// selection and one `OutputPin` for the write-enable flag.
+///
+/// Every byte is transferred as two nibbles, high nibble first, each with its own write-enable
+/// pulse. 16-bit formats are split into two bytes in the requested byte order, like on
+/// [PGpio8BitInterface](crate::PGpio8BitInterface).
+///
+/// All pins are supposed to be high-active, high for the D/C pin meaning "data" and the
+/// write-enable being pulled low before the setting of the bits and supposed to be sampled at a
+/// low to high edge, unless inverted with [PGpio4BitInterface::with_dc_polarity] and
+/// [PGpio4BitInterface::with_strobe_polarity]. Slow glass may need a pause between the nibbles,
+/// see [PGpio4BitInterface::with_nibble_delay].
+pub struct PGpio4BitInterface<BUS, DC, WR, D = NoDelay> {
+    bus: BUS,
+    dc: DC,
+    wr: WR,
+    delay: D,
+    dc_polarity: DcPolarity,
+    wr_polarity: StrobePolarity,
+    nibble_delay_ns: u32,
+}
+
+impl<BUS, DC, WR> PGpio4BitInterface<BUS, DC, WR>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    pub fn new(bus: BUS, dc: DC, wr: WR) -> Self {
+        Self {
+            bus,
+            dc,
+            wr,
+            delay: NoDelay,
+            dc_polarity: DcPolarity::default(),
+            wr_polarity: StrobePolarity::default(),
+            nibble_delay_ns: 0,
+        }
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio4BitInterface<BUS, DC, WR, D> {
+    /// Wait `ns` nanoseconds after every nibble, using `delay`
+    pub fn with_nibble_delay<D2>(self, delay: D2, ns: u32) -> PGpio4BitInterface<BUS, DC, WR, D2> {
+        PGpio4BitInterface {
+            bus: self.bus,
+            dc: self.dc,
+            wr: self.wr,
+            delay,
+            dc_polarity: self.dc_polarity,
+            wr_polarity: self.wr_polarity,
+            nibble_delay_ns: ns,
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pins used by it
+    pub fn release(self) -> (BUS, DC, WR) {
+        (self.bus, self.dc, self.wr)
+    }
+
+    /// Consume the display interface and return
+    /// the bus, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (BUS, DC, WR, D) {
+        (self.bus, self.dc, self.wr, self.delay)
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio4BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    D: DelayNs,
+{
+    /// Set the D/C pin to command and release the write-enable to its idle level
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.bus.invalidate();
+        self.wr_polarity.release(&mut self.wr)
+    }
+
+    fn write_data(&mut self, data: DataFormat<'_>) -> Result {
+        let mut strobe = NibbleStrobe {
+            wr: &mut self.wr,
+            polarity: self.wr_polarity,
+            delay: &mut self.delay,
+            nibble_delay_ns: self.nibble_delay_ns,
+        };
+        let result = write_format_8bit(&mut Nibbles(&mut self.bus), &mut strobe, data);
+        release_on_error(&mut strobe, result)
+    }
+}
+
+/// Byte-wide view of a 4-bit bus, writing every byte as two nibbles, high nibble first
+struct Nibbles<'a, BUS>(&'a mut BUS);
+
+impl<BUS: OutputBus<Word = u8>> OutputBus for Nibbles<'_, BUS> {
+    type Word = u8;
+
+    /// A whole byte needs two strobes, so it can only be written by the batch writes
+    fn set_value(&mut self, _value: u8) -> Result {
+        Err(DisplayError::DataFormatNotImplemented)
+    }
+
+    fn invalidate(&mut self) {
+        self.0.invalidate();
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = u8>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        let nibbles = words.flat_map(|word| [word >> 4, word & 0x0f]);
+        self.0.write_words_iter(nibbles, strobe)
+    }
+}
+
+/// Write-enable pausing for the nibble delay after every release
+struct NibbleStrobe<'a, WR, D> {
+    wr: &'a mut WR,
+    polarity: StrobePolarity,
+    delay: &'a mut D,
+    nibble_delay_ns: u32,
+}
+
+impl<WR: OutputPin, D: DelayNs> WriteStrobe for NibbleStrobe<'_, WR, D> {
+    fn assert(&mut self) -> Result {
+        self.polarity.assert(self.wr)
+    }
+
+    fn release(&mut self) -> Result {
+        self.polarity.release(self.wr)?;
+        if self.nibble_delay_ns != 0 {
+            self.delay.delay_ns(self.nibble_delay_ns);
+        }
+        Ok(())
+    }
+}
+
+impl<BUS, DC, WR, D> TypedWriteOnlyDataCommand for PGpio4BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    D: DelayNs,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds).map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf).map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, WR, D> WriteOnlyDataCommand for PGpio4BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_commands_typed(cmds).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
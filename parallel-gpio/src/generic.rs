@@ -0,0 +1,223 @@
+use core::ops::{BitAnd, BitOr, BitXor};
+
+use display_interface::DisplayError;
+use embedded_hal::digital::OutputPin;
+
+use crate::{OutputBus, Result, WriteStrobe};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Integer types usable as the word of a [GenericBus]
+pub trait BusWord:
+    sealed::Sealed
+    + Copy
+    + PartialEq
+    + BitAnd<Output = Self>
+    + BitOr<Output = Self>
+    + BitXor<Output = Self>
+{
+    /// Word with no bit set
+    const ZERO: Self;
+
+    /// Word with all bits set
+    const ONES: Self;
+
+    /// Number of bits of the word
+    const BITS: usize;
+
+    /// Whether bit `n` is set
+    fn bit(self, n: usize) -> bool;
+
+    /// Word with only bit `n` set
+    fn single_bit(n: usize) -> Self;
+}
+
+macro_rules! bus_word {
+    ($($Word:ident),*) => {
+        $(
+            impl sealed::Sealed for $Word {}
+
+            impl BusWord for $Word {
+                const ZERO: Self = 0;
+
+                const ONES: Self = !0;
+
+                const BITS: usize = $Word::BITS as usize;
+
+                fn bit(self, n: usize) -> bool {
+                    self & (1 << n) != 0
+                }
+
+                fn single_bit(n: usize) -> Self {
+                    1 << n
+                }
+            }
+        )*
+    };
+}
+
+bus_word!(u8, u16, u32);
+
+/// Width of a [GenericBus] in bits
+pub struct Width<const N: usize>;
+
+/// Maps the width of a [GenericBus] to the smallest word it fits in
+pub trait BusWidth {
+    /// [u8] for up to 8 bits, [u16] for up to 16 bits and [u32] for up to 32 bits
+    type Word: BusWord;
+}
+
+macro_rules! bus_width {
+    ($Word:ident: $($N:literal)*) => {
+        $(
+            impl BusWidth for Width<$N> {
+                type Word = $Word;
+            }
+        )*
+    };
+}
+
+bus_width!(u8: 1 2 3 4 5 6 7 8);
+bus_width!(u16: 9 10 11 12 13 14 15 16);
+bus_width!(u32: 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+
+type Word<const N: usize> = <Width<N> as BusWidth>::Word;
+
+/// A generic implementation of [OutputBus] using an array of `N` [OutputPin]s of the same type
+///
+/// This covers any width from 1 to 32 bits, e.g. 12 or 18 bits, with the smallest fitting
+/// integer as [OutputBus::Word]. HALs usually offer a type erased pin type to put pins of
+/// different ports into one array; [Generic8BitBus](crate::Generic8BitBus) and its siblings
+/// take a tuple of pins of different types instead.
+///
+/// The bus remembers the last value it set and only sets the pins of bits which differ from
+/// it, skipping repeated values altogether. This relies on nothing else driving the pins in
+/// between; if they are shared, call [invalidate](Self::invalidate) after using them otherwise,
+/// or turn the cache off with [with_caching](Self::with_caching).
+pub struct GenericBus<P, const N: usize>
+where
+    Width<N>: BusWidth,
+{
+    pins: [P; N],
+    last: Option<Word<N>>,
+    inversion: Option<Word<N>>,
+    caching: bool,
+}
+
+impl<P, const N: usize> GenericBus<P, N>
+where
+    P: OutputPin,
+    Width<N>: BusWidth,
+{
+    /// Creates a new bus. This does not change the state of the pins.
+    ///
+    /// The first pin in the array is the least significant bit.
+    pub fn new(pins: [P; N]) -> Self {
+        Self {
+            pins,
+            last: None,
+            inversion: None,
+            caching: true,
+        }
+    }
+
+    /// Invert the pins set in `mask`, e.g. for data lines routed through an inverting buffer
+    ///
+    /// Every value is still set as is, the pins of the mask are just driven to the opposite
+    /// level. This does not change the state of the pins.
+    pub fn with_inversion_mask(mut self, mask: Word<N>) -> Self {
+        self.inversion = Some(mask);
+        // The pins have to be driven anew at the next value
+        self.last = None;
+        self
+    }
+
+    /// Remember the last value set to skip unchanged pins, which is the default
+    ///
+    /// Without caching, every pin is set for every value.
+    pub fn with_caching(mut self, caching: bool) -> Self {
+        self.caching = caching;
+        self.last = None;
+        self
+    }
+
+    /// Forget the last value set, so all pins are set for the next value
+    ///
+    /// Call this after something else has driven the pins.
+    pub fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    /// Consumes the bus and returns the pins. This does not change the state of the pins.
+    pub fn release(self) -> [P; N] {
+        self.pins
+    }
+}
+
+impl<P, const N: usize> OutputBus for GenericBus<P, N>
+where
+    P: OutputPin,
+    Width<N>: BusWidth,
+{
+    type Word = Word<N>;
+
+    fn set_value(&mut self, value: Self::Word) -> Result {
+        if self.last == Some(value) {
+            return Ok(());
+        }
+
+        // Only known again once all pins have been set successfully
+        let changed = match self.last.take() {
+            Some(old_value) => value ^ old_value,
+            None => Word::<N>::ONES,
+        };
+        let levels = match self.inversion {
+            Some(mask) => value ^ mask,
+            None => value,
+        };
+
+        for (bit, pin) in self.pins.iter_mut().enumerate() {
+            if changed.bit(bit) {
+                if levels.bit(bit) {
+                    pin.set_high()
+                } else {
+                    pin.set_low()
+                }
+                .map_err(|_| DisplayError::BusWriteError)?;
+            }
+        }
+
+        if self.caching {
+            self.last = Some(value);
+        }
+        Ok(())
+    }
+
+    fn invalidate(&mut self) {
+        self.last = None;
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = Self::Word>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        let mut words = words.peekable();
+        while let Some(word) = words.next() {
+            strobe.assert()?;
+            self.set_value(word)?;
+            strobe.release()?;
+
+            // A run of identical words is already on the bus, so only pulse the strobe
+            let mut run = 0;
+            while self.last == Some(word) && words.next_if_eq(&word).is_some() {
+                run += 1;
+            }
+            strobe.strobe_n(run)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use display_interface::DisplayError;
+use embedded_hal::digital::{InputPin, OutputPin};
+
+use crate::{BidirectionalBus, Generic8BitBus, OutputBus, Result, WriteStrobe};
+
+/// A generic implementation of [BidirectionalBus] using open-drain pins
+///
+/// `embedded-hal` has no trait for switching the direction of a pin, so this bus expects pins
+/// which implement both [OutputPin] and [InputPin] while configured as open-drain outputs with
+/// pull-ups, as offered by most HALs, e.g. the `OpenDrain` output modes of the STM32 HALs or
+/// the `Flex` pins of `embassy`. [BidirectionalBus::set_input] releases all pins high, after
+/// which the display can pull them low and [BidirectionalBus::get_value] samples their levels.
+///
+/// Writes behave exactly like those of [Generic8BitBus], whose pins are driven low actively
+/// but only released high through the pull-ups. The signal edges are thus slower, which may
+/// require stretching the write-enable, see
+/// [PGpio8BitInterface::with_timing](crate::PGpio8BitInterface::with_timing).
+pub struct Generic8BitInOutBus<P0, P1, P2, P3, P4, P5, P6, P7> {
+    bus: Generic8BitBus<P0, P1, P2, P3, P4, P5, P6, P7>,
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> Generic8BitInOutBus<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin + InputPin,
+    P1: OutputPin + InputPin,
+    P2: OutputPin + InputPin,
+    P3: OutputPin + InputPin,
+    P4: OutputPin + InputPin,
+    P5: OutputPin + InputPin,
+    P6: OutputPin + InputPin,
+    P7: OutputPin + InputPin,
+{
+    /// Creates a new bus. This does not change the state of the pins.
+    ///
+    /// The first pin in the tuple is the least significant bit.
+    pub fn new(pins: (P0, P1, P2, P3, P4, P5, P6, P7)) -> Self {
+        Self {
+            bus: Generic8BitBus::new(pins),
+        }
+    }
+
+    /// Consumes the bus and returns the pins. This does not change the state of the pins.
+    pub fn release(self) -> (P0, P1, P2, P3, P4, P5, P6, P7) {
+        self.bus.release()
+    }
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> OutputBus
+    for Generic8BitInOutBus<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin + InputPin,
+    P1: OutputPin + InputPin,
+    P2: OutputPin + InputPin,
+    P3: OutputPin + InputPin,
+    P4: OutputPin + InputPin,
+    P5: OutputPin + InputPin,
+    P6: OutputPin + InputPin,
+    P7: OutputPin + InputPin,
+{
+    type Word = u8;
+
+    fn set_value(&mut self, value: u8) -> Result {
+        self.bus.set_value(value)
+    }
+
+    fn invalidate(&mut self) {
+        self.bus.invalidate();
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = u8>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        self.bus.write_words_iter(words, strobe)
+    }
+}
+
+impl<P0, P1, P2, P3, P4, P5, P6, P7> BidirectionalBus
+    for Generic8BitInOutBus<P0, P1, P2, P3, P4, P5, P6, P7>
+where
+    P0: OutputPin + InputPin,
+    P1: OutputPin + InputPin,
+    P2: OutputPin + InputPin,
+    P3: OutputPin + InputPin,
+    P4: OutputPin + InputPin,
+    P5: OutputPin + InputPin,
+    P6: OutputPin + InputPin,
+    P7: OutputPin + InputPin,
+{
+    fn set_input(&mut self) -> Result {
+        // The levels are up to the display from now on
+        self.bus.invalidate();
+
+        let pins = &mut self.bus.pins;
+        pins.0.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.1.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.2.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.3.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.4.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.5.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.6.set_high().map_err(|_| DisplayError::BusWriteError)?;
+        pins.7.set_high().map_err(|_| DisplayError::BusWriteError)
+    }
+
+    fn set_output(&mut self) -> Result {
+        // All pins are released high, which the next value will change as needed
+        self.bus.invalidate();
+        Ok(())
+    }
+
+    fn get_value(&mut self) -> Result<u8> {
+        let pins = &mut self.bus.pins;
+        let levels = [
+            pins.0.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.1.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.2.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.3.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.4.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.5.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.6.is_high().map_err(|_| DisplayError::BusReadError)?,
+            pins.7.is_high().map_err(|_| DisplayError::BusReadError)?,
+        ];
+
+        Ok(levels
+            .into_iter()
+            .enumerate()
+            .fold(0, |value, (bit, high)| value | (u8::from(high) << bit)))
+    }
+}
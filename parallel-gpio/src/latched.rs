@@ -0,0 +1,165 @@
+use display_interface::{
+    DataFormat, DcPolarity, DisplayError, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{
+    release_on_error, select, set_dc, write_format_16bit, NoDelay, OutputBus, PGpioInterfaceError,
+    Result, StrobePolarity, U8Packing, WriteEnable,
+};
+
+/// Parallel 16 Bit communication interface over 8 pins and a latch
+///
+/// This interface drives the 16-Bit "8080" bus of displays which route 8 GPIOs through a
+/// transparent latch like the 74HC573, using any 8-bit [OutputBus] implementation as well as one
+/// `OutputPin` each for the data/command selection, the write-enable and the latch-enable flag.
+///
+/// Every word is written as follows:
+///
+/// 1. the write-enable is asserted
+/// 2. the high byte is put on the bus
+/// 3. the latch-enable is pulsed high and low again, so the latch holds the high byte
+/// 4. the low byte is put on the bus
+/// 5. the write-enable is released, so the controller samples both bytes at once
+///
+/// [DataFormat::U8] and [DataFormat::U8Iter] bytes are zero-extended to one word each, like on
+/// [PGpio16BitInterface](crate::PGpio16BitInterface).
+///
+/// All pins are supposed to be high-active, high for the D/C pin meaning "data", the latch being
+/// transparent while the latch-enable is high and the write-enable being sampled at a low to high
+/// edge. The D/C pin and the write-enable strobe can be inverted with
+/// [PGpioLatched16BitInterface::with_dc_polarity] and
+/// [PGpioLatched16BitInterface::with_strobe_polarity].
+pub struct PGpioLatched16BitInterface<BUS, DC, WR, LE> {
+    bus: BUS,
+    dc: DC,
+    wr: WR,
+    le: LE,
+    dc_polarity: DcPolarity,
+    wr_polarity: StrobePolarity,
+}
+
+impl<BUS, DC, WR, LE> PGpioLatched16BitInterface<BUS, DC, WR, LE>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    LE: OutputPin,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    ///
+    /// The latch-enable pin is expected to be low, i.e. the latch holding its value.
+    pub fn new(bus: BUS, dc: DC, wr: WR, le: LE) -> Self {
+        Self {
+            bus,
+            dc,
+            wr,
+            le,
+            dc_polarity: DcPolarity::default(),
+            wr_polarity: StrobePolarity::default(),
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pins used by it
+    pub fn release(self) -> (BUS, DC, WR, LE) {
+        (self.bus, self.dc, self.wr, self.le)
+    }
+
+    /// Set the D/C pin to command, release the write-enable and pull the latch enable low
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.bus.invalidate();
+        self.wr_polarity.release(&mut self.wr)?;
+        self.le.set_low().map_err(|_| DisplayError::BusWriteError)
+    }
+
+    fn write_data(&mut self, data: DataFormat<'_>) -> Result {
+        let mut delay = NoDelay;
+        let mut strobe = self.wr.strobe(self.wr_polarity, &mut delay, None);
+        let mut bus = Latch {
+            bus: &mut self.bus,
+            le: &mut self.le,
+        };
+        let result = write_format_16bit(&mut bus, &mut strobe, data, U8Packing::ZeroExtend);
+        release_on_error(&mut strobe, result)
+    }
+}
+
+/// 16-bit view of an 8-bit bus, holding the high byte of every word in the latch
+struct Latch<'a, BUS, LE> {
+    bus: &'a mut BUS,
+    le: &'a mut LE,
+}
+
+impl<BUS: OutputBus<Word = u8>, LE: OutputPin> OutputBus for Latch<'_, BUS, LE> {
+    type Word = u16;
+
+    fn set_value(&mut self, value: u16) -> Result {
+        let [high, low] = value.to_be_bytes();
+
+        self.bus.set_value(high)?;
+        self.le
+            .set_high()
+            .map_err(|_| DisplayError::BusWriteError)?;
+        self.le.set_low().map_err(|_| DisplayError::BusWriteError)?;
+        self.bus.set_value(low)
+    }
+
+    fn invalidate(&mut self) {
+        self.bus.invalidate();
+    }
+}
+
+impl<BUS, DC, WR, LE> TypedWriteOnlyDataCommand for PGpioLatched16BitInterface<BUS, DC, WR, LE>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    LE: OutputPin,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds).map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf).map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, WR, LE> WriteOnlyDataCommand for PGpioLatched16BitInterface<BUS, DC, WR, LE>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: OutputPin,
+    LE: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_commands_typed(cmds).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
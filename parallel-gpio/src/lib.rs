@@ -1,28 +1,364 @@
 //! Generic parallel GPIO interface for display drivers
+//!
+//! # Resynchronizing the pins
+//!
+//! Every interface driving control pins has a `resync` method driving them to their idle levels.
+//! Call it after construction, or after other code, e.g. a bootloader, touched the pins. It also
+//! makes the bus forget the value it cached, so the next write drives every bus pin anew. With the
+//! `nb` feature, [PGpio8BitInterface::resync] and [PGpio16BitInterface::resync] also abandon a
+//! transfer left unfinished by `try_send_commands` or `try_send_data`.
 #![no_std]
+#![allow(async_fn_in_trait)]
 
-use embedded_hal::digital::OutputPin;
+#[cfg(feature = "async")]
+mod asynch;
+#[cfg(feature = "cortex-m")]
+mod bsrr;
+#[cfg(feature = "linux")]
+mod cdev;
+mod cs;
+mod dual;
+mod dyn_bus;
+mod expander;
+mod fn_bus;
+mod format;
+mod four_bit;
+mod generic;
+mod in_out;
+mod latched;
+mod low_power;
+mod m6800;
+mod nine_bit;
+mod null;
+mod pcf8574_lcd;
+mod read_write;
+mod recorder;
+mod self_test;
+mod shift_register;
+#[cfg(feature = "nb")]
+mod try_send;
+mod twenty_four_bit;
 
-pub use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+#[cfg(feature = "cortex-m")]
+pub use bsrr::{BsrrBus16, BsrrBus8};
+#[cfg(feature = "linux")]
+pub use cdev::{CdevBus16, CdevBus8, CdevError, CdevPin};
+pub use cs::{PGpio16BitInterfaceWithCs, PGpio8BitInterfaceWithCs, PGpioInterfaceWithCs};
+#[cfg(feature = "async")]
+pub use display_interface::AsyncWriteOnlyDataCommand;
+pub use display_interface::{
+    DataFormat, DcPolarity, DisplayError, LowPower, NoDelay, ReadData, TypedWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+pub use dual::{PGpio16BitDualInterface, PGpio8BitDualInterface, PGpioDualInterface, Target};
+pub use dyn_bus::{DynBus, DynBus16};
+pub use expander::{ExpanderBus16, ExpanderBus8, ExpanderPin, ExpanderPins, ExpanderPort};
+pub use fn_bus::{FnBus, FnBus16};
+pub use format::{write_format_16bit, write_format_8bit, U8Packing};
+pub use four_bit::PGpio4BitInterface;
+pub use generic::{BusWidth, BusWord, GenericBus, Width};
+pub use in_out::Generic8BitInOutBus;
+pub use latched::PGpioLatched16BitInterface;
+pub use m6800::PGpio6800Interface;
+pub use nine_bit::PGpio9BitInterface;
+pub use null::{NullOutputBus, NullPin};
+pub use pcf8574_lcd::Pcf8574Lcd4BitInterface;
+pub use read_write::PGpio8BitReadWriteInterface;
+pub use recorder::{PinEvent, PinRecorder, RecordedBus, RecordedDelay, RecordedPin, TimedEvent};
+pub use self_test::{loopback_echo, probe_lines, self_test, SelfTestError, WiringFault};
+pub use shift_register::{ShiftRegister16Bus, ShiftRegisterBus};
+pub use twenty_four_bit::{rgb565_to_rgb888, PGpio24BitInterface};
 
 type Result<T = ()> = core::result::Result<T, DisplayError>;
 
 /// This trait represents the data pins of a parallel bus.
 ///
-/// See [Generic8BitBus] and [Generic16BitBus] for generic implementations.
+/// See [GenericBus], [Generic4BitBus], [Generic8BitBus], [Generic9BitBus], [Generic16BitBus] and
+/// [Generic24BitBus] for generic implementations, [DynBus] and [DynBus16] for pins chosen at
+/// runtime, [ShiftRegisterBus] and [ShiftRegister16Bus] for buses driven through shift registers,
+/// `CdevBus8` and `CdevBus16` for Linux GPIO character devices,
+/// [ExpanderBus8] and [ExpanderBus16] for buses on I2C port expanders, and [FnBus] and [FnBus16]
+/// for writing whole GPIO ports at once.
 pub trait OutputBus {
     /// [u8] for 8-bit buses, [u16] for 16-bit buses, etc.
     type Word: Copy;
 
     fn set_value(&mut self, value: Self::Word) -> Result;
+
+    /// Forget any value the bus cached, so the next value drives every pin
+    ///
+    /// Interfaces call this when resuming from a low-power mode, see [LowPower]. Buses which
+    /// skip unchanged pins have to implement it; the default implementation does nothing.
+    fn invalidate(&mut self) {}
+
+    /// Put every word of `words` on the bus in turn, latching each one with `strobe`
+    ///
+    /// The strobe has to be asserted before and released after setting every word. The default
+    /// implementation does exactly that with [OutputBus::set_value]; buses which can amortize
+    /// work over a run of words should override it, keeping the sequence on the pins identical.
+    fn write_words(&mut self, words: &[Self::Word], strobe: &mut impl WriteStrobe) -> Result {
+        self.write_words_iter(words.iter().copied(), strobe)
+    }
+
+    /// Put every word of `words` on the bus in turn, latching each one with `strobe`
+    ///
+    /// See [OutputBus::write_words].
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = Self::Word>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        for word in words {
+            strobe.assert()?;
+            self.set_value(word)?;
+            strobe.release()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// This trait represents the write strobe latching every word put on a parallel bus.
+///
+/// The interfaces drive their write-enable pin through it when calling
+/// [OutputBus::write_words].
+pub trait WriteStrobe {
+    /// Move the strobe to its active level, before the word is put on the bus
+    fn assert(&mut self) -> Result;
+
+    /// Move the strobe back to its idle level, after the word has been put on the bus
+    fn release(&mut self) -> Result;
+
+    /// Latch the word already on the bus once more
+    fn strobe(&mut self) -> Result {
+        self.assert()?;
+        self.release()
+    }
+
+    /// Latch the word already on the bus `count` more times
+    ///
+    /// Strobes generated in hardware should override this to emit all pulses at once.
+    fn strobe_n(&mut self, count: usize) -> Result {
+        for _ in 0..count {
+            self.strobe()?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<S: WriteStrobe + ?Sized> WriteStrobe for &mut S {
+    fn assert(&mut self) -> Result {
+        (**self).assert()
+    }
+
+    fn release(&mut self) -> Result {
+        (**self).release()
+    }
+
+    fn strobe(&mut self) -> Result {
+        (**self).strobe()
+    }
+
+    fn strobe_n(&mut self, count: usize) -> Result {
+        (**self).strobe_n(count)
+    }
+}
+
+/// This trait represents the write-enable of [PGpio8BitInterface] and [PGpio16BitInterface].
+///
+/// It is implemented for every [OutputPin], which is toggled in software with the polarity and
+/// timing configured on the interface. Write-enable strobes generated in hardware, e.g. by a
+/// timer output or a PIO state machine, implement [WriteStrobe] instead and are passed to the
+/// interface wrapped in a [HardwareStrobe].
+pub trait WriteEnable {
+    /// Return the strobe latching the words of one call
+    ///
+    /// `polarity`, `delay` and `wait_ns` are the settings of the interface for software strobes,
+    /// see [PGpio8BitInterface::with_strobe_polarity] and [PGpio8BitInterface::with_timing].
+    fn strobe<'a, D: DelayNs>(
+        &'a mut self,
+        polarity: StrobePolarity,
+        delay: &'a mut D,
+        wait_ns: Option<u32>,
+    ) -> impl WriteStrobe + 'a;
+}
+
+impl<P: OutputPin> WriteEnable for P {
+    fn strobe<'a, D: DelayNs>(
+        &'a mut self,
+        polarity: StrobePolarity,
+        delay: &'a mut D,
+        wait_ns: Option<u32>,
+    ) -> impl WriteStrobe + 'a {
+        PinStrobe {
+            wr: self,
+            polarity,
+            delay,
+            wait_ns,
+        }
+    }
+}
+
+/// Write-enable generated in hardware by the [WriteStrobe] `S`
+///
+/// The polarity and timing configured on the interface are left to `S`.
+pub struct HardwareStrobe<S>(pub S);
+
+impl<S: WriteStrobe> WriteEnable for HardwareStrobe<S> {
+    fn strobe<'a, D: DelayNs>(
+        &'a mut self,
+        _polarity: StrobePolarity,
+        _delay: &'a mut D,
+        _wait_ns: Option<u32>,
+    ) -> impl WriteStrobe + 'a {
+        &mut self.0
+    }
+}
+
+/// [WriteStrobe] driving a write-enable pin, waiting before its release if configured
+struct PinStrobe<'a, WR, D> {
+    wr: &'a mut WR,
+    polarity: StrobePolarity,
+    delay: &'a mut D,
+    wait_ns: Option<u32>,
+}
+
+impl<WR, D> WriteStrobe for PinStrobe<'_, WR, D>
+where
+    WR: OutputPin,
+    D: DelayNs,
+{
+    fn assert(&mut self) -> Result {
+        self.polarity.assert(self.wr)
+    }
+
+    fn release(&mut self) -> Result {
+        if let Some(ns) = self.wait_ns {
+            self.delay.delay_ns(ns);
+        }
+        self.polarity.release(self.wr)
+    }
+}
+
+/// Pass on `result`, releasing `strobe` first if the write failed
+///
+/// A failed write may leave the write-enable asserted, so that the next write would complete a
+/// spurious cycle. Releasing it again is harmless if it was already idle; this is best effort,
+/// the error of the write is returned either way.
+pub(crate) fn release_on_error(strobe: &mut impl WriteStrobe, result: Result) -> Result {
+    if result.is_err() {
+        strobe.release().ok();
+    }
+    result
+}
+
+/// This trait represents the data pins of a parallel bus which can also be read.
+///
+/// The pins are outputs unless switched to inputs with [BidirectionalBus::set_input]; switching
+/// the direction of all pins at once is usually a single register write, so HALs should
+/// implement this trait directly on their ports. See [Generic8BitInOutBus] for a generic
+/// implementation using open-drain pins.
+pub trait BidirectionalBus: OutputBus {
+    /// Switch the data pins to inputs
+    fn set_input(&mut self) -> Result;
+
+    /// Switch the data pins back to outputs
+    ///
+    /// The output value is unknown afterwards, so implementations caching the last value set
+    /// must discard it here.
+    fn set_output(&mut self) -> Result;
+
+    /// Sample the data pins while they are inputs
+    fn get_value(&mut self) -> Result<Self::Word>;
+}
+
+/// Levels of the write-enable strobe
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StrobePolarity {
+    /// Idle high and pulled low for every word, so the data is sampled at the low to high edge
+    #[default]
+    ActiveLow,
+    /// Idle low and pulsed high for every word, so the data is sampled at the high to low edge
+    ActiveHigh,
+}
+
+impl StrobePolarity {
+    /// Move `pin` to its active level
+    pub(crate) fn assert(self, pin: &mut impl OutputPin) -> Result {
+        match self {
+            StrobePolarity::ActiveLow => pin.set_low(),
+            StrobePolarity::ActiveHigh => pin.set_high(),
+        }
+        .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Move `pin` back to its idle level
+    pub(crate) fn release(self, pin: &mut impl OutputPin) -> Result {
+        match self {
+            StrobePolarity::ActiveLow => pin.set_high(),
+            StrobePolarity::ActiveHigh => pin.set_low(),
+        }
+        .map_err(|_| DisplayError::BusWriteError)
+    }
+}
+
+/// Error of the parallel interfaces, keeping the error of the D/C pin
+///
+/// Converts into [DisplayError] for drivers written against [WriteOnlyDataCommand]. The
+/// [OutputBus] and [WriteStrobe] traits report [DisplayError] already, so their errors are kept
+/// as they are.
+#[derive(Debug)]
+pub enum PGpioInterfaceError<DC> {
+    /// Setting the D/C pin failed
+    Dc(DC),
+    /// Writing to the bus failed
+    Bus(DisplayError),
+}
+
+impl<DC> From<PGpioInterfaceError<DC>> for DisplayError {
+    fn from(e: PGpioInterfaceError<DC>) -> Self {
+        match e {
+            PGpioInterfaceError::Dc(_) => DisplayError::DCError,
+            PGpioInterfaceError::Bus(e) => e,
+        }
+    }
+}
+
+/// Set `dc` to select data if `data` is set, or commands otherwise
+pub(crate) fn select<P: OutputPin>(
+    dc: &mut P,
+    polarity: DcPolarity,
+    data: bool,
+) -> core::result::Result<(), P::Error> {
+    if polarity.is_high(data) {
+        dc.set_high()
+    } else {
+        dc.set_low()
+    }
+}
+
+/// Set `dc` to select data if `data` is set, or commands otherwise
+pub(crate) fn set_dc(dc: &mut impl OutputPin, polarity: DcPolarity, data: bool) -> Result {
+    select(dc, polarity, data).map_err(|_| DisplayError::DCError)
 }
 
 macro_rules! generic_bus {
     ($GenericxBitBus:ident { type Word = $Word:ident; Pins {$($PX:ident => $x:tt,)*}}) => {
         /// A generic implementation of [OutputBus] using [OutputPin]s
+        ///
+        /// The bus remembers the last value it set and only sets the pins of bits which differ
+        /// from it, skipping repeated values altogether. This relies on nothing else driving the
+        /// pins in between; if they are shared, e.g. with a keypad matrix, call
+        /// [invalidate](Self::invalidate) after using them otherwise, or turn the cache off with
+        /// [with_caching](Self::with_caching).
         pub struct $GenericxBitBus<$($PX, )*> {
             pins: ($($PX, )*),
             last: Option<$Word>,
+            inversion: $Word,
+            caching: bool,
         }
 
         impl<$($PX, )*> $GenericxBitBus<$($PX, )*>
@@ -33,7 +369,35 @@ macro_rules! generic_bus {
             ///
             /// The first pin in the tuple is the least significant bit.
             pub fn new(pins: ($($PX, )*)) -> Self {
-                Self { pins, last: None }
+                Self { pins, last: None, inversion: 0, caching: true }
+            }
+
+            /// Invert the pins set in `mask`, e.g. for data lines routed through an inverting
+            /// buffer
+            ///
+            /// Every value is still set as is, the pins of the mask are just driven to the opposite
+            /// level. This does not change the state of the pins.
+            pub fn with_inversion_mask(mut self, mask: $Word) -> Self {
+                self.inversion = mask;
+                // The pins have to be driven anew at the next value
+                self.last = None;
+                self
+            }
+
+            /// Remember the last value set to skip unchanged pins, which is the default
+            ///
+            /// Without caching, every pin is set for every value.
+            pub fn with_caching(mut self, caching: bool) -> Self {
+                self.caching = caching;
+                self.last = None;
+                self
+            }
+
+            /// Forget the last value set, so all pins are set for the next value
+            ///
+            /// Call this after something else has driven the pins.
+            pub fn invalidate(&mut self) {
+                self.last = None;
             }
 
             /// Consumes the bus and returns the pins. This does not change the state of the pins.
@@ -65,10 +429,12 @@ macro_rules! generic_bus {
                     None => !0, // all ones, this ensures that we will update all the pins
                 };
 
+                let levels = value ^ self.inversion;
+
                 $(
                     let mask = 1 << $x;
                     if changed & mask != 0 {
-                        if value & mask != 0 {
+                        if levels & mask != 0 {
                             self.pins.$x.set_high()
                         } else {
                             self.pins.$x.set_low()
@@ -77,7 +443,35 @@ macro_rules! generic_bus {
                     }
                 )*
 
-                self.last = Some(value);
+                if self.caching {
+                    self.last = Some(value);
+                }
+                Ok(())
+            }
+
+            fn invalidate(&mut self) {
+                self.last = None;
+            }
+
+            fn write_words_iter(
+                &mut self,
+                words: impl Iterator<Item = Self::Word>,
+                strobe: &mut impl WriteStrobe,
+            ) -> Result {
+                let mut words = words.peekable();
+                while let Some(word) = words.next() {
+                    strobe.assert()?;
+                    self.set_value(word)?;
+                    strobe.release()?;
+
+                    // A run of identical words is already on the bus, so only pulse the strobe
+                    let mut run = 0;
+                    while self.last == Some(word) && words.next_if_eq(&word).is_some() {
+                        run += 1;
+                    }
+                    strobe.strobe_n(run)?;
+                }
+
                 Ok(())
             }
         }
@@ -94,6 +488,18 @@ macro_rules! generic_bus {
     };
 }
 
+generic_bus! {
+    Generic4BitBus {
+        type Word = u8;
+        Pins {
+            P0 => 0,
+            P1 => 1,
+            P2 => 2,
+            P3 => 3,
+        }
+    }
+}
+
 generic_bus! {
     Generic8BitBus {
         type Word = u8;
@@ -110,6 +516,23 @@ generic_bus! {
     }
 }
 
+generic_bus! {
+    Generic9BitBus {
+        type Word = u16;
+        Pins {
+            P0 => 0,
+            P1 => 1,
+            P2 => 2,
+            P3 => 3,
+            P4 => 4,
+            P5 => 5,
+            P6 => 6,
+            P7 => 7,
+            P8 => 8,
+        }
+    }
+}
+
 generic_bus! {
     Generic16BitBus {
         type Word = u16;
@@ -134,30 +557,143 @@ generic_bus! {
     }
 }
 
+generic_bus! {
+    Generic24BitBus {
+        type Word = u32;
+        Pins {
+            P0 => 0,
+            P1 => 1,
+            P2 => 2,
+            P3 => 3,
+            P4 => 4,
+            P5 => 5,
+            P6 => 6,
+            P7 => 7,
+            P8 => 8,
+            P9 => 9,
+            P10 => 10,
+            P11 => 11,
+            P12 => 12,
+            P13 => 13,
+            P14 => 14,
+            P15 => 15,
+            P16 => 16,
+            P17 => 17,
+            P18 => 18,
+            P19 => 19,
+            P20 => 20,
+            P21 => 21,
+            P22 => 22,
+            P23 => 23,
+        }
+    }
+}
+
 /// Parallel 8 Bit communication interface
 ///
 /// This interface implements an 8-Bit "8080" style write-only display interface using any
 /// 8-bit [OutputBus] implementation as well as one
 /// `OutputPin` for the data/command selection and one `OutputPin` for the write-enable flag.
+/// The write-enable can also be generated in hardware, see [WriteEnable].
 ///
 /// All pins are supposed to be high-active, high for the D/C pin meaning "data" and the
 /// write-enable being pulled low before the setting of the bits and supposed to be sampled at a
-/// low to high edge.
-pub struct PGpio8BitInterface<BUS, DC, WR> {
+/// low to high edge, unless inverted with [PGpio8BitInterface::with_dc_polarity] and
+/// [PGpio8BitInterface::with_strobe_polarity]. Slow
+/// connections may need the write-enable to be stretched, see [PGpio8BitInterface::with_timing].
+///
+/// If a write fails, the write-enable is returned to its idle level (best effort) before the
+/// error is passed on, so the next call does not complete a spurious cycle. The D/C pin is left
+/// at the level selected by the failed call and the bus holds an unspecified value.
+pub struct PGpio8BitInterface<BUS, DC, WR, D = NoDelay> {
     bus: BUS,
     dc: DC,
     wr: WR,
+    delay: D,
+    dc_polarity: DcPolarity,
+    wr_polarity: StrobePolarity,
+    wr_wait_ns: Option<u32>,
+    #[cfg(feature = "async")]
+    yield_every: usize,
+    #[cfg(feature = "nb")]
+    progress: display_interface::TryProgress,
 }
 
 impl<BUS, DC, WR> PGpio8BitInterface<BUS, DC, WR>
 where
     BUS: OutputBus<Word = u8>,
     DC: OutputPin,
-    WR: OutputPin,
+    WR: WriteEnable,
 {
     /// Create new parallel GPIO interface for communication with a display driver
     pub fn new(bus: BUS, dc: DC, wr: WR) -> Self {
-        Self { bus, dc, wr }
+        Self {
+            bus,
+            dc,
+            wr,
+            delay: NoDelay,
+            dc_polarity: DcPolarity::default(),
+            wr_polarity: StrobePolarity::default(),
+            wr_wait_ns: None,
+            #[cfg(feature = "async")]
+            yield_every: 0,
+            #[cfg(feature = "nb")]
+            progress: display_interface::TryProgress::new(),
+        }
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio8BitInterface<BUS, DC, WR, D> {
+    /// Stretch the write-enable pulse of every word, using `delay`
+    ///
+    /// The write-enable is held low for at least `wr_low_ns` nanoseconds, and the bus value is
+    /// set at least `data_setup_ns` nanoseconds before the write-enable is released. The value
+    /// is set right after pulling the write-enable low, so both are met by a single wait of the
+    /// longer of the two between setting the bus and releasing the write-enable.
+    pub fn with_timing<D2>(
+        self,
+        delay: D2,
+        wr_low_ns: u32,
+        data_setup_ns: u32,
+    ) -> PGpio8BitInterface<BUS, DC, WR, D2> {
+        PGpio8BitInterface {
+            bus: self.bus,
+            dc: self.dc,
+            wr: self.wr,
+            delay,
+            dc_polarity: self.dc_polarity,
+            wr_polarity: self.wr_polarity,
+            wr_wait_ns: Some(wr_low_ns.max(data_setup_ns)),
+            #[cfg(feature = "async")]
+            yield_every: self.yield_every,
+            #[cfg(feature = "nb")]
+            progress: self.progress,
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Yield to the executor every `words` words written by the `async` implementation
+    ///
+    /// The pins are driven by blocking calls, so without yielding a long transfer keeps other
+    /// tasks from running until it is done. The default of 0 never yields, which gives the
+    /// highest throughput.
+    #[cfg(feature = "async")]
+    pub fn with_yield_every(mut self, words: usize) -> Self {
+        self.yield_every = words;
+        self
     }
 
     /// Consume the display interface and return
@@ -166,55 +702,106 @@ where
         (self.bus, self.dc, self.wr)
     }
 
-    fn write_iter(&mut self, iter: impl Iterator<Item = u8>) -> Result {
-        for value in iter {
-            self.wr.set_low().map_err(|_| DisplayError::BusWriteError)?;
-            self.bus.set_value(value)?;
-            self.wr
-                .set_high()
-                .map_err(|_| DisplayError::BusWriteError)?;
-        }
+    /// Consume the display interface and return
+    /// the bus, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (BUS, DC, WR, D) {
+        (self.bus, self.dc, self.wr, self.delay)
+    }
+}
 
-        Ok(())
+impl<BUS, DC, WR, D> PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    /// Write a single command word, bypassing the [DataFormat] handling
+    ///
+    /// This is an escape hatch for controllers needing non-standard cycles, e.g. during their
+    /// initialization: the D/C pin is set to command, `word` is put on the bus as is and the
+    /// write-enable pulsed once. Prefer [WriteOnlyDataCommand::send_commands] otherwise.
+    pub fn write_raw_command_word(&mut self, word: u8) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.write_raw_word(word)
     }
 
-    fn write_pairs(&mut self, iter: impl Iterator<Item = [u8; 2]>) -> Result {
-        use core::iter::once;
-        self.write_iter(iter.flat_map(|[first, second]| once(first).chain(once(second))))
+    /// Write a single data word, bypassing the [DataFormat] handling
+    ///
+    /// Like [PGpio8BitInterface::write_raw_command_word], but with the D/C pin set to data.
+    pub fn write_raw_data_word(&mut self, word: u8) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, true)?;
+        self.write_raw_word(word)
+    }
+
+    /// Set the D/C pin to command and release the write-enable to its idle level
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        #[cfg(feature = "nb")]
+        self.progress.reset();
+        let (bus, mut strobe) = self.bus_and_strobe();
+        bus.invalidate();
+        strobe.release()
+    }
+
+    fn write_raw_word(&mut self, word: u8) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = strobe.assert().and_then(|_| bus.set_value(word));
+        release_on_error(&mut strobe, result)?;
+        strobe.release()
+    }
+
+    /// Split into the bus and the strobe driving the write-enable
+    fn bus_and_strobe(&mut self) -> (&mut BUS, impl WriteStrobe + '_) {
+        let strobe = self
+            .wr
+            .strobe(self.wr_polarity, &mut self.delay, self.wr_wait_ns);
+        (&mut self.bus, strobe)
     }
 
     fn write_data(&mut self, data: DataFormat<'_>) -> Result {
-        match data {
-            DataFormat::U8(slice) => self.write_iter(slice.iter().copied()),
-            DataFormat::U8Iter(iter) => self.write_iter(iter),
-            DataFormat::U16(slice) => self.write_pairs(slice.iter().copied().map(u16::to_ne_bytes)),
-            DataFormat::U16BE(slice) => {
-                self.write_pairs(slice.iter().copied().map(u16::to_be_bytes))
-            }
-            DataFormat::U16LE(slice) => {
-                self.write_pairs(slice.iter().copied().map(u16::to_le_bytes))
-            }
-            DataFormat::U16BEIter(iter) => self.write_pairs(iter.map(u16::to_be_bytes)),
-            DataFormat::U16LEIter(iter) => self.write_pairs(iter.map(u16::to_le_bytes)),
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = write_format_8bit(bus, &mut strobe, data);
+        release_on_error(&mut strobe, result)
     }
 }
 
-impl<BUS, DC, WR> WriteOnlyDataCommand for PGpio8BitInterface<BUS, DC, WR>
+impl<BUS, DC, WR, D> TypedWriteOnlyDataCommand for PGpio8BitInterface<BUS, DC, WR, D>
 where
     BUS: OutputBus<Word = u8>,
     DC: OutputPin,
-    WR: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds).map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf).map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, WR, D> WriteOnlyDataCommand for PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
 {
     fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
-        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
-        self.write_data(cmds)
+        self.send_commands_typed(cmds).map_err(Into::into)
     }
 
     fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
-        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
-        self.write_data(buf)
+        self.send_data_typed(buf).map_err(Into::into)
     }
 }
 
@@ -223,25 +810,120 @@ where
 /// This interface implements a 16-Bit "8080" style write-only display interface using any
 /// 16-bit [OutputBus] implementation as well as one
 /// `OutputPin` for the data/command selection and one `OutputPin` for the write-enable flag.
+/// The write-enable can also be generated in hardware, see [WriteEnable].
 ///
 /// All pins are supposed to be high-active, high for the D/C pin meaning "data" and the
 /// write-enable being pulled low before the setting of the bits and supposed to be sampled at a
-/// low to high edge.
-pub struct PGpio16BitInterface<BUS, DC, WR> {
+/// low to high edge, unless inverted with [PGpio16BitInterface::with_dc_polarity] and
+/// [PGpio16BitInterface::with_strobe_polarity]. Slow connections may need the write-enable to be
+/// stretched, see [PGpio16BitInterface::with_timing].
+///
+/// Bytes are zero-extended to one word each by default, see
+/// [PGpio16BitInterface::with_u8_packing] for byte streams holding 16-bit pixels.
+///
+/// If a write fails, the write-enable is returned to its idle level (best effort) before the
+/// error is passed on, so the next call does not complete a spurious cycle. The D/C pin is left
+/// at the level selected by the failed call and the bus holds an unspecified value.
+pub struct PGpio16BitInterface<BUS, DC, WR, D = NoDelay> {
     bus: BUS,
     dc: DC,
     wr: WR,
+    delay: D,
+    u8_packing: U8Packing,
+    dc_polarity: DcPolarity,
+    wr_polarity: StrobePolarity,
+    wr_wait_ns: Option<u32>,
+    #[cfg(feature = "async")]
+    yield_every: usize,
+    #[cfg(feature = "nb")]
+    progress: display_interface::TryProgress,
 }
 
 impl<BUS, DC, WR> PGpio16BitInterface<BUS, DC, WR>
 where
     BUS: OutputBus<Word = u16>,
     DC: OutputPin,
-    WR: OutputPin,
+    WR: WriteEnable,
 {
     /// Create new parallel GPIO interface for communication with a display driver
     pub fn new(bus: BUS, dc: DC, wr: WR) -> Self {
-        Self { bus, dc, wr }
+        Self {
+            bus,
+            dc,
+            wr,
+            delay: NoDelay,
+            u8_packing: U8Packing::default(),
+            dc_polarity: DcPolarity::default(),
+            wr_polarity: StrobePolarity::default(),
+            wr_wait_ns: None,
+            #[cfg(feature = "async")]
+            yield_every: 0,
+            #[cfg(feature = "nb")]
+            progress: display_interface::TryProgress::new(),
+        }
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio16BitInterface<BUS, DC, WR, D> {
+    /// Stretch the write-enable pulse of every word, using `delay`
+    ///
+    /// The write-enable is held low for at least `wr_low_ns` nanoseconds, and the bus value is
+    /// set at least `data_setup_ns` nanoseconds before the write-enable is released. The value
+    /// is set right after pulling the write-enable low, so both are met by a single wait of the
+    /// longer of the two between setting the bus and releasing the write-enable.
+    pub fn with_timing<D2>(
+        self,
+        delay: D2,
+        wr_low_ns: u32,
+        data_setup_ns: u32,
+    ) -> PGpio16BitInterface<BUS, DC, WR, D2> {
+        PGpio16BitInterface {
+            bus: self.bus,
+            dc: self.dc,
+            wr: self.wr,
+            delay,
+            u8_packing: self.u8_packing,
+            dc_polarity: self.dc_polarity,
+            wr_polarity: self.wr_polarity,
+            wr_wait_ns: Some(wr_low_ns.max(data_setup_ns)),
+            #[cfg(feature = "async")]
+            yield_every: self.yield_every,
+            #[cfg(feature = "nb")]
+            progress: self.progress,
+        }
+    }
+
+    /// Select how [DataFormat::U8] and [DataFormat::U8Iter] data is put on the bus
+    ///
+    /// Commands and their parameters are always zero-extended to one word per byte.
+    pub fn with_u8_packing(mut self, packing: U8Packing) -> Self {
+        self.u8_packing = packing;
+        self
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Yield to the executor every `words` words written by the `async` implementation
+    ///
+    /// The pins are driven by blocking calls, so without yielding a long transfer keeps other
+    /// tasks from running until it is done. The default of 0 never yields, which gives the
+    /// highest throughput.
+    #[cfg(feature = "async")]
+    pub fn with_yield_every(mut self, words: usize) -> Self {
+        self.yield_every = words;
+        self
     }
 
     /// Consume the display interface and return
@@ -250,45 +932,107 @@ where
         (self.bus, self.dc, self.wr)
     }
 
-    fn write_iter(&mut self, iter: impl Iterator<Item = u16>) -> Result {
-        for value in iter {
-            self.wr.set_low().map_err(|_| DisplayError::BusWriteError)?;
-            self.bus.set_value(value)?;
-            self.wr
-                .set_high()
-                .map_err(|_| DisplayError::BusWriteError)?;
-        }
+    /// Consume the display interface and return
+    /// the bus, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (BUS, DC, WR, D) {
+        (self.bus, self.dc, self.wr, self.delay)
+    }
+}
 
-        Ok(())
+impl<BUS, DC, WR, D> PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    /// Write a single command word, bypassing the [DataFormat] handling
+    ///
+    /// This is an escape hatch for controllers needing non-standard cycles, e.g. during their
+    /// initialization: the D/C pin is set to command, `word` is put on the bus as is and the
+    /// write-enable pulsed once. Prefer [WriteOnlyDataCommand::send_commands] otherwise.
+    pub fn write_raw_command_word(&mut self, word: u16) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.write_raw_word(word)
     }
 
-    fn write_data(&mut self, data: DataFormat<'_>) -> Result {
-        match data {
-            DataFormat::U8(slice) => self.write_iter(slice.iter().copied().map(u16::from)),
-            DataFormat::U8Iter(iter) => self.write_iter(iter.map(u16::from)),
-            DataFormat::U16(slice) => self.write_iter(slice.iter().copied()),
-            DataFormat::U16BE(slice) => self.write_iter(slice.iter().copied()),
-            DataFormat::U16LE(slice) => self.write_iter(slice.iter().copied()),
-            DataFormat::U16BEIter(iter) => self.write_iter(iter),
-            DataFormat::U16LEIter(iter) => self.write_iter(iter),
-            _ => Err(DisplayError::DataFormatNotImplemented),
-        }
+    /// Write a single data word, bypassing the [DataFormat] handling
+    ///
+    /// Like [PGpio16BitInterface::write_raw_command_word], but with the D/C pin set to data.
+    pub fn write_raw_data_word(&mut self, word: u16) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, true)?;
+        self.write_raw_word(word)
+    }
+
+    /// Set the D/C pin to command and release the write-enable to its idle level
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        #[cfg(feature = "nb")]
+        self.progress.reset();
+        let (bus, mut strobe) = self.bus_and_strobe();
+        bus.invalidate();
+        strobe.release()
+    }
+
+    fn write_raw_word(&mut self, word: u16) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = strobe.assert().and_then(|_| bus.set_value(word));
+        release_on_error(&mut strobe, result)?;
+        strobe.release()
+    }
+
+    /// Split into the bus and the strobe driving the write-enable
+    fn bus_and_strobe(&mut self) -> (&mut BUS, impl WriteStrobe + '_) {
+        let strobe = self
+            .wr
+            .strobe(self.wr_polarity, &mut self.delay, self.wr_wait_ns);
+        (&mut self.bus, strobe)
+    }
+
+    fn write_data(&mut self, data: DataFormat<'_>, packing: U8Packing) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        let result = write_format_16bit(bus, &mut strobe, data, packing);
+        release_on_error(&mut strobe, result)
     }
 }
 
-impl<BUS, DC, WR> WriteOnlyDataCommand for PGpio16BitInterface<BUS, DC, WR>
+impl<BUS, DC, WR, D> TypedWriteOnlyDataCommand for PGpio16BitInterface<BUS, DC, WR, D>
 where
     BUS: OutputBus<Word = u16>,
     DC: OutputPin,
-    WR: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds, U8Packing::ZeroExtend)
+            .map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf, self.u8_packing)
+            .map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, WR, D> WriteOnlyDataCommand for PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
 {
     fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
-        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
-        self.write_data(cmds)
+        self.send_commands_typed(cmds).map_err(Into::into)
     }
 
     fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
-        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
-        self.write_data(buf)
+        self.send_data_typed(buf).map_err(Into::into)
     }
 }
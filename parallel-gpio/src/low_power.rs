@@ -0,0 +1,62 @@
+//! Parking the pins of the parallel interfaces for low-power modes
+
+use display_interface::{DisplayError, LowPower};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{OutputBus, PGpio16BitInterface, PGpio8BitInterface, Result, WriteEnable, WriteStrobe};
+
+/// Release the write-enable to its idle level and drive the bus to zero
+fn park<W: Default>(bus: &mut impl OutputBus<Word = W>, strobe: &mut impl WriteStrobe) -> Result {
+    strobe.release()?;
+    bus.set_value(W::default())?;
+    bus.invalidate();
+    Ok(())
+}
+
+/// Release the write-enable to its idle level and forget the value cached by the bus
+fn unpark(bus: &mut impl OutputBus, strobe: &mut impl WriteStrobe) -> Result {
+    bus.invalidate();
+    strobe.release()
+}
+
+/// The D/C pin is driven low, the write-enable released to its idle level and all bus pins driven
+/// low. The bus cache is invalidated on both calls, so the next write drives every bus pin anew.
+impl<BUS, DC, WR, D> LowPower for PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn suspend(&mut self) -> Result {
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+        let (bus, mut strobe) = self.bus_and_strobe();
+        park(bus, &mut strobe)
+    }
+
+    fn resume(&mut self) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        unpark(bus, &mut strobe)
+    }
+}
+
+/// The D/C pin is driven low, the write-enable released to its idle level and all bus pins driven
+/// low. The bus cache is invalidated on both calls, so the next write drives every bus pin anew.
+impl<BUS, DC, WR, D> LowPower for PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn suspend(&mut self) -> Result {
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+        let (bus, mut strobe) = self.bus_and_strobe();
+        park(bus, &mut strobe)
+    }
+
+    fn resume(&mut self) -> Result {
+        let (bus, mut strobe) = self.bus_and_strobe();
+        unpark(bus, &mut strobe)
+    }
+}
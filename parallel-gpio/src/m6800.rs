@@ -0,0 +1,168 @@
+use display_interface::{
+    DataFormat, DcPolarity, DisplayError, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    release_on_error, select, set_dc, write_format_8bit, NoDelay, OutputBus, PGpioInterfaceError,
+    Result, StrobePolarity, WriteEnable,
+};
+
+/// Parallel 8 Bit communication interface using the Motorola 6800 protocol
+///
+/// This interface implements an 8-Bit "6800" style write-only display interface as found on the
+/// ST7565, T6963 or HD44780, using any 8-bit [OutputBus] implementation as well as one
+/// `OutputPin` each for the data/command selection, the enable clock and the read/write line.
+///
+/// The read/write line is pulled low to select writing at the start of every call. For every byte
+/// the enable clock is then raised, the byte put on the bus and the enable clock lowered again,
+/// so the controller latches the byte at the falling edge. 16-bit formats are split into two
+/// bytes in the requested byte order, like on [PGpio8BitInterface](crate::PGpio8BitInterface).
+///
+/// The D/C pin is supposed to be high for data, unless inverted with
+/// [PGpio6800Interface::with_dc_polarity]. Slow glass may need the enable pulse to be stretched,
+/// see [PGpio6800Interface::with_timing].
+pub struct PGpio6800Interface<BUS, DC, E, RW, D = NoDelay> {
+    bus: BUS,
+    dc: DC,
+    e: E,
+    rw: RW,
+    delay: D,
+    dc_polarity: DcPolarity,
+    e_wait_ns: Option<u32>,
+}
+
+impl<BUS, DC, E, RW> PGpio6800Interface<BUS, DC, E, RW>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    E: OutputPin,
+    RW: OutputPin,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    ///
+    /// The enable pin is expected to be low.
+    pub fn new(bus: BUS, dc: DC, e: E, rw: RW) -> Self {
+        Self {
+            bus,
+            dc,
+            e,
+            rw,
+            delay: NoDelay,
+            dc_polarity: DcPolarity::default(),
+            e_wait_ns: None,
+        }
+    }
+}
+
+impl<BUS, DC, E, RW, D> PGpio6800Interface<BUS, DC, E, RW, D> {
+    /// Stretch the enable pulse of every byte, using `delay`
+    ///
+    /// The enable clock is held high for at least `e_high_ns` nanoseconds, and the byte is put on
+    /// the bus at least `data_setup_ns` nanoseconds before the falling edge. The byte is set right
+    /// after the rising edge, so both are met by a single wait of the longer of the two before the
+    /// falling edge.
+    pub fn with_timing<D2>(
+        self,
+        delay: D2,
+        e_high_ns: u32,
+        data_setup_ns: u32,
+    ) -> PGpio6800Interface<BUS, DC, E, RW, D2> {
+        PGpio6800Interface {
+            bus: self.bus,
+            dc: self.dc,
+            e: self.e,
+            rw: self.rw,
+            delay,
+            dc_polarity: self.dc_polarity,
+            e_wait_ns: Some(e_high_ns.max(data_setup_ns)),
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pins used by it
+    pub fn release(self) -> (BUS, DC, E, RW) {
+        (self.bus, self.dc, self.e, self.rw)
+    }
+
+    /// Consume the display interface and return
+    /// the bus, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (BUS, DC, E, RW, D) {
+        (self.bus, self.dc, self.e, self.rw, self.delay)
+    }
+}
+
+impl<BUS, DC, E, RW, D> PGpio6800Interface<BUS, DC, E, RW, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    E: OutputPin,
+    RW: OutputPin,
+    D: DelayNs,
+{
+    /// Set the D/C pin to command, R/W low for writing and pull the enable low
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.bus.invalidate();
+        self.rw.set_low().map_err(|_| DisplayError::BusWriteError)?;
+        self.e.set_low().map_err(|_| DisplayError::BusWriteError)
+    }
+
+    fn write_data(&mut self, data: DataFormat<'_>) -> Result {
+        self.rw.set_low().map_err(|_| DisplayError::BusWriteError)?;
+
+        // The enable clock strobes every byte like a high-active write-enable
+        let mut strobe = self
+            .e
+            .strobe(StrobePolarity::ActiveHigh, &mut self.delay, self.e_wait_ns);
+        let result = write_format_8bit(&mut self.bus, &mut strobe, data);
+        release_on_error(&mut strobe, result)
+    }
+}
+
+impl<BUS, DC, E, RW, D> TypedWriteOnlyDataCommand for PGpio6800Interface<BUS, DC, E, RW, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    E: OutputPin,
+    RW: OutputPin,
+    D: DelayNs,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds).map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf).map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, E, RW, D> WriteOnlyDataCommand for PGpio6800Interface<BUS, DC, E, RW, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    E: OutputPin,
+    RW: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_commands_typed(cmds).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
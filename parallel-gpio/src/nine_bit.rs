@@ -0,0 +1,109 @@
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use embedded_hal::digital::OutputPin;
+
+use crate::{
+    release_on_error, write_format_8bit, NoDelay, OutputBus, Result, StrobePolarity, WriteEnable,
+    WriteStrobe,
+};
+
+/// Bit 8 of every bus word, set for data and cleared for commands
+const DATA_BIT: u16 = 1 << 8;
+
+/// Parallel 9 Bit communication interface
+///
+/// This interface implements the 9-Bit "8080" mode offered by some controllers, where the ninth
+/// data line carries the data/command selection. It uses any 9-bit [OutputBus] implementation,
+/// e.g. [Generic9BitBus](crate::Generic9BitBus), and one `OutputPin` for the write-enable flag;
+/// there is no separate D/C pin.
+///
+/// Every byte is written as one bus word, with bit 8 low for commands and high for data. 16-bit
+/// formats are split into two bytes in the requested byte order, like on
+/// [PGpio8BitInterface](crate::PGpio8BitInterface). The write-enable is pulled low before the
+/// setting of the bits and supposed to be sampled at a low to high edge, unless inverted with
+/// [PGpio9BitInterface::with_strobe_polarity].
+pub struct PGpio9BitInterface<BUS, WR> {
+    bus: BUS,
+    wr: WR,
+    wr_polarity: StrobePolarity,
+}
+
+impl<BUS, WR> PGpio9BitInterface<BUS, WR>
+where
+    BUS: OutputBus<Word = u16>,
+    WR: OutputPin,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    pub fn new(bus: BUS, wr: WR) -> Self {
+        Self {
+            bus,
+            wr,
+            wr_polarity: StrobePolarity::default(),
+        }
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pin used by it
+    pub fn release(self) -> (BUS, WR) {
+        (self.bus, self.wr)
+    }
+
+    /// Release the write-enable to its idle level, the D/C bit is driven with the next word
+    pub fn resync(&mut self) -> Result {
+        self.bus.invalidate();
+        self.wr_polarity.release(&mut self.wr)
+    }
+
+    fn write_data(&mut self, dc: u16, data: DataFormat<'_>) -> Result {
+        let mut delay = NoDelay;
+        let mut strobe = self.wr.strobe(self.wr_polarity, &mut delay, None);
+        let result = write_format_8bit(&mut DcBit(&mut self.bus, dc), &mut strobe, data);
+        release_on_error(&mut strobe, result)
+    }
+}
+
+/// 8-bit view of a 9-bit bus, adding the given D/C bit to every word
+struct DcBit<'a, BUS>(&'a mut BUS, u16);
+
+impl<BUS: OutputBus<Word = u16>> OutputBus for DcBit<'_, BUS> {
+    type Word = u8;
+
+    fn set_value(&mut self, value: u8) -> Result {
+        self.0.set_value(self.1 | u16::from(value))
+    }
+
+    fn invalidate(&mut self) {
+        self.0.invalidate();
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = u8>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        let dc = self.1;
+        self.0
+            .write_words_iter(words.map(|word| dc | u16::from(word)), strobe)
+    }
+}
+
+impl<BUS, WR> WriteOnlyDataCommand for PGpio9BitInterface<BUS, WR>
+where
+    BUS: OutputBus<Word = u16>,
+    WR: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.write_data(0, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.write_data(DATA_BIT, buf)
+    }
+}
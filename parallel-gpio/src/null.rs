@@ -0,0 +1,86 @@
+//! Bus and pin discarding everything set on them, for benchmarks
+
+use core::{convert::Infallible, hint::black_box, marker::PhantomData};
+
+use embedded_hal::digital::{ErrorType, OutputPin};
+
+use crate::{OutputBus, Result};
+
+/// [OutputBus] of `W` accepting every value at once and only counting the words set
+///
+/// Paired with [NullPin]s for D/C and write-enable it measures the time an interface, or a driver
+/// rendering through it, spends on its own, without any GPIO in the way. The words are counted
+/// rather than bytes since that is what the bus transfers, e.g. one word per byte on an 8-bit bus
+/// and one per pixel on a 16-bit bus. Every value is passed through [black_box], so the work
+/// producing it isn't optimized away.
+///
+/// ```
+/// use display_interface_parallel_gpio::{
+///     DataFormat, NullOutputBus, NullPin, PGpio8BitInterface, WriteOnlyDataCommand,
+/// };
+///
+/// let mut iface = PGpio8BitInterface::new(NullOutputBus::<u8>::new(), NullPin, NullPin);
+/// iface.send_data(DataFormat::U16BE(&mut [0; 100])).unwrap();
+/// let (bus, _, _) = iface.release();
+/// assert_eq!(bus.words(), 200);
+/// ```
+#[derive(Debug)]
+pub struct NullOutputBus<W> {
+    words: usize,
+    word: PhantomData<W>,
+}
+
+impl<W> Default for NullOutputBus<W> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<W> NullOutputBus<W> {
+    /// Create a bus which hasn't seen any words yet
+    pub const fn new() -> Self {
+        Self {
+            words: 0,
+            word: PhantomData,
+        }
+    }
+
+    /// Number of words set since creation or the last [reset](Self::reset)
+    pub fn words(&self) -> usize {
+        self.words
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        self.words = 0;
+    }
+}
+
+impl<W: Copy> OutputBus for NullOutputBus<W> {
+    type Word = W;
+
+    fn set_value(&mut self, value: W) -> Result {
+        black_box(value);
+        self.words += 1;
+        Ok(())
+    }
+}
+
+/// [OutputPin] which does nothing, for the D/C, write-enable or chip select pin next to a
+/// [NullOutputBus]
+#[derive(Debug, Default)]
+pub struct NullPin;
+
+impl ErrorType for NullPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NullPin {
+    fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+        Ok(())
+    }
+}
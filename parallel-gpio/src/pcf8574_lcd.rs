@@ -0,0 +1,171 @@
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, i2c::I2c};
+
+use crate::{write_format_8bit, OutputBus, Result, WriteStrobe};
+
+/// Register select, high for data
+const RS: u8 = 1 << 0;
+/// Enable clock, latching the nibble at its falling edge
+const EN: u8 = 1 << 2;
+/// Backlight transistor
+const BACKLIGHT: u8 = 1 << 3;
+
+/// Port values buffered per I2C write, two for every nibble
+const BUFFER_SIZE: usize = 32;
+
+/// HD44780 communication interface through the common PCF8574 I2C backpack
+///
+/// Character LCD backpacks wire the port of a PCF8574 to the LCD as follows: P0 to RS, P1 to
+/// RW, P2 to EN, P3 to the backlight and P4 to P7 to D4 to D7. Every byte is transferred in the
+/// 4-bit mode of the HD44780 as two nibbles, high nibble first, like on
+/// [PGpio4BitInterface](crate::PGpio4BitInterface).
+///
+/// Every call first sets RS with EN low, so RS is stable before the first rising edge of EN.
+/// Each nibble is then put on D4 to D7 together with EN high, followed by the same nibble with
+/// EN low, which latches it. All port values of a call are sent in as few I2C writes as
+/// possible; at 100 kHz a byte takes 360 µs, more than the execution time of all commands but
+/// clear display and return home, which need an additional wait of 1.52 ms by the driver.
+///
+/// The controller has to be switched to 4-bit mode with [Pcf8574Lcd4BitInterface::init] before
+/// sending any command.
+pub struct Pcf8574Lcd4BitInterface<I2C> {
+    i2c: I2C,
+    address: u8,
+    backlight: bool,
+}
+
+impl<I2C> Pcf8574Lcd4BitInterface<I2C>
+where
+    I2C: I2c,
+{
+    /// Create new interface for the backpack at `address`, with the backlight on
+    ///
+    /// The address is usually 0x27 for the PCF8574 and 0x3f for the PCF8574A.
+    pub fn new(i2c: I2C, address: u8) -> Self {
+        Self {
+            i2c,
+            address,
+            backlight: true,
+        }
+    }
+
+    /// Switch the backlight on or off right away
+    pub fn set_backlight(&mut self, on: bool) -> Result {
+        self.backlight = on;
+        let port = self.port(0);
+        self.write(&[port])
+    }
+
+    /// Switch the controller into 4-bit mode, regardless of the mode it is in
+    ///
+    /// This is the initialization by instruction from the HD44780 datasheet: three `0x3`
+    /// nibbles, i.e. the upper half of "function set: 8-bit", followed by a `0x2` nibble selecting
+    /// 4-bit mode. The controller needs 40 ms after power-on before the first nibble, which is
+    /// left to the caller. Afterwards, send the full function set command with the number of
+    /// lines and the font.
+    pub fn init(&mut self, delay: &mut impl DelayNs) -> Result {
+        for (nibble, wait_us) in [(0x3, 4_100), (0x3, 100), (0x3, 100), (0x2, 100)] {
+            let port = self.port(nibble << 4);
+            self.write(&[port, port | EN, port])?;
+            delay.delay_us(wait_us);
+        }
+
+        Ok(())
+    }
+
+    /// Consume the display interface and return the I2C bus used by it
+    pub fn release(self) -> I2C {
+        self.i2c
+    }
+
+    /// Port value for `bits` with the backlight bit applied
+    fn port(&self, bits: u8) -> u8 {
+        if self.backlight {
+            bits | BACKLIGHT
+        } else {
+            bits
+        }
+    }
+
+    fn write(&mut self, ports: &[u8]) -> Result {
+        self.i2c
+            .write(self.address, ports)
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    fn write_data(&mut self, rs: u8, data: DataFormat<'_>) -> Result {
+        let mut ports = Ports {
+            iface: self,
+            rs,
+            buf: [0; BUFFER_SIZE],
+            len: 0,
+        };
+        ports.buf[0] = ports.iface.port(rs);
+        ports.len = 1;
+
+        write_format_8bit(&mut ports, &mut EnableInPorts, data)?;
+        ports.flush()
+    }
+}
+
+/// Port values of one call, sent in as few I2C writes as possible
+struct Ports<'a, I2C> {
+    iface: &'a mut Pcf8574Lcd4BitInterface<I2C>,
+    rs: u8,
+    buf: [u8; BUFFER_SIZE],
+    len: usize,
+}
+
+impl<I2C: I2c> Ports<'_, I2C> {
+    /// Send the buffered port values
+    fn flush(&mut self) -> Result {
+        let len = core::mem::take(&mut self.len);
+        self.iface.write(&self.buf[..len])
+    }
+}
+
+impl<I2C: I2c> OutputBus for Ports<'_, I2C> {
+    type Word = u8;
+
+    /// Buffer both nibbles of `value`, each with EN high followed by EN low
+    fn set_value(&mut self, value: u8) -> Result {
+        if self.len + 4 > BUFFER_SIZE {
+            self.flush()?;
+        }
+
+        for nibble in [value & 0xf0, value << 4] {
+            let port = self.iface.port(nibble | self.rs);
+            self.buf[self.len] = port | EN;
+            self.buf[self.len + 1] = port;
+            self.len += 2;
+        }
+
+        Ok(())
+    }
+}
+
+/// The enable clock is pulsed by the port values themselves, so there is nothing to strobe
+struct EnableInPorts;
+
+impl WriteStrobe for EnableInPorts {
+    fn assert(&mut self) -> Result {
+        Ok(())
+    }
+
+    fn release(&mut self) -> Result {
+        Ok(())
+    }
+}
+
+impl<I2C> WriteOnlyDataCommand for Pcf8574Lcd4BitInterface<I2C>
+where
+    I2C: I2c,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.write_data(0, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.write_data(RS, buf)
+    }
+}
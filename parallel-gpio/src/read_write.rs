@@ -0,0 +1,136 @@
+use display_interface::{
+    DataFormat, DcPolarity, DisplayError, ReadData, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
+use embedded_hal::digital::OutputPin;
+
+use crate::{
+    set_dc, BidirectionalBus, PGpio8BitInterface, PGpioInterfaceError, Result, StrobePolarity,
+    WriteEnable,
+};
+
+/// Parallel 8 Bit communication interface with read support
+///
+/// This interface extends [PGpio8BitInterface] with a read strobe, using any 8-bit
+/// [BidirectionalBus] implementation as well as one `OutputPin` each for the data/command
+/// selection, the write-enable and the read-enable flag. Writes behave exactly like those of
+/// [PGpio8BitInterface].
+///
+/// Reads switch the bus to inputs, then pull the read-enable low, sample the bus and release it
+/// high again for every byte. The bus is switched back to outputs afterwards, even if the read
+/// failed, so the next write finds it in the expected state.
+pub struct PGpio8BitReadWriteInterface<BUS, DC, WR, RD> {
+    iface: PGpio8BitInterface<BUS, DC, WR>,
+    rd: RD,
+}
+
+impl<BUS, DC, WR, RD> PGpio8BitReadWriteInterface<BUS, DC, WR, RD>
+where
+    BUS: BidirectionalBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    RD: OutputPin,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    ///
+    /// The read-enable pin is expected to be high, i.e. inactive.
+    pub fn new(bus: BUS, dc: DC, wr: WR, rd: RD) -> Self {
+        Self {
+            iface: PGpio8BitInterface::new(bus, dc, wr),
+            rd,
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.iface = self.iface.with_dc_polarity(polarity);
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// See [PGpio8BitInterface::with_strobe_polarity].
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.iface = self.iface.with_strobe_polarity(polarity);
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pins used by it
+    pub fn release(self) -> (BUS, DC, WR, RD) {
+        let (bus, dc, wr) = self.iface.release();
+        (bus, dc, wr, self.rd)
+    }
+
+    /// Release the read-enable high, switch the bus to outputs and
+    /// [resync](PGpio8BitInterface::resync) the write side
+    pub fn resync(&mut self) -> Result {
+        self.rd.set_high().map_err(|_| DisplayError::BusReadError)?;
+        self.iface.bus.set_output()?;
+        self.iface.resync()
+    }
+
+    fn read_iter<'a>(&mut self, iter: impl Iterator<Item = &'a mut u8>) -> Result {
+        for value in iter {
+            self.rd.set_low().map_err(|_| DisplayError::BusReadError)?;
+            *value = self.iface.bus.get_value()?;
+            self.rd.set_high().map_err(|_| DisplayError::BusReadError)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<BUS, DC, WR, RD> TypedWriteOnlyDataCommand for PGpio8BitReadWriteInterface<BUS, DC, WR, RD>
+where
+    BUS: BidirectionalBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    RD: OutputPin,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        self.iface.send_commands_typed(cmds)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        self.iface.send_data_typed(buf)
+    }
+}
+
+impl<BUS, DC, WR, RD> WriteOnlyDataCommand for PGpio8BitReadWriteInterface<BUS, DC, WR, RD>
+where
+    BUS: BidirectionalBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    RD: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.iface.send_commands(cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.iface.send_data(buf)
+    }
+}
+
+impl<BUS, DC, WR, RD> ReadData for PGpio8BitReadWriteInterface<BUS, DC, WR, RD>
+where
+    BUS: BidirectionalBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    RD: OutputPin,
+{
+    fn read_data(&mut self, buf: &mut [u8]) -> Result {
+        set_dc(&mut self.iface.dc, self.iface.dc_polarity, true)?;
+        self.iface.bus.set_input()?;
+
+        let result = self.read_iter(buf.iter_mut());
+        let restored = self.iface.bus.set_output();
+
+        result.and(restored)
+    }
+}
@@ -0,0 +1,196 @@
+//! Pin-level recording of what an interface puts on the wires
+
+use core::{
+    cell::{Cell, Ref, RefCell},
+    convert::Infallible,
+    marker::PhantomData,
+};
+
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{ErrorType, OutputPin},
+};
+
+use crate::{OutputBus, Result};
+
+/// A change on the wires seen by a [PinRecorder]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinEvent {
+    /// The pin of the given name was driven high (`true`) or low (`false`)
+    Pin(&'static str, bool),
+    /// The value was put on the bus
+    Bus(u32),
+    /// A call without an effect on the wires, with an argument, logged with
+    /// [note](PinRecorder::note)
+    Note(&'static str, u32),
+}
+
+/// A [PinEvent] with the time it happened
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedEvent {
+    /// Nanoseconds waited with the recorder's [delay](PinRecorder::delay) before the event
+    pub at_ns: u64,
+    /// What happened
+    pub event: PinEvent,
+}
+
+/// Shared log of the pin and bus changes of an interface, holding up to `N` events
+///
+/// Hand the [pins](Self::pin), [bus](Self::bus) and [delay](Self::delay) of the recorder to an
+/// interface, drive it and check the [events](Self::events) against the ordering and timing the
+/// display controller requires, e.g. that D/C is set before the first word or that every word is
+/// on the bus before the write-enable rises. Time doesn't pass on its own: the timestamps are the
+/// nanoseconds waited with the recorder's delay, so timing settings of an interface show up as
+/// gaps between the events.
+///
+/// The recorder is meant for tests, both of this crate and of downstream buses and drivers; it
+/// panics once more than `N` events are recorded.
+///
+/// ```
+/// use display_interface_parallel_gpio::{
+///     DataFormat, PGpio8BitInterface, PinEvent::*, PinRecorder, WriteOnlyDataCommand,
+/// };
+///
+/// let recorder = PinRecorder::<16>::new();
+/// let mut iface = PGpio8BitInterface::new(recorder.bus(), recorder.pin("dc"), recorder.pin("wr"));
+/// iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+///
+/// let events: Vec<_> = recorder.events().iter().map(|e| e.event).collect();
+/// assert_eq!(
+///     events,
+///     [Pin("dc", false), Pin("wr", false), Bus(0x2c), Pin("wr", true)]
+/// );
+/// ```
+pub struct PinRecorder<const N: usize> {
+    events: RefCell<[TimedEvent; N]>,
+    len: Cell<usize>,
+    now_ns: Cell<u64>,
+}
+
+impl<const N: usize> Default for PinRecorder<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> PinRecorder<N> {
+    /// Create an empty recorder at time zero
+    pub const fn new() -> Self {
+        const EMPTY: TimedEvent = TimedEvent {
+            at_ns: 0,
+            event: PinEvent::Bus(0),
+        };
+        Self {
+            events: RefCell::new([EMPTY; N]),
+            len: Cell::new(0),
+            now_ns: Cell::new(0),
+        }
+    }
+
+    /// An [OutputPin] logging its changes under `name`
+    pub fn pin(&self, name: &'static str) -> RecordedPin<'_, N> {
+        RecordedPin {
+            recorder: self,
+            name,
+        }
+    }
+
+    /// An [OutputBus] of `W` logging every value set, e.g. `RecordedBus<'_, u16, N>` for a
+    /// 16-bit bus
+    ///
+    /// Every value is logged, the bus doesn't skip repeated ones.
+    pub fn bus<W>(&self) -> RecordedBus<'_, W, N> {
+        RecordedBus {
+            recorder: self,
+            word: PhantomData,
+        }
+    }
+
+    /// A [DelayNs] advancing the time of the recorder instead of waiting
+    pub fn delay(&self) -> RecordedDelay<'_, N> {
+        RecordedDelay { recorder: self }
+    }
+
+    /// Log a call of a custom bus or strobe under `name`, e.g. a cache invalidation or a batch of
+    /// `value` words, in order with the pin changes
+    pub fn note(&self, name: &'static str, value: u32) {
+        self.push(PinEvent::Note(name, value));
+    }
+
+    /// The events recorded so far, in the order they happened
+    pub fn events(&self) -> Ref<'_, [TimedEvent]> {
+        Ref::map(self.events.borrow(), |events| &events[..self.len.get()])
+    }
+
+    /// Nanoseconds waited with the recorder's delay so far
+    pub fn now_ns(&self) -> u64 {
+        self.now_ns.get()
+    }
+
+    /// Drop the events recorded so far; the time keeps running
+    pub fn clear(&self) {
+        self.len.set(0);
+    }
+
+    fn push(&self, event: PinEvent) {
+        let len = self.len.get();
+        assert!(len < N, "pin recorder full");
+        self.events.borrow_mut()[len] = TimedEvent {
+            at_ns: self.now_ns.get(),
+            event,
+        };
+        self.len.set(len + 1);
+    }
+}
+
+/// [OutputPin] of a [PinRecorder]
+pub struct RecordedPin<'a, const N: usize> {
+    recorder: &'a PinRecorder<N>,
+    name: &'static str,
+}
+
+impl<const N: usize> ErrorType for RecordedPin<'_, N> {
+    type Error = Infallible;
+}
+
+impl<const N: usize> OutputPin for RecordedPin<'_, N> {
+    fn set_low(&mut self) -> core::result::Result<(), Infallible> {
+        self.recorder.push(PinEvent::Pin(self.name, false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Infallible> {
+        self.recorder.push(PinEvent::Pin(self.name, true));
+        Ok(())
+    }
+}
+
+/// [OutputBus] of a [PinRecorder]
+pub struct RecordedBus<'a, W, const N: usize> {
+    recorder: &'a PinRecorder<N>,
+    word: PhantomData<W>,
+}
+
+impl<W, const N: usize> OutputBus for RecordedBus<'_, W, N>
+where
+    W: Copy + Into<u32>,
+{
+    type Word = W;
+
+    fn set_value(&mut self, value: W) -> Result {
+        self.recorder.push(PinEvent::Bus(value.into()));
+        Ok(())
+    }
+}
+
+/// [DelayNs] of a [PinRecorder]
+pub struct RecordedDelay<'a, const N: usize> {
+    recorder: &'a PinRecorder<N>,
+}
+
+impl<const N: usize> DelayNs for RecordedDelay<'_, N> {
+    fn delay_ns(&mut self, ns: u32) {
+        let now = &self.recorder.now_ns;
+        now.set(now.get() + u64::from(ns));
+    }
+}
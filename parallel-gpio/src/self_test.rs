@@ -0,0 +1,153 @@
+//! Finding swapped, shorted and unconnected data lines during bring-up
+
+use display_interface::DisplayError;
+use embedded_hal::delay::DelayNs;
+
+use crate::{BidirectionalBus, BusWord, OutputBus, Result};
+
+/// Data lines which didn't read back what was driven onto them, one bit per line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WiringFault<W> {
+    /// Every line which read back wrong at least once
+    pub mismatched: W,
+    /// Lines which never read back high, e.g. unconnected lines pulled low or shorted to ground
+    pub stuck_low: W,
+    /// Lines which never read back low, e.g. unconnected lines pulled high or shorted to supply
+    pub stuck_high: W,
+}
+
+/// Error of [self_test]
+#[derive(Clone, Debug)]
+pub enum SelfTestError<W> {
+    /// Reading back a pattern failed
+    Bus(DisplayError),
+    /// Some lines misbehave
+    ///
+    /// Lines in [mismatched](WiringFault::mismatched) but neither stuck low nor stuck high are
+    /// usually swapped with or shorted to another line of the mask.
+    Wiring(WiringFault<W>),
+}
+
+impl<W> From<DisplayError> for SelfTestError<W> {
+    fn from(error: DisplayError) -> Self {
+        SelfTestError::Bus(error)
+    }
+}
+
+/// Check the lowest `lines` data lines with walking-one and walking-zero patterns
+///
+/// For every line, `echo` is called with a word with only that line high and with a word with
+/// only that line low, and has to return the word read back. How the words get back is up to
+/// `echo`:
+///
+/// - Through the controller, writing the word to a register or into the frame memory and reading
+///   it back, e.g. with [PGpio8BitReadWriteInterface](crate::PGpio8BitReadWriteInterface).
+/// - Through loopback jumpers to another set of pins, see [loopback_echo].
+///
+/// Lines above `lines` are driven low and ignored when reading back; `lines` is capped at the
+/// width of the word. All lines which ever read back wrong are reported in a [WiringFault].
+///
+/// ```
+/// use display_interface_parallel_gpio::{
+///     self_test, DataFormat, DisplayError, ReadData, SelfTestError, WriteOnlyDataCommand,
+/// };
+///
+/// /// Write the word as a pixel into the frame memory of an 8-bit ILI9341 and read it back
+/// fn check_wiring(
+///     di: &mut (impl WriteOnlyDataCommand + ReadData),
+/// ) -> Result<(), SelfTestError<u8>> {
+///     self_test(8, |word| {
+///         di.send_commands(DataFormat::U8(&[0x2c]))?;
+///         di.send_data(DataFormat::U8(&[word, word, word]))?;
+///         di.send_commands(DataFormat::U8(&[0x2e]))?;
+///         // A dummy byte precedes the red channel of the pixel
+///         let mut pixel = [0; 2];
+///         di.read_data(&mut pixel)?;
+///         // The controller drops the low 2 bits of every channel, so those lines can't be
+///         // checked this way and are passed as written
+///         Ok(pixel[1] | (word & 0x03))
+///     })
+/// }
+/// ```
+pub fn self_test<W: BusWord>(
+    lines: usize,
+    mut echo: impl FnMut(W) -> Result<W>,
+) -> core::result::Result<(), SelfTestError<W>> {
+    let lines = lines.min(W::BITS);
+    let mask = (0..lines).fold(W::ZERO, |mask, n| mask | W::single_bit(n));
+
+    let mut fault = WiringFault {
+        mismatched: W::ZERO,
+        stuck_low: mask,
+        stuck_high: mask,
+    };
+
+    for n in 0..lines {
+        let one = W::single_bit(n);
+        for pattern in [one, one ^ mask] {
+            let read = echo(pattern)? & mask;
+            fault.mismatched = fault.mismatched | (read ^ pattern);
+            fault.stuck_low = fault.stuck_low & (read ^ W::ONES);
+            fault.stuck_high = fault.stuck_high & read;
+        }
+    }
+
+    if fault.mismatched == W::ZERO {
+        Ok(())
+    } else {
+        Err(SelfTestError::Wiring(fault))
+    }
+}
+
+/// Put `word` on `bus` and sample it with `input`, whose pins are wired to the same lines by
+/// loopback jumpers
+///
+/// Meant as the `echo` of [self_test], e.g. with the display removed and the connector bridged
+/// to spare pins:
+///
+/// ```
+/// # use display_interface_parallel_gpio::{
+/// #     loopback_echo, self_test, BidirectionalBus, OutputBus, SelfTestError,
+/// # };
+/// fn check_wiring(
+///     bus: &mut impl OutputBus<Word = u8>,
+///     spare: &mut impl BidirectionalBus<Word = u8>,
+/// ) -> Result<(), SelfTestError<u8>> {
+///     self_test(8, |word| loopback_echo(bus, spare, word))
+/// }
+/// ```
+pub fn loopback_echo<W>(
+    bus: &mut impl OutputBus<Word = W>,
+    input: &mut impl BidirectionalBus<Word = W>,
+    word: W,
+) -> Result<W> {
+    bus.set_value(word)?;
+    input.set_input()?;
+    input.get_value()
+}
+
+/// Drive every one of the lowest `lines` data lines high in turn for `hold_ms` milliseconds
+///
+/// A write-only check for buses which can't be read back: follow the walking one with an LED or a
+/// probe on the controller side of the connector, or a logic analyzer. All lines are driven low
+/// before the first and after the last line.
+pub fn probe_lines<BUS>(
+    bus: &mut BUS,
+    lines: usize,
+    delay: &mut impl DelayNs,
+    hold_ms: u32,
+) -> Result
+where
+    BUS: OutputBus,
+    BUS::Word: BusWord,
+{
+    bus.set_value(BUS::Word::ZERO)?;
+    delay.delay_ms(hold_ms);
+
+    for n in 0..lines.min(BUS::Word::BITS) {
+        bus.set_value(BUS::Word::single_bit(n))?;
+        delay.delay_ms(hold_ms);
+    }
+
+    bus.set_value(BUS::Word::ZERO)
+}
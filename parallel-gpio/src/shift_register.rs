@@ -0,0 +1,96 @@
+use display_interface::DisplayError;
+use embedded_hal::{digital::OutputPin, spi::SpiDevice};
+
+use crate::{OutputBus, Result};
+
+macro_rules! shift_register_bus {
+    ($(#[$meta:meta])* $ShiftRegisterBus:ident { type Word = $Word:ident; }) => {
+        $(#[$meta])*
+        pub struct $ShiftRegisterBus<SPI, LATCH> {
+            spi: SPI,
+            latch: LATCH,
+            last: Option<$Word>,
+        }
+
+        impl<SPI, LATCH> $ShiftRegisterBus<SPI, LATCH>
+        where
+            SPI: SpiDevice,
+            LATCH: OutputPin,
+        {
+            /// Creates a new bus. This does not change the state of the outputs.
+            ///
+            /// The latch pin is expected to be low.
+            pub fn new(spi: SPI, latch: LATCH) -> Self {
+                Self {
+                    spi,
+                    latch,
+                    last: None,
+                }
+            }
+
+            /// Consumes the bus and returns the SPI device and latch pin. This does not change the
+            /// state of the outputs.
+            pub fn release(self) -> (SPI, LATCH) {
+                (self.spi, self.latch)
+            }
+        }
+
+        impl<SPI, LATCH> OutputBus for $ShiftRegisterBus<SPI, LATCH>
+        where
+            SPI: SpiDevice,
+            LATCH: OutputPin,
+        {
+            type Word = $Word;
+
+            fn set_value(&mut self, value: Self::Word) -> Result {
+                if self.last == Some(value) {
+                    // Shifting and latching the same value again doesn't change the outputs
+                    return Ok(());
+                }
+
+                // The outputs are unknown until the latch has been pulsed successfully
+                self.last = None;
+
+                self.spi
+                    .write(&value.to_be_bytes())
+                    .map_err(|_| DisplayError::BusWriteError)?;
+                self.latch
+                    .set_high()
+                    .map_err(|_| DisplayError::BusWriteError)?;
+                self.latch
+                    .set_low()
+                    .map_err(|_| DisplayError::BusWriteError)?;
+
+                self.last = Some(value);
+                Ok(())
+            }
+
+            fn invalidate(&mut self) {
+                self.last = None;
+            }
+        }
+    };
+}
+
+shift_register_bus! {
+    /// An implementation of [OutputBus] using a 74HC595 shift register fed by SPI
+    ///
+    /// Every new value is shifted out MSB first in a single SPI transaction, after which the
+    /// latch pin (`RCLK`) is pulsed high to move it to the outputs. Values identical to the
+    /// previous one are skipped.
+    ShiftRegisterBus {
+        type Word = u8;
+    }
+}
+
+shift_register_bus! {
+    /// An implementation of [OutputBus] using two chained 74HC595 shift registers fed by SPI
+    ///
+    /// Every new value is shifted out MSB first in a single SPI transaction, after which the
+    /// latch pin (`RCLK`) is pulsed high to move it to the outputs. The high byte is shifted out
+    /// first, so it ends up in the second register of the chain and the low byte in the one
+    /// connected to the SPI data line. Values identical to the previous one are skipped.
+    ShiftRegister16Bus {
+        type Word = u16;
+    }
+}
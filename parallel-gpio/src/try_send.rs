@@ -0,0 +1,97 @@
+//! Non-blocking transfers in chunks for superloops
+//!
+//! Every call puts at most 64 bytes worth of words on the bus, following the same
+//! state machine as the SPI interfaces: the D/C pin is set at the start of a transfer, and the
+//! progress is kept in the interface until the last chunk has been written.
+
+use display_interface::{DataFormat, DisplayError, TryWriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{set_dc, OutputBus, PGpio16BitInterface, PGpio8BitInterface, U8Packing, WriteEnable};
+
+/// Maximum number of bytes written per call
+const CHUNK_BYTES: usize = 64;
+
+impl<BUS, DC, WR, D> TryWriteOnlyDataCommand for PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn try_send_commands(&mut self, cmds: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(cmds, false)
+    }
+
+    fn try_send_data(&mut self, buf: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(buf, true)
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio8BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u8>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn try_send(&mut self, words: DataFormat<'_>, data: bool) -> nb::Result<(), DisplayError> {
+        let (new, words) = self.progress.next_format_chunk(data, words, CHUNK_BYTES)?;
+        let result = if new {
+            set_dc(&mut self.dc, self.dc_polarity, data)
+        } else {
+            Ok(())
+        }
+        .and_then(|_| self.write_data(words));
+        self.progress.finish(result)
+    }
+}
+
+impl<BUS, DC, WR, D> TryWriteOnlyDataCommand for PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn try_send_commands(&mut self, cmds: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(cmds, false, U8Packing::ZeroExtend)
+    }
+
+    fn try_send_data(&mut self, buf: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(buf, true, self.u8_packing)
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio16BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u16>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn try_send(
+        &mut self,
+        words: DataFormat<'_>,
+        data: bool,
+        packing: U8Packing,
+    ) -> nb::Result<(), DisplayError> {
+        // Checked up front, so a transfer isn't cut short at its last chunk
+        if let DataFormat::U8(slice) = &words {
+            if packing == U8Packing::BigEndian && slice.len() % 2 != 0 {
+                self.progress.reset();
+                return Err(nb::Error::Other(DisplayError::InvalidFormatError));
+            }
+        }
+
+        // Chunks of bytes stay even, so packing never pairs bytes of different chunks
+        let (new, words) = self.progress.next_format_chunk(data, words, CHUNK_BYTES)?;
+        let result = if new {
+            set_dc(&mut self.dc, self.dc_polarity, data)
+        } else {
+            Ok(())
+        }
+        .and_then(|_| self.write_data(words, packing));
+        self.progress.finish(result)
+    }
+}
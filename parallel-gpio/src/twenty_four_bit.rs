@@ -0,0 +1,230 @@
+use display_interface::{DataFormat, DcPolarity, TypedWriteOnlyDataCommand, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::{
+    release_on_error, select, set_dc, write_format_16bit, NoDelay, OutputBus, PGpioInterfaceError,
+    Result, StrobePolarity, U8Packing, WriteEnable, WriteStrobe,
+};
+
+/// The bits of a bus word which are put on the bus
+const MASK: u32 = 0x00ff_ffff;
+
+/// Expand the RGB565 `color` to RGB888
+///
+/// The missing low bits of each channel are filled with its top bits, so black stays black and
+/// white stays white: `0x001f` becomes `0x0000ff` and `0x8410` becomes `0x848284`.
+pub fn rgb565_to_rgb888(color: u16) -> u32 {
+    let color = u32::from(color);
+    let r = (color >> 11) & 0x1f;
+    let g = (color >> 5) & 0x3f;
+    let b = color & 0x1f;
+    let r = (r << 3) | (r >> 2);
+    let g = (g << 2) | (g >> 4);
+    let b = (b << 3) | (b >> 2);
+    (r << 16) | (g << 8) | b
+}
+
+/// Parallel 24 Bit communication interface
+///
+/// This interface implements the 24-Bit "8080" mode of high-color controllers, e.g. the NT35510,
+/// using any 24-bit [OutputBus] implementation, e.g. [Generic24BitBus](crate::Generic24BitBus),
+/// as well as one `OutputPin` for the data/command selection and one for the write-enable flag.
+/// The write-enable can also be generated in hardware, see [WriteEnable].
+///
+/// Words are put on the bus as follows:
+///
+/// - [DataFormat::U24] and [DataFormat::U24Iter] values are written as one word each, with the
+///   top byte dropped.
+/// - Bytes are zero-extended to one word each.
+/// - 16-bit data is taken as RGB565 pixels and expanded to RGB888 by
+///   [rgb565_to_rgb888], replicating the top bits of every channel into its missing low bits.
+///   The byte order of the format doesn't matter, as every value is written as a whole. 16-bit
+///   commands are zero-extended instead.
+///
+/// All pins are supposed to be high-active, high for the D/C pin meaning "data" and the
+/// write-enable being pulled low before the setting of the bits and supposed to be sampled at a
+/// low to high edge, unless inverted with [PGpio24BitInterface::with_dc_polarity] and
+/// [PGpio24BitInterface::with_strobe_polarity]. Slow connections may need the write-enable to be
+/// stretched, see [PGpio24BitInterface::with_timing].
+pub struct PGpio24BitInterface<BUS, DC, WR, D = NoDelay> {
+    bus: BUS,
+    dc: DC,
+    wr: WR,
+    delay: D,
+    dc_polarity: DcPolarity,
+    wr_polarity: StrobePolarity,
+    wr_wait_ns: Option<u32>,
+}
+
+impl<BUS, DC, WR> PGpio24BitInterface<BUS, DC, WR>
+where
+    BUS: OutputBus<Word = u32>,
+    DC: OutputPin,
+    WR: WriteEnable,
+{
+    /// Create new parallel GPIO interface for communication with a display driver
+    pub fn new(bus: BUS, dc: DC, wr: WR) -> Self {
+        Self {
+            bus,
+            dc,
+            wr,
+            delay: NoDelay,
+            dc_polarity: DcPolarity::default(),
+            wr_polarity: StrobePolarity::default(),
+            wr_wait_ns: None,
+        }
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio24BitInterface<BUS, DC, WR, D> {
+    /// Stretch the write-enable pulse of every word, using `delay`
+    ///
+    /// See [PGpio16BitInterface::with_timing](crate::PGpio16BitInterface::with_timing).
+    pub fn with_timing<D2>(
+        self,
+        delay: D2,
+        wr_low_ns: u32,
+        data_setup_ns: u32,
+    ) -> PGpio24BitInterface<BUS, DC, WR, D2> {
+        PGpio24BitInterface {
+            bus: self.bus,
+            dc: self.dc,
+            wr: self.wr,
+            delay,
+            dc_polarity: self.dc_polarity,
+            wr_polarity: self.wr_polarity,
+            wr_wait_ns: Some(wr_low_ns.max(data_setup_ns)),
+        }
+    }
+
+    /// Select the levels of the D/C pin
+    pub fn with_dc_polarity(mut self, polarity: DcPolarity) -> Self {
+        self.dc_polarity = polarity;
+        self
+    }
+
+    /// Select the levels of the write-enable strobe
+    ///
+    /// The write-enable pin is expected to be at the idle level of `polarity`.
+    pub fn with_strobe_polarity(mut self, polarity: StrobePolarity) -> Self {
+        self.wr_polarity = polarity;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the bus and GPIO pins used by it
+    pub fn release(self) -> (BUS, DC, WR) {
+        (self.bus, self.dc, self.wr)
+    }
+
+    /// Consume the display interface and return
+    /// the bus, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (BUS, DC, WR, D) {
+        (self.bus, self.dc, self.wr, self.delay)
+    }
+}
+
+impl<BUS, DC, WR, D> PGpio24BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u32>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    /// Set the D/C pin to command and release the write-enable to its idle level
+    pub fn resync(&mut self) -> Result {
+        set_dc(&mut self.dc, self.dc_polarity, false)?;
+        self.bus.invalidate();
+        self.wr
+            .strobe(self.wr_polarity, &mut self.delay, self.wr_wait_ns)
+            .release()
+    }
+
+    fn write_data(&mut self, data: DataFormat<'_>, expand: fn(u16) -> u32) -> Result {
+        let mut strobe = self
+            .wr
+            .strobe(self.wr_polarity, &mut self.delay, self.wr_wait_ns);
+        let result = match data {
+            DataFormat::U24(slice) => self
+                .bus
+                .write_words_iter(slice.iter().map(|word| word & MASK), &mut strobe),
+            DataFormat::U24Iter(iter) => self
+                .bus
+                .write_words_iter(iter.map(|word| word & MASK), &mut strobe),
+            // Bytes are zero-extended, only 16-bit words are expanded
+            DataFormat::U8(_) | DataFormat::U8Iter(_) => {
+                let mut bus = Widened(&mut self.bus, u32::from);
+                write_format_16bit(&mut bus, &mut strobe, data, U8Packing::ZeroExtend)
+            }
+            _ => {
+                let mut bus = Widened(&mut self.bus, expand);
+                write_format_16bit(&mut bus, &mut strobe, data, U8Packing::ZeroExtend)
+            }
+        };
+        release_on_error(&mut strobe, result)
+    }
+}
+
+/// 16-bit view of a 24-bit bus, widening every word with the given function
+struct Widened<'a, BUS>(&'a mut BUS, fn(u16) -> u32);
+
+impl<BUS: OutputBus<Word = u32>> OutputBus for Widened<'_, BUS> {
+    type Word = u16;
+
+    fn set_value(&mut self, value: u16) -> Result {
+        self.0.set_value((self.1)(value))
+    }
+
+    fn invalidate(&mut self) {
+        self.0.invalidate();
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = u16>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result {
+        self.0.write_words_iter(words.map(self.1), strobe)
+    }
+}
+
+impl<BUS, DC, WR, D> TypedWriteOnlyDataCommand for PGpio24BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u32>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    type Error = PGpioInterfaceError<DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, false).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(cmds, u32::from)
+            .map_err(PGpioInterfaceError::Bus)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
+        select(&mut self.dc, self.dc_polarity, true).map_err(PGpioInterfaceError::Dc)?;
+        self.write_data(buf, rgb565_to_rgb888)
+            .map_err(PGpioInterfaceError::Bus)
+    }
+}
+
+impl<BUS, DC, WR, D> WriteOnlyDataCommand for PGpio24BitInterface<BUS, DC, WR, D>
+where
+    BUS: OutputBus<Word = u32>,
+    DC: OutputPin,
+    WR: WriteEnable,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_commands_typed(cmds).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
@@ -0,0 +1,113 @@
+//! The `async` implementations strobe the same sequence as the blocking ones
+#![cfg(feature = "async")]
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    AsyncWriteOnlyDataCommand, DataFormat, PGpio16BitInterface, PGpio8BitInterface, PinEvent::*,
+    U8Packing, WriteOnlyDataCommand,
+};
+
+use common::{block_on, count_pending, take, Recorder};
+
+fn words() -> Vec<u16> {
+    (0..10u16)
+        .map(|i| i.wrapping_mul(0x0101) ^ 0x1234)
+        .collect()
+}
+
+#[test]
+fn eight_bit_sequences_match() {
+    let blocking = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(blocking.bus::<u8>(), blocking.pin("dc"), blocking.pin("wr"));
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut words())).unwrap();
+
+    let asynch = Recorder::new();
+    let mut iface = PGpio8BitInterface::new(asynch.bus::<u8>(), asynch.pin("dc"), asynch.pin("wr"))
+        .with_yield_every(3);
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&[0x2c]),
+    ))
+    .unwrap();
+    let (_, yields) = count_pending(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16BEIter(&mut words().into_iter()),
+    ));
+
+    // 20 bytes in batches of 3
+    assert_eq!(yields, 6);
+    let asynch = take(&asynch);
+    assert_eq!(take(&blocking), asynch);
+    assert_eq!(
+        asynch[..7],
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x12),
+        ]
+    );
+}
+
+#[test]
+fn sixteen_bit_sequences_match() {
+    let blocking = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        blocking.bus::<u16>(),
+        blocking.pin("dc"),
+        blocking.pin("wr"),
+    );
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16(&words())).unwrap();
+
+    let asynch = Recorder::new();
+    let mut iface =
+        PGpio16BitInterface::new(asynch.bus::<u16>(), asynch.pin("dc"), asynch.pin("wr"));
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8Iter(&mut [0x2c].into_iter()),
+    ))
+    .unwrap();
+    let (_, yields) = count_pending(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16(&words()),
+    ));
+
+    // Never yields by default
+    assert_eq!(yields, 0);
+    assert_eq!(take(&blocking), take(&asynch));
+}
+
+#[test]
+fn packed_sequences_match() {
+    let bytes = [0x12, 0x34, 0x56, 0x78, 0x9a];
+
+    let blocking = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        blocking.bus::<u16>(),
+        blocking.pin("dc"),
+        blocking.pin("wr"),
+    )
+    .with_u8_packing(U8Packing::BigEndianPadded);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&bytes)).unwrap();
+
+    let asynch = Recorder::new();
+    let mut iface =
+        PGpio16BitInterface::new(asynch.bus::<u16>(), asynch.pin("dc"), asynch.pin("wr"))
+            .with_u8_packing(U8Packing::BigEndianPadded);
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&bytes),
+    ))
+    .unwrap();
+
+    let blocking = take(&blocking);
+    assert_eq!(blocking, take(&asynch));
+    assert!(blocking.contains(&Bus(0x9a00)));
+}
@@ -0,0 +1,46 @@
+//! Set/reset words written by the `BSRR` buses
+#![cfg(feature = "cortex-m")]
+
+use display_interface_parallel_gpio::{BsrrBus16, BsrrBus8, OutputBus};
+
+#[test]
+fn sets_and_resets_bus_pins_only() {
+    let mut bsrr = 0u32;
+    let mut bus = unsafe { BsrrBus8::new(&mut bsrr, 4, 8) };
+
+    bus.set_value(0xa5).unwrap();
+    assert_eq!(bsrr, 0x05a0_0a50);
+
+    bus.set_value(0x00).unwrap();
+    assert_eq!(bsrr, 0x0ff0_0000);
+}
+
+#[test]
+fn nine_bit_bus() {
+    let mut bsrr = 0u32;
+    let mut bus = unsafe { BsrrBus16::new(&mut bsrr, 0, 9) };
+
+    bus.set_value(0x1ff).unwrap();
+    assert_eq!(bsrr, 0x0000_01ff);
+}
+
+#[test]
+fn value_exceeding_width_leaves_other_pins_alone() {
+    let mut bsrr = 0u32;
+    let mut bus = unsafe { BsrrBus16::new(&mut bsrr, 0, 9) };
+
+    bus.set_value(0xfe00).unwrap();
+    assert_eq!(bsrr, 0x01ff_0000);
+
+    // Bits shifted beyond pin 15 must not end up in the reset half either
+    let mut bus = unsafe { BsrrBus8::new(&mut bsrr, 10, 4) };
+    bus.set_value(0xff).unwrap();
+    assert_eq!(bsrr, 0x0000_3c00);
+}
+
+#[test]
+#[should_panic(expected = "the bus pins must fit")]
+fn pins_outside_port() {
+    let mut bsrr = 0u32;
+    let _ = unsafe { BsrrBus8::new(&mut bsrr, 9, 8) };
+}
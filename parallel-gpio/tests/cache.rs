@@ -0,0 +1,67 @@
+//! The last-value cache of the generic buses on pins shared with other users
+
+mod common;
+
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{
+    DataFormat, Generic8BitBus, OutputBus, PGpio8BitInterface, WriteOnlyDataCommand,
+};
+
+use common::{NoPin, Port, PortPin};
+
+type Bus = Generic8BitBus<PortPin, PortPin, PortPin, PortPin, PortPin, PortPin, PortPin, PortPin>;
+
+fn bus(port: &Rc<Port>) -> Bus {
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = port.pins();
+    Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7))
+}
+
+/// Drive the pins from somewhere else, e.g. a keypad scanner
+fn scan(port: &Port) {
+    port.set_levels(0xff);
+}
+
+#[test]
+fn stale_cache_skips_pins() {
+    let port = Rc::new(Port::default());
+    let mut bus = bus(&port);
+
+    bus.set_value(0x12).unwrap();
+    scan(&port);
+    bus.set_value(0x12).unwrap();
+
+    // This is what the cache relies on not to happen
+    assert_eq!(port.levels(), 0xff);
+}
+
+#[test]
+fn invalidate_sets_all_pins_again() {
+    let port = Rc::new(Port::default());
+    let mut bus = bus(&port);
+
+    bus.set_value(0x12).unwrap();
+    scan(&port);
+    bus.invalidate();
+    bus.set_value(0x12).unwrap();
+
+    assert_eq!(port.levels(), 0x12);
+    assert_eq!(port.writes(), 16);
+}
+
+#[test]
+fn without_caching_every_pin_is_set() {
+    let port = Rc::new(Port::default());
+    let mut bus = bus(&port).with_caching(false);
+
+    bus.set_value(0x12).unwrap();
+    scan(&port);
+    bus.set_value(0x12).unwrap();
+    assert_eq!(port.levels(), 0x12);
+    assert_eq!(port.writes(), 16);
+
+    // Runs of identical words are set in full, too
+    let mut iface = PGpio8BitInterface::new(bus, NoPin, NoPin);
+    iface.send_data(DataFormat::U8(&[0x34; 4])).unwrap();
+    assert_eq!(port.writes(), 48);
+}
@@ -0,0 +1,246 @@
+//! Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use core::{
+    cell::Cell,
+    convert::Infallible,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{
+    DisplayError, OutputBus, PinEvent, PinRecorder, RecordedBus, RecordedPin,
+};
+use embedded_hal::digital::{ErrorKind, ErrorType, OutputPin};
+
+/// Recorder large enough for the transfers of the tests
+pub type Recorder = PinRecorder<1024>;
+
+/// Pin of a [Recorder]
+pub type TestPin<'a> = RecordedPin<'a, 1024>;
+
+/// Bus of `W` of a [Recorder]
+pub type TestBus<'a, W> = RecordedBus<'a, W, 1024>;
+
+/// Take the events recorded so far, without their timestamps
+pub fn take<const N: usize>(recorder: &PinRecorder<N>) -> Vec<PinEvent> {
+    let events = recorder.events().iter().map(|e| e.event).collect();
+    recorder.clear();
+    events
+}
+
+/// Take the events recorded so far, along with their timestamps
+pub fn take_timed<const N: usize>(recorder: &PinRecorder<N>) -> Vec<(u64, PinEvent)> {
+    let events = recorder
+        .events()
+        .iter()
+        .map(|e| (e.at_ns, e.event))
+        .collect();
+    recorder.clear();
+    events
+}
+
+/// The values put on the bus so far
+pub fn bus_values<const N: usize>(recorder: &PinRecorder<N>) -> Vec<u32> {
+    recorder
+        .events()
+        .iter()
+        .filter_map(|e| match e.event {
+            PinEvent::Bus(value) => Some(value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// How often the pin `name` was driven to `level` so far
+pub fn count<const N: usize>(recorder: &PinRecorder<N>, name: &'static str, level: bool) -> usize {
+    recorder
+        .events()
+        .iter()
+        .filter(|e| e.event == PinEvent::Pin(name, level))
+        .count()
+}
+
+/// The level the pin `name` was last driven to, if at all
+pub fn level<const N: usize>(recorder: &PinRecorder<N>, name: &str) -> Option<bool> {
+    recorder.events().iter().rev().find_map(|e| match e.event {
+        PinEvent::Pin(pin, level) if pin == name => Some(level),
+        _ => None,
+    })
+}
+
+/// Pin or bus failing its `fail_at`th operation (counting from 1), passing on all others
+///
+/// Failed operations don't reach the wrapped pin or bus.
+pub struct Faulty<T> {
+    inner: T,
+    ops: Rc<Cell<usize>>,
+    fail_at: usize,
+}
+
+impl<T> Faulty<T> {
+    pub fn new(inner: T, fail_at: usize) -> Self {
+        Self {
+            inner,
+            ops: Rc::default(),
+            fail_at,
+        }
+    }
+
+    /// Counter of the operations attempted, including the failed one
+    pub fn ops(&self) -> Rc<Cell<usize>> {
+        self.ops.clone()
+    }
+
+    fn fails(&self) -> bool {
+        self.ops.set(self.ops.get() + 1);
+        self.ops.get() == self.fail_at
+    }
+}
+
+impl<T: OutputPin> ErrorType for Faulty<T> {
+    type Error = ErrorKind;
+}
+
+impl<T: OutputPin> OutputPin for Faulty<T> {
+    fn set_low(&mut self) -> Result<(), ErrorKind> {
+        if self.fails() {
+            return Err(ErrorKind::Other);
+        }
+        self.inner.set_low().map_err(|_| ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), ErrorKind> {
+        if self.fails() {
+            return Err(ErrorKind::Other);
+        }
+        self.inner.set_high().map_err(|_| ErrorKind::Other)
+    }
+}
+
+impl<T: OutputBus> OutputBus for Faulty<T> {
+    type Word = T::Word;
+
+    fn set_value(&mut self, value: T::Word) -> Result<(), DisplayError> {
+        if self.fails() {
+            return Err(DisplayError::BusWriteError);
+        }
+        self.inner.set_value(value)
+    }
+
+    fn invalidate(&mut self) {
+        self.inner.invalidate();
+    }
+}
+
+/// Pin which isn't looked at
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Levels of up to 32 pins, plus the number of pin changes
+#[derive(Default)]
+pub struct Port {
+    levels: Cell<u32>,
+    writes: Cell<usize>,
+}
+
+impl Port {
+    /// The first `N` pins, the first one being bit 0
+    pub fn pins<const N: usize>(self: &Rc<Self>) -> [PortPin; N] {
+        core::array::from_fn(|bit| PortPin(self.clone(), bit))
+    }
+
+    pub fn levels(&self) -> u32 {
+        self.levels.get()
+    }
+
+    /// Drive the pins from somewhere else, e.g. a keypad scanner
+    pub fn set_levels(&self, levels: u32) {
+        self.levels.set(levels);
+    }
+
+    pub fn writes(&self) -> usize {
+        self.writes.get()
+    }
+}
+
+/// Pin of a [Port]
+pub struct PortPin(Rc<Port>, usize);
+
+impl PortPin {
+    fn set(&mut self, high: bool) -> Result<(), Infallible> {
+        let levels = self.0.levels.get() & !(1 << self.1);
+        self.0.levels.set(levels | (u32::from(high) << self.1));
+        self.0.writes.set(self.0.writes.get() + 1);
+        Ok(())
+    }
+}
+
+impl ErrorType for PortPin {
+    type Error = Infallible;
+}
+
+impl OutputPin for PortPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.set(false)
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.set(true)
+    }
+}
+
+/// Waker which does nothing, the tests poll until done anyway
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(clone(core::ptr::null())) }
+}
+
+/// Poll `future` to completion without an executor
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    count_pending(future).0
+}
+
+/// Poll `future` to completion, also returning how often it returned `Pending`
+pub fn count_pending<F: Future>(future: F) -> (F::Output, usize) {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut pending = 0;
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, pending),
+            Poll::Pending => pending += 1,
+        }
+    }
+}
+
+/// Poll `future` once and drop it, like a caller cancelling it at its first await point
+///
+/// Returns whether it completed.
+pub fn poll_once<F: Future>(future: F) -> bool {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    pin!(future).poll(&mut cx).is_ready()
+}
@@ -0,0 +1,118 @@
+//! Chip select framing around the parallel interfaces
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio16BitInterface, PGpio16BitInterfaceWithCs, PGpio8BitInterface,
+    PGpio8BitInterfaceWithCs, PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{take, Faulty, Recorder, TestBus, TestPin};
+
+type Interface<'a> =
+    PGpio8BitInterfaceWithCs<Faulty<TestBus<'a, u8>>, TestPin<'a>, TestPin<'a>, TestPin<'a>>;
+
+/// Interface whose bus fails its `fail_at`th value
+fn interface(recorder: &Recorder, fail_at: usize) -> Interface<'_> {
+    PGpio8BitInterfaceWithCs::new(
+        PGpio8BitInterface::new(
+            Faulty::new(recorder.bus(), fail_at),
+            recorder.pin("dc"),
+            recorder.pin("wr"),
+        ),
+        recorder.pin("cs"),
+    )
+}
+
+#[test]
+fn selected_around_every_call() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 0);
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("cs", false),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("cs", true),
+            Pin("cs", false),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true),
+            Pin("cs", true),
+        ]
+    );
+}
+
+#[test]
+fn deselected_after_failure() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 1);
+
+    assert!(matches!(
+        iface.send_data(DataFormat::U8(&[0xff])),
+        Err(DisplayError::BusWriteError)
+    ));
+
+    let events = take(&recorder);
+    assert_eq!(events.first(), Some(&Pin("cs", false)));
+    assert_eq!(events.last(), Some(&Pin("cs", true)));
+}
+
+#[test]
+fn sixteen_bit() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterfaceWithCs::new(
+        PGpio16BitInterface::new(
+            recorder.bus::<u16>(),
+            recorder.pin("dc"),
+            recorder.pin("wr"),
+        ),
+        recorder.pin("cs"),
+    );
+
+    iface.send_data(DataFormat::U16(&[0xf800])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("cs", false),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0xf800),
+            Pin("wr", true),
+            Pin("cs", true)
+        ]
+    );
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn deselected_when_cancelled() {
+    use display_interface_parallel_gpio::AsyncWriteOnlyDataCommand;
+
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterfaceWithCs::new(
+        PGpio8BitInterface::new(recorder.bus(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_yield_every(1),
+        recorder.pin("cs"),
+    );
+
+    let done = common::poll_once(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&[1, 2, 3]),
+    ));
+
+    assert!(!done);
+    let events = take(&recorder);
+    assert_eq!(events.first(), Some(&Pin("cs", false)));
+    assert_eq!(events.last(), Some(&Pin("cs", true)));
+    assert!(!events.contains(&Bus(3)));
+}
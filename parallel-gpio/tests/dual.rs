@@ -0,0 +1,168 @@
+//! Chip select patterns of the dual-controller interface
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio8BitDualInterface, PGpio8BitInterface, PinEvent, PinEvent::*,
+    Target, WriteOnlyDataCommand,
+};
+
+use common::{take, Faulty, Recorder, TestBus, TestPin};
+
+type Interface<'a> = PGpio8BitDualInterface<
+    Faulty<TestBus<'a, u8>>,
+    TestPin<'a>,
+    TestPin<'a>,
+    TestPin<'a>,
+    Faulty<TestPin<'a>>,
+>;
+
+/// Interface whose bus fails its `bus_fails_at`th value and whose second chip select fails its
+/// `cs2_fails_at`th change
+fn interface(recorder: &Recorder, bus_fails_at: usize, cs2_fails_at: usize) -> Interface<'_> {
+    PGpio8BitDualInterface::new(
+        PGpio8BitInterface::new(
+            Faulty::new(recorder.bus(), bus_fails_at),
+            recorder.pin("dc"),
+            recorder.pin("wr"),
+        ),
+        recorder.pin("cs1"),
+        Faulty::new(recorder.pin("cs2"), cs2_fails_at),
+    )
+}
+
+fn command(target: Target) -> Vec<PinEvent> {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 0, 0);
+    iface.select(target);
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    take(&recorder)
+}
+
+#[test]
+fn first_controller_by_default() {
+    let recorder = Recorder::new();
+    assert_eq!(interface(&recorder, 0, 0).target(), Target::First);
+
+    assert_eq!(
+        command(Target::First),
+        [
+            Pin("cs1", false),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("cs1", true)
+        ]
+    );
+}
+
+#[test]
+fn second_controller() {
+    assert_eq!(
+        command(Target::Second),
+        [
+            Pin("cs2", false),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("cs2", true)
+        ]
+    );
+}
+
+#[test]
+fn both_controllers_are_broadcast_to() {
+    assert_eq!(
+        command(Target::Both),
+        [
+            Pin("cs1", false),
+            Pin("cs2", false),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("cs1", true),
+            Pin("cs2", true)
+        ]
+    );
+}
+
+#[test]
+fn selection_applies_to_data() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 0, 0);
+    iface.select(Target::Both);
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.select(Target::Second);
+    recorder.clear();
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("cs2", false),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true),
+            Pin("cs2", true)
+        ]
+    );
+}
+
+#[test]
+fn deselected_after_failure() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 1, 0);
+    iface.select(Target::Both);
+
+    let result = iface.send_data(DataFormat::U8(&[0xff]));
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    let events = take(&recorder);
+    assert_eq!(
+        events[events.len() - 2..],
+        [Pin("cs1", true), Pin("cs2", true)]
+    );
+}
+
+#[test]
+fn first_controller_released_if_second_fails() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, 0, 1);
+    iface.select(Target::Both);
+
+    let result = iface.send_data(DataFormat::U8(&[0x12]));
+    assert!(matches!(result, Err(DisplayError::CSError)));
+    assert_eq!(take(&recorder), [Pin("cs1", false), Pin("cs1", true)]);
+}
+
+#[cfg(feature = "async")]
+#[test]
+fn deselected_when_cancelled() {
+    use display_interface_parallel_gpio::AsyncWriteOnlyDataCommand;
+
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitDualInterface::new(
+        PGpio8BitInterface::new(recorder.bus(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_yield_every(1),
+        recorder.pin("cs1"),
+        recorder.pin("cs2"),
+    );
+    iface.select(Target::Both);
+
+    let done = common::poll_once(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&[1, 2, 3]),
+    ));
+
+    assert!(!done);
+    let events = take(&recorder);
+    assert_eq!(events[..2], [Pin("cs1", false), Pin("cs2", false)]);
+    assert_eq!(
+        events[events.len() - 2..],
+        [Pin("cs1", true), Pin("cs2", true)]
+    );
+    assert!(!events.contains(&Bus(3)));
+}
@@ -0,0 +1,53 @@
+//! Buses over arrays of `dyn OutputPin`s
+
+mod common;
+
+use core::convert::Infallible;
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{DynBus, DynBus16, OutputBus};
+use embedded_hal::digital::OutputPin;
+
+use common::{Port, PortPin};
+
+fn dyn_pins<const N: usize>(
+    pins: &mut [PortPin; N],
+) -> [&mut dyn OutputPin<Error = Infallible>; N] {
+    let mut pins = pins.iter_mut();
+    core::array::from_fn(|_| pins.next().unwrap() as &mut dyn OutputPin<Error = Infallible>)
+}
+
+#[test]
+fn only_changed_pins_are_set() {
+    let port = Rc::new(Port::default());
+    let mut pins = port.pins::<8>();
+    let mut bus = DynBus::new(dyn_pins(&mut pins));
+
+    bus.set_value(0x81).unwrap();
+    bus.set_value(0x81).unwrap();
+    bus.set_value(0x83).unwrap();
+    bus.release();
+
+    assert_eq!(port.levels(), 0x83);
+    assert_eq!(port.writes(), 9);
+}
+
+#[test]
+fn fewer_pins_than_bits() {
+    let port = Rc::new(Port::default());
+    let mut pins = port.pins::<9>();
+    let mut bus = DynBus16::new(dyn_pins(&mut pins));
+
+    bus.set_value(0x1ff).unwrap();
+    bus.release();
+
+    assert_eq!(port.levels(), 0x1ff);
+}
+
+#[test]
+#[should_panic(expected = "too many pins")]
+fn too_many_pins() {
+    let port = Rc::new(Port::default());
+    let mut pins = port.pins::<9>();
+    DynBus::new(dyn_pins(&mut pins));
+}
@@ -0,0 +1,44 @@
+//! Byte order of the 16-bit formats on the 8-bit bus
+
+mod common;
+
+use display_interface_parallel_gpio::{DataFormat, PGpio8BitInterface, WriteOnlyDataCommand};
+
+use common::{bus_values, NoPin, Recorder};
+
+fn send(format: DataFormat<'_>) -> Vec<u32> {
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterface::new(recorder.bus::<u8>(), NoPin, NoPin);
+    iface.send_data(format).unwrap();
+    bus_values(&recorder)
+}
+
+#[test]
+fn u16_is_native_byte_order() {
+    #[cfg(target_endian = "little")]
+    let expected = [0x34, 0x12, 0x78, 0x56];
+    #[cfg(target_endian = "big")]
+    let expected = [0x12, 0x34, 0x56, 0x78];
+
+    assert_eq!(send(DataFormat::U16(&[0x1234, 0x5678])), expected);
+}
+
+#[test]
+fn explicit_byte_orders() {
+    assert_eq!(
+        send(DataFormat::U16BE(&mut [0x1234, 0x5678])),
+        [0x12, 0x34, 0x56, 0x78]
+    );
+    assert_eq!(
+        send(DataFormat::U16LE(&mut [0x1234, 0x5678])),
+        [0x34, 0x12, 0x78, 0x56]
+    );
+    assert_eq!(
+        send(DataFormat::U16BEIter(&mut [0x1234, 0x5678].into_iter())),
+        [0x12, 0x34, 0x56, 0x78]
+    );
+    assert_eq!(
+        send(DataFormat::U16LEIter(&mut [0x1234, 0x5678].into_iter())),
+        [0x34, 0x12, 0x78, 0x56]
+    );
+}
@@ -0,0 +1,210 @@
+//! Slice formats and their iterator counterparts strobe identical words onto the bus
+//!
+//! Checked with `PinRecorder` for the 8-bit, 16-bit and 24-bit interfaces. The exception are odd
+//! byte counts with [U8Packing::BigEndian]: slices are rejected up front, iterators only fail once
+//! the last byte turns out to have no partner, after all complete pairs went out.
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpio16BitInterface, PGpio24BitInterface, PGpio8BitInterface, PinEvent,
+    PinRecorder, U8Packing, WriteOnlyDataCommand,
+};
+use proptest::{collection::vec, prelude::*};
+
+type Recorder = PinRecorder<2048>;
+
+/// The interfaces every pair of formats is checked with
+#[derive(Clone, Copy, Debug)]
+enum Setup {
+    Eight,
+    #[cfg(feature = "async")]
+    EightAsync,
+    Sixteen(U8Packing),
+    #[cfg(feature = "async")]
+    SixteenAsync(U8Packing),
+    TwentyFour,
+}
+
+const SETUPS: &[Setup] = &[
+    Setup::Eight,
+    #[cfg(feature = "async")]
+    Setup::EightAsync,
+    Setup::Sixteen(U8Packing::ZeroExtend),
+    Setup::Sixteen(U8Packing::BigEndian),
+    Setup::Sixteen(U8Packing::BigEndianPadded),
+    #[cfg(feature = "async")]
+    Setup::SixteenAsync(U8Packing::BigEndianPadded),
+    Setup::TwentyFour,
+];
+
+/// The outcome of a call, with the error as text since `DisplayError` can't be compared
+type Outcome = (Result<(), String>, Vec<PinEvent>);
+
+/// Send `format` as commands or data, with the error as text
+fn call(
+    iface: &mut impl WriteOnlyDataCommand,
+    commands: bool,
+    format: DataFormat<'_>,
+) -> Result<(), String> {
+    let result = if commands {
+        iface.send_commands(format)
+    } else {
+        iface.send_data(format)
+    };
+    result.map_err(|e| format!("{e:?}"))
+}
+
+/// Send `format` as commands or data through the `async` implementation, with the error as text
+#[cfg(feature = "async")]
+fn call_async(
+    iface: &mut impl display_interface_parallel_gpio::AsyncWriteOnlyDataCommand,
+    commands: bool,
+    format: DataFormat<'_>,
+) -> Result<(), String> {
+    let result = if commands {
+        common::block_on(iface.send_commands(format))
+    } else {
+        common::block_on(iface.send_data(format))
+    };
+    result.map_err(|e| format!("{e:?}"))
+}
+
+/// Send `format` as commands or data through the interface of `setup` and record the pins
+fn send(setup: Setup, commands: bool, format: DataFormat<'_>) -> Outcome {
+    let recorder = Recorder::new();
+    let (dc, wr) = (recorder.pin("dc"), recorder.pin("wr"));
+
+    let result = match setup {
+        Setup::Eight => call(
+            &mut PGpio8BitInterface::new(recorder.bus::<u8>(), dc, wr),
+            commands,
+            format,
+        ),
+        #[cfg(feature = "async")]
+        Setup::EightAsync => call_async(
+            &mut PGpio8BitInterface::new(recorder.bus::<u8>(), dc, wr).with_yield_every(1),
+            commands,
+            format,
+        ),
+        Setup::Sixteen(packing) => call(
+            &mut PGpio16BitInterface::new(recorder.bus::<u16>(), dc, wr).with_u8_packing(packing),
+            commands,
+            format,
+        ),
+        #[cfg(feature = "async")]
+        Setup::SixteenAsync(packing) => call_async(
+            &mut PGpio16BitInterface::new(recorder.bus::<u16>(), dc, wr)
+                .with_u8_packing(packing)
+                .with_yield_every(1),
+            commands,
+            format,
+        ),
+        Setup::TwentyFour => call(
+            &mut PGpio24BitInterface::new(recorder.bus::<u32>(), dc, wr),
+            commands,
+            format,
+        ),
+    };
+
+    (result, common::take(&recorder))
+}
+
+/// Run `check` with every setup, for commands and for data
+fn each_setup(
+    mut check: impl FnMut(Setup, bool) -> Result<(), TestCaseError>,
+) -> Result<(), TestCaseError> {
+    for &setup in SETUPS {
+        for commands in [false, true] {
+            check(setup, commands)?;
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    /// Even byte counts, so every packing accepts them
+    #[test]
+    fn u8_slice_and_iter_match(bytes in vec(any::<[u8; 2]>(), 0..150)) {
+        let bytes = bytes.concat();
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U8(&bytes));
+            let iter = send(setup, commands, DataFormat::U8Iter(&mut bytes.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16be_slice_and_iter_match(words in vec(any::<u16>(), 0..150)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16BE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16BEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16le_slice_and_iter_match(words in vec(any::<u16>(), 0..150)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16LE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16LEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// `U16` is in native byte order, so it matches the iterator of the native order
+    #[test]
+    fn u16_slice_and_native_iter_match(words in vec(any::<u16>(), 0..150)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16(&words));
+            let mut iter = words.iter().copied();
+            let iter = if cfg!(target_endian = "big") {
+                send(setup, commands, DataFormat::U16BEIter(&mut iter))
+            } else {
+                send(setup, commands, DataFormat::U16LEIter(&mut iter))
+            };
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// Only the 24-bit interface takes 24-bit words, the others reject both variants alike
+    #[test]
+    fn u24_slice_and_iter_match(words in vec(0..=0xff_ffffu32, 0..150)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U24(&words));
+            let iter = send(setup, commands, DataFormat::U24Iter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// Odd byte counts: an odd slice is rejected before anything is written, an odd iterator
+    /// writes all complete pairs before failing on the last byte
+    #[test]
+    fn odd_bytes_differ_with_big_endian_packing(bytes in vec(any::<u8>(), 0..150)) {
+        let mut bytes = bytes;
+        bytes.push(0x5a);
+        if bytes.len() % 2 == 0 {
+            bytes.push(0xa5);
+        }
+        let setup = Setup::Sixteen(U8Packing::BigEndian);
+
+        let (result, events) = send(setup, false, DataFormat::U8(&bytes));
+        prop_assert_eq!(result, Err("InvalidFormatError".into()));
+        prop_assert_eq!(events.len(), 2);
+
+        let (result, events) = send(setup, false, DataFormat::U8Iter(&mut bytes.iter().copied()));
+        prop_assert_eq!(result, Err("InvalidFormatError".into()));
+        prop_assert_eq!(events.len(), 2 + bytes.len() / 2 * 3);
+
+        // Padding makes both variants agree again
+        let setup = Setup::Sixteen(U8Packing::BigEndianPadded);
+        let slice = send(setup, false, DataFormat::U8(&bytes));
+        let iter = send(setup, false, DataFormat::U8Iter(&mut bytes.iter().copied()));
+        prop_assert_eq!(slice, iter);
+    }
+}
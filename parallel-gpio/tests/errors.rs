@@ -0,0 +1,178 @@
+//! Pin states after failed writes
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio16BitInterface, PGpio4BitInterface, PGpio6800Interface,
+    PGpio8BitInterface, PGpio9BitInterface, PGpioInterfaceError, PGpioLatched16BitInterface,
+    StrobePolarity, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
+use embedded_hal::digital::ErrorKind;
+
+use common::{level, Faulty, Recorder};
+
+#[test]
+fn write_enable_is_released_when_the_bus_fails() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterface::new(
+        Faulty::new(recorder.bus(), 2),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    );
+
+    let result = iface.send_data(DataFormat::U8(&[1, 2, 3]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(true));
+}
+
+#[test]
+fn typed_errors_keep_the_dc_pin_error() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterface::new(
+        recorder.bus(),
+        Faulty::new(recorder.pin("dc"), 1),
+        recorder.pin("wr"),
+    );
+
+    let result = iface.send_commands_typed(DataFormat::U8(&[0x2c]));
+    assert!(matches!(
+        result,
+        Err(PGpioInterfaceError::Dc(ErrorKind::Other))
+    ));
+
+    let mut iface = PGpio8BitInterface::new(
+        Faulty::new(recorder.bus(), 1),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    );
+
+    let result = iface.send_data_typed(DataFormat::U8(&[1]));
+    assert!(matches!(
+        result,
+        Err(PGpioInterfaceError::Bus(DisplayError::BusWriteError))
+    ));
+}
+
+#[test]
+fn write_enable_is_released_when_its_release_fails() {
+    let recorder = Recorder::new();
+    // The second operation releases the strobe of the first byte
+    let wr = Faulty::new(recorder.pin("wr"), 2);
+    let ops = wr.ops();
+    let mut iface = PGpio8BitInterface::new(recorder.bus(), recorder.pin("dc"), wr);
+
+    let result = iface.send_commands(DataFormat::U8(&[0x2c, 0x00]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(true));
+    assert_eq!(ops.get(), 3);
+}
+
+#[test]
+fn raw_words_release_the_write_enable() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        Faulty::new(recorder.bus(), 1),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    let result = iface.write_raw_data_word(0xffff);
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(false));
+}
+
+#[test]
+fn active_high_write_enable_is_released_low() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        Faulty::new(recorder.bus(), 3),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    let result = iface.send_data(DataFormat::U16BE(&mut [1, 2, 3, 4]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(false));
+}
+
+#[test]
+fn write_enable_is_untouched_when_dc_fails() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        Faulty::new(recorder.pin("dc"), 1),
+        recorder.pin("wr"),
+    );
+
+    let result = iface.send_data(DataFormat::U16(&[1]));
+
+    assert!(matches!(result, Err(DisplayError::DCError)));
+    assert_eq!(level(&recorder, "wr"), None);
+}
+
+#[test]
+fn four_bit_write_enable_is_released() {
+    let recorder = Recorder::new();
+    // Fails the low nibble of the first byte
+    let mut iface = PGpio4BitInterface::new(
+        Faulty::new(recorder.bus(), 2),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    );
+
+    let result = iface.send_commands(DataFormat::U8(&[0x28]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(true));
+}
+
+#[test]
+fn nine_bit_write_enable_is_released() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio9BitInterface::new(Faulty::new(recorder.bus(), 1), recorder.pin("wr"));
+
+    let result = iface.send_data(DataFormat::U8(&[0x12]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(true));
+}
+
+#[test]
+fn latched_write_enable_is_released() {
+    let recorder = Recorder::new();
+    // Fails the low byte, after the high byte was latched
+    let mut iface = PGpioLatched16BitInterface::new(
+        Faulty::new(recorder.bus(), 2),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+        recorder.pin("le"),
+    );
+
+    let result = iface.send_data(DataFormat::U16(&[0xf800]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "wr"), Some(true));
+    assert_eq!(level(&recorder, "le"), Some(false));
+}
+
+#[test]
+fn m6800_enable_is_released() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio6800Interface::new(
+        Faulty::new(recorder.bus(), 1),
+        recorder.pin("dc"),
+        recorder.pin("e"),
+        recorder.pin("rw"),
+    );
+
+    let result = iface.send_commands(DataFormat::U8(&[0xaf]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(level(&recorder, "e"), Some(false));
+}
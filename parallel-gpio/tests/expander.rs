@@ -0,0 +1,74 @@
+//! I2C writes of the port expander buses and pins, checked with `embedded-hal-mock`
+
+use display_interface_parallel_gpio::{
+    DataFormat, ExpanderBus16, ExpanderBus8, ExpanderPins, ExpanderPort, OutputBus,
+    PGpio8BitInterface, WriteOnlyDataCommand,
+};
+use embedded_hal_mock::eh1::i2c::{Mock as I2cMock, Transaction as I2cTransaction};
+
+#[test]
+fn pcf8574_raw_writes() {
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(0x20, vec![0x12]),
+        I2cTransaction::write(0x20, vec![0x34]),
+    ]);
+
+    let mut bus = ExpanderBus8::new(i2c, ExpanderPort::Pcf8574(0x20));
+    bus.configure().unwrap();
+    bus.set_value(0x12).unwrap();
+    bus.set_value(0x12).unwrap();
+    bus.set_value(0x34).unwrap();
+
+    bus.release().done();
+}
+
+#[test]
+fn mcp23017_interface() {
+    // Data on port A, D/C on GPB0 and WR on GPB1
+    let mut data = I2cMock::new(&[
+        I2cTransaction::write(0x27, vec![0x00, 0x00]),
+        I2cTransaction::write(0x27, vec![0x14, 0x2c]),
+        I2cTransaction::write(0x27, vec![0x14, 0xff]),
+    ]);
+    let mut control = I2cMock::new(&[
+        I2cTransaction::write(0x27, vec![0x01, 0x00]),
+        I2cTransaction::write(0x27, vec![0x15, 0b00]),
+        I2cTransaction::write(0x27, vec![0x15, 0b00]),
+        I2cTransaction::write(0x27, vec![0x15, 0b10]),
+        I2cTransaction::write(0x27, vec![0x15, 0b11]),
+        I2cTransaction::write(0x27, vec![0x15, 0b01]),
+        I2cTransaction::write(0x27, vec![0x15, 0b11]),
+    ]);
+
+    let pins = ExpanderPins::new(control.clone(), ExpanderPort::Mcp23017B(0x27));
+    pins.configure().unwrap();
+    let mut bus = ExpanderBus8::new(data.clone(), ExpanderPort::Mcp23017A(0x27));
+    bus.configure().unwrap();
+
+    let mut iface = PGpio8BitInterface::new(bus, pins.pin(0), pins.pin(1));
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.send_data(DataFormat::U8(&[0xff])).unwrap();
+
+    drop(iface);
+    data.done();
+    control.done();
+}
+
+#[test]
+fn sixteen_bit_writes_changed_ports_only() {
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(0x20, vec![0x34]),
+        I2cTransaction::write(0x21, vec![0x12]),
+        I2cTransaction::write(0x21, vec![0x56]),
+    ]);
+
+    let mut bus = ExpanderBus16::new(
+        i2c,
+        ExpanderPort::Pcf8574(0x20),
+        ExpanderPort::Pcf8574(0x21),
+    );
+    bus.set_value(0x1234).unwrap();
+    bus.set_value(0x5634).unwrap();
+
+    bus.release().done();
+}
@@ -0,0 +1,51 @@
+//! Values handed to the closure of the closure-backed buses
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, FnBus, FnBus16, PGpio16BitInterface, PGpio8BitInterface,
+    WriteOnlyDataCommand,
+};
+
+use common::NoPin;
+
+#[test]
+fn every_value_is_written() {
+    let mut port = Vec::new();
+    let bus = FnBus::new(|value| {
+        port.push(value);
+        Ok(())
+    });
+
+    let mut iface = PGpio8BitInterface::new(bus, NoPin, NoPin);
+    iface
+        .send_data(DataFormat::U8(&[0x00, 0x00, 0xff]))
+        .unwrap();
+
+    assert_eq!(port, [0x00, 0x00, 0xff]);
+}
+
+#[test]
+fn sixteen_bit_values() {
+    let mut port = Vec::new();
+    let bus = FnBus16::new(|value| {
+        port.push(value);
+        Ok(())
+    });
+
+    let mut iface = PGpio16BitInterface::new(bus, NoPin, NoPin);
+    iface.send_data(DataFormat::U16(&[0xf800, 0x001f])).unwrap();
+
+    assert_eq!(port, [0xf800, 0x001f]);
+}
+
+#[test]
+fn closure_errors_are_returned() {
+    let bus = FnBus::new(|_| Err(DisplayError::BusWriteError));
+
+    let mut iface = PGpio8BitInterface::new(bus, NoPin, NoPin);
+    assert!(matches!(
+        iface.send_commands(DataFormat::U8(&[0x2c])),
+        Err(DisplayError::BusWriteError)
+    ));
+}
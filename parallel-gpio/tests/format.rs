@@ -0,0 +1,112 @@
+//! The format handling shared by the parallel interfaces, called directly
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    write_format_16bit, write_format_8bit, DataFormat, DisplayError, PinEvent, PinEvent::*,
+    StrobePolarity, U8Packing, WriteEnable,
+};
+
+use common::{take, Recorder};
+
+const ASSERT: PinEvent = Pin("wr", false);
+const RELEASE: PinEvent = Pin("wr", true);
+
+fn write8(format: DataFormat<'_>) -> (Result<(), DisplayError>, Vec<PinEvent>) {
+    let recorder = Recorder::new();
+    let (mut wr, mut delay) = (recorder.pin("wr"), recorder.delay());
+    let result = write_format_8bit(
+        &mut recorder.bus(),
+        &mut wr.strobe(StrobePolarity::ActiveLow, &mut delay, None),
+        format,
+    );
+    (result, take(&recorder))
+}
+
+fn write16(
+    format: DataFormat<'_>,
+    packing: U8Packing,
+) -> (Result<(), DisplayError>, Vec<PinEvent>) {
+    let recorder = Recorder::new();
+    let (mut wr, mut delay) = (recorder.pin("wr"), recorder.delay());
+    let result = write_format_16bit(
+        &mut recorder.bus(),
+        &mut wr.strobe(StrobePolarity::ActiveLow, &mut delay, None),
+        format,
+        packing,
+    );
+    (result, take(&recorder))
+}
+
+/// The values put on the bus, checking that each was strobed
+fn words(events: Vec<PinEvent>) -> Vec<u32> {
+    events
+        .chunks(3)
+        .map(|cycle| match cycle {
+            [ASSERT, Bus(value), RELEASE] => *value,
+            _ => panic!("unexpected cycle {cycle:?}"),
+        })
+        .collect()
+}
+
+#[test]
+fn every_byte_is_strobed() {
+    let (result, events) = write8(DataFormat::U8(&[0x12, 0x34]));
+    assert!(result.is_ok());
+    assert_eq!(
+        events,
+        [ASSERT, Bus(0x12), RELEASE, ASSERT, Bus(0x34), RELEASE]
+    );
+}
+
+#[test]
+fn eight_bit_byte_orders() {
+    let (_, events) = write8(DataFormat::U16BE(&mut [0x1234, 0x5678]));
+    assert_eq!(words(events), [0x12, 0x34, 0x56, 0x78]);
+
+    let (_, events) = write8(DataFormat::U16LE(&mut [0x1234, 0x5678]));
+    assert_eq!(words(events), [0x34, 0x12, 0x78, 0x56]);
+
+    let (_, events) = write8(DataFormat::U16BEIter(&mut [0x1234].into_iter()));
+    assert_eq!(words(events), [0x12, 0x34]);
+
+    let (_, events) = write8(DataFormat::U16LEIter(&mut [0x1234].into_iter()));
+    assert_eq!(words(events), [0x34, 0x12]);
+
+    let (_, events) = write8(DataFormat::U16(&[0x1234]));
+    assert_eq!(words(events), 0x1234u16.to_ne_bytes().map(u32::from));
+
+    let (_, events) = write8(DataFormat::U8Iter(&mut [0xab, 0xcd].into_iter()));
+    assert_eq!(words(events), [0xab, 0xcd]);
+}
+
+#[test]
+fn sixteen_bit_words_are_written_whole() {
+    let (_, events) = write16(DataFormat::U16BE(&mut [0x1234]), U8Packing::default());
+    assert_eq!(words(events), [0x1234]);
+
+    let (_, events) = write16(DataFormat::U16LE(&mut [0x1234]), U8Packing::default());
+    assert_eq!(words(events), [0x1234]);
+
+    let (_, events) = write16(
+        DataFormat::U16LEIter(&mut [0x1234, 0x5678].into_iter()),
+        U8Packing::default(),
+    );
+    assert_eq!(words(events), [0x1234, 0x5678]);
+}
+
+#[test]
+fn sixteen_bit_bytes_follow_the_packing() {
+    let (_, events) = write16(DataFormat::U8(&[0x12, 0x34, 0x56]), U8Packing::ZeroExtend);
+    assert_eq!(words(events), [0x12, 0x34, 0x56]);
+
+    let (_, events) = write16(
+        DataFormat::U8(&[0x12, 0x34, 0x56]),
+        U8Packing::BigEndianPadded,
+    );
+    assert_eq!(words(events), [0x1234, 0x5600]);
+
+    let (result, events) = write16(DataFormat::U8(&[0x12, 0x34, 0x56]), U8Packing::BigEndian);
+    assert!(matches!(result, Err(DisplayError::InvalidFormatError)));
+    assert!(events.is_empty());
+}
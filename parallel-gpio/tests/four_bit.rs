@@ -0,0 +1,95 @@
+//! Nibble order and strobing of the 4-bit interface
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpio4BitInterface, PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{bus_values, take, take_timed, Recorder, TestBus, TestPin};
+
+fn interface(recorder: &Recorder) -> PGpio4BitInterface<TestBus<'_, u8>, TestPin<'_>, TestPin<'_>> {
+    PGpio4BitInterface::new(recorder.bus(), recorder.pin("dc"), recorder.pin("wr"))
+}
+
+#[test]
+fn command_high_nibble_first() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface.send_commands(DataFormat::U8(&[0x28, 0x0c])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x2),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0x8),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0x0),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0xc),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn data_high_nibble_first() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface
+        .send_data(DataFormat::U8Iter(&mut [0xa5].into_iter()))
+        .unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0xa),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0x5),
+            Pin("wr", true)
+        ]
+    );
+}
+
+#[test]
+fn u16_bytes_in_requested_order() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface.send_data(DataFormat::U16LE(&mut [0x1234])).unwrap();
+
+    assert_eq!(bus_values(&recorder), [0x3, 0x4, 0x1, 0x2]);
+}
+
+#[test]
+fn nibble_delay_after_every_strobe() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder).with_nibble_delay(recorder.delay(), 37_000);
+
+    iface.send_data(DataFormat::U8(&[0x41])).unwrap();
+
+    assert_eq!(
+        take_timed(&recorder),
+        [
+            (0, Pin("dc", true)),
+            (0, Pin("wr", false)),
+            (0, Bus(0x4)),
+            (0, Pin("wr", true)),
+            (37_000, Pin("wr", false)),
+            (37_000, Bus(0x1)),
+            (37_000, Pin("wr", true)),
+        ]
+    );
+    assert_eq!(recorder.now_ns(), 74_000);
+}
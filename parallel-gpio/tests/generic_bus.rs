@@ -0,0 +1,76 @@
+//! The const generic bus at various widths
+
+mod common;
+
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{
+    DataFormat, GenericBus, OutputBus, PGpio16BitInterface, WriteOnlyDataCommand,
+};
+
+use common::{NoPin, Port};
+
+#[test]
+fn eight_bits_use_u8() {
+    let port = Rc::new(Port::default());
+    let mut bus = GenericBus::new(port.pins::<8>());
+
+    bus.set_value(0xa5u8).unwrap();
+    assert_eq!(port.levels(), 0xa5);
+}
+
+#[test]
+fn twelve_bits_use_u16() {
+    let port = Rc::new(Port::default());
+    let mut bus = GenericBus::new(port.pins::<12>());
+
+    bus.set_value(0xabcu16).unwrap();
+    assert_eq!(port.levels(), 0xabc);
+    assert_eq!(port.writes(), 12);
+
+    // Only bits 0 and 1 change
+    bus.set_value(0xabfu16).unwrap();
+    assert_eq!(port.levels(), 0xabf);
+    assert_eq!(port.writes(), 14);
+
+    // Bits beyond the width are ignored
+    bus.set_value(0xfabfu16).unwrap();
+    assert_eq!(port.levels(), 0xabf);
+}
+
+#[test]
+fn eighteen_bits_use_u32() {
+    let port = Rc::new(Port::default());
+    let mut bus = GenericBus::new(port.pins::<18>()).with_inversion_mask(0x3_0000);
+
+    bus.set_value(0x2_5555u32).unwrap();
+    assert_eq!(port.levels(), 0x1_5555);
+}
+
+#[test]
+fn cache_control() {
+    let port = Rc::new(Port::default());
+    let mut bus = GenericBus::new(port.pins::<9>());
+
+    bus.set_value(0x100u16).unwrap();
+    bus.invalidate();
+    bus.set_value(0x100u16).unwrap();
+    assert_eq!(port.writes(), 18);
+
+    let mut bus = bus.with_caching(false);
+    bus.set_value(0x100u16).unwrap();
+    bus.set_value(0x100u16).unwrap();
+    assert_eq!(port.writes(), 36);
+}
+
+#[test]
+fn sixteen_bit_interface_on_twelve_pins() {
+    let port = Rc::new(Port::default());
+    let mut iface = PGpio16BitInterface::new(GenericBus::new(port.pins::<12>()), NoPin, NoPin);
+
+    iface
+        .send_data(DataFormat::U16BE(&mut [0x0fff; 8]))
+        .unwrap();
+    assert_eq!(port.levels(), 0xfff);
+    assert_eq!(port.writes(), 12);
+}
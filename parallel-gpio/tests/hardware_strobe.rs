@@ -0,0 +1,73 @@
+//! Write-enable strobes generated in hardware
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, Generic8BitBus, HardwareStrobe, PGpio16BitInterface,
+    PGpio8BitInterface, PinEvent, PinEvent::*, StrobePolarity, WriteOnlyDataCommand, WriteStrobe,
+};
+
+use common::{take, NoPin, Recorder};
+
+const PULSE: PinEvent = Note("pulse", 1);
+
+/// Strobe pulsing once at the release, like a timer triggered by the bus write
+struct TimerStrobe<'a>(&'a Recorder);
+
+impl WriteStrobe for TimerStrobe<'_> {
+    fn assert(&mut self) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn release(&mut self) -> Result<(), DisplayError> {
+        self.0.note("pulse", 1);
+        Ok(())
+    }
+
+    fn strobe_n(&mut self, count: usize) -> Result<(), DisplayError> {
+        self.0.note("pulses", count as u32);
+        Ok(())
+    }
+}
+
+#[test]
+fn every_word_is_strobed_by_the_hardware() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        NoPin,
+        HardwareStrobe(TimerStrobe(&recorder)),
+    )
+    // Left to the hardware
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface
+        .send_data(DataFormat::U16BE(&mut [0xf800, 0x07e0]))
+        .unwrap();
+    iface.write_raw_command_word(0x2c).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [Bus(0xf800), PULSE, Bus(0x07e0), PULSE, Bus(0x2c), PULSE]
+    );
+}
+
+#[test]
+fn runs_are_strobed_at_once() {
+    let recorder = Recorder::new();
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = core::array::from_fn(|_| NoPin);
+    let mut iface = PGpio8BitInterface::new(
+        Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7)),
+        NoPin,
+        HardwareStrobe(TimerStrobe(&recorder)),
+    );
+
+    iface
+        .send_data(DataFormat::U8(&[0x00, 0x00, 0x00, 0xff, 0xff]))
+        .unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [PULSE, Note("pulses", 2), PULSE, Note("pulses", 1)]
+    );
+}
@@ -0,0 +1,127 @@
+//! Reads through the generic bus on open-drain pins
+
+mod common;
+
+use core::{cell::Cell, convert::Infallible};
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{
+    BidirectionalBus, DataFormat, Generic8BitInOutBus, OutputBus, PGpio8BitReadWriteInterface,
+    ReadData, WriteOnlyDataCommand,
+};
+use embedded_hal::digital::{ErrorType, InputPin, OutputPin};
+
+use common::NoPin;
+
+/// Wired-AND data lines with pull-ups, pulled low by the MCU or the display
+#[derive(Default)]
+struct Lines {
+    mcu_low: Cell<u8>,
+    display_low: Cell<u8>,
+    writes: Cell<usize>,
+}
+
+impl Lines {
+    fn levels(&self) -> u8 {
+        !(self.mcu_low.get() | self.display_low.get())
+    }
+
+    /// Let the display drive `value` onto the lines
+    fn respond(&self, value: u8) {
+        self.display_low.set(!value);
+    }
+}
+
+struct OpenDrain(Rc<Lines>, u8);
+
+impl ErrorType for OpenDrain {
+    type Error = Infallible;
+}
+
+impl OutputPin for OpenDrain {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.mcu_low.set(self.0.mcu_low.get() | 1 << self.1);
+        self.0.writes.set(self.0.writes.get() + 1);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.mcu_low.set(self.0.mcu_low.get() & !(1 << self.1));
+        self.0.writes.set(self.0.writes.get() + 1);
+        Ok(())
+    }
+}
+
+impl InputPin for OpenDrain {
+    fn is_high(&mut self) -> Result<bool, Infallible> {
+        Ok(self.0.levels() & 1 << self.1 != 0)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Infallible> {
+        self.is_high().map(|high| !high)
+    }
+}
+
+type Bus = Generic8BitInOutBus<
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+    OpenDrain,
+>;
+
+fn bus(lines: &Rc<Lines>) -> Bus {
+    let [p0, p1, p2, p3, p4, p5, p6, p7] =
+        core::array::from_fn(|n| OpenDrain(lines.clone(), n as u8));
+    Generic8BitInOutBus::new((p0, p1, p2, p3, p4, p5, p6, p7))
+}
+
+#[test]
+fn input_releases_all_lines() {
+    let lines = Rc::new(Lines::default());
+    let mut bus = bus(&lines);
+
+    bus.set_value(0x00).unwrap();
+    assert_eq!(lines.levels(), 0x00);
+
+    bus.set_input().unwrap();
+    assert_eq!(lines.levels(), 0xff);
+
+    lines.respond(0xa5);
+    assert_eq!(bus.get_value().unwrap(), 0xa5);
+}
+
+#[test]
+fn output_drives_all_lines_again() {
+    let lines = Rc::new(Lines::default());
+    let mut bus = bus(&lines);
+
+    bus.set_value(0x0f).unwrap();
+    bus.set_input().unwrap();
+    bus.set_output().unwrap();
+    lines.writes.set(0);
+
+    // The same value as before the read, which must not be skipped
+    bus.set_value(0x0f).unwrap();
+    assert_eq!(lines.levels(), 0x0f);
+    assert_eq!(lines.writes.get(), 8);
+}
+
+#[test]
+fn read_write_interface() {
+    let lines = Rc::new(Lines::default());
+    let mut iface = PGpio8BitReadWriteInterface::new(bus(&lines), NoPin, NoPin, NoPin);
+
+    iface.send_commands(DataFormat::U8(&[0x04])).unwrap();
+    lines.respond(0x93);
+    let mut id = [0; 1];
+    iface.read_data(&mut id).unwrap();
+    assert_eq!(id, [0x93]);
+
+    lines.respond(0xff);
+    iface.send_commands(DataFormat::U8(&[0x04])).unwrap();
+    assert_eq!(lines.levels(), 0x04);
+}
@@ -0,0 +1,56 @@
+//! Inverted pins on the generic buses
+
+mod common;
+
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{Generic16BitBus, Generic8BitBus, OutputBus};
+
+use common::{Port, PortPin};
+
+type Bus8 = Generic8BitBus<PortPin, PortPin, PortPin, PortPin, PortPin, PortPin, PortPin, PortPin>;
+
+fn bus8(port: &Rc<Port>) -> Bus8 {
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = port.pins();
+    Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7))
+}
+
+#[test]
+fn masked_pins_are_inverted() {
+    let port = Rc::new(Port::default());
+    let mut bus = bus8(&port).with_inversion_mask(0xf0);
+
+    bus.set_value(0x00).unwrap();
+    assert_eq!(port.levels(), 0xf0);
+
+    bus.set_value(0x5a).unwrap();
+    assert_eq!(port.levels(), 0xaa);
+}
+
+#[test]
+fn cache_works_on_the_value() {
+    let port = Rc::new(Port::default());
+    let mut bus = bus8(&port).with_inversion_mask(0x0f);
+
+    bus.set_value(0x12).unwrap();
+    bus.set_value(0x12).unwrap();
+    assert_eq!(port.writes(), 8);
+
+    // Only bit 0 changes
+    bus.set_value(0x13).unwrap();
+    assert_eq!(port.writes(), 9);
+    assert_eq!(port.levels(), 0x1c);
+}
+
+#[test]
+fn sixteen_bit_mask() {
+    let port = Rc::new(Port::default());
+    let [p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15] = port.pins();
+    let mut bus = Generic16BitBus::new((
+        p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15,
+    ))
+    .with_inversion_mask(0x8001);
+
+    bus.set_value(0xf800).unwrap();
+    assert_eq!(port.levels(), 0x7801);
+}
@@ -0,0 +1,84 @@
+//! Ordering of the latch and write-enable pulses of the latched 16-bit interface
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpioLatched16BitInterface, PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{take, Recorder, TestBus, TestPin};
+
+fn interface(
+    recorder: &Recorder,
+) -> PGpioLatched16BitInterface<TestBus<'_, u8>, TestPin<'_>, TestPin<'_>, TestPin<'_>> {
+    PGpioLatched16BitInterface::new(
+        recorder.bus(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+        recorder.pin("le"),
+    )
+}
+
+#[test]
+fn latch_before_write() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface
+        .send_data(DataFormat::U16BE(&mut [0xf800, 0x07e0]))
+        .unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0xf8),
+            Pin("le", true),
+            Pin("le", false),
+            Bus(0x00),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0x07),
+            Pin("le", true),
+            Pin("le", false),
+            Bus(0xe0),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn byte_order_does_not_matter() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    iface.send_data(DataFormat::U16(&[0x1234])).unwrap();
+
+    let events = take(&recorder);
+    assert_eq!(events[1..7], events[8..]);
+    assert_eq!(events[2], Bus(0x12));
+}
+
+#[test]
+fn bytes_are_zero_extended() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x00),
+            Pin("le", true),
+            Pin("le", false),
+            Bus(0x2c),
+            Pin("wr", true),
+        ]
+    );
+}
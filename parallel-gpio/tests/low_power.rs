@@ -0,0 +1,114 @@
+//! Parking the pins for low-power modes
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, LowPower, OutputBus, PGpio16BitInterface, PGpio8BitInterface,
+    PGpio8BitInterfaceWithCs, PinEvent, PinEvent::*, StrobePolarity, WriteOnlyDataCommand,
+};
+
+use common::{take, Recorder, TestBus};
+
+const INVALIDATE: PinEvent = Note("invalidate", 0);
+
+/// Bus skipping repeated values like the generic buses, recording what reaches the pins
+struct CachingBus<'a, W> {
+    recorder: &'a Recorder,
+    bus: TestBus<'a, W>,
+    last: Option<W>,
+}
+
+impl<'a, W> CachingBus<'a, W> {
+    fn new(recorder: &'a Recorder) -> Self {
+        Self {
+            recorder,
+            bus: recorder.bus(),
+            last: None,
+        }
+    }
+}
+
+impl<W: Copy + PartialEq + Into<u32>> OutputBus for CachingBus<'_, W> {
+    type Word = W;
+
+    fn set_value(&mut self, value: W) -> Result<(), DisplayError> {
+        if self.last != Some(value) {
+            self.bus.set_value(value)?;
+            self.last = Some(value);
+        }
+        Ok(())
+    }
+
+    fn invalidate(&mut self) {
+        self.recorder.note("invalidate", 0);
+        self.last = None;
+    }
+}
+
+#[test]
+fn suspend_parks_and_resume_redrives() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterfaceWithCs::new(
+        PGpio8BitInterface::new(
+            CachingBus::<u8>::new(&recorder),
+            recorder.pin("dc"),
+            recorder.pin("wr"),
+        ),
+        recorder.pin("cs"),
+    );
+
+    iface.send_data(DataFormat::U8(&[0x2c])).unwrap();
+    recorder.clear();
+
+    iface.suspend().unwrap();
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("cs", true),
+            Pin("dc", false),
+            Pin("wr", true),
+            Bus(0),
+            INVALIDATE
+        ]
+    );
+
+    iface.resume().unwrap();
+    assert_eq!(
+        take(&recorder),
+        [Pin("cs", true), INVALIDATE, Pin("wr", true)]
+    );
+
+    // The value of the bus is unknown after resuming, so it is driven even if unchanged
+    iface.send_data(DataFormat::U8(&[0x00])).unwrap();
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("cs", false),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0),
+            Pin("wr", true),
+            Pin("cs", true)
+        ]
+    );
+}
+
+#[test]
+fn strobe_parked_at_its_idle_level() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        CachingBus::<u16>::new(&recorder),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.suspend().unwrap();
+    assert_eq!(
+        take(&recorder),
+        [Pin("dc", false), Pin("wr", false), Bus(0), INVALIDATE]
+    );
+
+    iface.resume().unwrap();
+    assert_eq!(take(&recorder), [INVALIDATE, Pin("wr", false)]);
+}
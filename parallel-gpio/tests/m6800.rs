@@ -0,0 +1,83 @@
+//! Sequencing of the 6800 style interface
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpio6800Interface, PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{take, take_timed, Recorder, TestBus, TestPin};
+
+fn interface(
+    recorder: &Recorder,
+) -> PGpio6800Interface<TestBus<'_, u8>, TestPin<'_>, TestPin<'_>, TestPin<'_>> {
+    PGpio6800Interface::new(
+        recorder.bus(),
+        recorder.pin("dc"),
+        recorder.pin("e"),
+        recorder.pin("rw"),
+    )
+}
+
+#[test]
+fn data_valid_before_falling_edge() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface.send_commands(DataFormat::U8(&[0xaf, 0x40])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("rw", false),
+            Pin("e", true),
+            Bus(0xaf),
+            Pin("e", false),
+            Pin("e", true),
+            Bus(0x40),
+            Pin("e", false),
+        ]
+    );
+}
+
+#[test]
+fn u16_data_split_into_bytes() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder);
+
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("rw", false),
+            Pin("e", true),
+            Bus(0x12),
+            Pin("e", false),
+            Pin("e", true),
+            Bus(0x34),
+            Pin("e", false),
+        ]
+    );
+}
+
+#[test]
+fn stretched_enable_pulse() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder).with_timing(recorder.delay(), 450, 195);
+
+    iface.send_data(DataFormat::U8(&[0x01])).unwrap();
+
+    assert_eq!(
+        take_timed(&recorder),
+        [
+            (0, Pin("dc", true)),
+            (0, Pin("rw", false)),
+            (0, Pin("e", true)),
+            (0, Bus(0x01)),
+            (450, Pin("e", false)),
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+//! D/C encoding in bit 8 of the 9-bit interface
+
+mod common;
+
+use display_interface_parallel_gpio::{DataFormat, PGpio9BitInterface, WriteOnlyDataCommand};
+
+use common::{bus_values, NoPin, Recorder};
+
+#[test]
+fn commands_clear_bit_8() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio9BitInterface::new(recorder.bus::<u16>(), NoPin);
+    iface.send_commands(DataFormat::U8(&[0x22, 0xff])).unwrap();
+    assert_eq!(bus_values(&recorder), [0x022, 0x0ff]);
+}
+
+#[test]
+fn data_sets_bit_8() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio9BitInterface::new(recorder.bus::<u16>(), NoPin);
+    iface
+        .send_data(DataFormat::U8Iter(&mut [0x00, 0x80].into_iter()))
+        .unwrap();
+    assert_eq!(bus_values(&recorder), [0x100, 0x180]);
+}
+
+#[test]
+fn u16_data_is_split_into_bytes() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio9BitInterface::new(recorder.bus::<u16>(), NoPin);
+    iface
+        .send_data(DataFormat::U16BE(&mut [0x1234, 0xabcd]))
+        .unwrap();
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(
+        bus_values(&recorder),
+        [0x112, 0x134, 0x1ab, 0x1cd, 0x134, 0x112]
+    );
+}
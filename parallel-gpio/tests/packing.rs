@@ -0,0 +1,90 @@
+//! Packing of bytes into words on the 16-bit bus
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio16BitInterface, U8Packing, WriteOnlyDataCommand,
+};
+
+use common::{bus_values, NoPin, Recorder};
+
+fn send(packing: U8Packing, format: DataFormat<'_>) -> (Result<(), DisplayError>, Vec<u32>) {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio16BitInterface::new(recorder.bus::<u16>(), NoPin, NoPin).with_u8_packing(packing);
+    let result = iface.send_data(format);
+    (result, bus_values(&recorder))
+}
+
+#[test]
+fn bytes_are_zero_extended_by_default() {
+    let (result, words) = send(U8Packing::default(), DataFormat::U8(&[0x12, 0x34, 0x56]));
+    assert!(result.is_ok());
+    assert_eq!(words, [0x12, 0x34, 0x56]);
+}
+
+#[test]
+fn bytes_are_packed_big_endian() {
+    let (result, words) = send(
+        U8Packing::BigEndian,
+        DataFormat::U8(&[0x12, 0x34, 0x56, 0x78]),
+    );
+    assert!(result.is_ok());
+    assert_eq!(words, [0x1234, 0x5678]);
+
+    let mut iter = [0x12, 0x34, 0x56, 0x78].into_iter();
+    let (result, words) = send(U8Packing::BigEndian, DataFormat::U8Iter(&mut iter));
+    assert!(result.is_ok());
+    assert_eq!(words, [0x1234, 0x5678]);
+}
+
+#[test]
+fn odd_slice_is_rejected_up_front() {
+    let (result, words) = send(U8Packing::BigEndian, DataFormat::U8(&[0x12, 0x34, 0x56]));
+    assert!(matches!(result, Err(DisplayError::InvalidFormatError)));
+    assert!(words.is_empty());
+}
+
+#[test]
+fn odd_iterator_is_rejected_after_complete_words() {
+    let mut iter = [0x12, 0x34, 0x56].into_iter();
+    let (result, words) = send(U8Packing::BigEndian, DataFormat::U8Iter(&mut iter));
+    assert!(matches!(result, Err(DisplayError::InvalidFormatError)));
+    assert_eq!(words, [0x1234]);
+}
+
+#[test]
+fn odd_byte_is_padded() {
+    let (result, words) = send(
+        U8Packing::BigEndianPadded,
+        DataFormat::U8(&[0x12, 0x34, 0x56]),
+    );
+    assert!(result.is_ok());
+    assert_eq!(words, [0x1234, 0x5600]);
+
+    let mut iter = [0x12, 0x34, 0x56].into_iter();
+    let (result, words) = send(U8Packing::BigEndianPadded, DataFormat::U8Iter(&mut iter));
+    assert!(result.is_ok());
+    assert_eq!(words, [0x1234, 0x5600]);
+}
+
+#[test]
+fn commands_are_not_packed() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(recorder.bus::<u16>(), NoPin, NoPin)
+        .with_u8_packing(U8Packing::BigEndian);
+    iface
+        .send_commands(DataFormat::U8(&[0x2c, 0x00, 0x01]))
+        .unwrap();
+    assert_eq!(bus_values(&recorder), [0x2c, 0x00, 0x01]);
+}
+
+#[test]
+fn words_are_unaffected() {
+    let (result, words) = send(
+        U8Packing::BigEndian,
+        DataFormat::U16BE(&mut [0x1234, 0x5678]),
+    );
+    assert!(result.is_ok());
+    assert_eq!(words, [0x1234, 0x5678]);
+}
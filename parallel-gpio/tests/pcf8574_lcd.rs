@@ -0,0 +1,127 @@
+//! Port values written to the PCF8574 of a character LCD backpack, checked with
+//! `embedded-hal-mock`
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, Pcf8574Lcd4BitInterface, WriteOnlyDataCommand,
+};
+use embedded_hal::i2c::ErrorKind;
+use embedded_hal_mock::eh1::{
+    delay::{CheckedDelay, Transaction as DelayTransaction},
+    i2c::{Mock as I2cMock, Transaction as I2cTransaction},
+};
+
+const ADDR: u8 = 0x27;
+
+const RS: u8 = 0x01;
+const EN: u8 = 0x04;
+const BL: u8 = 0x08;
+
+#[test]
+fn initialization_sends_three_8bit_nibbles_then_4bit() {
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(ADDR, vec![0x30 | BL, 0x30 | BL | EN, 0x30 | BL]),
+        I2cTransaction::write(ADDR, vec![0x30 | BL, 0x30 | BL | EN, 0x30 | BL]),
+        I2cTransaction::write(ADDR, vec![0x30 | BL, 0x30 | BL | EN, 0x30 | BL]),
+        I2cTransaction::write(ADDR, vec![0x20 | BL, 0x20 | BL | EN, 0x20 | BL]),
+    ]);
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::blocking_delay_us(4_100),
+        DelayTransaction::blocking_delay_us(100),
+        DelayTransaction::blocking_delay_us(100),
+        DelayTransaction::blocking_delay_us(100),
+    ]);
+
+    let mut iface = Pcf8574Lcd4BitInterface::new(i2c, ADDR);
+    iface.init(&mut delay).unwrap();
+
+    iface.release().done();
+    delay.done();
+}
+
+#[test]
+fn nibbles_are_latched_by_falling_enable() {
+    let i2c = I2cMock::new(&[
+        // Function set 0x28: RS low first, then high and low nibble, each with an EN pulse
+        I2cTransaction::write(
+            ADDR,
+            vec![BL, 0x20 | BL | EN, 0x20 | BL, 0x80 | BL | EN, 0x80 | BL],
+        ),
+        // 'H'
+        I2cTransaction::write(
+            ADDR,
+            vec![
+                RS | BL,
+                0x40 | RS | BL | EN,
+                0x40 | RS | BL,
+                0x80 | RS | BL | EN,
+                0x80 | RS | BL,
+            ],
+        ),
+    ]);
+
+    let mut iface = Pcf8574Lcd4BitInterface::new(i2c, ADDR);
+    iface.send_commands(DataFormat::U8(&[0x28])).unwrap();
+    iface.send_data(DataFormat::U8(b"H")).unwrap();
+
+    iface.release().done();
+}
+
+#[test]
+fn backlight_is_applied_to_every_write() {
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(ADDR, vec![0x00]),
+        I2cTransaction::write(
+            ADDR,
+            vec![RS, 0x30 | RS | EN, 0x30 | RS, 0x10 | RS | EN, 0x10 | RS],
+        ),
+        I2cTransaction::write(ADDR, vec![BL]),
+    ]);
+
+    let mut iface = Pcf8574Lcd4BitInterface::new(i2c, ADDR);
+    iface.set_backlight(false).unwrap();
+    iface.send_data(DataFormat::U8(b"1")).unwrap();
+    iface.set_backlight(true).unwrap();
+
+    iface.release().done();
+}
+
+#[test]
+fn long_writes_are_split() {
+    // 7 bytes of 4 port values each fit into the first write after the RS setup
+    let text = b"Hello, world";
+    let ports: Vec<u8> = text
+        .iter()
+        .flat_map(|&c| {
+            [c & 0xf0, c << 4].into_iter().flat_map(|nibble| {
+                let port = nibble | RS | BL;
+                [port | EN, port]
+            })
+        })
+        .collect();
+    let mut first = vec![RS | BL];
+    first.extend_from_slice(&ports[..28]);
+    let i2c = I2cMock::new(&[
+        I2cTransaction::write(ADDR, first),
+        I2cTransaction::write(ADDR, ports[28..].to_vec()),
+    ]);
+
+    let mut iface = Pcf8574Lcd4BitInterface::new(i2c, ADDR);
+    iface.send_data(DataFormat::U8(text)).unwrap();
+
+    iface.release().done();
+}
+
+#[test]
+fn bus_errors_are_reported() {
+    let i2c = I2cMock::new(&[I2cTransaction::write(
+        ADDR,
+        vec![BL, BL | EN, BL, 0x10 | BL | EN, 0x10 | BL],
+    )
+    .with_error(ErrorKind::Other)]);
+
+    let mut iface = Pcf8574Lcd4BitInterface::new(i2c, ADDR);
+    let result = iface.send_commands(DataFormat::U8(&[0x01]));
+
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    iface.release().done();
+}
@@ -0,0 +1,192 @@
+//! Exact order of the pin and bus changes for every `DataFormat`, checked with `PinRecorder`
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio16BitInterface, PGpio8BitInterface, PinEvent, PinEvent::*,
+    U8Packing, WriteOnlyDataCommand,
+};
+
+use common::{take, Recorder, TestBus, TestPin};
+
+/// D/C selected for `data`, followed by a write cycle for every word
+fn expected(data: bool, words: &[u32]) -> Vec<PinEvent> {
+    let mut events = vec![Pin("dc", data)];
+    for &word in words {
+        events.extend([Pin("wr", false), Bus(word), Pin("wr", true)]);
+    }
+    events
+}
+
+/// D/C selected for `data`, followed by the release of the write-enable after a failure
+fn failed(data: bool) -> Vec<PinEvent> {
+    vec![Pin("dc", data), Pin("wr", true)]
+}
+
+fn eight_bit(recorder: &Recorder) -> impl WriteOnlyDataCommand + '_ {
+    PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+}
+
+type SixteenBit<'a> = PGpio16BitInterface<TestBus<'a, u16>, TestPin<'a>, TestPin<'a>>;
+
+fn sixteen_bit(recorder: &Recorder) -> SixteenBit<'_> {
+    PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+}
+
+#[test]
+fn eight_bit_commands() {
+    let recorder = Recorder::new();
+    let mut iface = eight_bit(&recorder);
+
+    iface.send_commands(DataFormat::U8(&[0x2a, 0x00])).unwrap();
+    assert_eq!(take(&recorder), expected(false, &[0x2a, 0x00]));
+
+    iface
+        .send_commands(DataFormat::U16BE(&mut [0x2b01]))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(false, &[0x2b, 0x01]));
+}
+
+#[test]
+fn eight_bit_data_formats() {
+    let recorder = Recorder::new();
+    let mut iface = eight_bit(&recorder);
+
+    iface.send_data(DataFormat::U8(&[0x12, 0x34])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    iface
+        .send_data(DataFormat::U8Iter(&mut [0x12, 0x34].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    let native = 0x1234u16.to_ne_bytes().map(u32::from);
+    iface.send_data(DataFormat::U16(&[0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &native));
+
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    iface.send_data(DataFormat::U16LE(&mut [0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x34, 0x12]));
+
+    iface
+        .send_data(DataFormat::U16BEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x34, 0x12]));
+
+    // D/C is selected before the format is found to be unsupported, the write-enable is
+    // released again after the failure
+    assert!(matches!(
+        iface.send_data(DataFormat::U24(&[0x123456])),
+        Err(DisplayError::DataFormatNotImplemented)
+    ));
+    assert_eq!(take(&recorder), failed(true));
+}
+
+#[test]
+fn sixteen_bit_commands() {
+    let recorder = Recorder::new();
+    let mut iface = sixteen_bit(&recorder);
+
+    // Commands are always zero-extended, whatever the packing of data
+    let mut iface_packed = sixteen_bit(&recorder).with_u8_packing(U8Packing::BigEndian);
+    iface_packed
+        .send_commands(DataFormat::U8(&[0x2a, 0x00]))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(false, &[0x2a, 0x00]));
+
+    iface.send_commands(DataFormat::U16(&[0x002b])).unwrap();
+    assert_eq!(take(&recorder), expected(false, &[0x002b]));
+}
+
+#[test]
+fn sixteen_bit_data_formats() {
+    let recorder = Recorder::new();
+    let mut iface = sixteen_bit(&recorder);
+
+    iface.send_data(DataFormat::U8(&[0x12, 0x34])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    iface
+        .send_data(DataFormat::U8Iter(&mut [0x12, 0x34].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x12, 0x34]));
+
+    iface.send_data(DataFormat::U16(&[0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    iface.send_data(DataFormat::U16LE(&mut [0x1234])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    iface
+        .send_data(DataFormat::U16BEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    assert!(matches!(
+        iface.send_data(DataFormat::U24(&[0x123456])),
+        Err(DisplayError::DataFormatNotImplemented)
+    ));
+    assert_eq!(take(&recorder), failed(true));
+}
+
+#[test]
+fn sixteen_bit_packed_bytes() {
+    let recorder = Recorder::new();
+    let mut iface = sixteen_bit(&recorder).with_u8_packing(U8Packing::BigEndian);
+
+    iface.send_data(DataFormat::U8(&[0x12, 0x34])).unwrap();
+    assert_eq!(take(&recorder), expected(true, &[0x1234]));
+
+    // Odd slices are rejected before anything is written
+    assert!(matches!(
+        iface.send_data(DataFormat::U8(&[0x12])),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    assert_eq!(take(&recorder), failed(true));
+}
+
+#[test]
+fn data_is_set_up_before_the_rising_edge() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_timing(recorder.delay(), 40, 60);
+
+    iface.send_data(DataFormat::U8(&[0x12, 0x34])).unwrap();
+
+    let events = recorder.events();
+    let times: Vec<_> = events.iter().map(|e| (e.event, e.at_ns)).collect();
+    assert_eq!(
+        times,
+        [
+            (Pin("dc", true), 0),
+            (Pin("wr", false), 0),
+            (Bus(0x12), 0),
+            (Pin("wr", true), 60),
+            (Pin("wr", false), 60),
+            (Bus(0x34), 60),
+            (Pin("wr", true), 120),
+        ]
+    );
+    drop(events);
+    assert_eq!(recorder.now_ns(), 120);
+}
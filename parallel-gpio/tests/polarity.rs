@@ -0,0 +1,160 @@
+//! Levels of the D/C pin and edge order of the write-enable strobe for both polarities
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DcPolarity, PGpio16BitInterface, PGpio4BitInterface, PGpio8BitInterface,
+    PGpio9BitInterface, PinEvent::*, StrobePolarity, WriteOnlyDataCommand,
+};
+
+use common::{take, Recorder};
+
+#[test]
+fn active_low_by_default() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"));
+
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true)
+        ]
+    );
+}
+
+#[test]
+fn active_high_8_bit() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.send_data(DataFormat::U8(&[0x12, 0x34])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", true),
+            Bus(0x12),
+            Pin("wr", false),
+            Pin("wr", true),
+            Bus(0x34),
+            Pin("wr", false),
+        ]
+    );
+}
+
+#[test]
+fn active_high_16_bit() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.send_commands(DataFormat::U16(&[0x2c])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", true),
+            Bus(0x2c),
+            Pin("wr", false)
+        ]
+    );
+}
+
+#[test]
+fn active_high_9_bit() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio9BitInterface::new(recorder.bus::<u16>(), recorder.pin("wr"))
+        .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [Pin("wr", true), Bus(0x112), Pin("wr", false)]
+    );
+}
+
+#[test]
+fn active_high_4_bit() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio4BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", true),
+            Bus(0x1),
+            Pin("wr", false),
+            Pin("wr", true),
+            Bus(0x2),
+            Pin("wr", false),
+        ]
+    );
+}
+
+#[test]
+fn inverted_dc() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_dc_polarity(DcPolarity::LowData);
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x2c),
+            Pin("wr", true),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn inverted_dc_16_bit() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_dc_polarity(DcPolarity::LowData);
+
+    iface.send_data(DataFormat::U16(&[0xf800])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0xf800),
+            Pin("wr", true)
+        ]
+    );
+}
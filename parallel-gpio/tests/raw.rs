@@ -0,0 +1,61 @@
+//! Raw single word writes on the parallel interfaces
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DcPolarity, PGpio16BitInterface, PGpio8BitInterface, PinEvent::*,
+};
+
+use common::{take, Recorder};
+
+#[test]
+fn one_cycle_per_word() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"));
+
+    iface.write_raw_command_word(0x01).unwrap();
+    iface.write_raw_data_word(0x02).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0x01),
+            Pin("wr", true),
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x02),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn sixteen_bit_words_are_written_whole() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_dc_polarity(DcPolarity::LowData);
+
+    iface.write_raw_command_word(0x1234).unwrap();
+    iface.write_raw_data_word(0xf800).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x1234),
+            Pin("wr", true),
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0xf800),
+            Pin("wr", true),
+        ]
+    );
+}
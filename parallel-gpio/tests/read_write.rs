@@ -0,0 +1,140 @@
+//! Direction switching and strobing of the read-write interface
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    BidirectionalBus, DataFormat, DisplayError, OutputBus, PGpio8BitReadWriteInterface, PinEvent,
+    PinEvent::*, ReadData, WriteOnlyDataCommand,
+};
+
+use common::{take, Recorder, TestBus, TestPin};
+
+const INPUT: PinEvent = Note("input", 0);
+const OUTPUT: PinEvent = Note("output", 0);
+
+/// Bus returning the bytes of `input` in turn, failing once they are used up
+///
+/// Values set are recorded as such, values read are noted as `get`.
+struct ReadBus<'a> {
+    recorder: &'a Recorder,
+    bus: TestBus<'a, u8>,
+    input: Vec<u8>,
+}
+
+impl OutputBus for ReadBus<'_> {
+    type Word = u8;
+
+    fn set_value(&mut self, value: u8) -> Result<(), DisplayError> {
+        self.bus.set_value(value)
+    }
+}
+
+impl BidirectionalBus for ReadBus<'_> {
+    fn set_input(&mut self) -> Result<(), DisplayError> {
+        self.recorder.note("input", 0);
+        Ok(())
+    }
+
+    fn set_output(&mut self) -> Result<(), DisplayError> {
+        self.recorder.note("output", 0);
+        Ok(())
+    }
+
+    fn get_value(&mut self) -> Result<u8, DisplayError> {
+        if self.input.is_empty() {
+            return Err(DisplayError::BusReadError);
+        }
+        let value = self.input.remove(0);
+        self.recorder.note("get", value.into());
+        Ok(value)
+    }
+}
+
+fn interface<'a>(
+    recorder: &'a Recorder,
+    input: &[u8],
+) -> PGpio8BitReadWriteInterface<ReadBus<'a>, TestPin<'a>, TestPin<'a>, TestPin<'a>> {
+    let bus = ReadBus {
+        recorder,
+        bus: recorder.bus(),
+        input: input.to_vec(),
+    };
+    PGpio8BitReadWriteInterface::new(
+        bus,
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+        recorder.pin("rd"),
+    )
+}
+
+#[test]
+fn read_strobes_rd_on_inputs() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, &[0x00, 0x93, 0x41]);
+
+    let mut id = [0; 3];
+    iface.read_data(&mut id).unwrap();
+
+    assert_eq!(id, [0x00, 0x93, 0x41]);
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            INPUT,
+            Pin("rd", false),
+            Note("get", 0x00),
+            Pin("rd", true),
+            Pin("rd", false),
+            Note("get", 0x93),
+            Pin("rd", true),
+            Pin("rd", false),
+            Note("get", 0x41),
+            Pin("rd", true),
+            OUTPUT,
+        ]
+    );
+}
+
+#[test]
+fn writes_are_unchanged() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, &[]);
+
+    iface.send_commands(DataFormat::U8(&[0xd3])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", false),
+            Pin("wr", false),
+            Bus(0xd3),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn failed_read_restores_outputs() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, &[0x54]);
+
+    let mut buf = [0; 2];
+    assert!(matches!(
+        iface.read_data(&mut buf),
+        Err(DisplayError::BusReadError)
+    ));
+
+    assert_eq!(take(&recorder).last(), Some(&OUTPUT));
+}
+
+#[test]
+fn resync_restores_outputs_and_idle_levels() {
+    let recorder = Recorder::new();
+    let mut iface = interface(&recorder, &[]);
+
+    iface.resync().unwrap();
+    assert_eq!(
+        take(&recorder),
+        [Pin("rd", true), OUTPUT, Pin("dc", false), Pin("wr", true)]
+    );
+}
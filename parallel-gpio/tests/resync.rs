@@ -0,0 +1,204 @@
+//! Re-asserting the idle levels of the control pins
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DcPolarity, GenericBus, PGpio16BitInterface, PGpio24BitInterface,
+    PGpio4BitInterface, PGpio6800Interface, PGpio8BitInterface, PGpio8BitInterfaceWithCs,
+    PGpio9BitInterface, PGpioDualInterface, PGpioLatched16BitInterface, PinEvent, PinRecorder,
+    RecordedPin, StrobePolarity, Target, WriteOnlyDataCommand,
+};
+
+use PinEvent::Pin;
+
+use common::take;
+
+type Recorder = PinRecorder<64>;
+
+const DATA_PINS: [&str; 8] = ["d0", "d1", "d2", "d3", "d4", "d5", "d6", "d7"];
+
+/// Data pins of the wider buses, named apart from the control pins
+const BITS: [&str; 24] = [
+    "b0", "b1", "b2", "b3", "b4", "b5", "b6", "b7", "b8", "b9", "b10", "b11", "b12", "b13", "b14",
+    "b15", "b16", "b17", "b18", "b19", "b20", "b21", "b22", "b23",
+];
+
+fn bits<const N: usize>(recorder: &Recorder) -> [RecordedPin<'_, 64>; N] {
+    core::array::from_fn(|i| recorder.pin(BITS[i]))
+}
+
+/// Number of data pins driven since the last check
+fn driven(recorder: &Recorder) -> usize {
+    take(recorder)
+        .iter()
+        .filter(|event| matches!(event, Pin(name, _) if BITS.contains(name)))
+        .count()
+}
+
+/// Send a zero word twice, the second time after `resync`, and return the number of data pins
+/// driven the second time along with the events of `resync` itself
+fn resync_between<DI: WriteOnlyDataCommand>(
+    recorder: &Recorder,
+    iface: &mut DI,
+    resync: impl FnOnce(&mut DI),
+) -> (Vec<PinEvent>, usize) {
+    iface.send_commands(DataFormat::U8(&[0x00])).unwrap();
+    recorder.clear();
+    resync(iface);
+    let events = take(recorder);
+    iface.send_commands(DataFormat::U8(&[0x00])).unwrap();
+    (events, driven(recorder))
+}
+
+#[test]
+fn resync_drives_idle_levels_and_forgets_the_bus() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(DATA_PINS.map(|name| recorder.pin(name)));
+    let mut iface = PGpio8BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    iface.send_data(DataFormat::U8(&[0x00])).unwrap();
+    recorder.clear();
+    // Unchanged values are skipped by the bus
+    iface.send_data(DataFormat::U8(&[0x00])).unwrap();
+    assert_eq!(
+        take(&recorder),
+        [Pin("dc", true), Pin("wr", false), Pin("wr", true)]
+    );
+
+    iface.resync().unwrap();
+    assert_eq!(take(&recorder), [Pin("dc", false), Pin("wr", true)]);
+
+    // The bus no longer trusts its cache, so every pin is driven again
+    iface.send_data(DataFormat::U8(&[0x00])).unwrap();
+    let mut expected = vec![Pin("dc", true), Pin("wr", false)];
+    expected.extend(DATA_PINS.map(|name| Pin(name, false)));
+    expected.push(Pin("wr", true));
+    assert_eq!(take(&recorder), expected);
+}
+
+#[test]
+fn resync_follows_the_polarities() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_dc_polarity(DcPolarity::LowData)
+    .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    iface.resync().unwrap();
+    assert_eq!(take(&recorder), [Pin("dc", true), Pin("wr", false)]);
+}
+
+#[test]
+fn chip_select_is_deasserted_first() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterfaceWithCs::new(
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr")),
+        recorder.pin("cs"),
+    );
+
+    iface.resync().unwrap();
+    assert_eq!(
+        take(&recorder),
+        [Pin("cs", true), Pin("dc", false), Pin("wr", true)]
+    );
+}
+
+#[test]
+fn twenty_four_bit() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<24>(&recorder));
+    let mut iface = PGpio24BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(events, [Pin("dc", false), Pin("wr", true)]);
+    assert_eq!(driven, 24);
+}
+
+#[test]
+fn nine_bit() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<9>(&recorder));
+    let mut iface = PGpio9BitInterface::new(bus, recorder.pin("wr"))
+        .with_strobe_polarity(StrobePolarity::ActiveHigh);
+
+    // The D/C bit is part of the bus, so only the write-enable is released
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(events, [Pin("wr", false)]);
+    assert_eq!(driven, 9);
+}
+
+#[test]
+fn four_bit() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<4>(&recorder));
+    let mut iface = PGpio4BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    // Both nibbles of the zero byte are the same, so only the first one drives the pins
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(events, [Pin("dc", false), Pin("wr", true)]);
+    assert_eq!(driven, 4);
+}
+
+#[test]
+fn latched_sixteen_bit() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<8>(&recorder));
+    let mut iface = PGpioLatched16BitInterface::new(
+        bus,
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+        recorder.pin("le"),
+    );
+
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(
+        events,
+        [Pin("dc", false), Pin("wr", true), Pin("le", false)]
+    );
+    assert_eq!(driven, 8);
+}
+
+#[test]
+fn m6800() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<8>(&recorder));
+    let mut iface = PGpio6800Interface::new(
+        bus,
+        recorder.pin("dc"),
+        recorder.pin("e"),
+        recorder.pin("rw"),
+    )
+    .with_dc_polarity(DcPolarity::LowData);
+
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(events, [Pin("dc", true), Pin("rw", false), Pin("e", false)]);
+    assert_eq!(driven, 8);
+}
+
+#[test]
+fn dual_deselects_both_controllers() {
+    let recorder = Recorder::new();
+    let bus = GenericBus::new(bits::<8>(&recorder));
+    let mut iface = PGpioDualInterface::new(
+        PGpio8BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr")),
+        recorder.pin("cs1"),
+        recorder.pin("cs2"),
+    );
+    // Whatever the target, both chip selects are deasserted
+    iface.select(Target::First);
+
+    let (events, driven) = resync_between(&recorder, &mut iface, |di| di.resync().unwrap());
+    assert_eq!(
+        events,
+        [
+            Pin("cs1", true),
+            Pin("cs2", true),
+            Pin("dc", false),
+            Pin("wr", true)
+        ]
+    );
+    assert_eq!(driven, 8);
+}
@@ -0,0 +1,109 @@
+//! Runs of identical words on the generic buses only pulse the write-enable
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, Generic16BitBus, Generic8BitBus, PGpio16BitInterface, PGpio8BitInterface,
+    PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{count, take, Recorder, TestPin};
+
+const DATA_PINS: [&str; 16] = [
+    "d0", "d1", "d2", "d3", "d4", "d5", "d6", "d7", "d8", "d9", "d10", "d11", "d12", "d13", "d14",
+    "d15",
+];
+
+fn data_pins<const N: usize>(recorder: &Recorder) -> [TestPin<'_>; N] {
+    core::array::from_fn(|bit| recorder.pin(DATA_PINS[bit]))
+}
+
+fn pulses(recorder: &Recorder) -> usize {
+    count(recorder, "wr", true)
+}
+
+fn data_writes(recorder: &Recorder) -> usize {
+    recorder
+        .events()
+        .iter()
+        .filter(|e| matches!(e.event, Pin(name, _) if DATA_PINS.contains(&name)))
+        .count()
+}
+
+#[test]
+fn every_word_of_a_fill_is_strobed() {
+    let recorder = Recorder::new();
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = data_pins(&recorder);
+    let bus = Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7));
+    let mut iface = PGpio8BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    iface.send_data(DataFormat::U8(&[0x55; 100])).unwrap();
+    assert_eq!(pulses(&recorder), 100);
+    // The pins are only set for the first word
+    assert_eq!(data_writes(&recorder), 8);
+
+    recorder.clear();
+    iface
+        .send_data(DataFormat::U8Iter(&mut core::iter::repeat(0x55).take(50)))
+        .unwrap();
+    assert_eq!(pulses(&recorder), 50);
+    assert_eq!(data_writes(&recorder), 0);
+}
+
+#[test]
+fn runs_are_split_at_changes() {
+    let recorder = Recorder::new();
+    let [p0, p1, p2, p3, p4, p5, p6, p7] = data_pins(&recorder);
+    let bus = Generic8BitBus::new((p0, p1, p2, p3, p4, p5, p6, p7));
+    let mut iface = PGpio8BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    iface
+        .send_data(DataFormat::U8(&[0x01, 0x01, 0x03, 0x03, 0x03, 0x01]))
+        .unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Pin("d0", true),
+            Pin("d1", false),
+            Pin("d2", false),
+            Pin("d3", false),
+            Pin("d4", false),
+            Pin("d5", false),
+            Pin("d6", false),
+            Pin("d7", false),
+            Pin("wr", true),
+            Pin("wr", false),
+            Pin("wr", true),
+            Pin("wr", false),
+            Pin("d1", true),
+            Pin("wr", true),
+            Pin("wr", false),
+            Pin("wr", true),
+            Pin("wr", false),
+            Pin("wr", true),
+            Pin("wr", false),
+            Pin("d1", false),
+            Pin("wr", true),
+        ]
+    );
+}
+
+#[test]
+fn every_word_of_a_sixteen_bit_fill_is_strobed() {
+    let recorder = Recorder::new();
+    let [p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15] =
+        data_pins(&recorder);
+    let bus = Generic16BitBus::new((
+        p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15,
+    ));
+    let mut iface = PGpio16BitInterface::new(bus, recorder.pin("dc"), recorder.pin("wr"));
+
+    iface
+        .send_data(DataFormat::U16BE(&mut [0xf800; 64]))
+        .unwrap();
+    assert_eq!(pulses(&recorder), 64);
+    assert_eq!(data_writes(&recorder), 16);
+}
@@ -0,0 +1,200 @@
+//! Finding wiring faults with walking patterns
+
+use core::cell::Cell;
+
+use display_interface_parallel_gpio::{
+    loopback_echo, probe_lines, self_test, BidirectionalBus, DisplayError, OutputBus, PinEvent,
+    PinRecorder, SelfTestError, WiringFault,
+};
+
+/// Data lines with faults between the pins driving them and the pins reading them
+#[derive(Default)]
+struct Wiring {
+    driven: Cell<u16>,
+    stuck_low: u16,
+    stuck_high: u16,
+    /// Two lines swapped between the connector and the controller
+    swapped: Option<(u32, u32)>,
+    /// Two lines bridged, so either one driven low pulls both low
+    shorted: Option<(u32, u32)>,
+    reads: Cell<usize>,
+    fail: bool,
+}
+
+impl Wiring {
+    fn read_back(&self) -> u16 {
+        let mut value = self.driven.get();
+        if let Some((a, b)) = self.swapped {
+            let (bit_a, bit_b) = (value >> a & 1, value >> b & 1);
+            value &= !(1 << a | 1 << b);
+            value |= bit_a << b | bit_b << a;
+        }
+        if let Some((a, b)) = self.shorted {
+            if value >> a & value >> b & 1 == 0 {
+                value &= !(1 << a | 1 << b);
+            }
+        }
+        value & !self.stuck_low | self.stuck_high
+    }
+}
+
+/// The pins driving a [Wiring]
+struct Out<'a>(&'a Wiring);
+
+impl OutputBus for Out<'_> {
+    type Word = u16;
+
+    fn set_value(&mut self, value: u16) -> Result<(), DisplayError> {
+        self.0.driven.set(value);
+        Ok(())
+    }
+}
+
+/// The pins at the other end of the loopback jumpers of a [Wiring]
+struct Spare<'a>(&'a Wiring);
+
+impl OutputBus for Spare<'_> {
+    type Word = u16;
+
+    fn set_value(&mut self, _value: u16) -> Result<(), DisplayError> {
+        unreachable!("the spare pins are only read")
+    }
+}
+
+impl BidirectionalBus for Spare<'_> {
+    fn set_input(&mut self) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn set_output(&mut self) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn get_value(&mut self) -> Result<u16, DisplayError> {
+        if self.0.fail {
+            return Err(DisplayError::BusReadError);
+        }
+        self.0.reads.set(self.0.reads.get() + 1);
+        Ok(self.0.read_back())
+    }
+}
+
+/// Run the self test of `lines` lines over `wiring`, echoing through the jumpers
+fn check(wiring: &Wiring, lines: usize) -> Result<(), SelfTestError<u16>> {
+    self_test(lines, |word| {
+        loopback_echo(&mut Out(wiring), &mut Spare(wiring), word)
+    })
+}
+
+fn fault(result: Result<(), SelfTestError<u16>>) -> WiringFault<u16> {
+    match result {
+        Err(SelfTestError::Wiring(fault)) => fault,
+        other => panic!("expected a wiring fault, got {other:?}"),
+    }
+}
+
+#[test]
+fn correct_wiring_passes() {
+    let wiring = Wiring::default();
+    check(&wiring, 16).unwrap();
+    // A walking one and a walking zero for every line
+    assert_eq!(wiring.reads.get(), 32);
+
+    let wiring = Wiring::default();
+    check(&wiring, 9).unwrap();
+    assert_eq!(wiring.reads.get(), 18);
+}
+
+#[test]
+fn stuck_lines() {
+    let wiring = Wiring {
+        stuck_low: 0x0008,
+        stuck_high: 0x0100,
+        ..Wiring::default()
+    };
+    assert_eq!(
+        fault(check(&wiring, 16)),
+        WiringFault {
+            mismatched: 0x0108,
+            stuck_low: 0x0008,
+            stuck_high: 0x0100,
+        }
+    );
+}
+
+#[test]
+fn swapped_and_shorted_lines_are_mismatched_only() {
+    let wiring = Wiring {
+        swapped: Some((1, 2)),
+        ..Wiring::default()
+    };
+    assert_eq!(
+        fault(check(&wiring, 8)),
+        WiringFault {
+            mismatched: 0x06,
+            stuck_low: 0,
+            stuck_high: 0,
+        }
+    );
+
+    let wiring = Wiring {
+        shorted: Some((4, 5)),
+        ..Wiring::default()
+    };
+    assert_eq!(
+        fault(check(&wiring, 8)),
+        WiringFault {
+            mismatched: 0x30,
+            stuck_low: 0,
+            stuck_high: 0,
+        }
+    );
+}
+
+#[test]
+fn lines_beyond_the_count_are_ignored() {
+    let wiring = Wiring {
+        stuck_high: 0x0200,
+        ..Wiring::default()
+    };
+    check(&wiring, 9).unwrap();
+    assert!(matches!(
+        check(&wiring, 10),
+        Err(SelfTestError::Wiring(WiringFault {
+            stuck_high: 0x0200,
+            ..
+        }))
+    ));
+}
+
+#[test]
+fn read_errors_are_passed_on() {
+    let wiring = Wiring {
+        fail: true,
+        ..Wiring::default()
+    };
+    assert!(matches!(
+        check(&wiring, 8),
+        Err(SelfTestError::Bus(DisplayError::BusReadError))
+    ));
+}
+
+#[test]
+fn probing_walks_a_one_across_the_lines() {
+    let recorder = PinRecorder::<16>::new();
+    probe_lines(&mut recorder.bus::<u8>(), 4, &mut recorder.delay(), 500).unwrap();
+
+    let events = recorder.events();
+    let walk: Vec<_> = events.iter().map(|e| (e.event, e.at_ns)).collect();
+    assert_eq!(
+        walk,
+        [
+            (PinEvent::Bus(0x00), 0),
+            (PinEvent::Bus(0x01), 500_000_000),
+            (PinEvent::Bus(0x02), 1_000_000_000),
+            (PinEvent::Bus(0x04), 1_500_000_000),
+            (PinEvent::Bus(0x08), 2_000_000_000),
+            (PinEvent::Bus(0x00), 2_500_000_000),
+        ]
+    );
+}
@@ -0,0 +1,100 @@
+//! SPI transactions and latch pulses of the shift register buses, checked with `embedded-hal-mock`
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpio16BitInterface, PGpio8BitInterface, ShiftRegister16Bus, ShiftRegisterBus,
+    WriteOnlyDataCommand,
+};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+fn shift(bytes: Vec<u8>) -> [SpiTransaction<u8>; 3] {
+    [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(bytes),
+        SpiTransaction::transaction_end(),
+    ]
+}
+
+fn pulses(n: usize) -> Vec<PinTransaction> {
+    (0..n)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::Low),
+                PinTransaction::set(State::High),
+            ]
+        })
+        .collect()
+}
+
+fn latch_pulses(n: usize) -> Vec<PinTransaction> {
+    (0..n)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::High),
+                PinTransaction::set(State::Low),
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn one_transaction_and_latch_per_word() {
+    let spi = SpiMock::new(&[shift(vec![0x2a]), shift(vec![0x00]), shift(vec![0x10])].concat());
+    let latch = PinMock::new(&latch_pulses(3));
+    let dc = PinMock::new(&[PinTransaction::set(State::Low)]);
+    let wr = PinMock::new(&pulses(3));
+
+    let mut iface = PGpio8BitInterface::new(ShiftRegisterBus::new(spi, latch), dc, wr);
+    iface
+        .send_commands(DataFormat::U8(&[0x2a, 0x00, 0x10]))
+        .unwrap();
+
+    let (bus, mut dc, mut wr) = iface.release();
+    let (mut spi, mut latch) = bus.release();
+    spi.done();
+    latch.done();
+    dc.done();
+    wr.done();
+}
+
+#[test]
+fn repeated_words_are_not_shifted_again() {
+    let spi = SpiMock::new(&[shift(vec![0xff]), shift(vec![0x00])].concat());
+    let latch = PinMock::new(&latch_pulses(2));
+    let dc = PinMock::new(&[PinTransaction::set(State::High)]);
+    let wr = PinMock::new(&pulses(5));
+
+    let mut iface = PGpio8BitInterface::new(ShiftRegisterBus::new(spi, latch), dc, wr);
+    iface
+        .send_data(DataFormat::U8(&[0xff, 0xff, 0xff, 0x00, 0x00]))
+        .unwrap();
+
+    let (bus, mut dc, mut wr) = iface.release();
+    let (mut spi, mut latch) = bus.release();
+    spi.done();
+    latch.done();
+    dc.done();
+    wr.done();
+}
+
+#[test]
+fn chained_registers_shift_high_byte_first() {
+    let spi = SpiMock::new(&[shift(vec![0xf8, 0x00]), shift(vec![0x07, 0xe0])].concat());
+    let latch = PinMock::new(&latch_pulses(2));
+    let dc = PinMock::new(&[PinTransaction::set(State::High)]);
+    let wr = PinMock::new(&pulses(2));
+
+    let mut iface = PGpio16BitInterface::new(ShiftRegister16Bus::new(spi, latch), dc, wr);
+    iface
+        .send_data(DataFormat::U16LE(&mut [0xf800, 0x07e0]))
+        .unwrap();
+
+    let (bus, mut dc, mut wr) = iface.release();
+    let (mut spi, mut latch) = bus.release();
+    spi.done();
+    latch.done();
+    dc.done();
+    wr.done();
+}
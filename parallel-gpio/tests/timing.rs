@@ -0,0 +1,75 @@
+//! Write-enable stretching of the 8- and 16-bit interfaces
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, PGpio16BitInterface, PGpio8BitInterface, PinEvent::*, WriteOnlyDataCommand,
+};
+
+use common::{take, take_timed, Recorder};
+
+#[test]
+fn untimed_writes_do_not_wait() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"));
+
+    iface.send_data(DataFormat::U8(&[0x12])).unwrap();
+
+    assert_eq!(
+        take(&recorder),
+        [
+            Pin("dc", true),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true)
+        ]
+    );
+    assert_eq!(recorder.now_ns(), 0);
+}
+
+#[test]
+fn longer_wait_before_releasing_wr() {
+    let recorder = Recorder::new();
+    let mut iface =
+        PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), recorder.pin("wr"))
+            .with_timing(recorder.delay(), 50, 120);
+
+    iface.send_commands(DataFormat::U8(&[0x2c, 0x00])).unwrap();
+
+    assert_eq!(
+        take_timed(&recorder),
+        [
+            (0, Pin("dc", false)),
+            (0, Pin("wr", false)),
+            (0, Bus(0x2c)),
+            (120, Pin("wr", true)),
+            (120, Pin("wr", false)),
+            (120, Bus(0x00)),
+            (240, Pin("wr", true)),
+        ]
+    );
+}
+
+#[test]
+fn sixteen_bit_timing() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(
+        recorder.bus::<u16>(),
+        recorder.pin("dc"),
+        recorder.pin("wr"),
+    )
+    .with_timing(recorder.delay(), 66, 10);
+
+    iface.send_data(DataFormat::U16(&[0xf800])).unwrap();
+
+    assert_eq!(
+        take_timed(&recorder),
+        [
+            (0, Pin("dc", true)),
+            (0, Pin("wr", false)),
+            (0, Bus(0xf800)),
+            (66, Pin("wr", true))
+        ]
+    );
+}
@@ -0,0 +1,80 @@
+//! Non-blocking transfers in chunks
+
+#![cfg(feature = "nb")]
+
+mod common;
+
+use display_interface::TryWriteOnlyDataCommand;
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, PGpio16BitInterface, PGpio8BitInterface, PinEvent, U8Packing,
+};
+
+use common::{bus_values, NoPin, Recorder};
+
+/// Call `f` until it no longer blocks, returning the result and the number of calls
+fn poll(mut f: impl FnMut() -> nb::Result<(), DisplayError>) -> (Result<(), DisplayError>, usize) {
+    let mut calls = 1;
+    loop {
+        match f() {
+            Err(nb::Error::WouldBlock) => calls += 1,
+            Err(nb::Error::Other(e)) => return (Err(e), calls),
+            Ok(()) => return (Ok(()), calls),
+        }
+    }
+}
+
+#[test]
+fn eight_bit_writes_64_bytes_per_call() {
+    let data: Vec<u8> = (0..=255).collect();
+    let recorder = Recorder::new();
+    let mut iface = PGpio8BitInterface::new(recorder.bus::<u8>(), recorder.pin("dc"), NoPin);
+
+    assert!(iface.try_send_commands(DataFormat::U8(&[0x2c])).is_ok());
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U8(&data)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 4);
+
+    let mut pixels = [0x1234u16; 33];
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U16LE(&mut pixels)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 2);
+
+    let bus: Vec<u32> = bus_values(&recorder);
+    let data: Vec<u32> = data.into_iter().map(u32::from).collect();
+    assert_eq!(bus[..257], [[0x2c].as_slice(), &data].concat());
+    assert_eq!(bus[257..259], [0x34, 0x12]);
+    assert_eq!(bus.len(), 257 + 66);
+    // Set once per transfer
+    let dc: Vec<_> = recorder
+        .events()
+        .iter()
+        .filter_map(|e| match e.event {
+            PinEvent::Pin("dc", level) => Some(level),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(dc, [false, true, true]);
+}
+
+#[test]
+fn sixteen_bit_packing_checks_the_whole_slice() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio16BitInterface::new(recorder.bus::<u16>(), NoPin, NoPin)
+        .with_u8_packing(U8Packing::BigEndian);
+
+    assert!(matches!(
+        iface.try_send_data(DataFormat::U8(&[0; 129])),
+        Err(nb::Error::Other(DisplayError::InvalidFormatError))
+    ));
+
+    let data: Vec<u8> = (0..130).collect();
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U8(&data)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 3);
+
+    let expected: Vec<u32> = data
+        .chunks(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]).into())
+        .collect();
+    assert_eq!(bus_values(&recorder), expected);
+}
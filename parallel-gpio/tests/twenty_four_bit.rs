@@ -0,0 +1,64 @@
+//! The 24-bit bus and interface
+
+mod common;
+
+use std::rc::Rc;
+
+use display_interface_parallel_gpio::{
+    rgb565_to_rgb888, DataFormat, Generic24BitBus, OutputBus, PGpio24BitInterface,
+    WriteOnlyDataCommand,
+};
+
+use common::{bus_values, NoPin, Port, Recorder};
+
+#[test]
+fn bus_maps_bits_to_pins() {
+    let port = Rc::new(Port::default());
+    let [p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15, p16, p17, p18, p19, p20, p21, p22, p23] =
+        port.pins();
+    let mut bus = Generic24BitBus::new((
+        p0, p1, p2, p3, p4, p5, p6, p7, p8, p9, p10, p11, p12, p13, p14, p15, p16, p17, p18, p19,
+        p20, p21, p22, p23,
+    ));
+
+    for color in [0xff0000, 0x00ff00, 0x0000ff, 0xffffff, 0x123456, 0x000000] {
+        bus.set_value(color).unwrap();
+        assert_eq!(port.levels(), color);
+    }
+}
+
+#[test]
+fn rgb565_expansion_replicates_top_bits() {
+    assert_eq!(rgb565_to_rgb888(0x0000), 0x000000);
+    assert_eq!(rgb565_to_rgb888(0xffff), 0xffffff);
+    assert_eq!(rgb565_to_rgb888(0xf800), 0xff0000);
+    assert_eq!(rgb565_to_rgb888(0x07e0), 0x00ff00);
+    assert_eq!(rgb565_to_rgb888(0x001f), 0x0000ff);
+    assert_eq!(rgb565_to_rgb888(0x8410), 0x848284);
+    assert_eq!(rgb565_to_rgb888(0x0821), 0x080408);
+}
+
+#[test]
+fn formats_map_to_words() {
+    let recorder = Recorder::new();
+    let mut iface = PGpio24BitInterface::new(recorder.bus::<u32>(), NoPin, NoPin);
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface.send_commands(DataFormat::U16(&[0x2c00])).unwrap();
+    iface
+        .send_data(DataFormat::U24(&[0xff12_3456, 0x00ab_cdef]))
+        .unwrap();
+    iface
+        .send_data(DataFormat::U24Iter(&mut [0x00ff_0000].into_iter()))
+        .unwrap();
+    iface.send_data(DataFormat::U16BE(&mut [0xf800])).unwrap();
+    iface
+        .send_data(DataFormat::U16LEIter(&mut [0x07e0].into_iter()))
+        .unwrap();
+    iface.send_data(DataFormat::U8(&[0x80])).unwrap();
+
+    assert_eq!(
+        bus_values(&recorder),
+        [0x2c, 0x2c00, 0x123456, 0xabcdef, 0xff0000, 0xff0000, 0x00ff00, 0x80]
+    );
+}
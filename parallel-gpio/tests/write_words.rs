@@ -0,0 +1,152 @@
+//! Overridden batch writes put the same sequence on the pins as the default implementation
+
+mod common;
+
+use display_interface_parallel_gpio::{
+    DataFormat, DisplayError, OutputBus, PGpio16BitInterface, PGpio8BitInterface, PinEvent,
+    PinEvent::*, WriteOnlyDataCommand, WriteStrobe,
+};
+
+use common::{take, Recorder, TestBus};
+
+/// Bus overriding both batch writes, noting every batch and every iterator
+struct BatchingBus<'a, W>(&'a Recorder, TestBus<'a, W>);
+
+impl<W: Copy + Into<u32>> OutputBus for BatchingBus<'_, W> {
+    type Word = W;
+
+    fn set_value(&mut self, value: W) -> Result<(), DisplayError> {
+        self.1.set_value(value)
+    }
+
+    fn write_words(
+        &mut self,
+        words: &[W],
+        strobe: &mut impl WriteStrobe,
+    ) -> Result<(), DisplayError> {
+        self.0.note("batch", words.len() as u32);
+        for word in words {
+            strobe.assert()?;
+            self.1.set_value(*word)?;
+            strobe.release()?;
+        }
+        Ok(())
+    }
+
+    fn write_words_iter(
+        &mut self,
+        words: impl Iterator<Item = W>,
+        strobe: &mut impl WriteStrobe,
+    ) -> Result<(), DisplayError> {
+        self.0.note("iter", 0);
+        for word in words {
+            strobe.assert()?;
+            self.1.set_value(word)?;
+            strobe.release()?;
+        }
+        Ok(())
+    }
+}
+
+fn send8<'a, BUS: OutputBus<Word = u8>>(
+    recorder: &'a Recorder,
+    make: fn(&'a Recorder) -> BUS,
+    format: DataFormat<'_>,
+) -> Vec<PinEvent> {
+    let mut iface = PGpio8BitInterface::new(make(recorder), recorder.pin("dc"), recorder.pin("wr"));
+    iface.send_data(format).unwrap();
+    take(recorder)
+}
+
+fn send16<'a, BUS: OutputBus<Word = u16>>(
+    recorder: &'a Recorder,
+    make: fn(&'a Recorder) -> BUS,
+    format: DataFormat<'_>,
+) -> Vec<PinEvent> {
+    let mut iface =
+        PGpio16BitInterface::new(make(recorder), recorder.pin("dc"), recorder.pin("wr"));
+    iface.send_data(format).unwrap();
+    take(recorder)
+}
+
+fn without_notes(events: Vec<PinEvent>) -> Vec<PinEvent> {
+    events
+        .into_iter()
+        .filter(|e| !matches!(e, Note(..)))
+        .collect()
+}
+
+#[test]
+fn u8_slices_are_batched_identically() {
+    let data = [0x12, 0x34, 0x34, 0x56];
+    let recorder = Recorder::new();
+
+    let default = send8(&recorder, |r| r.bus(), DataFormat::U8(&data));
+    let batched = send8(
+        &recorder,
+        |r| BatchingBus(r, r.bus()),
+        DataFormat::U8(&data),
+    );
+
+    assert_eq!(batched[1], Note("batch", 4));
+    assert_eq!(without_notes(batched), default);
+}
+
+#[test]
+fn u16_slices_are_batched_identically() {
+    let data = [0xf800, 0x07e0, 0x001f];
+    let recorder = Recorder::new();
+
+    let default = send16(&recorder, |r| r.bus(), DataFormat::U16(&data));
+    let batched = send16(
+        &recorder,
+        |r| BatchingBus(r, r.bus()),
+        DataFormat::U16(&data),
+    );
+
+    assert_eq!(batched[1], Note("batch", 3));
+    assert_eq!(without_notes(batched), default);
+}
+
+#[test]
+fn iterators_use_the_iterator_path() {
+    let recorder = Recorder::new();
+    let batched = send8(
+        &recorder,
+        |r| BatchingBus(r, r.bus()),
+        DataFormat::U8Iter(&mut [0x12, 0x34].into_iter()),
+    );
+
+    assert_eq!(
+        batched,
+        [
+            Pin("dc", true),
+            Note("iter", 0),
+            Pin("wr", false),
+            Bus(0x12),
+            Pin("wr", true),
+            Pin("wr", false),
+            Bus(0x34),
+            Pin("wr", true)
+        ]
+    );
+}
+
+#[test]
+fn u16_words_on_the_8_bit_bus_use_the_iterator_path() {
+    let recorder = Recorder::new();
+
+    let default = send8(
+        &recorder,
+        |r| r.bus(),
+        DataFormat::U16BE(&mut [0x1234, 0x3456]),
+    );
+    let batched = send8(
+        &recorder,
+        |r| BatchingBus(r, r.bus()),
+        DataFormat::U16BE(&mut [0x1234, 0x3456]),
+    );
+
+    assert_eq!(batched[1], Note("iter", 0));
+    assert_eq!(without_notes(batched), default);
+}
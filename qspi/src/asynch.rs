@@ -0,0 +1,49 @@
+//! Generic asynchronous quad SPI interface for display drivers
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+
+use crate::{transfer::asynch as transfer, Lanes, QspiInterface};
+
+type Result = core::result::Result<(), DisplayError>;
+
+/// An asynchronous QSPI peripheral which can perform write transfers
+///
+/// See [QspiWrite](crate::QspiWrite) for the meaning of the parameters.
+pub trait AsyncQspiWrite {
+    /// Error type of the peripheral
+    type Error;
+
+    /// Perform one write transfer
+    async fn write(
+        &mut self,
+        opcode: u8,
+        address: u32,
+        data: &[u8],
+        data_lanes: Lanes,
+    ) -> core::result::Result<(), Self::Error>;
+}
+
+impl<QSPI> QspiInterface<QSPI>
+where
+    QSPI: AsyncQspiWrite,
+{
+    /// Send the command `cmd` together with its parameters `params`
+    ///
+    /// See [QspiInterface::send_command].
+    pub async fn send_command_async(&mut self, cmd: u8, params: &[u8]) -> Result {
+        transfer::send_command(self, cmd, params).await
+    }
+}
+
+impl<QSPI> AsyncWriteOnlyDataCommand for QspiInterface<QSPI>
+where
+    QSPI: AsyncQspiWrite,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        transfer::send_commands(self, cmds).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        transfer::send_data(self, buf).await
+    }
+}
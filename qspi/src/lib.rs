@@ -0,0 +1,125 @@
+//! Generic quad SPI interface for display drivers
+//!
+//! Controllers like the RM67162 AMOLED driver are accessed over QSPI with a fixed framing: every
+//! transfer starts with an 8-bit opcode and a 24-bit address sent over a single lane, where the
+//! middle address byte carries the MIPI DCS command. Command parameters follow on a single lane,
+//! while pixel data is sent with a different opcode over all four lanes.
+
+#![no_std]
+#![allow(async_fn_in_trait)]
+
+mod asynch;
+mod transfer;
+
+pub use asynch::AsyncQspiWrite;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+type Result = core::result::Result<(), DisplayError>;
+
+pub(crate) const BUFFER_SIZE: usize = 64;
+
+/// Opcode used for command transfers with single lane parameters
+pub const OPCODE_WRITE_COMMAND: u8 = 0x02;
+
+/// Opcode used for pixel transfers over four lanes
+pub const OPCODE_WRITE_PIXELS: u8 = 0x32;
+
+/// MIPI DCS "memory write" command starting a new pixel transfer
+pub(crate) const RAMWR: u8 = 0x2C;
+
+/// MIPI DCS "memory write continue" command continuing a pixel transfer
+pub(crate) const RAMWRC: u8 = 0x3C;
+
+/// Number of lanes used for the data phase of a QSPI transfer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lanes {
+    /// Data is sent over one lane, like regular SPI
+    Single,
+    /// Data is sent over two lanes
+    Dual,
+    /// Data is sent over four lanes
+    Quad,
+}
+
+/// A QSPI peripheral which can perform write transfers
+///
+/// This is the trait HALs implement for their QSPI peripherals. Chip select is expected to be
+/// asserted for the duration of each call.
+pub trait QspiWrite {
+    /// Error type of the peripheral
+    type Error;
+
+    /// Perform one write transfer
+    ///
+    /// The `opcode` and the 24-bit `address` are always sent over a single lane, the `data` is
+    /// sent over the requested number of `data_lanes`.
+    fn write(
+        &mut self,
+        opcode: u8,
+        address: u32,
+        data: &[u8],
+        data_lanes: Lanes,
+    ) -> core::result::Result<(), Self::Error>;
+}
+
+/// QSPI display interface.
+///
+/// Every command goes out in a single transfer together with its parameters, so they have to be
+/// passed to `send_commands` in one call, the command followed by its parameters, or to
+/// [send_command](Self::send_command). Commands are sent right away, so a driver waiting after
+/// e.g. sleep out waits after the command went out.
+///
+/// A memory write command (0x2C or 0x3C) starts a pixel transfer instead: the following
+/// `send_data` calls send pixel data over four lanes, the first transfer carrying the memory
+/// write command, the following ones 0x3C. A memory write without pixels has no effect, so
+/// nothing is sent for it. `send_data` outside a memory write fails with an
+/// [InvalidFormatError](DisplayError::InvalidFormatError), as parameters can't be sent without
+/// their command.
+///
+/// Commands and parameters from iterators are staged in a buffer of 64 bytes, longer ones fail
+/// with a [CapacityExceeded](DisplayError::CapacityExceeded) error.
+pub struct QspiInterface<QSPI> {
+    qspi: QSPI,
+    /// Command of the next pixel transfer within a memory write
+    pixel_command: Option<u8>,
+}
+
+impl<QSPI> QspiInterface<QSPI> {
+    /// Create new QSPI interface for communication with a display driver
+    pub fn new(qspi: QSPI) -> Self {
+        Self {
+            qspi,
+            pixel_command: None,
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver
+    pub fn release(self) -> QSPI {
+        self.qspi
+    }
+}
+
+impl<QSPI> QspiInterface<QSPI>
+where
+    QSPI: QspiWrite,
+{
+    /// Send the command `cmd` together with its parameters `params`
+    pub fn send_command(&mut self, cmd: u8, params: &[u8]) -> Result {
+        transfer::blocking::send_command(self, cmd, params)
+    }
+}
+
+impl<QSPI> WriteOnlyDataCommand for QspiInterface<QSPI>
+where
+    QSPI: QspiWrite,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        transfer::blocking::send_commands(self, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        transfer::blocking::send_data(self, buf)
+    }
+}
@@ -0,0 +1,164 @@
+//! Bus transfers shared by the blocking and `async` implementations
+//!
+//! The functions are written once and expanded into the `blocking` and `asynch` variants, so a
+//! fix to the framing or chunking always applies to both.
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    use byte_slice_cast::*;
+    use display_interface::{DataFormat, DisplayError};
+
+    #[maybe_async_cfg::only_if(async)]
+    use crate::AsyncQspiWrite as Qspi;
+    #[maybe_async_cfg::only_if(sync)]
+    use crate::QspiWrite as Qspi;
+    use crate::{
+        Lanes, QspiInterface, BUFFER_SIZE, OPCODE_WRITE_COMMAND, OPCODE_WRITE_PIXELS, RAMWR, RAMWRC,
+    };
+
+    type Result = core::result::Result<(), DisplayError>;
+
+    pub(crate) async fn send_commands<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        cmds: DataFormat<'_>,
+    ) -> Result {
+        let mut buf = [0; BUFFER_SIZE];
+        let bytes = match cmds {
+            DataFormat::U8(slice) => slice,
+            DataFormat::U8Iter(iter) => stage(&mut buf, iter)?,
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        };
+        match bytes.split_first() {
+            Some((&cmd, params)) => send_command(iface, cmd, params).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Send `cmd` with `params` right away, or start a memory write with it
+    pub(crate) async fn send_command<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        cmd: u8,
+        params: &[u8],
+    ) -> Result {
+        if cmd == RAMWR || cmd == RAMWRC {
+            // The memory write command heads the transfer of the first pixels
+            iface.pixel_command = Some(cmd);
+            write_pixel_chunk(iface, params).await
+        } else {
+            iface.pixel_command = None;
+            write_command(&mut iface.qspi, cmd, params).await
+        }
+    }
+
+    pub(crate) async fn send_data<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        buf: DataFormat<'_>,
+    ) -> Result {
+        if iface.pixel_command.is_none() {
+            // Parameters can only be sent in the transfer of their command
+            return Err(DisplayError::InvalidFormatError);
+        }
+        write_pixels(iface, buf).await
+    }
+
+    /// Copy the bytes of `iter` into `buf`, failing if they don't fit
+    fn stage<'b>(
+        buf: &'b mut [u8; BUFFER_SIZE],
+        iter: &mut dyn Iterator<Item = u8>,
+    ) -> core::result::Result<&'b [u8], DisplayError> {
+        let mut len = 0;
+        for byte in iter {
+            *buf.get_mut(len).ok_or(DisplayError::CapacityExceeded)? = byte;
+            len += 1;
+        }
+        Ok(&buf[..len])
+    }
+
+    /// Send `cmd` with `params` over a single lane, the command in the middle address byte
+    async fn write_command<QSPI: Qspi>(qspi: &mut QSPI, cmd: u8, params: &[u8]) -> Result {
+        qspi.write(
+            OPCODE_WRITE_COMMAND,
+            u32::from(cmd) << 8,
+            params,
+            Lanes::Single,
+        )
+        .await
+        .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Send one chunk of pixels, the first one with the memory write command
+    async fn write_pixel_chunk<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        bytes: &[u8],
+    ) -> Result {
+        let Some(cmd) = iface.pixel_command else {
+            return Err(DisplayError::InvalidFormatError);
+        };
+        if bytes.is_empty() {
+            return Ok(());
+        }
+
+        iface
+            .qspi
+            .write(OPCODE_WRITE_PIXELS, u32::from(cmd) << 8, bytes, Lanes::Quad)
+            .await
+            .map_err(|_| DisplayError::BusWriteError)?;
+        // Every chunk but the first continues where the previous one left off
+        iface.pixel_command = Some(RAMWRC);
+        Ok(())
+    }
+
+    /// Send the bytes of `bytes` as pixels, in chunks of [BUFFER_SIZE] bytes
+    async fn write_pixel_bytes<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        bytes: impl Iterator<Item = u8>,
+    ) -> Result {
+        let mut buf = [0; BUFFER_SIZE];
+        let mut i = 0;
+
+        for byte in bytes {
+            buf[i] = byte;
+            i += 1;
+
+            if i == buf.len() {
+                write_pixel_chunk(iface, &buf).await?;
+                i = 0;
+            }
+        }
+
+        write_pixel_chunk(iface, &buf[..i]).await
+    }
+
+    async fn write_pixels<QSPI: Qspi>(
+        iface: &mut QspiInterface<QSPI>,
+        data: DataFormat<'_>,
+    ) -> Result {
+        match data {
+            DataFormat::U8(slice) => write_pixel_chunk(iface, slice).await,
+            DataFormat::U16(slice) => write_pixel_chunk(iface, slice.as_byte_slice()).await,
+            DataFormat::U16LE(slice) => {
+                for v in slice.iter_mut() {
+                    *v = v.to_le();
+                }
+                write_pixel_chunk(iface, slice.as_byte_slice()).await
+            }
+            DataFormat::U16BE(slice) => {
+                for v in slice.iter_mut() {
+                    *v = v.to_be();
+                }
+                write_pixel_chunk(iface, slice.as_byte_slice()).await
+            }
+            DataFormat::U8Iter(iter) => write_pixel_bytes(iface, iter).await,
+            DataFormat::U16LEIter(iter) => {
+                write_pixel_bytes(iface, iter.flat_map(u16::to_le_bytes)).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                write_pixel_bytes(iface, iter.flat_map(u16::to_be_bytes)).await
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
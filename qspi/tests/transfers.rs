@@ -0,0 +1,199 @@
+//! Opcode, address and lanes of the transfers for commands, parameters and pixels
+
+mod common;
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_qspi::{
+    AsyncQspiWrite, Lanes, QspiInterface, QspiWrite, OPCODE_WRITE_COMMAND, OPCODE_WRITE_PIXELS,
+};
+
+use common::block_on;
+
+/// One transfer as seen by the peripheral
+#[derive(Debug, PartialEq)]
+struct Transfer {
+    opcode: u8,
+    address: u32,
+    data: Vec<u8>,
+    lanes: Lanes,
+}
+
+fn command(cmd: u8, params: &[u8]) -> Transfer {
+    Transfer {
+        opcode: OPCODE_WRITE_COMMAND,
+        address: u32::from(cmd) << 8,
+        data: params.to_vec(),
+        lanes: Lanes::Single,
+    }
+}
+
+fn pixels(cmd: u8, data: &[u8]) -> Transfer {
+    Transfer {
+        opcode: OPCODE_WRITE_PIXELS,
+        address: u32::from(cmd) << 8,
+        data: data.to_vec(),
+        lanes: Lanes::Quad,
+    }
+}
+
+/// Peripheral keeping every transfer
+#[derive(Default)]
+struct Qspi(Vec<Transfer>);
+
+impl QspiWrite for Qspi {
+    type Error = ();
+
+    fn write(&mut self, opcode: u8, address: u32, data: &[u8], lanes: Lanes) -> Result<(), ()> {
+        self.0.push(Transfer {
+            opcode,
+            address,
+            data: data.to_vec(),
+            lanes,
+        });
+        Ok(())
+    }
+}
+
+impl AsyncQspiWrite for Qspi {
+    type Error = ();
+
+    async fn write(
+        &mut self,
+        opcode: u8,
+        address: u32,
+        data: &[u8],
+        lanes: Lanes,
+    ) -> Result<(), ()> {
+        QspiWrite::write(self, opcode, address, data, lanes)
+    }
+}
+
+/// Calls of a typical driver: window, memory write and display on
+fn draw(di: &mut impl WriteOnlyDataCommand) -> Result<(), DisplayError> {
+    di.send_commands(DataFormat::U8(&[0x2a, 0x00, 0x00, 0x00, 0xef]))?;
+    di.send_commands(DataFormat::U8Iter(
+        &mut [0x2b, 0x00, 0x10, 0x00, 0x1f].into_iter(),
+    ))?;
+    di.send_commands(DataFormat::U8(&[0x2c]))?;
+    di.send_data(DataFormat::U16BE(&mut [0x1234, 0x5678]))?;
+    di.send_data(DataFormat::U16LEIter(&mut (0..40)))?;
+    di.send_commands(DataFormat::U8(&[0x29]))
+}
+
+async fn draw_async(
+    di: &mut impl display_interface::AsyncWriteOnlyDataCommand,
+) -> Result<(), DisplayError> {
+    di.send_commands(DataFormat::U8(&[0x2a, 0x00, 0x00, 0x00, 0xef]))
+        .await?;
+    di.send_commands(DataFormat::U8Iter(
+        &mut [0x2b, 0x00, 0x10, 0x00, 0x1f].into_iter(),
+    ))
+    .await?;
+    di.send_commands(DataFormat::U8(&[0x2c])).await?;
+    di.send_data(DataFormat::U16BE(&mut [0x1234, 0x5678]))
+        .await?;
+    di.send_data(DataFormat::U16LEIter(&mut (0..40))).await?;
+    di.send_commands(DataFormat::U8(&[0x29])).await
+}
+
+fn expected_draw() -> Vec<Transfer> {
+    let words: Vec<u8> = (0..40u16).flat_map(u16::to_le_bytes).collect();
+    vec![
+        command(0x2a, &[0x00, 0x00, 0x00, 0xef]),
+        command(0x2b, &[0x00, 0x10, 0x00, 0x1f]),
+        pixels(0x2c, &[0x12, 0x34, 0x56, 0x78]),
+        // Iterators are staged in chunks of 64 bytes
+        pixels(0x3c, &words[..64]),
+        pixels(0x3c, &words[64..]),
+        command(0x29, &[]),
+    ]
+}
+
+#[test]
+fn every_command_is_sent_once() {
+    let mut di = QspiInterface::new(Qspi::default());
+    draw(&mut di).unwrap();
+    assert_eq!(di.release().0, expected_draw());
+
+    let mut di = QspiInterface::new(Qspi::default());
+    block_on(draw_async(&mut di)).unwrap();
+    assert_eq!(di.release().0, expected_draw());
+}
+
+#[test]
+fn commands_are_sent_right_away() {
+    let mut di = QspiInterface::new(Qspi::default());
+    di.send_commands(DataFormat::U8(&[0x36, 0x48])).unwrap();
+    // Sleep out has to go out before the driver waits
+    di.send_commands(DataFormat::U8(&[0x11])).unwrap();
+    assert_eq!(di.release().0, [command(0x36, &[0x48]), command(0x11, &[])]);
+
+    let mut di = QspiInterface::new(Qspi::default());
+    di.send_commands(DataFormat::U8(&[])).unwrap();
+    di.send_command(0x3a, &[0x55]).unwrap();
+    block_on(di.send_command_async(0x29, &[])).unwrap();
+    assert_eq!(di.release().0, [command(0x3a, &[0x55]), command(0x29, &[])]);
+}
+
+#[test]
+fn parameters_need_a_command() {
+    let mut di = QspiInterface::new(Qspi::default());
+    assert!(matches!(
+        di.send_data(DataFormat::U8(&[0x00])),
+        Err(DisplayError::InvalidFormatError)
+    ));
+
+    // Parameters sent apart from their command can't be framed
+    di.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    assert!(matches!(
+        di.send_data(DataFormat::U8(&[0x00, 0x01])),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    assert!(matches!(
+        di.send_data(DataFormat::U8Iter(&mut [0; 4].into_iter())),
+        Err(DisplayError::InvalidFormatError)
+    ));
+
+    // A command ends the memory write
+    di.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    di.send_data(DataFormat::U8(&[0xff; 2])).unwrap();
+    di.send_commands(DataFormat::U8(&[0x29])).unwrap();
+    assert!(matches!(
+        di.send_data(DataFormat::U8(&[0xff; 2])),
+        Err(DisplayError::InvalidFormatError)
+    ));
+
+    assert_eq!(
+        di.release().0,
+        [
+            command(0x2a, &[]),
+            pixels(0x2c, &[0xff; 2]),
+            command(0x29, &[])
+        ]
+    );
+}
+
+#[test]
+fn iterators_beyond_the_staging_buffer_are_rejected() {
+    let mut di = QspiInterface::new(Qspi::default());
+    assert!(matches!(
+        di.send_commands(DataFormat::U8Iter(&mut (0..=64).map(|b| b as u8))),
+        Err(DisplayError::CapacityExceeded)
+    ));
+    assert!(di.release().0.is_empty());
+}
+
+#[test]
+fn memory_write_continue_resumes_the_pixels() {
+    let mut di = QspiInterface::new(Qspi::default());
+    di.send_commands(DataFormat::U8(&[0x3c])).unwrap();
+    di.send_data(DataFormat::U8(&[0xff; 4])).unwrap();
+    // An empty memory write is dropped
+    di.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    di.send_commands(DataFormat::U8(&[0x29])).unwrap();
+
+    assert_eq!(
+        di.release().0,
+        [pixels(0x3c, &[0xff; 4]), command(0x29, &[])]
+    );
+}
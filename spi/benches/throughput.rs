@@ -0,0 +1,199 @@
+//! Bytes per second through `SpiInterface` for every supported `DataFormat`
+//!
+//! The interface writes to a `NullSpiDevice`, so this measures the overhead of the interface
+//! itself: byte swapping, serializing iterators and splitting transfers. Run with
+//! `cargo bench --bench throughput`; add `--features static-buffer` to also sweep the size of the
+//! staging buffer used for iterator formats.
+//!
+//! The `spi-async` group measures how well the `async` implementation overlaps generating pixels
+//! with writing them, using a device which takes as long as a 40 MHz bus would for every write
+//! without blocking the executor, like a DMA transfer. With overlap, generating and sending the
+//! pixels takes about as long as the slower of the two on its own rather than their sum.
+
+use std::{
+    future::{poll_fn, Future},
+    hint::black_box,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+    time::{Duration, Instant},
+};
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::{NoCs, NullSpiDevice, SpiInterface};
+use embedded_hal::spi::{ErrorType, Operation};
+
+/// One 64x64 tile of RGB565 pixels
+const PIXELS: usize = 64 * 64;
+const BYTES: usize = PIXELS * 2;
+
+fn send_all_formats(c: &mut Criterion, group: &str, iface: &mut impl WriteOnlyDataCommand) {
+    let bytes = [0x5au8; BYTES];
+    let pixels = [0xa55au16; PIXELS];
+    let mut swapped = pixels;
+
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    group.bench_function("U8", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8(black_box(&bytes))))
+    });
+    group.bench_function("U16", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16(black_box(&pixels))))
+    });
+    group.bench_function("U16BE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BE(black_box(&mut swapped))))
+    });
+    group.bench_function("U16LE", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LE(black_box(&mut swapped))))
+    });
+    group.bench_function("U8Iter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U8Iter(&mut black_box(bytes).into_iter())))
+    });
+    group.bench_function("U16BEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16BEIter(&mut black_box(pixels).into_iter())))
+    });
+    group.bench_function("U16LEIter", |b| {
+        b.iter(|| iface.send_data(DataFormat::U16LEIter(&mut black_box(pixels).into_iter())))
+    });
+
+    group.finish();
+}
+
+fn formats(c: &mut Criterion) {
+    // `NoCs` does nothing, which makes it a data/command pin as fast as the null device
+    let mut iface = SpiInterface::new(NullSpiDevice::new(), NoCs);
+    send_all_formats(c, "spi", &mut iface);
+}
+
+/// Iterator formats with staging buffers of different sizes, each filled buffer is one transfer
+#[cfg(feature = "static-buffer")]
+fn staging_sweep(c: &mut Criterion) {
+    let pixels = [0xa55au16; PIXELS];
+
+    let mut group = c.benchmark_group("spi-staging");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    for size in [16, 64, 256, 1024, 4096] {
+        let buffer = Box::leak(vec![0; size].into_boxed_slice());
+        let mut iface = SpiInterface::new_with_buffer(NullSpiDevice::new(), NoCs, buffer);
+        group.bench_with_input(
+            criterion::BenchmarkId::new("U16BEIter", size),
+            &size,
+            |b, _| {
+                b.iter(|| {
+                    iface.send_data(DataFormat::U16BEIter(&mut black_box(pixels).into_iter()))
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+#[cfg(not(feature = "static-buffer"))]
+fn staging_sweep(_: &mut Criterion) {}
+
+/// Device whose writes complete after the time they would take on a 40 MHz bus
+struct DmaSpiDevice;
+
+impl ErrorType for DmaSpiDevice {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_hal_async::spi::SpiDevice for DmaSpiDevice {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Self::Error> {
+        let len: usize = operations
+            .iter()
+            .map(|op| match op {
+                Operation::Write(buf) => buf.len(),
+                _ => 0,
+            })
+            .sum();
+        let done = Instant::now() + Duration::from_nanos(len as u64 * 200);
+
+        poll_fn(|cx| {
+            if Instant::now() >= done {
+                Poll::Ready(Ok(()))
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+/// Poll `future` to completion without an executor
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    let waker = unsafe { Waker::from_raw(clone(core::ptr::null())) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+/// A procedurally generated pixel, taking about as long as sending it at 40 MHz
+fn shade(i: u16) -> u16 {
+    (0..130).fold(black_box(i), |v, _| black_box(v.rotate_left(5) ^ 0x9e37))
+}
+
+fn overlap(c: &mut Criterion) {
+    let pixels: Vec<u16> = (0..PIXELS as u16).map(shade).collect();
+
+    let mut group = c.benchmark_group("spi-async");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+
+    // Generating the pixels on its own
+    let mut iface = SpiInterface::new(NullSpiDevice::new(), NoCs);
+    group.bench_function("generate", |b| {
+        b.iter(|| {
+            let iter = &mut (0..PIXELS as u16).map(shade);
+            block_on(display_interface::AsyncWriteOnlyDataCommand::send_data(
+                &mut iface,
+                DataFormat::U16BEIter(iter),
+            ))
+        })
+    });
+
+    // Sending ready-made pixels on its own
+    let mut iface = SpiInterface::new(DmaSpiDevice, NoCs);
+    group.bench_function("send", |b| {
+        b.iter(|| {
+            let iter = &mut black_box(&pixels).iter().copied();
+            block_on(display_interface::AsyncWriteOnlyDataCommand::send_data(
+                &mut iface,
+                DataFormat::U16BEIter(iter),
+            ))
+        })
+    });
+
+    // Both, overlapped
+    group.bench_function("generate-and-send", |b| {
+        b.iter(|| {
+            let iter = &mut (0..PIXELS as u16).map(shade);
+            block_on(display_interface::AsyncWriteOnlyDataCommand::send_data(
+                &mut iface,
+                DataFormat::U16BEIter(iter),
+            ))
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, formats, staging_sweep, overlap);
+criterion_main!(benches);
@@ -1,139 +1,351 @@
 //! Generic asynchronous SPI interface for display drivers
+//!
+//! # Cancellation
+//!
+//! The futures returned by `send_commands` and `send_data` may be dropped at any await point,
+//! e.g. when racing a flush against a timeout. The interfaces guarantee the following state
+//! afterwards:
+//!
+//! - The data/command pin is set at the start of every call, so the next call always starts in
+//!   the correct phase regardless of where the previous one was cancelled.
+//! - Iterator formats are staged in a buffer owned by the future; bytes which were taken from
+//!   the iterator but not yet written are discarded and never sent as part of a later call.
+//! - Every chunk is written in its own `SpiDevice` transaction. Ending a transaction which is
+//!   cancelled while in flight is up to the `SpiDevice` implementation.
+//! - `SpiBusInterface` and `CascadedSpiInterface` deassert chip select when the future is
+//!   dropped.
+//! - `U16LE`/`U16BE` slices are converted in place before being sent, so after cancellation the
+//!   caller's buffer holds the converted values, just like after a completed call.
 
-use byte_slice_cast::*;
-use embedded_hal::digital::OutputPin;
-use embedded_hal_async::spi::SpiDevice;
+use embedded_hal::{
+    digital::OutputPin,
+    spi::{ErrorType, Operation},
+};
+use embedded_hal_async::{
+    delay::DelayNs,
+    spi::{SpiBus, SpiDevice},
+};
 
-use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use display_interface::{AsyncReadData, AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
 
-use crate::{SpiInterface, BUFFER_SIZE};
+use crate::{
+    bus::{BusDevice, CsGuard, CsTiming},
+    cascaded::{assert_cs, deassert_cs},
+    format::asynch::{send_u8, send_u8_staged},
+    packed::asynch::send_packed,
+    read::asynch::read_ops,
+    sequence::{
+        asynch::{send_sequence, send_sequence_bus},
+        command_then_wait, SequenceStep,
+    },
+    CascadedSpiInterface, Spi3WirePackedInterface, SpiBusInterface, SpiDataInterface, SpiInterface,
+    WriteOptions,
+};
 
 type Result = core::result::Result<(), DisplayError>;
 
-async fn send_u8<SPI>(spi: &mut SPI, words: DataFormat<'_>) -> Result
+/// Write `format` to `spi`, using `chunk` to serialize iterator formats
+///
+/// See [write_format](crate::write_format).
+pub async fn write_format_async<SPI>(
+    spi: &mut SPI,
+    format: DataFormat<'_>,
+    chunk: &mut [u8],
+) -> core::result::Result<(), DisplayError>
 where
     SPI: SpiDevice,
 {
-    match words {
-        DataFormat::U8(slice) => spi
-            .write(slice)
+    send_u8_staged(spi, format, WriteOptions::PLAIN, 0, Some(chunk))
+        .await
+        .map_err(DisplayError::from)
+}
+
+impl<SPI, DC> AsyncWriteOnlyDataCommand for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+
+        // Send words over SPI
+        let opts = self.command_options();
+        let yield_every = self.yield_every;
+        let (spi, staging) = self.spi_and_staging();
+        send_u8_staged(spi, cmds, opts, yield_every, staging)
             .await
-            .map_err(|_| DisplayError::BusWriteError),
-        DataFormat::U16(slice) => spi
-            .write(slice.as_byte_slice())
+            .map_err(DisplayError::from)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+
+        // Send words over SPI
+        let opts = self.data_options();
+        let yield_every = self.yield_every;
+        let (spi, staging) = self.spi_and_staging();
+        send_u8_staged(spi, buf, opts, yield_every, staging)
             .await
-            .map_err(|_| DisplayError::BusWriteError),
-        DataFormat::U16LE(slice) => {
-            for v in slice.as_mut() {
-                *v = v.to_le();
+            .map_err(DisplayError::from)
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Send the command `cmd` and read its response into `buf` within one transaction
+    ///
+    /// See [SpiInterface::read_register].
+    pub async fn read_register_async(&mut self, cmd: u8, buf: &mut [u8]) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+
+        read_ops(&mut self.spi, Some(&[cmd]), buf, self.read_dummy_bits).await
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Send `steps`, keeping delays within the transactions
+    ///
+    /// See [SpiInterface::send_sequence].
+    pub async fn send_sequence_async(&mut self, steps: &[SequenceStep<'_>]) -> Result {
+        self.check_sequence_support()?;
+        send_sequence(&mut self.spi, &mut self.dc, steps).await
+    }
+
+    /// Send the command `cmd`, wait `ns` nanoseconds, then send `params` as data
+    ///
+    /// See [SpiInterface::send_command_then_wait].
+    pub async fn send_command_then_wait_async(
+        &mut self,
+        cmd: u8,
+        params: &[u8],
+        ns: u32,
+    ) -> Result {
+        let cmd = [cmd];
+        let (steps, n) = command_then_wait(&cmd, params, ns);
+        self.send_sequence_async(&steps[..n]).await
+    }
+}
+
+impl<SPI, DC> AsyncReadData for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+
+        read_ops(&mut self.spi, None, buf, self.read_dummy_bits).await
+    }
+}
+
+impl<SPI> SpiDevice for BusDevice<'_, SPI>
+where
+    SPI: SpiBus,
+{
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> core::result::Result<(), <Self as ErrorType>::Error> {
+        for op in operations {
+            match op {
+                Operation::Read(buf) => self.0.read(buf).await?,
+                Operation::Write(buf) => self.0.write(buf).await?,
+                Operation::Transfer(read, write) => self.0.transfer(read, write).await?,
+                Operation::TransferInPlace(buf) => self.0.transfer_in_place(buf).await?,
+                // There is no delay source; the format handling never emits delays
+                Operation::DelayNs(_) => self.0.flush().await?,
             }
-            spi.write(slice.as_byte_slice())
-                .await
-                .map_err(|_| DisplayError::BusWriteError)
         }
-        DataFormat::U16BE(slice) => {
-            for v in slice.as_mut() {
-                *v = v.to_be();
-            }
-            spi.write(slice.as_byte_slice())
-                .await
-                .map_err(|_| DisplayError::BusWriteError)
+
+        Ok(())
+    }
+}
+
+impl<SPI, DC, CS, D> SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    async fn send_async(&mut self, data: bool, words: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        if data {
+            self.dc.set_high()
+        } else {
+            self.dc.set_low()
         }
-        DataFormat::U8Iter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-
-            for v in iter.into_iter() {
-                buf[i] = v;
-                i += 1;
-
-                if i == buf.len() {
-                    spi.write(&buf)
-                        .await
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
+        .map_err(|_| DisplayError::DCError)?;
 
-            if i > 0 {
-                spi.write(&buf[..i])
-                    .await
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
+        // Deasserted on drop if the future is cancelled
+        let CsTiming { setup_ns, hold_ns } = self.cs_timing;
+        let cs = CsGuard::assert(&mut self.cs)?;
+        if setup_ns > 0 {
+            self.delay.delay_ns(setup_ns).await;
+        }
 
-            Ok(())
+        // Make sure all words left the bus before deasserting chip select
+        let mut result = send_u8(&mut BusDevice(&mut self.spi), words, WriteOptions::PLAIN, 0)
+            .await
+            .map_err(DisplayError::from);
+        if result.is_ok() {
+            result = self
+                .spi
+                .flush()
+                .await
+                .map_err(|_| DisplayError::BusWriteError);
         }
-        DataFormat::U16LEIter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-
-            for v in iter.map(u16::to_le) {
-                buf[i] = v;
-                i += 1;
-
-                if i == buf.len() {
-                    spi.write(buf.as_byte_slice())
-                        .await
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
 
-            if i > 0 {
-                spi.write(buf[..i].as_byte_slice())
-                    .await
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
+        if hold_ns > 0 {
+            self.delay.delay_ns(hold_ns).await;
+        }
+        result.and(cs.deassert())
+    }
+
+    /// Send `steps` with chip select asserted throughout, pausing with `delay`
+    ///
+    /// See [SpiBusInterface::send_sequence].
+    pub async fn send_sequence_async(
+        &mut self,
+        steps: &[SequenceStep<'_>],
+        delay: &mut impl DelayNs,
+    ) -> Result {
+        send_sequence_bus(
+            &mut self.spi,
+            &mut self.dc,
+            &mut self.cs,
+            &mut self.delay,
+            self.cs_timing,
+            steps,
+            delay,
+        )
+        .await
+    }
 
-            Ok(())
+    /// Send the command `cmd`, wait `ns` nanoseconds with `delay`, then send `params` as data,
+    /// all with chip select asserted
+    ///
+    /// See [SpiBusInterface::send_command_then_wait].
+    pub async fn send_command_then_wait_async(
+        &mut self,
+        cmd: u8,
+        params: &[u8],
+        ns: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result {
+        let cmd = [cmd];
+        let (steps, n) = command_then_wait(&cmd, params, ns);
+        self.send_sequence_async(&steps[..n], delay).await
+    }
+}
+
+impl<SPI, DC, CS, D> AsyncWriteOnlyDataCommand for SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_async(false, cmds).await
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_async(true, buf).await
+    }
+}
+
+impl<SPI, DC, CS1, CS2> CascadedSpiInterface<SPI, DC, CS1, CS2>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    async fn send_async(&mut self, data: bool, words: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        if data {
+            self.dc.set_high()
+        } else {
+            self.dc.set_low()
         }
-        DataFormat::U16BEIter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-            let len = buf.len();
-
-            for v in iter.map(u16::to_be) {
-                buf[i] = v;
-                i += 1;
-
-                if i == len {
-                    spi.write(buf.as_byte_slice())
-                        .await
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
+        .map_err(|_| DisplayError::DCError)?;
 
-            if i > 0 {
-                spi.write(buf[..i].as_byte_slice())
-                    .await
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
+        // Deasserted on drop if the future is cancelled
+        let guards = assert_cs(self.half, &mut self.cs1, &mut self.cs2)?;
 
-            Ok(())
+        // Make sure all words left the bus before deasserting chip select
+        let mut result = send_u8(&mut BusDevice(&mut self.spi), words, WriteOptions::PLAIN, 0)
+            .await
+            .map_err(DisplayError::from);
+        if result.is_ok() {
+            result = self
+                .spi
+                .flush()
+                .await
+                .map_err(|_| DisplayError::BusWriteError);
         }
-        _ => Err(DisplayError::DataFormatNotImplemented),
+
+        deassert_cs(guards, result)
     }
 }
 
-impl<SPI, DC> AsyncWriteOnlyDataCommand for SpiInterface<SPI, DC>
+impl<SPI, DC, CS1, CS2> AsyncWriteOnlyDataCommand for CascadedSpiInterface<SPI, DC, CS1, CS2>
 where
-    SPI: SpiDevice,
+    SPI: SpiBus,
     DC: OutputPin,
+    CS1: OutputPin,
+    CS2: OutputPin,
 {
     async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
-        // 1 = data, 0 = command
-        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+        self.send_async(false, cmds).await
+    }
 
-        // Send words over SPI
-        send_u8(&mut self.spi, cmds).await
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_async(true, buf).await
+    }
+}
+
+impl<SPI> AsyncWriteOnlyDataCommand for SpiDataInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.check_commands()?;
+        send_u8(&mut self.spi, cmds, WriteOptions::PLAIN, 0)
+            .await
+            .map_err(DisplayError::from)
     }
 
     async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        send_u8(&mut self.spi, buf, WriteOptions::PLAIN, 0)
+            .await
+            .map_err(DisplayError::from)
+    }
+}
+
+impl<SPI> AsyncWriteOnlyDataCommand for Spi3WirePackedInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
         // 1 = data, 0 = command
-        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+        send_packed(&mut self.spi, false, cmds).await
+    }
 
-        // Send words over SPI
-        send_u8(&mut self.spi, buf).await
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        send_packed(&mut self.spi, true, buf).await
     }
 }
@@ -0,0 +1,266 @@
+//! SPI interface owning the raw bus and an optional chip select pin
+
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, DisplayError, LowPower, WriteOnlyDataCommand};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{ErrorType as PinErrorType, OutputPin},
+    spi::{ErrorType, Operation, SpiBus, SpiDevice},
+};
+
+use crate::{send_u8, NoDelay, Result, WriteOptions};
+
+/// Placeholder for a chip select line which is hard-wired on the board
+pub struct NoCs;
+
+impl PinErrorType for NoCs {
+    type Error = Infallible;
+}
+
+impl OutputPin for NoCs {
+    fn set_low(&mut self) -> core::result::Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> core::result::Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Timing of the chip select line managed by [SpiBusInterface]
+///
+/// Some controllers need time between asserting chip select and the first clock edge (setup),
+/// or between the last clock edge and deasserting chip select (hold). Datasheets usually ask for
+/// tens of nanoseconds, but slow level shifters can stretch this to microseconds. Both default to
+/// zero, which doesn't wait at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CsTiming {
+    /// Time from asserting chip select to the first transfer, in nanoseconds
+    pub setup_ns: u32,
+    /// Time from the end of the last transfer to deasserting chip select, in nanoseconds
+    pub hold_ns: u32,
+}
+
+/// Asserted chip select which is deasserted again when dropped
+///
+/// This keeps chip select from being left asserted when an `async` send is cancelled half way.
+pub(crate) struct CsGuard<'a, CS: OutputPin>(Option<&'a mut CS>);
+
+impl<'a, CS: OutputPin> CsGuard<'a, CS> {
+    /// Assert chip select
+    pub(crate) fn assert(cs: &'a mut CS) -> core::result::Result<Self, DisplayError> {
+        cs.set_low().map_err(|_| DisplayError::CSError)?;
+        Ok(Self(Some(cs)))
+    }
+
+    /// Deassert chip select, reporting failure to do so
+    pub(crate) fn deassert(mut self) -> Result {
+        match self.0.take() {
+            Some(cs) => cs.set_high().map_err(|_| DisplayError::CSError),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<CS: OutputPin> Drop for CsGuard<'_, CS> {
+    fn drop(&mut self) {
+        if let Some(cs) = self.0.take() {
+            cs.set_high().ok();
+        }
+    }
+}
+
+/// Adapter presenting a borrowed bus as a device so the format handling can be shared
+///
+/// Chip select is handled by the interface around a whole send call, so this performs the
+/// operations on the bus without any framing of its own.
+pub(crate) struct BusDevice<'a, SPI>(pub(crate) &'a mut SPI);
+
+impl<SPI> ErrorType for BusDevice<'_, SPI>
+where
+    SPI: ErrorType,
+{
+    type Error = SPI::Error;
+}
+
+impl<SPI> SpiDevice for BusDevice<'_, SPI>
+where
+    SPI: SpiBus,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> core::result::Result<(), Self::Error> {
+        for op in operations {
+            match op {
+                Operation::Read(buf) => self.0.read(buf)?,
+                Operation::Write(buf) => self.0.write(buf)?,
+                Operation::Transfer(read, write) => self.0.transfer(read, write)?,
+                Operation::TransferInPlace(buf) => self.0.transfer_in_place(buf)?,
+                // There is no delay source; the format handling never emits delays
+                Operation::DelayNs(_) => self.0.flush()?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// SPI display interface owning the bus.
+///
+/// This combines an exclusively owned SPI bus, a data/command pin and a chip select pin which
+/// is asserted for the duration of each `send_commands`/`send_data` call. Use
+/// [SpiBusInterface::new_without_cs] if chip select is hard-wired on the board.
+///
+/// Since chip select is deasserted at the end of every call, it always toggles between a command
+/// and the data following it, which is what controllers latching state on chip select edges
+/// require. With `SpiDevice` based interfaces the HAL owns chip select and gives no such
+/// guarantee.
+///
+/// Setup and hold times for chip select can be configured with
+/// [SpiBusInterface::with_cs_timing]. This is not available for `SpiDevice` based interfaces,
+/// where the HAL owns chip select and needs to be configured accordingly instead.
+pub struct SpiBusInterface<SPI, DC, CS, D = NoDelay> {
+    pub(crate) spi: SPI,
+    pub(crate) dc: DC,
+    pub(crate) cs: CS,
+    pub(crate) delay: D,
+    pub(crate) cs_timing: CsTiming,
+}
+
+/// SPI display interface with a chip select pin managed by this crate
+///
+/// This is the successor of the `SPIInterface` of display-interface-spi 0.4, which asserted
+/// chip select around every call as well.
+pub type SpiInterfaceWithCs<SPI, DC, CS, D = NoDelay> = SpiBusInterface<SPI, DC, CS, D>;
+
+impl<SPI, DC, CS> SpiBusInterface<SPI, DC, CS> {
+    /// Create new SPI interface for communication with a display driver
+    pub fn new(spi: SPI, dc: DC, cs: CS) -> Self {
+        Self {
+            spi,
+            dc,
+            cs,
+            delay: NoDelay,
+            cs_timing: CsTiming::default(),
+        }
+    }
+}
+
+impl<SPI, DC, CS, D> SpiBusInterface<SPI, DC, CS, D> {
+    /// Wait according to `timing` around asserting and deasserting chip select, using `delay`
+    pub fn with_cs_timing<D2>(
+        self,
+        timing: CsTiming,
+        delay: D2,
+    ) -> SpiBusInterface<SPI, DC, CS, D2> {
+        SpiBusInterface {
+            spi: self.spi,
+            dc: self.dc,
+            cs: self.cs,
+            delay,
+            cs_timing: timing,
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver and GPIO pins used by it
+    pub fn release(self) -> (SPI, DC, CS) {
+        (self.spi, self.dc, self.cs)
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver, GPIO pins and delay used by it
+    pub fn release_with_delay(self) -> (SPI, DC, CS, D) {
+        (self.spi, self.dc, self.cs, self.delay)
+    }
+}
+
+impl<SPI, DC> SpiBusInterface<SPI, DC, NoCs> {
+    /// Create new SPI interface for a display with a hard-wired chip select
+    pub fn new_without_cs(spi: SPI, dc: DC) -> Self {
+        Self::new(spi, dc, NoCs)
+    }
+}
+
+impl<SPI, DC, CS, D> SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn send(&mut self, data: bool, words: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        if data {
+            self.dc.set_high()
+        } else {
+            self.dc.set_low()
+        }
+        .map_err(|_| DisplayError::DCError)?;
+
+        let CsTiming { setup_ns, hold_ns } = self.cs_timing;
+        let cs = CsGuard::assert(&mut self.cs)?;
+        if setup_ns > 0 {
+            self.delay.delay_ns(setup_ns);
+        }
+
+        // Make sure all words left the bus before deasserting chip select
+        let result = send_u8(&mut BusDevice(&mut self.spi), words, WriteOptions::PLAIN, 0)
+            .map_err(DisplayError::from)
+            .and_then(|_| self.spi.flush().map_err(|_| DisplayError::BusWriteError));
+
+        if hold_ns > 0 {
+            self.delay.delay_ns(hold_ns);
+        }
+        result.and(cs.deassert())
+    }
+}
+
+impl<SPI, DC, CS, D> SpiBusInterface<SPI, DC, CS, D>
+where
+    DC: OutputPin,
+    CS: OutputPin,
+{
+    /// Deassert chip select high, then drive the D/C pin low for command
+    pub fn resync(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.dc.set_low().map_err(|_| DisplayError::DCError)
+    }
+}
+
+/// Suspending waits for the bus to finish, then deasserts chip select and drives the D/C pin low.
+/// Resuming deasserts chip select again; every call sets both pins anew, so nothing else is needed.
+impl<SPI, DC, CS, D> LowPower for SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+{
+    fn suspend(&mut self) -> Result {
+        self.spi.flush().map_err(|_| DisplayError::BusWriteError)?;
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        self.dc.set_low().map_err(|_| DisplayError::DCError)
+    }
+
+    fn resume(&mut self) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)
+    }
+}
+
+impl<SPI, DC, CS, D> WriteOnlyDataCommand for SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send(false, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send(true, buf)
+    }
+}
@@ -0,0 +1,180 @@
+//! SPI interface for panels made of two controllers sharing the bus
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{digital::OutputPin, spi::SpiBus};
+
+use crate::{
+    bus::{BusDevice, CsGuard},
+    send_u8, Result, WriteOptions,
+};
+
+/// Controller(s) addressed by a [CascadedSpiInterface]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Half {
+    /// The controller behind the first chip select
+    #[default]
+    Left,
+    /// The controller behind the second chip select
+    Right,
+    /// Both controllers at once, e.g. for broadcasting commands
+    Both,
+}
+
+/// SPI display interface for cascaded controllers with separate chip selects
+///
+/// Large e-paper panels, like the 12.48" Waveshare and several 7.5" ones, are driven by two
+/// controllers sharing SCK, MOSI and D/C, each with its own chip select and BUSY line. This
+/// interface owns the bus and both chip selects, asserting the one(s) chosen with
+/// [CascadedSpiInterface::select] around each `send_commands`/`send_data` call, like
+/// [SpiBusInterface](crate::SpiBusInterface) does for a single controller.
+///
+/// BUSY is left to the driver, which has to wait for the controller(s) it addresses:
+///
+/// ```
+/// # use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+/// # use display_interface_spi::{CascadedSpiInterface, Half};
+/// # use embedded_hal::{digital::{InputPin, OutputPin}, spi::SpiBus};
+/// /// The controllers pull BUSY low while they are refreshing
+/// fn wait_idle(busy: &mut impl InputPin) {
+///     while busy.is_low().unwrap_or(false) {}
+/// }
+///
+/// fn refresh(
+///     iface: &mut CascadedSpiInterface<impl SpiBus, impl OutputPin, impl OutputPin, impl OutputPin>,
+///     busy_left: &mut impl InputPin,
+///     busy_right: &mut impl InputPin,
+/// ) -> Result<(), DisplayError> {
+///     // Display refresh, sent to both halves at once
+///     iface.select(Half::Both);
+///     iface.send_commands(DataFormat::U8(&[0x12]))?;
+///     wait_idle(busy_left);
+///     wait_idle(busy_right);
+///     Ok(())
+/// }
+/// ```
+pub struct CascadedSpiInterface<SPI, DC, CS1, CS2> {
+    pub(crate) spi: SPI,
+    pub(crate) dc: DC,
+    pub(crate) cs1: CS1,
+    pub(crate) cs2: CS2,
+    pub(crate) half: Half,
+}
+
+impl<SPI, DC, CS1, CS2> CascadedSpiInterface<SPI, DC, CS1, CS2> {
+    /// Create new SPI interface for two cascaded controllers, addressing the left one
+    pub fn new(spi: SPI, dc: DC, cs1: CS1, cs2: CS2) -> Self {
+        Self {
+            spi,
+            dc,
+            cs1,
+            cs2,
+            half: Half::default(),
+        }
+    }
+
+    /// Address `half` with all following calls
+    pub fn select(&mut self, half: Half) {
+        self.half = half;
+    }
+
+    /// The controller(s) currently addressed
+    pub fn selected(&self) -> Half {
+        self.half
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver and GPIO pins used by it
+    pub fn release(self) -> (SPI, DC, CS1, CS2) {
+        (self.spi, self.dc, self.cs1, self.cs2)
+    }
+}
+
+impl<SPI, DC, CS1, CS2> CascadedSpiInterface<SPI, DC, CS1, CS2>
+where
+    DC: OutputPin,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    /// Deassert both chip selects high, then drive the D/C pin low for command
+    pub fn resync(&mut self) -> Result {
+        let left = self.cs1.set_high().map_err(|_| DisplayError::CSError);
+        let right = self.cs2.set_high().map_err(|_| DisplayError::CSError);
+        left.and(right)?;
+        self.dc.set_low().map_err(|_| DisplayError::DCError)
+    }
+}
+
+/// Chip select guards of the selected halves
+pub(crate) type Guards<'a, CS1, CS2> = (Option<CsGuard<'a, CS1>>, Option<CsGuard<'a, CS2>>);
+
+/// Assert the chip selects of `half`
+///
+/// If the second one fails, the first one is deasserted again when its guard is dropped.
+pub(crate) fn assert_cs<'a, CS1: OutputPin, CS2: OutputPin>(
+    half: Half,
+    cs1: &'a mut CS1,
+    cs2: &'a mut CS2,
+) -> core::result::Result<Guards<'a, CS1, CS2>, DisplayError> {
+    let left = match half {
+        Half::Left | Half::Both => Some(CsGuard::assert(cs1)?),
+        Half::Right => None,
+    };
+    let right = match half {
+        Half::Right | Half::Both => Some(CsGuard::assert(cs2)?),
+        Half::Left => None,
+    };
+    Ok((left, right))
+}
+
+/// Deassert both guards, passing on `result` unless deasserting fails
+pub(crate) fn deassert_cs<CS1: OutputPin, CS2: OutputPin>(
+    (left, right): Guards<'_, CS1, CS2>,
+    result: Result,
+) -> Result {
+    let left = left.map_or(Ok(()), CsGuard::deassert);
+    let right = right.map_or(Ok(()), CsGuard::deassert);
+    result.and(left).and(right)
+}
+
+impl<SPI, DC, CS1, CS2> CascadedSpiInterface<SPI, DC, CS1, CS2>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    fn send(&mut self, data: bool, words: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        if data {
+            self.dc.set_high()
+        } else {
+            self.dc.set_low()
+        }
+        .map_err(|_| DisplayError::DCError)?;
+
+        let guards = assert_cs(self.half, &mut self.cs1, &mut self.cs2)?;
+
+        // Make sure all words left the bus before deasserting chip select
+        let result = send_u8(&mut BusDevice(&mut self.spi), words, WriteOptions::PLAIN, 0)
+            .map_err(DisplayError::from)
+            .and_then(|_| self.spi.flush().map_err(|_| DisplayError::BusWriteError));
+
+        deassert_cs(guards, result)
+    }
+}
+
+impl<SPI, DC, CS1, CS2> WriteOnlyDataCommand for CascadedSpiInterface<SPI, DC, CS1, CS2>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS1: OutputPin,
+    CS2: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send(false, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send(true, buf)
+    }
+}
@@ -0,0 +1,141 @@
+//! Shims for HALs which only implement embedded-hal 0.2
+
+use core::fmt::Debug;
+
+use eh02::{blocking::spi::Write, digital::v2::OutputPin as OutputPinV2};
+use embedded_hal::{
+    digital::{self, OutputPin},
+    spi::{self, ErrorKind, ErrorType, Operation, SpiBus, SpiDevice},
+};
+
+use crate::{SpiBusInterface, SpiInterface};
+
+/// Error of the embedded-hal 0.2 shims
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Eh02Error<E> {
+    /// Error returned by the wrapped 0.2 implementation
+    Hal(E),
+    /// The operation has no counterpart in the wrapped 0.2 trait, e.g. reading from a bus
+    /// which only implements `Write`
+    Unsupported,
+}
+
+impl<E: Debug> digital::Error for Eh02Error<E> {
+    fn kind(&self) -> digital::ErrorKind {
+        digital::ErrorKind::Other
+    }
+}
+
+impl<E: Debug> spi::Error for Eh02Error<E> {
+    fn kind(&self) -> ErrorKind {
+        ErrorKind::Other
+    }
+}
+
+/// Output pin implementing only the embedded-hal 0.2 `digital::v2::OutputPin`
+pub struct Eh02Pin<P>(pub P);
+
+impl<P> digital::ErrorType for Eh02Pin<P>
+where
+    P: OutputPinV2,
+    P::Error: Debug,
+{
+    type Error = Eh02Error<P::Error>;
+}
+
+impl<P> OutputPin for Eh02Pin<P>
+where
+    P: OutputPinV2,
+    P::Error: Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.0.set_low().map_err(Eh02Error::Hal)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.0.set_high().map_err(Eh02Error::Hal)
+    }
+}
+
+/// SPI bus implementing only the embedded-hal 0.2 `blocking::spi::Write<u8>`
+///
+/// This can be used both as a bus and as a device with chip select hard-wired or managed by the
+/// interface. Only writes are supported, reads fail with [Eh02Error::Unsupported]. The 0.2 trait
+/// has no notion of delays, so `DelayNs` operations, i.e. the pauses of [crate::Pacing], are
+/// skipped.
+pub struct Eh02Spi<SPI>(pub SPI);
+
+impl<SPI> ErrorType for Eh02Spi<SPI>
+where
+    SPI: Write<u8>,
+    SPI::Error: Debug,
+{
+    type Error = Eh02Error<SPI::Error>;
+}
+
+impl<SPI> SpiBus for Eh02Spi<SPI>
+where
+    SPI: Write<u8>,
+    SPI::Error: Debug,
+{
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+        Err(Eh02Error::Unsupported)
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        self.0.write(words).map_err(Eh02Error::Hal)
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), Self::Error> {
+        Err(Eh02Error::Unsupported)
+    }
+
+    fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Self::Error> {
+        Err(Eh02Error::Unsupported)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // Blocking 0.2 writes have completed when they return
+        Ok(())
+    }
+}
+
+impl<SPI> SpiDevice for Eh02Spi<SPI>
+where
+    SPI: Write<u8>,
+    SPI::Error: Debug,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Self::Error> {
+        // Reject the whole transaction before putting anything on the bus
+        let supported =
+            |op: &Operation<'_, u8>| matches!(op, Operation::Write(_) | Operation::DelayNs(_));
+        if !operations.iter().all(supported) {
+            return Err(Eh02Error::Unsupported);
+        }
+
+        for op in operations {
+            if let Operation::Write(buf) = op {
+                SpiBus::write(self, buf)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<SPI, DC> SpiInterface<Eh02Spi<SPI>, Eh02Pin<DC>> {
+    /// Create new SPI interface from embedded-hal 0.2 implementations
+    ///
+    /// Chip select has to be hard-wired or handled by `spi`; use
+    /// [SpiBusInterface::new_eh02] to let the interface drive it.
+    pub fn new_eh02(spi: SPI, dc: DC) -> Self {
+        Self::new(Eh02Spi(spi), Eh02Pin(dc))
+    }
+}
+
+impl<SPI, DC, CS> SpiBusInterface<Eh02Spi<SPI>, Eh02Pin<DC>, Eh02Pin<CS>> {
+    /// Create new SPI interface driving chip select from embedded-hal 0.2 implementations
+    pub fn new_eh02(spi: SPI, dc: DC, cs: CS) -> Self {
+        Self::new(Eh02Spi(spi), Eh02Pin(dc), Eh02Pin(cs))
+    }
+}
@@ -0,0 +1,61 @@
+//! SPI interface for displays without a data/command line
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::spi::SpiDevice;
+
+use crate::{send_u8, Result, WriteOptions};
+
+/// SPI display interface for a pure data stream.
+///
+/// Some displays, like Sharp Memory LCDs, have no data/command signal and frame their commands
+/// within the data instead. This interface needs no data/command pin. By default
+/// `send_commands` fails with [Unsupported](DisplayError::Unsupported); use
+/// [SpiDataInterface::with_commands_as_data] to send commands like data.
+pub struct SpiDataInterface<SPI> {
+    pub(crate) spi: SPI,
+    commands_as_data: bool,
+}
+
+impl<SPI> SpiDataInterface<SPI> {
+    /// Create new SPI interface for communication with a display driver
+    pub fn new(spi: SPI) -> Self {
+        Self {
+            spi,
+            commands_as_data: false,
+        }
+    }
+
+    /// Send the words passed to `send_commands` like data instead of failing
+    pub fn with_commands_as_data(mut self) -> Self {
+        self.commands_as_data = true;
+        self
+    }
+
+    pub(crate) fn check_commands(&self) -> Result {
+        if self.commands_as_data {
+            Ok(())
+        } else {
+            Err(DisplayError::Unsupported)
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for SpiDataInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.check_commands()?;
+        send_u8(&mut self.spi, cmds, WriteOptions::PLAIN, 0).map_err(DisplayError::from)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        send_u8(&mut self.spi, buf, WriteOptions::PLAIN, 0).map_err(DisplayError::from)
+    }
+}
@@ -0,0 +1,99 @@
+//! SPI interface for peripherals driving the data/command line in hardware
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{
+    digital::PinState,
+    spi::{ErrorType, Operation, SpiDevice},
+};
+
+use crate::{send_u8, Result, WriteOptions};
+
+/// An SPI device which drives the data/command (DCX) line itself as part of the transfer
+///
+/// Some peripherals, like the nRF52840's SPIM3 or the ESP32's LCD peripheral, can drive the D/C
+/// line in hardware, which is faster and avoids races between the D/C pin and DMA transfers.
+/// HAL crates implement this trait for such peripherals.
+pub trait DcxSpiDevice: SpiDevice {
+    /// Write `words` in one transaction with the D/C line at level `dc`
+    ///
+    /// [PinState::High] selects data, [PinState::Low] selects commands.
+    fn write_with_dc(
+        &mut self,
+        dc: PinState,
+        words: &[u8],
+    ) -> core::result::Result<(), Self::Error>;
+}
+
+/// Adapter routing the writes of the format handling through [DcxSpiDevice::write_with_dc]
+struct DcxDevice<'a, SPI> {
+    spi: &'a mut SPI,
+    dc: PinState,
+}
+
+impl<SPI> ErrorType for DcxDevice<'_, SPI>
+where
+    SPI: ErrorType,
+{
+    type Error = SPI::Error;
+}
+
+impl<SPI> SpiDevice for DcxDevice<'_, SPI>
+where
+    SPI: DcxSpiDevice,
+{
+    fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> core::result::Result<(), Self::Error> {
+        for op in operations {
+            match op {
+                Operation::Write(words) => self.spi.write_with_dc(self.dc, words)?,
+                op => self.spi.transaction(core::slice::from_mut(op))?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// SPI display interface with a hardware-managed data/command line.
+///
+/// This wraps an SPI peripheral implementing [DcxSpiDevice], so no GPIO is needed for the
+/// data/command signal.
+pub struct SpiDcxInterface<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> SpiDcxInterface<SPI> {
+    /// Create new SPI interface for communication with a display driver
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for SpiDcxInterface<SPI>
+where
+    SPI: DcxSpiDevice,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        let mut spi = DcxDevice {
+            spi: &mut self.spi,
+            dc: PinState::Low,
+        };
+        send_u8(&mut spi, cmds, WriteOptions::PLAIN, 0).map_err(DisplayError::from)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        let mut spi = DcxDevice {
+            spi: &mut self.spi,
+            dc: PinState::High,
+        };
+        send_u8(&mut spi, buf, WriteOptions::PLAIN, 0).map_err(DisplayError::from)
+    }
+}
@@ -0,0 +1,80 @@
+//! Owned buffer transfers for SPI peripherals with an `embedded-dma` style API
+
+use display_interface::{DisplayError, DmaTransfer, DmaWriteOnlyDataCommand};
+use embedded_dma::ReadBuffer;
+use embedded_hal::{
+    digital::OutputPin,
+    spi::{ErrorType, SpiDevice},
+};
+
+use crate::SpiInterface;
+
+/// An SPI device which can transmit an owned buffer through DMA
+///
+/// HAL crates implement this next to `SpiDevice` for their DMA capable peripherals. The
+/// transfer is performed within a single transaction which lasts until the returned
+/// [DmaSpiTransfer] completed; dropping the transfer before that has to wait for or abort it.
+pub trait DmaSpiWrite: ErrorType {
+    /// Transfer in flight, owning the buffer
+    type Transfer<'a, B>: DmaSpiTransfer<B, Error = Self::Error>
+    where
+        Self: 'a,
+        B: ReadBuffer<Word = u8> + 'static;
+
+    /// Start transmitting the contents of `buf`, handing it back if that failed
+    fn write_owned<B>(&mut self, buf: B) -> Result<Self::Transfer<'_, B>, (B, Self::Error)>
+    where
+        B: ReadBuffer<Word = u8> + 'static;
+}
+
+/// A transfer started by [DmaSpiWrite::write_owned]
+pub trait DmaSpiTransfer<B>: ErrorType {
+    /// Whether the transfer completed, successfully or not
+    fn is_done(&mut self) -> bool;
+
+    /// Wait for the transfer to complete and hand back the buffer, together with the error if
+    /// it failed
+    fn wait(self) -> Result<B, (B, Self::Error)>;
+}
+
+/// Transfer started by [SpiInterface] through [DmaWriteOnlyDataCommand]
+pub struct SpiDmaTransfer<T>(T);
+
+impl<T, B> DmaTransfer<B> for SpiDmaTransfer<T>
+where
+    T: DmaSpiTransfer<B>,
+{
+    fn is_done(&mut self) -> bool {
+        self.0.is_done()
+    }
+
+    fn wait(self) -> Result<B, (B, DisplayError)> {
+        self.0
+            .wait()
+            .map_err(|(buf, _)| (buf, DisplayError::BusWriteError))
+    }
+}
+
+impl<SPI, DC, B> DmaWriteOnlyDataCommand<B> for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice + DmaSpiWrite,
+    DC: OutputPin,
+    B: ReadBuffer<Word = u8> + 'static,
+{
+    type Transfer<'a>
+        = SpiDmaTransfer<SPI::Transfer<'a, B>>
+    where
+        Self: 'a;
+
+    fn send_data_owned(&mut self, buf: B) -> Result<Self::Transfer<'_>, (B, DisplayError)> {
+        // 1 = data, 0 = command
+        if self.dc.set_high().is_err() {
+            return Err((buf, DisplayError::DCError));
+        }
+
+        self.spi
+            .write_owned(buf)
+            .map(SpiDmaTransfer)
+            .map_err(|(buf, _)| (buf, DisplayError::BusWriteError))
+    }
+}
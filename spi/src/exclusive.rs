@@ -0,0 +1,58 @@
+//! Convenience constructors for displays which are the only device on their SPI bus
+
+use display_interface::DisplayError;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_bus::spi::ExclusiveDevice;
+
+use crate::SpiInterface;
+
+/// SPI display interface with exclusive access to the bus
+///
+/// Since [ExclusiveDevice] implements both the blocking and the asynchronous `SpiDevice` traits,
+/// the same type is used for blocking and `async` drivers, provided the bus, chip select and delay
+/// implement the respective traits.
+pub type ExclusiveSpiInterface<SPI, CS, D, DC> = SpiInterface<ExclusiveDevice<SPI, CS, D>, DC>;
+
+impl<SPI, CS, D, DC> SpiInterface<ExclusiveDevice<SPI, CS, D>, DC>
+where
+    CS: OutputPin,
+{
+    /// Create new SPI interface for a display which is the only device on the bus
+    ///
+    /// This wraps the bus, chip select pin and delay into an [ExclusiveDevice], deasserting
+    /// chip select in the process. The delay is used for delays within SPI transactions, so it
+    /// must be a real delay implementation matching the bus (blocking or `async`).
+    ///
+    /// ```
+    /// use display_interface_spi::{ExclusiveSpiInterface, SpiInterface};
+    /// use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiBus};
+    ///
+    /// fn setup<SPI, CS, D, DC>(
+    ///     bus: SPI,
+    ///     cs: CS,
+    ///     delay: D,
+    ///     dc: DC,
+    /// ) -> ExclusiveSpiInterface<SPI, CS, D, DC>
+    /// where
+    ///     SPI: SpiBus,
+    ///     CS: OutputPin,
+    ///     D: DelayNs,
+    ///     DC: OutputPin,
+    /// {
+    ///     SpiInterface::new_exclusive(bus, cs, delay, dc).unwrap()
+    /// }
+    /// ```
+    pub fn new_exclusive(bus: SPI, cs: CS, delay: D, dc: DC) -> Result<Self, DisplayError> {
+        let spi = ExclusiveDevice::new(bus, cs, delay).map_err(|_| DisplayError::CSError)?;
+        Ok(Self::new(spi, dc))
+    }
+
+    /// Consume the display interface and return
+    /// the [ExclusiveDevice] and data/command pin used by it
+    ///
+    /// `embedded-hal-bus` does not offer a way to take the [ExclusiveDevice] apart again, but the
+    /// bus remains accessible through [ExclusiveDevice::bus_mut].
+    pub fn release_exclusive(self) -> (ExclusiveDevice<SPI, CS, D>, DC) {
+        self.release()
+    }
+}
@@ -0,0 +1,464 @@
+//! Format handling shared by the blocking and `async` implementations
+//!
+//! The `send_u8` and `write` functions are written once and expanded into the `blocking` and
+//! `asynch` variants, so a fix to the handling of a format always applies to both.
+
+use byte_slice_cast::{AsByteSlice, ToByteSlice};
+use display_interface::DisplayError;
+
+use crate::SpiInterfaceError;
+
+/// Failure of a write, keeping the error of the SPI device
+pub(crate) enum WriteError<E> {
+    /// The SPI device failed
+    Spi(E),
+    /// The data couldn't be written, e.g. because of an unsupported format
+    Format(DisplayError),
+}
+
+/// Result of a write
+pub(crate) type WriteResult<E> = core::result::Result<(), WriteError<E>>;
+
+impl<E> From<DisplayError> for WriteError<E> {
+    fn from(e: DisplayError) -> Self {
+        Self::Format(e)
+    }
+}
+
+impl<E> From<WriteError<E>> for DisplayError {
+    fn from(e: WriteError<E>) -> Self {
+        match e {
+            WriteError::Spi(_) => DisplayError::BusWriteError,
+            WriteError::Format(e) => e,
+        }
+    }
+}
+
+impl<E, DC> From<WriteError<E>> for SpiInterfaceError<E, DC> {
+    fn from(e: WriteError<E>) -> Self {
+        match e {
+            WriteError::Spi(e) => Self::Spi(e),
+            WriteError::Format(e) => Self::Format(e),
+        }
+    }
+}
+
+/// Fill `buf` from `iter`, returning the number of words written
+fn fill<T>(buf: &mut [T], iter: &mut impl Iterator<Item = T>) -> usize {
+    let mut i = 0;
+
+    for (slot, v) in buf.iter_mut().zip(iter) {
+        *slot = v;
+        i += 1;
+    }
+
+    i
+}
+
+/// Table of all bytes with their bits reversed
+static REVERSED: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = (i as u8).reverse_bits();
+        i += 1;
+    }
+    table
+};
+
+/// Reverse the order of the bits in `v`
+fn reverse_bits(v: u8) -> u8 {
+    REVERSED[usize::from(v)]
+}
+
+/// Fill the byte buffer `buf` with the in-memory bytes of the words from `iter`, returning the
+/// number of bytes written
+fn fill_bytes<T: ToByteSlice>(buf: &mut [u8], iter: &mut impl Iterator<Item = T>) -> usize {
+    let size = core::mem::size_of::<T>();
+    let mut i = 0;
+
+    for (slot, v) in buf.chunks_exact_mut(size).zip(iter) {
+        slot.copy_from_slice(core::slice::from_ref(&v).as_byte_slice());
+        i += size;
+    }
+
+    i
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    #[maybe_async_cfg::only_if(async)]
+    use core::{
+        future::{poll_fn, Future},
+        pin::{pin, Pin},
+        task::{Context, Poll},
+    };
+
+    use byte_slice_cast::*;
+    use display_interface::{DataFormat, DisplayError};
+    use embedded_hal::spi::Operation;
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::spi::SpiDevice;
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::spi::SpiDevice;
+
+    use super::{fill, fill_bytes, reverse_bits, WriteError, WriteResult};
+    use crate::{BitOrder, WriteOptions, BUFFER_SIZE, CHUNKS_PER_TRANSACTION};
+
+    /// Future which returns `Pending` once, giving the executor a chance to run other tasks
+    #[maybe_async_cfg::only_if(async)]
+    struct YieldNow(bool);
+
+    #[maybe_async_cfg::only_if(async)]
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    /// Counts flushed chunks and yields to the executor every `every` chunks (never if 0)
+    ///
+    /// The blocking variant never yields.
+    #[maybe_async_cfg::only_if(sync)]
+    struct Yielder;
+
+    #[maybe_async_cfg::only_if(sync)]
+    impl Yielder {
+        fn new(_every: usize) -> Self {
+            Self
+        }
+
+        fn chunk_flushed(&mut self) {}
+    }
+
+    #[maybe_async_cfg::only_if(async)]
+    struct Yielder {
+        every: usize,
+        flushed: usize,
+    }
+
+    #[maybe_async_cfg::only_if(async)]
+    impl Yielder {
+        fn new(every: usize) -> Self {
+            Self { every, flushed: 0 }
+        }
+
+        async fn chunk_flushed(&mut self) {
+            if self.every == 0 {
+                return;
+            }
+
+            self.flushed += 1;
+            if self.flushed == self.every {
+                self.flushed = 0;
+                YieldNow(false).await;
+            }
+        }
+    }
+
+    pub(crate) async fn write<SPI>(
+        spi: &mut SPI,
+        bytes: &[u8],
+        word_size: usize,
+        opts: WriteOptions,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+    {
+        let (chunk_len, pause) = opts.chunking(bytes.len(), word_size)?;
+
+        if pause == 0 && chunk_len >= bytes.len() {
+            return spi.write(bytes).await.map_err(WriteError::Spi);
+        }
+
+        // Platforms limiting the transfer length, like Linux spidev, apply the limit to the
+        // whole transaction, so each chunk gets its own. Paced chunks share a transaction
+        // otherwise, keeping chip select asserted in between.
+        let per_transaction = match opts.max_transfer_len {
+            Some(_) => 1,
+            None => CHUNKS_PER_TRANSACTION,
+        };
+        let mut chunks = bytes.chunks(chunk_len);
+        loop {
+            let mut ops: [Operation<'_, u8>; 2 * CHUNKS_PER_TRANSACTION] =
+                core::array::from_fn(|_| Operation::DelayNs(0));
+            let mut n = 0;
+
+            for chunk in chunks.by_ref().take(per_transaction) {
+                ops[n] = Operation::Write(chunk);
+                n += 1;
+
+                if pause > 0 {
+                    ops[n] = Operation::DelayNs(pause);
+                    n += 1;
+                }
+            }
+
+            if n == 0 {
+                return Ok(());
+            }
+
+            spi.transaction(&mut ops[..n])
+                .await
+                .map_err(WriteError::Spi)?;
+        }
+    }
+
+    /// Write the words from `iter` through a staging buffer
+    #[maybe_async_cfg::only_if(sync)]
+    fn write_iter<SPI, T>(
+        spi: &mut SPI,
+        mut iter: impl Iterator<Item = T>,
+        opts: WriteOptions,
+        _yield_every: usize,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+        T: ToByteSlice + Copy + Default,
+    {
+        let mut buf = [T::default(); BUFFER_SIZE];
+
+        loop {
+            let len = fill(&mut buf, &mut iter);
+            if len == 0 {
+                return Ok(());
+            }
+
+            write(
+                spi,
+                buf[..len].as_byte_slice(),
+                core::mem::size_of::<T>(),
+                opts,
+            )?;
+        }
+    }
+
+    /// Write the words from `iter` using two staging buffers
+    ///
+    /// While one buffer is being written the other is filled from the iterator, so for
+    /// `SpiDevice` implementations which start the transfer (e.g. DMA) on the first poll,
+    /// generating the next chunk overlaps with sending the current one. Implementations which
+    /// complete the write within the first poll degrade to the plain fill-then-write sequence.
+    #[maybe_async_cfg::only_if(async)]
+    async fn write_iter<SPI, T>(
+        spi: &mut SPI,
+        mut iter: impl Iterator<Item = T>,
+        opts: WriteOptions,
+        yield_every: usize,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+        T: ToByteSlice + Copy + Default,
+    {
+        let mut yielder = Yielder::new(yield_every);
+        let mut bufs = [[T::default(); BUFFER_SIZE]; 2];
+        let (first, second) = bufs.split_at_mut(1);
+        let (mut front, mut back) = (&mut first[0], &mut second[0]);
+
+        let mut len = fill(front, &mut iter);
+
+        while len > 0 {
+            let mut next = None;
+
+            {
+                let mut write = pin!(write(
+                    spi,
+                    front[..len].as_byte_slice(),
+                    core::mem::size_of::<T>(),
+                    opts,
+                ));
+
+                poll_fn(|cx| {
+                    let poll = write.as_mut().poll(cx);
+
+                    // Kick off the transfer first, then stage the next chunk while it is in flight,
+                    // unless the write already failed and the words would only be discarded
+                    if next.is_none() && !matches!(poll, Poll::Ready(Err(_))) {
+                        next = Some(fill(back, &mut iter));
+                    }
+
+                    poll
+                })
+                .await?;
+            }
+
+            core::mem::swap(&mut front, &mut back);
+            len = next.unwrap_or(0);
+
+            yielder.chunk_flushed().await;
+        }
+
+        Ok(())
+    }
+
+    /// Write the words from `iter` through the caller provided byte buffer `staging`
+    async fn write_iter_staged<SPI, T>(
+        spi: &mut SPI,
+        mut iter: impl Iterator<Item = T>,
+        opts: WriteOptions,
+        staging: &mut [u8],
+        yield_every: usize,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+        T: ToByteSlice,
+    {
+        let mut yielder = Yielder::new(yield_every);
+        let word_size = core::mem::size_of::<T>();
+        if staging.len() < word_size {
+            return Err(DisplayError::InvalidFormatError.into());
+        }
+
+        loop {
+            let len = fill_bytes(staging, &mut iter);
+            if len == 0 {
+                return Ok(());
+            }
+
+            write(spi, &staging[..len], word_size, opts).await?;
+            yielder.chunk_flushed().await;
+        }
+    }
+
+    /// Write `words` in the byte order of their format
+    ///
+    /// `yield_every` is only used by the `async` variant, see
+    /// [SpiInterface::with_yield_every](crate::SpiInterface::with_yield_every).
+    pub(crate) async fn send_u8<SPI>(
+        spi: &mut SPI,
+        words: DataFormat<'_>,
+        opts: WriteOptions,
+        yield_every: usize,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+    {
+        send_u8_staged(spi, words, opts, yield_every, None).await
+    }
+
+    /// Write the words from `iter` through `staging` if given, or a buffer on the stack
+    async fn write_words<SPI, T>(
+        spi: &mut SPI,
+        iter: impl Iterator<Item = T>,
+        opts: WriteOptions,
+        yield_every: usize,
+        staging: Option<&mut [u8]>,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+        T: ToByteSlice + Copy + Default,
+    {
+        match staging {
+            Some(staging) => write_iter_staged(spi, iter, opts, staging, yield_every).await,
+            None => write_iter(spi, iter, opts, yield_every).await,
+        }
+    }
+
+    /// Write `words` in the byte order of their format, serializing iterators into `staging`
+    /// instead of a buffer on the stack if given
+    pub(crate) async fn send_u8_staged<SPI>(
+        spi: &mut SPI,
+        words: DataFormat<'_>,
+        opts: WriteOptions,
+        yield_every: usize,
+        staging: Option<&mut [u8]>,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+    {
+        if opts.bit_order == BitOrder::LsbFirst {
+            let map = reverse_bits as fn(u8) -> u8;
+            return send_buffered(spi, words, opts, yield_every, staging, map).await;
+        }
+
+        if opts.bounce_slices {
+            let map = core::convert::identity as fn(u8) -> u8;
+            return send_buffered(spi, words, opts, yield_every, staging, map).await;
+        }
+
+        match words {
+            DataFormat::U8(slice) => write(spi, slice, 1, opts).await,
+            DataFormat::U16(slice) => write(spi, slice.as_byte_slice(), 2, opts).await,
+            DataFormat::U16LE(slice) => {
+                for v in slice.iter_mut() {
+                    *v = v.to_le();
+                }
+                write(spi, slice.as_byte_slice(), 2, opts).await
+            }
+            DataFormat::U16BE(slice) => {
+                for v in slice.iter_mut() {
+                    *v = v.to_be();
+                }
+                write(spi, slice.as_byte_slice(), 2, opts).await
+            }
+            DataFormat::U8Iter(iter) => write_words(spi, iter, opts, yield_every, staging).await,
+            DataFormat::U16LEIter(iter) => {
+                write_words(spi, iter.map(u16::to_le), opts, yield_every, staging).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                write_words(spi, iter.map(u16::to_be), opts, yield_every, staging).await
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented.into()),
+        }
+    }
+
+    /// Write `words` with `map` applied to every byte
+    ///
+    /// All formats go through a staging buffer, so the caller's slices are neither handed to the
+    /// SPI device nor modified.
+    async fn send_buffered<SPI>(
+        spi: &mut SPI,
+        words: DataFormat<'_>,
+        opts: WriteOptions,
+        yield_every: usize,
+        staging: Option<&mut [u8]>,
+        map: fn(u8) -> u8,
+    ) -> WriteResult<SPI::Error>
+    where
+        SPI: SpiDevice,
+    {
+        let u8_map = |v: &u8| map(*v);
+        let u16_map = |v: u16| u16::from_ne_bytes(v.to_ne_bytes().map(map));
+
+        match words {
+            DataFormat::U8(slice) => {
+                write_words(spi, slice.iter().map(u8_map), opts, yield_every, staging).await
+            }
+            DataFormat::U16(slice) => {
+                let iter = slice.iter().map(|v| u16_map(*v));
+                write_words(spi, iter, opts, yield_every, staging).await
+            }
+            DataFormat::U16LE(slice) => {
+                let iter = slice.iter().map(|v| u16_map(v.to_le()));
+                write_words(spi, iter, opts, yield_every, staging).await
+            }
+            DataFormat::U16BE(slice) => {
+                let iter = slice.iter().map(|v| u16_map(v.to_be()));
+                write_words(spi, iter, opts, yield_every, staging).await
+            }
+            DataFormat::U8Iter(iter) => {
+                write_words(spi, iter.map(map), opts, yield_every, staging).await
+            }
+            DataFormat::U16LEIter(iter) => {
+                let iter = iter.map(|v| u16_map(v.to_le()));
+                write_words(spi, iter, opts, yield_every, staging).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                let iter = iter.map(|v| u16_map(v.to_be()));
+                write_words(spi, iter, opts, yield_every, staging).await
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented.into()),
+        }
+    }
+}
@@ -1,107 +1,209 @@
 //! Generic SPI interface for display drivers
+//!
+//! # Resynchronizing the pins
+//!
+//! The interfaces driving a D/C or chip select pin themselves have a `resync` method driving
+//! those pins to their idle levels. Call it after construction, or after other code, e.g. a
+//! bootloader, touched the pins. With the `nb` feature, [SpiInterface::resync] also abandons a
+//! transfer left unfinished by `try_send_commands` or `try_send_data`.
 
 #![no_std]
 
 mod asynch;
+mod bus;
+mod cascaded;
+#[cfg(feature = "eh02")]
+mod compat;
+mod data;
+mod dcx;
+#[cfg(feature = "embedded-dma")]
+mod dma;
+#[cfg(feature = "embedded-hal-bus")]
+mod exclusive;
+mod format;
+mod null;
+mod packed;
+mod read;
+mod sequence;
+mod sharp;
+mod st7920;
+#[cfg(feature = "nb")]
+mod try_send;
 
-use byte_slice_cast::*;
-use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+pub use bus::{CsTiming, NoCs, SpiBusInterface, SpiInterfaceWithCs};
+pub use cascaded::{CascadedSpiInterface, Half};
+#[cfg(feature = "eh02")]
+pub use compat::{Eh02Error, Eh02Pin, Eh02Spi};
+pub use data::SpiDataInterface;
+pub use dcx::{DcxSpiDevice, SpiDcxInterface};
+pub use display_interface::NoDelay;
+#[cfg(feature = "embedded-dma")]
+pub use dma::{DmaSpiTransfer, DmaSpiWrite, SpiDmaTransfer};
+#[cfg(feature = "embedded-hal-bus")]
+pub use exclusive::ExclusiveSpiInterface;
+pub use null::NullSpiDevice;
+pub use packed::Spi3WirePackedInterface;
+pub use sequence::{SequenceStep, MAX_SEQUENCE_STEPS};
+pub use sharp::SharpMemoryInterface;
+pub use st7920::St7920SerialInterface;
+
+pub use asynch::write_format_async;
+
+pub(crate) use format::blocking::{send_u8, send_u8_staged};
+
+use display_interface::{
+    DataFormat, DisplayError, LowPower, TypedWriteOnlyDataCommand, WriteOnlyDataCommand,
+};
 use embedded_hal::{digital::OutputPin, spi::SpiDevice};
 
-type Result = core::result::Result<(), DisplayError>;
+pub(crate) type Result = core::result::Result<(), DisplayError>;
 
 pub(crate) const BUFFER_SIZE: usize = 64;
 
-fn send_u8<SPI>(spi: &mut SPI, words: DataFormat<'_>) -> Result
-where
-    SPI: SpiDevice,
-{
-    match words {
-        DataFormat::U8(slice) => spi.write(slice).map_err(|_| DisplayError::BusWriteError),
-        DataFormat::U16(slice) => spi
-            .write(slice.as_byte_slice())
-            .map_err(|_| DisplayError::BusWriteError),
-        DataFormat::U16LE(slice) => {
-            for v in slice.as_mut() {
-                *v = v.to_le();
-            }
-            spi.write(slice.as_byte_slice())
-                .map_err(|_| DisplayError::BusWriteError)
-        }
-        DataFormat::U16BE(slice) => {
-            for v in slice.as_mut() {
-                *v = v.to_be();
-            }
-            spi.write(slice.as_byte_slice())
-                .map_err(|_| DisplayError::BusWriteError)
+/// Pause between writes, in nanoseconds
+#[derive(Clone, Copy)]
+pub(crate) enum Gap {
+    /// Write back-to-back
+    None,
+    /// Pause after every byte
+    Byte(u32),
+    /// Pause after every chunk
+    Chunk(u32),
+}
+
+/// Pauses inserted between writes for controllers which cannot keep up with the bus
+///
+/// The pauses are performed as `DelayNs` operations within the SPI transaction, so they are
+/// handled by the `SpiDevice` implementation and don't busy-wait in the `async` implementation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pacing {
+    /// Pause after every command byte, in microseconds
+    pub command_byte_gap_us: u32,
+    /// Pause after every chunk of data, in microseconds
+    pub chunk_gap_us: u32,
+}
+
+impl Pacing {
+    pub(crate) fn command_gap(&self) -> Gap {
+        if self.command_byte_gap_us > 0 {
+            Gap::Byte(self.command_byte_gap_us.saturating_mul(1000))
+        } else {
+            self.data_gap()
         }
-        DataFormat::U8Iter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-
-            for v in iter.into_iter() {
-                buf[i] = v;
-                i += 1;
-
-                if i == buf.len() {
-                    spi.write(&buf).map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
-
-            if i > 0 {
-                spi.write(&buf[..i])
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
-
-            Ok(())
+    }
+
+    pub(crate) fn data_gap(&self) -> Gap {
+        if self.chunk_gap_us > 0 {
+            Gap::Chunk(self.chunk_gap_us.saturating_mul(1000))
+        } else {
+            Gap::None
         }
-        DataFormat::U16LEIter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-
-            for v in iter.map(u16::to_le) {
-                buf[i] = v;
-                i += 1;
-
-                if i == buf.len() {
-                    spi.write(buf.as_byte_slice())
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
-
-            if i > 0 {
-                spi.write(buf[..i].as_byte_slice())
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
-
-            Ok(())
+    }
+}
+
+/// Order in which the bits of each byte are sent
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitOrder {
+    /// Most significant bit first, which is what almost all controllers expect
+    #[default]
+    MsbFirst,
+    /// Least significant bit first
+    LsbFirst,
+}
+
+/// Maximum number of paced chunks written within one transaction
+pub(crate) const CHUNKS_PER_TRANSACTION: usize = 8;
+
+/// How the bytes of a `DataFormat` are split into writes
+#[derive(Clone, Copy)]
+pub(crate) struct WriteOptions {
+    pub(crate) gap: Gap,
+    pub(crate) max_transfer_len: Option<usize>,
+    pub(crate) bit_order: BitOrder,
+    pub(crate) bounce_slices: bool,
+}
+
+impl WriteOptions {
+    /// Write every buffer in one go, back-to-back
+    pub(crate) const PLAIN: Self = Self {
+        gap: Gap::None,
+        max_transfer_len: None,
+        bit_order: BitOrder::MsbFirst,
+        bounce_slices: false,
+    };
+
+    /// Length of the chunks a buffer of `len` bytes made up of `word_size` byte words is split
+    /// into, and the pause after each chunk in nanoseconds
+    pub(crate) fn chunking(
+        &self,
+        len: usize,
+        word_size: usize,
+    ) -> core::result::Result<(usize, u32), DisplayError> {
+        if self.max_transfer_len.is_some_and(|max| max < word_size) {
+            return Err(DisplayError::InvalidFormatError);
         }
-        DataFormat::U16BEIter(iter) => {
-            let mut buf = [0; BUFFER_SIZE];
-            let mut i = 0;
-            let len = buf.len();
-
-            for v in iter.map(u16::to_be) {
-                buf[i] = v;
-                i += 1;
-
-                if i == len {
-                    spi.write(buf.as_byte_slice())
-                        .map_err(|_| DisplayError::BusWriteError)?;
-                    i = 0;
-                }
-            }
-
-            if i > 0 {
-                spi.write(buf[..i].as_byte_slice())
-                    .map_err(|_| DisplayError::BusWriteError)?;
-            }
-
-            Ok(())
+
+        let (chunk_len, pause) = match self.gap {
+            Gap::None => (len, 0),
+            Gap::Byte(ns) => (1, ns),
+            Gap::Chunk(ns) => (len, ns),
+        };
+
+        let chunk_len = match self.max_transfer_len {
+            Some(max) => chunk_len.min(max),
+            None => chunk_len,
+        };
+
+        // Never split a word across two chunks
+        let chunk_len = chunk_len.max(word_size.min(len));
+        let chunk_len = chunk_len - chunk_len % word_size;
+
+        Ok((chunk_len.max(1), pause))
+    }
+}
+
+/// Write `format` to `spi`, using `chunk` to serialize iterator formats
+///
+/// This is the format handling of [SpiInterface] for interfaces which need to frame the data
+/// differently, e.g. with a prefix byte. Slices are written in one transfer, with `U16LE` and
+/// `U16BE` slices being converted to the requested byte order in place first. Iterators are
+/// written in transfers of up to `chunk.len()` bytes, each in its own transaction. `chunk`
+/// needs to hold at least one word of the format, otherwise
+/// [InvalidFormatError](DisplayError::InvalidFormatError) is returned. Nothing is written for
+/// empty iterators.
+///
+/// See [write_format_async] for `async` interfaces.
+pub fn write_format<SPI>(
+    spi: &mut SPI,
+    format: DataFormat<'_>,
+    chunk: &mut [u8],
+) -> core::result::Result<(), DisplayError>
+where
+    SPI: SpiDevice,
+{
+    send_u8_staged(spi, format, WriteOptions::PLAIN, 0, Some(chunk)).map_err(Into::into)
+}
+
+/// Error of [SpiInterface], keeping the errors of the SPI device and the D/C pin
+///
+/// Converts into [DisplayError] for drivers written against [WriteOnlyDataCommand].
+#[derive(Debug)]
+pub enum SpiInterfaceError<SPI, DC> {
+    /// The SPI device failed
+    Spi(SPI),
+    /// Setting the D/C pin failed
+    Dc(DC),
+    /// The data couldn't be sent, e.g. because of an unsupported format
+    Format(DisplayError),
+}
+
+impl<SPI, DC> From<SpiInterfaceError<SPI, DC>> for DisplayError {
+    fn from(e: SpiInterfaceError<SPI, DC>) -> Self {
+        match e {
+            SpiInterfaceError::Spi(_) => DisplayError::BusWriteError,
+            SpiInterfaceError::Dc(_) => DisplayError::DCError,
+            SpiInterfaceError::Format(e) => e,
         }
-        _ => Err(DisplayError::DataFormatNotImplemented),
     }
 }
 
@@ -111,12 +213,146 @@ where
 pub struct SpiInterface<SPI, DC> {
     spi: SPI,
     dc: DC,
+    yield_every: usize,
+    pacing: Pacing,
+    max_transfer_len: Option<usize>,
+    bit_order: BitOrder,
+    bounce_slices: bool,
+    read_dummy_bits: u8,
+    #[cfg(feature = "static-buffer")]
+    staging: Option<&'static mut [u8]>,
+    #[cfg(feature = "nb")]
+    progress: display_interface::TryProgress,
 }
 
 impl<SPI, DC> SpiInterface<SPI, DC> {
     /// Create new SPI interface for communication with a display driver
     pub fn new(spi: SPI, dc: DC) -> Self {
-        Self { spi, dc }
+        Self {
+            spi,
+            dc,
+            yield_every: 0,
+            pacing: Pacing::default(),
+            max_transfer_len: None,
+            bit_order: BitOrder::MsbFirst,
+            bounce_slices: false,
+            read_dummy_bits: 0,
+            #[cfg(feature = "static-buffer")]
+            staging: None,
+            #[cfg(feature = "nb")]
+            progress: display_interface::TryProgress::new(),
+        }
+    }
+
+    /// Create new SPI interface which serializes iterator formats into `buffer`
+    ///
+    /// Many HALs only use DMA for buffers in particular memory regions, which the staging buffer
+    /// on the stack usually isn't in. Pass a buffer placed in DMA capable memory, e.g. from
+    /// `static_cell` or `cortex_m::singleton!`, to let all iterator transfers use DMA:
+    ///
+    /// - ESP32: internal SRAM, not the stack in PSRAM or flash cached memory.
+    /// - STM32H7: AXI SRAM or the D2 SRAMs, not the DTCM which holds the stack by default.
+    /// - STM32F7: SRAM1/SRAM2, not the DTCM.
+    /// - nRF52: any RAM; EasyDMA can't read from flash.
+    ///
+    /// The buffer needs to hold at least one word of the format. The size of each transfer is
+    /// bounded by the buffer, so larger buffers mean fewer, longer transfers. Slice formats are
+    /// still written from the caller's slice. In the `async` implementation iterator transfers
+    /// are not overlapped with filling the next chunk when using a single buffer. HALs which don't
+    /// use DMA work the same as with [SpiInterface::new].
+    #[cfg(feature = "static-buffer")]
+    pub fn new_with_buffer(spi: SPI, dc: DC, buffer: &'static mut [u8]) -> Self {
+        let mut iface = Self::new(spi, dc);
+        iface.staging = Some(buffer);
+        iface
+    }
+
+    /// The SPI device and the staging buffer for iterator formats, if any
+    pub(crate) fn spi_and_staging(&mut self) -> (&mut SPI, Option<&mut [u8]>) {
+        #[cfg(feature = "static-buffer")]
+        let staging = self.staging.as_deref_mut();
+        #[cfg(not(feature = "static-buffer"))]
+        let staging = None;
+
+        (&mut self.spi, staging)
+    }
+
+    /// Split writes into transfers of at most `len` bytes
+    ///
+    /// Some platforms limit the length of a single transfer, e.g. Linux spidev to its `bufsiz`
+    /// (4096 bytes by default) and nRF EasyDMA to 255 bytes. Transfers are split without ever
+    /// separating the two bytes of a 16-bit word, and every chunk is written in a transaction of
+    /// its own, as spidev limits the bytes of a whole transaction. So chip select may be
+    /// deasserted between the chunks. A limit smaller than one word results in an
+    /// [InvalidFormatError](DisplayError::InvalidFormatError) for 16-bit formats. By default
+    /// transfers are not split.
+    pub fn with_max_transfer_len(mut self, len: usize) -> Self {
+        self.max_transfer_len = Some(len);
+        self
+    }
+
+    pub(crate) fn command_options(&self) -> WriteOptions {
+        WriteOptions {
+            gap: self.pacing.command_gap(),
+            max_transfer_len: self.max_transfer_len,
+            bit_order: self.bit_order,
+            bounce_slices: self.bounce_slices,
+        }
+    }
+
+    pub(crate) fn data_options(&self) -> WriteOptions {
+        WriteOptions {
+            gap: self.pacing.data_gap(),
+            max_transfer_len: self.max_transfer_len,
+            bit_order: self.bit_order,
+            bounce_slices: self.bounce_slices,
+        }
+    }
+
+    /// Reverse the bits of every byte in software
+    ///
+    /// This is meant for controllers expecting LSB-first bytes when the SPI peripheral can't be
+    /// configured accordingly through the HAL. Since slice formats are sent through the staging
+    /// buffer as well in this mode, the caller's slices are left untouched. By default bytes are
+    /// sent as they are, MSB-first.
+    pub fn with_bit_order(mut self, order: BitOrder) -> Self {
+        self.bit_order = order;
+        self
+    }
+
+    /// Copy slice formats through the staging buffer instead of passing them to the SPI device
+    ///
+    /// Some DMA engines can't read from flash, so `const` tables and images stored in flash make
+    /// the HAL fail or fall back to slow transfers without DMA. This affects e.g. the ESP32 family,
+    /// where flash is mapped into the address space but inaccessible to the GDMA, and nRF52, whose
+    /// EasyDMA only reads RAM. With bouncing enabled slices are copied in chunks of whole words
+    /// through the staging buffer on the stack, or the one passed to `new_with_buffer` with the
+    /// `static-buffer` feature, which is why `U16LE` and `U16BE` slices are not converted in place
+    /// in this mode. Disabled by default.
+    pub fn with_bounce_const_data(mut self, bounce: bool) -> Self {
+        self.bounce_slices = bounce;
+        self
+    }
+
+    /// Pause between writes for controllers which cannot accept bytes back-to-back
+    ///
+    /// Without pacing, which is the default, all words are written as fast as the bus allows.
+    pub fn with_pacing(mut self, pacing: Pacing) -> Self {
+        self.pacing = pacing;
+        self
+    }
+
+    /// Yield to the executor after every `chunks` staging buffer flushes in the `async`
+    /// implementation
+    ///
+    /// Filling the staging buffer from an iterator is pure CPU work, and on fast buses the writes
+    /// may complete without ever suspending, so a large iterator transfer can otherwise keep other
+    /// tasks from running until it is done. Yielding does not depend on a particular executor. The
+    /// default of 0 never yields, which gives the highest throughput. Slice formats are written
+    /// in one go and are not affected.
+    pub fn with_yield_every(mut self, chunks: usize) -> Self {
+        self.yield_every = chunks;
+        self
     }
 
     /// Consume the display interface and return
@@ -126,24 +362,75 @@ impl<SPI, DC> SpiInterface<SPI, DC> {
     }
 }
 
-impl<SPI, DC> WriteOnlyDataCommand for SpiInterface<SPI, DC>
+impl<SPI, DC> TypedWriteOnlyDataCommand for SpiInterface<SPI, DC>
 where
     SPI: SpiDevice,
     DC: OutputPin,
 {
-    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+    type Error = SpiInterfaceError<SPI::Error, DC::Error>;
+
+    fn send_commands_typed(
+        &mut self,
+        cmds: DataFormat<'_>,
+    ) -> core::result::Result<(), Self::Error> {
         // 1 = data, 0 = command
-        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+        self.dc.set_low().map_err(SpiInterfaceError::Dc)?;
 
         // Send words over SPI
-        send_u8(&mut self.spi, cmds)
+        let opts = self.command_options();
+        let (spi, staging) = self.spi_and_staging();
+        Ok(send_u8_staged(spi, cmds, opts, 0, staging)?)
     }
 
-    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> core::result::Result<(), Self::Error> {
         // 1 = data, 0 = command
-        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+        self.dc.set_high().map_err(SpiInterfaceError::Dc)?;
 
         // Send words over SPI
-        send_u8(&mut self.spi, buf)
+        let opts = self.data_options();
+        let (spi, staging) = self.spi_and_staging();
+        Ok(send_u8_staged(spi, buf, opts, 0, staging)?)
+    }
+}
+
+impl<SPI, DC> WriteOnlyDataCommand for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send_commands_typed(cmds).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send_data_typed(buf).map_err(Into::into)
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    DC: OutputPin,
+{
+    /// Drive the D/C pin low for command, leaving chip select to the [SpiDevice]
+    pub fn resync(&mut self) -> Result {
+        #[cfg(feature = "nb")]
+        self.progress.reset();
+        self.dc.set_low().map_err(|_| DisplayError::DCError)
+    }
+}
+
+/// Chip select is managed by the [SpiDevice] and already deasserted between calls, so suspending
+/// only drives the D/C pin low. Nothing is cached, every call sets the D/C pin anew.
+impl<SPI, DC> LowPower for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    fn suspend(&mut self) -> Result {
+        self.dc.set_low().map_err(|_| DisplayError::DCError)
+    }
+
+    fn resume(&mut self) -> Result {
+        Ok(())
     }
 }
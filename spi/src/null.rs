@@ -0,0 +1,76 @@
+//! SPI device discarding everything written to it, for benchmarks
+
+use core::{convert::Infallible, hint::black_box};
+
+use embedded_hal::spi::{ErrorType, Operation, SpiDevice};
+
+/// [SpiDevice] accepting every transaction at once and only counting the bytes written
+///
+/// Paired with [NoCs](crate::NoCs) as data/command pin it measures the time an interface, or a
+/// driver rendering through it, spends on its own, without any bus in the way. The written bytes
+/// are passed through [black_box], so the work producing them isn't optimized away. Reads leave
+/// the buffers untouched.
+///
+/// ```
+/// use display_interface_spi::{NoCs, NullSpiDevice, SpiInterface};
+/// use display_interface::{DataFormat, WriteOnlyDataCommand};
+///
+/// let mut iface = SpiInterface::new(NullSpiDevice::new(), NoCs);
+/// iface.send_data(DataFormat::U16BEIter(&mut (0..100))).unwrap();
+/// let (spi, _) = iface.release();
+/// assert_eq!(spi.bytes(), 200);
+/// ```
+#[derive(Debug, Default)]
+pub struct NullSpiDevice {
+    bytes: usize,
+}
+
+impl NullSpiDevice {
+    /// Create a device which hasn't seen any bytes yet
+    pub const fn new() -> Self {
+        Self { bytes: 0 }
+    }
+
+    /// Number of bytes written since creation or the last [reset](Self::reset)
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Start counting from zero again
+    pub fn reset(&mut self) {
+        self.bytes = 0;
+    }
+
+    fn count(&mut self, operations: &mut [Operation<'_, u8>]) {
+        for op in operations {
+            let written = match op {
+                Operation::Write(buf) => black_box(&**buf).len(),
+                Operation::Transfer(_, write) => black_box(&**write).len(),
+                Operation::TransferInPlace(buf) => black_box(&**buf).len(),
+                Operation::Read(_) | Operation::DelayNs(_) => 0,
+            };
+            self.bytes += written;
+        }
+    }
+}
+
+impl ErrorType for NullSpiDevice {
+    type Error = Infallible;
+}
+
+impl SpiDevice for NullSpiDevice {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        self.count(operations);
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::spi::SpiDevice for NullSpiDevice {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        self.count(operations);
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+//! 3-wire SPI interface packing 9-bit words into a plain 8-bit byte stream
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use embedded_hal::spi::SpiDevice;
+
+use crate::Result;
+
+/// Number of 9-bit words staged per transaction, a multiple of 8 so transactions end on a byte
+/// boundary
+const WORDS_PER_CHUNK: usize = 64;
+
+/// Bytes taken up by [WORDS_PER_CHUNK] 9-bit words
+const PACKED_CHUNK: usize = WORDS_PER_CHUNK * 9 / 8;
+
+/// Packs 9-bit words, MSB first, into a byte buffer
+struct Packer {
+    buf: [u8; PACKED_CHUNK],
+    words: usize,
+}
+
+impl Packer {
+    fn new() -> Self {
+        Self {
+            buf: [0; PACKED_CHUNK],
+            words: 0,
+        }
+    }
+
+    /// Append the data/command bit `dc` followed by `byte`
+    fn push(&mut self, dc: bool, byte: u8) {
+        let word = (u16::from(dc) << 8) | u16::from(byte);
+
+        // Every group of 8 words takes up 9 bytes, word `k` of a group starts at bit `k` of
+        // byte `k` of the group
+        let k = self.words % 8;
+        let i = self.words / 8 * 9 + k;
+
+        if k == 0 {
+            self.buf[i] = (word >> 1) as u8;
+        } else {
+            self.buf[i] |= (word >> (k + 1)) as u8;
+        }
+        self.buf[i + 1] = (word << (7 - k)) as u8;
+
+        self.words += 1;
+    }
+
+    fn is_full(&self) -> bool {
+        self.words == WORDS_PER_CHUNK
+    }
+
+    /// The packed bytes, with the last byte padded with zeros
+    fn bytes(&self) -> &[u8] {
+        &self.buf[..(self.words * 9).div_ceil(8)]
+    }
+
+    fn clear(&mut self) {
+        self.words = 0;
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    use display_interface::{DataFormat, DisplayError};
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::spi::SpiDevice;
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::spi::SpiDevice;
+
+    use super::Packer;
+    use crate::Result;
+
+    async fn write_packed<SPI>(spi: &mut SPI, dc: bool, bytes: impl Iterator<Item = u8>) -> Result
+    where
+        SPI: SpiDevice,
+    {
+        let mut packer = Packer::new();
+
+        for byte in bytes {
+            packer.push(dc, byte);
+
+            if packer.is_full() {
+                spi.write(packer.bytes())
+                    .await
+                    .map_err(|_| DisplayError::BusWriteError)?;
+                packer.clear();
+            }
+        }
+
+        if packer.words > 0 {
+            spi.write(packer.bytes())
+                .await
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+
+        Ok(())
+    }
+
+    /// Send `words` with the data/command bit `dc` in front of every byte
+    pub(crate) async fn send_packed<SPI>(spi: &mut SPI, dc: bool, words: DataFormat<'_>) -> Result
+    where
+        SPI: SpiDevice,
+    {
+        match words {
+            DataFormat::U8(slice) => write_packed(spi, dc, slice.iter().copied()).await,
+            DataFormat::U16(slice) => {
+                write_packed(spi, dc, slice.iter().flat_map(|v| v.to_ne_bytes())).await
+            }
+            DataFormat::U16LE(slice) => {
+                write_packed(spi, dc, slice.iter().flat_map(|v| v.to_le_bytes())).await
+            }
+            DataFormat::U16BE(slice) => {
+                write_packed(spi, dc, slice.iter().flat_map(|v| v.to_be_bytes())).await
+            }
+            DataFormat::U8Iter(iter) => write_packed(spi, dc, iter).await,
+            DataFormat::U16LEIter(iter) => {
+                write_packed(spi, dc, iter.flat_map(u16::to_le_bytes)).await
+            }
+            DataFormat::U16BEIter(iter) => {
+                write_packed(spi, dc, iter.flat_map(u16::to_be_bytes)).await
+            }
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
+
+/// 3-wire SPI display interface for SPI peripherals with 8-bit frames only.
+///
+/// Controllers in 3-wire mode expect 9-bit words made up of the data/command bit followed by
+/// the byte. This interface packs every 8 such words into 9 bytes, so any `SpiDevice` can
+/// drive the display without a data/command pin or support for 9-bit frames.
+///
+/// Each call is sent in transactions of up to 64 words. If the number of bytes in a call isn't
+/// a multiple of 8, the last byte is padded with up to 7 zero bits. These never complete a word,
+/// so the controller discards them when chip select is deasserted at the end of the
+/// transaction. Controllers which keep incomplete words across chip select cycles are not
+/// supported.
+pub struct Spi3WirePackedInterface<SPI> {
+    pub(crate) spi: SPI,
+}
+
+impl<SPI> Spi3WirePackedInterface<SPI> {
+    /// Create new SPI interface for communication with a display driver
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver
+    pub fn release(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> WriteOnlyDataCommand for Spi3WirePackedInterface<SPI>
+where
+    SPI: SpiDevice,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        blocking::send_packed(&mut self.spi, false, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        // 1 = data, 0 = command
+        blocking::send_packed(&mut self.spi, true, buf)
+    }
+}
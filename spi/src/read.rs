@@ -0,0 +1,109 @@
+//! Reading registers back from the display
+
+use display_interface::{DisplayError, ReadData};
+use embedded_hal::{digital::OutputPin, spi::SpiDevice};
+
+use crate::{Result, SpiInterface};
+
+/// Drop the leading `bits` dummy bits from the bit stream made up of `buf` followed by `extra`
+fn skip_dummy_bits(buf: &mut [u8], extra: u8, bits: u8) {
+    if bits == 0 || bits >= 8 {
+        return;
+    }
+
+    for i in 0..buf.len() {
+        let next = buf.get(i + 1).copied().unwrap_or(extra);
+        buf[i] = (buf[i] << bits) | (next >> (8 - bits));
+    }
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    use display_interface::DisplayError;
+    use embedded_hal::spi::Operation;
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::spi::SpiDevice;
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::spi::SpiDevice;
+
+    use super::skip_dummy_bits;
+    use crate::Result;
+
+    /// Write `prefix` and read into `buf` within one transaction, skipping `bits` dummy bits
+    /// before the data
+    pub(crate) async fn read_ops<SPI>(
+        spi: &mut SPI,
+        prefix: Option<&[u8]>,
+        buf: &mut [u8],
+        bits: u8,
+    ) -> Result
+    where
+        SPI: SpiDevice,
+    {
+        let mut dummy = [0; 1];
+        let prefix = Operation::Write(prefix.unwrap_or(&[]));
+
+        // Dummy bits short of a byte shift the data into one more byte read after it
+        let result = match bits {
+            0 => spi.transaction(&mut [prefix, Operation::Read(buf)]).await,
+            8 => {
+                spi.transaction(&mut [prefix, Operation::Read(&mut dummy), Operation::Read(buf)])
+                    .await
+            }
+            _ => {
+                spi.transaction(&mut [prefix, Operation::Read(buf), Operation::Read(&mut dummy)])
+                    .await
+            }
+        };
+        result.map_err(|_| DisplayError::BusReadError)?;
+
+        skip_dummy_bits(buf, dummy[0], bits);
+        Ok(())
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC> {
+    /// Skip `bits` dummy clock cycles before the data returned by read commands
+    ///
+    /// ILI93xx controllers insert a dummy clock cycle before the data returned by multi-byte
+    /// read commands like RDDID (0x04) and RDDST (0x09). Values from 1 to 7 are handled by
+    /// clocking in one additional byte and shifting the result, 8 skips a whole dummy byte.
+    /// Larger values are treated as 8. The default is 0.
+    pub fn with_read_dummy_bits(mut self, bits: u8) -> Self {
+        self.read_dummy_bits = bits.min(8);
+        self
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Send the command `cmd` and read its response into `buf` within one transaction
+    ///
+    /// The data/command pin stays low while reading; controllers ignore it while they drive the
+    /// data line, so chip select can stay asserted between command and response.
+    pub fn read_register(&mut self, cmd: u8, buf: &mut [u8]) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_low().map_err(|_| DisplayError::DCError)?;
+
+        blocking::read_ops(&mut self.spi, Some(&[cmd]), buf, self.read_dummy_bits)
+    }
+}
+
+impl<SPI, DC> ReadData for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    fn read_data(&mut self, buf: &mut [u8]) -> Result {
+        // 1 = data, 0 = command
+        self.dc.set_high().map_err(|_| DisplayError::DCError)?;
+
+        blocking::read_ops(&mut self.spi, None, buf, self.read_dummy_bits)
+    }
+}
@@ -0,0 +1,322 @@
+//! Command sequences with delays kept within the transactions
+
+use display_interface::DisplayError;
+use embedded_hal::{
+    delay::DelayNs,
+    digital::OutputPin,
+    spi::{Operation, SpiBus, SpiDevice},
+};
+
+use crate::{BitOrder, Result, SpiBusInterface, SpiInterface};
+
+/// Maximum number of steps of a sequence sent with [SpiInterface::send_sequence]
+pub const MAX_SEQUENCE_STEPS: usize = 16;
+
+/// One step of a sequence sent with [SpiInterface::send_sequence]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SequenceStep<'a> {
+    /// Write the bytes with the D/C pin low
+    Command(&'a [u8]),
+    /// Write the bytes with the D/C pin high
+    Data(&'a [u8]),
+    /// Pause for the given number of nanoseconds
+    DelayNs(u32),
+}
+
+impl SequenceStep<'_> {
+    /// Whether the step is written as data, `None` for delays
+    fn is_data(&self) -> Option<bool> {
+        match self {
+            SequenceStep::Command(_) => Some(false),
+            SequenceStep::Data(_) => Some(true),
+            SequenceStep::DelayNs(_) => None,
+        }
+    }
+}
+
+/// Split `steps` into runs written with the same level of the D/C pin, each sent as one
+/// transaction
+///
+/// Delays belong to the run before them, leading delays to the first run.
+pub(crate) fn phase_runs<'s, 'a>(
+    steps: &'s [SequenceStep<'a>],
+) -> core::result::Result<impl Iterator<Item = (bool, &'s [SequenceStep<'a>])>, DisplayError> {
+    if steps.len() > MAX_SEQUENCE_STEPS {
+        return Err(DisplayError::InvalidFormatError);
+    }
+
+    let mut rest = steps;
+    Ok(core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let data = rest.iter().find_map(SequenceStep::is_data).unwrap_or(false);
+        let len = rest
+            .iter()
+            .position(|step| step.is_data().is_some_and(|d| d != data))
+            .unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(len);
+        rest = tail;
+        Some((data, run))
+    }))
+}
+
+/// The operations performing the run `steps`, and how many of them are used
+pub(crate) fn sequence_ops<'a>(
+    steps: &[SequenceStep<'a>],
+) -> ([Operation<'a, u8>; MAX_SEQUENCE_STEPS], usize) {
+    let mut ops: [Operation<'a, u8>; MAX_SEQUENCE_STEPS] =
+        core::array::from_fn(|_| Operation::DelayNs(0));
+    for (op, step) in ops.iter_mut().zip(steps) {
+        *op = match *step {
+            SequenceStep::Command(bytes) | SequenceStep::Data(bytes) => Operation::Write(bytes),
+            SequenceStep::DelayNs(ns) => Operation::DelayNs(ns),
+        };
+    }
+
+    (ops, steps.len().min(MAX_SEQUENCE_STEPS))
+}
+
+/// The steps of [SpiInterface::send_command_then_wait], leaving out empty parameters
+pub(crate) fn command_then_wait<'a>(
+    cmd: &'a [u8; 1],
+    params: &'a [u8],
+    ns: u32,
+) -> ([SequenceStep<'a>; 3], usize) {
+    let steps = [
+        SequenceStep::Command(cmd),
+        SequenceStep::DelayNs(ns),
+        SequenceStep::Data(params),
+    ];
+    (steps, if params.is_empty() { 2 } else { 3 })
+}
+
+#[maybe_async_cfg::maybe(
+    sync(cfg(all()), self = "blocking"),
+    async(cfg(all()), self = "asynch")
+)]
+pub(crate) mod shared {
+    use display_interface::DisplayError;
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::delay::DelayNs;
+    use embedded_hal::digital::OutputPin;
+    #[maybe_async_cfg::only_if(sync)]
+    use embedded_hal::spi::{SpiBus, SpiDevice};
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::delay::DelayNs;
+    #[maybe_async_cfg::only_if(async)]
+    use embedded_hal_async::spi::{SpiBus, SpiDevice};
+
+    use super::{phase_runs, sequence_ops, SequenceStep};
+    use crate::{
+        bus::{CsGuard, CsTiming},
+        Result,
+    };
+
+    fn set_dc(dc: &mut impl OutputPin, data: bool) -> Result {
+        // 1 = data, 0 = command
+        if data { dc.set_high() } else { dc.set_low() }.map_err(|_| DisplayError::DCError)
+    }
+
+    /// Send `steps` to `spi`, one transaction per run of steps of the same phase
+    pub(crate) async fn send_sequence<SPI, DC>(
+        spi: &mut SPI,
+        dc: &mut DC,
+        steps: &[SequenceStep<'_>],
+    ) -> Result
+    where
+        SPI: SpiDevice,
+        DC: OutputPin,
+    {
+        for (data, run) in phase_runs(steps)? {
+            let (mut ops, n) = sequence_ops(run);
+            set_dc(dc, data)?;
+            spi.transaction(&mut ops[..n])
+                .await
+                .map_err(|_| DisplayError::BusWriteError)?;
+        }
+        Ok(())
+    }
+
+    /// Send `steps` to `spi` within a single assertion of `cs`, pausing with `pause`
+    ///
+    /// `delay` is only used for the chip select `timing`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn send_sequence_bus<SPI, DC, CS, D, P>(
+        spi: &mut SPI,
+        dc: &mut DC,
+        cs: &mut CS,
+        delay: &mut D,
+        timing: CsTiming,
+        steps: &[SequenceStep<'_>],
+        pause: &mut P,
+    ) -> Result
+    where
+        SPI: SpiBus,
+        DC: OutputPin,
+        CS: OutputPin,
+        D: DelayNs,
+        P: DelayNs,
+    {
+        let mut runs = phase_runs(steps)?.peekable();
+        let Some(&(mut phase, _)) = runs.peek() else {
+            return Ok(());
+        };
+        set_dc(dc, phase)?;
+
+        // Deasserted on drop if the future is cancelled
+        let cs = CsGuard::assert(cs)?;
+        if timing.setup_ns > 0 {
+            delay.delay_ns(timing.setup_ns).await;
+        }
+
+        let mut result = Ok(());
+        for (data, run) in runs {
+            if data != phase {
+                result = set_dc(dc, data);
+                phase = data;
+            }
+            if result.is_ok() {
+                result = write_run(spi, pause, run).await;
+            }
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if timing.hold_ns > 0 {
+            delay.delay_ns(timing.hold_ns).await;
+        }
+        result.and(cs.deassert())
+    }
+
+    /// Write the bytes of `run`, pausing with `delay`
+    ///
+    /// The bus is flushed before every pause and at the end, so no byte is still being shifted
+    /// out while waiting or when the D/C pin changes.
+    async fn write_run<SPI, D>(spi: &mut SPI, delay: &mut D, run: &[SequenceStep<'_>]) -> Result
+    where
+        SPI: SpiBus,
+        D: DelayNs,
+    {
+        let mut pending = false;
+        for step in run {
+            match *step {
+                SequenceStep::Command(bytes) | SequenceStep::Data(bytes) => {
+                    spi.write(bytes)
+                        .await
+                        .map_err(|_| DisplayError::BusWriteError)?;
+                    pending = true;
+                }
+                SequenceStep::DelayNs(ns) => {
+                    if pending {
+                        spi.flush().await.map_err(|_| DisplayError::BusWriteError)?;
+                        pending = false;
+                    }
+                    delay.delay_ns(ns).await;
+                }
+            }
+        }
+
+        if pending {
+            spi.flush().await.map_err(|_| DisplayError::BusWriteError)?;
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC> {
+    /// Fail if the interface changes the bytes or transfers of a write, which sequences don't
+    pub(crate) fn check_sequence_support(&self) -> Result {
+        if self.bit_order != BitOrder::MsbFirst || self.max_transfer_len.is_some() {
+            return Err(DisplayError::Unsupported);
+        }
+        Ok(())
+    }
+}
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Send `steps`, keeping delays within the transactions
+    ///
+    /// The D/C pin can't change while a transaction is running, so every run of steps of the same
+    /// phase, commands or data, is sent as one transaction, with the D/C pin set before it. Delays
+    /// are performed as `DelayNs` operations by the `SpiDevice` as part of the run before them,
+    /// so chip select stays asserted during a pause after a command. It is up to the `SpiDevice`
+    /// whether chip select is deasserted between the transactions, i.e. between that pause and
+    /// the data following it; use [SpiBusInterface::send_sequence] for controllers which need it
+    /// asserted throughout.
+    ///
+    /// The bytes are sent as they are, without pacing. Interfaces set up to reverse the bits with
+    /// [with_bit_order](SpiInterface::with_bit_order) or to split transfers with
+    /// [with_max_transfer_len](SpiInterface::with_max_transfer_len) can't keep a run in one
+    /// transaction, so they fail with [Unsupported](DisplayError::Unsupported). More than
+    /// [MAX_SEQUENCE_STEPS] steps result in an
+    /// [InvalidFormatError](DisplayError::InvalidFormatError). Nothing is sent in either case.
+    pub fn send_sequence(&mut self, steps: &[SequenceStep<'_>]) -> Result {
+        self.check_sequence_support()?;
+        blocking::send_sequence(&mut self.spi, &mut self.dc, steps)
+    }
+
+    /// Send the command `cmd`, wait `ns` nanoseconds, then send `params` as data
+    ///
+    /// The wait is part of the transaction of the command, the parameters are sent in a
+    /// transaction of their own, which is left out if there are none. See
+    /// [SpiInterface::send_sequence].
+    pub fn send_command_then_wait(&mut self, cmd: u8, params: &[u8], ns: u32) -> Result {
+        let cmd = [cmd];
+        let (steps, n) = command_then_wait(&cmd, params, ns);
+        self.send_sequence(&steps[..n])
+    }
+}
+
+impl<SPI, DC, CS, D> SpiBusInterface<SPI, DC, CS, D>
+where
+    SPI: SpiBus,
+    DC: OutputPin,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Send `steps` with chip select asserted throughout
+    ///
+    /// Unlike [SpiInterface::send_sequence], chip select is asserted once around the whole
+    /// sequence, with the D/C pin changed in between, so it stays asserted from a command through
+    /// a pause to its parameters. Pauses are performed with `delay`, after flushing the bus.
+    ///
+    /// The bytes are sent as they are. More than [MAX_SEQUENCE_STEPS] steps result in an
+    /// [InvalidFormatError](DisplayError::InvalidFormatError) before anything is sent.
+    pub fn send_sequence(
+        &mut self,
+        steps: &[SequenceStep<'_>],
+        delay: &mut impl DelayNs,
+    ) -> Result {
+        blocking::send_sequence_bus(
+            &mut self.spi,
+            &mut self.dc,
+            &mut self.cs,
+            &mut self.delay,
+            self.cs_timing,
+            steps,
+            delay,
+        )
+    }
+
+    /// Send the command `cmd`, wait `ns` nanoseconds with `delay`, then send `params` as data,
+    /// all with chip select asserted
+    ///
+    /// See [SpiBusInterface::send_sequence].
+    pub fn send_command_then_wait(
+        &mut self,
+        cmd: u8,
+        params: &[u8],
+        ns: u32,
+        delay: &mut impl DelayNs,
+    ) -> Result {
+        let cmd = [cmd];
+        let (steps, n) = command_then_wait(&cmd, params, ns);
+        self.send_sequence(&steps[..n], delay)
+    }
+}
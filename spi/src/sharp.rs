@@ -0,0 +1,171 @@
+//! SPI interface for Sharp memory LCDs
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiBus};
+
+use crate::{bus::BusDevice, send_u8, BitOrder, CsTiming, NoDelay, Result, WriteOptions};
+
+/// Data update bit of the mode byte, selecting the write of lines
+const MODE_UPDATE: u8 = 0x01;
+
+/// SPI display interface for Sharp memory LCDs, e.g. the LS027B7DH01 or LS013B7DH03
+///
+/// These panels have no D/C line: every transfer starts with a mode byte, and their chip select
+/// (SCS) is active-high. The bytes are given in the order the panel clocks them in, LSB-first;
+/// the mode bits are thus `0x01` for a data update (M0), `0x02` for the VCOM level (M1) and
+/// `0x04` to clear the memory (M2).
+///
+/// - [WriteOnlyDataCommand::send_commands] takes a single mode byte. A mode without the data
+///   update bit is sent right away followed by the dummy byte, e.g. to clear the memory or to
+///   toggle VCOM. A data update mode is stored for the following data transfers instead.
+/// - [WriteOnlyDataCommand::send_data] takes one or more complete lines, each being the line
+///   address, the pixel bytes and a dummy byte. They are sent in one transfer after the stored
+///   data update mode and followed by the closing dummy byte. Without a data update mode this
+///   results in an [InvalidFormatError](DisplayError::InvalidFormatError).
+///
+/// SCS is asserted high around every transfer. The panels need microseconds of setup and hold
+/// time for it, see [SharpMemoryInterface::with_cs_timing]. HALs which can't send LSB-first can
+/// have the bits reversed in software, see [SharpMemoryInterface::with_bit_order].
+pub struct SharpMemoryInterface<SPI, CS, D = NoDelay> {
+    spi: SPI,
+    cs: CS,
+    delay: D,
+    cs_timing: CsTiming,
+    bit_order: BitOrder,
+    update_mode: Option<u8>,
+}
+
+impl<SPI, CS> SharpMemoryInterface<SPI, CS> {
+    /// Create new SPI interface for communication with a Sharp memory LCD
+    ///
+    /// The SPI bus is expected to send LSB-first.
+    pub fn new(spi: SPI, cs: CS) -> Self {
+        Self {
+            spi,
+            cs,
+            delay: NoDelay,
+            cs_timing: CsTiming::default(),
+            bit_order: BitOrder::MsbFirst,
+            update_mode: None,
+        }
+    }
+}
+
+impl<SPI, CS, D> SharpMemoryInterface<SPI, CS, D> {
+    /// Wait according to `timing` around asserting and deasserting chip select, using `delay`
+    ///
+    /// The LS027B7DH01 e.g. needs 3 µs of setup and 1 µs of hold time.
+    pub fn with_cs_timing<D2>(
+        self,
+        timing: CsTiming,
+        delay: D2,
+    ) -> SharpMemoryInterface<SPI, CS, D2> {
+        SharpMemoryInterface {
+            spi: self.spi,
+            cs: self.cs,
+            delay,
+            cs_timing: timing,
+            bit_order: self.bit_order,
+            update_mode: self.update_mode,
+        }
+    }
+
+    /// Reverse the bits of every byte in software with [BitOrder::LsbFirst]
+    ///
+    /// This is meant for HALs which can't configure the SPI peripheral to send LSB-first. By
+    /// default bytes are sent as they are.
+    pub fn with_bit_order(mut self, order: BitOrder) -> Self {
+        self.bit_order = order;
+        self
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver and GPIO pin used by it
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver, GPIO pin and delay used by it
+    pub fn release_with_delay(self) -> (SPI, CS, D) {
+        (self.spi, self.cs, self.delay)
+    }
+}
+
+impl<SPI, CS, D> SharpMemoryInterface<SPI, CS, D>
+where
+    CS: OutputPin,
+{
+    /// Deassert SCS low, keeping the stored data update mode
+    pub fn resync(&mut self) -> Result {
+        self.cs.set_low().map_err(|_| DisplayError::CSError)
+    }
+}
+
+impl<SPI, CS, D> SharpMemoryInterface<SPI, CS, D>
+where
+    SPI: SpiBus,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    /// Send `mode`, `payload` if any and the closing dummy byte within one chip select window
+    fn transfer(&mut self, mode: u8, payload: Option<DataFormat<'_>>) -> Result {
+        let CsTiming { setup_ns, hold_ns } = self.cs_timing;
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        if setup_ns > 0 {
+            self.delay.delay_ns(setup_ns);
+        }
+
+        let opts = WriteOptions {
+            bit_order: self.bit_order,
+            ..WriteOptions::PLAIN
+        };
+        let mut spi = BusDevice(&mut self.spi);
+        // Make sure all bytes left the bus before deasserting chip select
+        let result = send_u8(&mut spi, DataFormat::U8(&[mode]), opts, 0)
+            .map_err(DisplayError::from)
+            .and_then(|_| match payload {
+                Some(payload) => send_u8(&mut spi, payload, opts, 0).map_err(DisplayError::from),
+                None => Ok(()),
+            })
+            .and_then(|_| {
+                send_u8(&mut spi, DataFormat::U8(&[0]), opts, 0).map_err(DisplayError::from)
+            })
+            .and_then(|_| self.spi.flush().map_err(|_| DisplayError::BusWriteError));
+
+        if hold_ns > 0 {
+            self.delay.delay_ns(hold_ns);
+        }
+        let deassert = self.cs.set_low().map_err(|_| DisplayError::CSError);
+        result.and(deassert)
+    }
+}
+
+impl<SPI, CS, D> WriteOnlyDataCommand for SharpMemoryInterface<SPI, CS, D>
+where
+    SPI: SpiBus,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        let mode = match cmds {
+            DataFormat::U8(&[mode]) => mode,
+            _ => return Err(DisplayError::InvalidFormatError),
+        };
+
+        if mode & MODE_UPDATE != 0 {
+            self.update_mode = Some(mode);
+            Ok(())
+        } else {
+            self.transfer(mode, None)
+        }
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        let mode = self.update_mode.ok_or(DisplayError::InvalidFormatError)?;
+        match buf {
+            DataFormat::U8(_) | DataFormat::U8Iter(_) => self.transfer(mode, Some(buf)),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! SPI interface for the serial mode of the ST7920
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use embedded_hal::{delay::DelayNs, digital::OutputPin, spi::SpiBus};
+
+use crate::{NoDelay, Result, BUFFER_SIZE};
+
+/// Synchronizing byte starting a transfer of commands
+const SYNC_COMMAND: u8 = 0xf8;
+/// Synchronizing byte starting a transfer of data
+const SYNC_DATA: u8 = 0xfa;
+
+/// SPI display interface for the serial mode of the ST7920 graphic LCD controller
+///
+/// The ST7920 doesn't take plain bytes in serial mode: every transfer starts with a
+/// synchronizing byte, `0xf8` for commands and `0xfa` for data, and every byte is then sent as
+/// two bytes carrying its high and low nibble in their upper four bits. This interface does
+/// that framing, asserting chip select (the RS pin of the module, active-high) around each
+/// `send_commands`/`send_data` call. 16-bit formats are split into two bytes in the requested
+/// byte order.
+///
+/// The controller needs 72 µs to execute every instruction, which the SPI bus alone doesn't
+/// leave it at common clock rates; see [St7920SerialInterface::with_byte_delay].
+pub struct St7920SerialInterface<SPI, CS, D = NoDelay> {
+    spi: SPI,
+    cs: CS,
+    delay: D,
+    byte_delay_ns: u32,
+}
+
+impl<SPI, CS> St7920SerialInterface<SPI, CS> {
+    /// Create new SPI interface for communication with an ST7920
+    pub fn new(spi: SPI, cs: CS) -> Self {
+        Self {
+            spi,
+            cs,
+            delay: NoDelay,
+            byte_delay_ns: 0,
+        }
+    }
+}
+
+impl<SPI, CS, D> St7920SerialInterface<SPI, CS, D> {
+    /// Wait `ns` nanoseconds after every byte, using `delay`
+    ///
+    /// The datasheet asks for 72 µs, i.e. `72_000`. Every byte is then written and flushed on
+    /// its own before waiting.
+    pub fn with_byte_delay<D2>(self, delay: D2, ns: u32) -> St7920SerialInterface<SPI, CS, D2> {
+        St7920SerialInterface {
+            spi: self.spi,
+            cs: self.cs,
+            delay,
+            byte_delay_ns: ns,
+        }
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver and GPIO pin used by it
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
+    }
+
+    /// Consume the display interface and return
+    /// the underlying peripheral driver, GPIO pin and delay used by it
+    pub fn release_with_delay(self) -> (SPI, CS, D) {
+        (self.spi, self.cs, self.delay)
+    }
+}
+
+impl<SPI, CS, D> St7920SerialInterface<SPI, CS, D>
+where
+    CS: OutputPin,
+{
+    /// Deassert chip select low
+    pub fn resync(&mut self) -> Result {
+        self.cs.set_low().map_err(|_| DisplayError::CSError)
+    }
+}
+
+impl<SPI, CS, D> St7920SerialInterface<SPI, CS, D>
+where
+    SPI: SpiBus,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn write(&mut self, bytes: &[u8]) -> Result {
+        self.spi
+            .write(bytes)
+            .and_then(|_| self.spi.flush())
+            .map_err(|_| DisplayError::BusWriteError)
+    }
+
+    /// Send `sync` followed by every byte of `iter` split into nibbles
+    fn write_framed(&mut self, sync: u8, iter: impl Iterator<Item = u8>) -> Result {
+        let mut buf = [0; BUFFER_SIZE];
+        buf[0] = sync;
+        let mut len = 1;
+
+        for value in iter {
+            if len + 2 > BUFFER_SIZE {
+                self.write(&buf[..len])?;
+                len = 0;
+            }
+
+            buf[len] = value & 0xf0;
+            buf[len + 1] = value << 4;
+            len += 2;
+
+            if self.byte_delay_ns > 0 {
+                self.write(&buf[..len])?;
+                len = 0;
+                self.delay.delay_ns(self.byte_delay_ns);
+            }
+        }
+
+        if len > 0 {
+            self.write(&buf[..len])?;
+        }
+        Ok(())
+    }
+
+    fn write_pairs(&mut self, sync: u8, iter: impl Iterator<Item = [u8; 2]>) -> Result {
+        self.write_framed(sync, iter.flatten())
+    }
+
+    fn write_data(&mut self, sync: u8, data: DataFormat<'_>) -> Result {
+        match data {
+            DataFormat::U8(slice) => self.write_framed(sync, slice.iter().copied()),
+            DataFormat::U8Iter(iter) => self.write_framed(sync, iter),
+            DataFormat::U16(slice) => {
+                self.write_pairs(sync, slice.iter().copied().map(u16::to_ne_bytes))
+            }
+            DataFormat::U16BE(slice) => {
+                self.write_pairs(sync, slice.iter().copied().map(u16::to_be_bytes))
+            }
+            DataFormat::U16LE(slice) => {
+                self.write_pairs(sync, slice.iter().copied().map(u16::to_le_bytes))
+            }
+            DataFormat::U16BEIter(iter) => self.write_pairs(sync, iter.map(u16::to_be_bytes)),
+            DataFormat::U16LEIter(iter) => self.write_pairs(sync, iter.map(u16::to_le_bytes)),
+            _ => Err(DisplayError::DataFormatNotImplemented),
+        }
+    }
+
+    fn send(&mut self, sync: u8, data: DataFormat<'_>) -> Result {
+        self.cs.set_high().map_err(|_| DisplayError::CSError)?;
+        let result = self.write_data(sync, data);
+        let deassert = self.cs.set_low().map_err(|_| DisplayError::CSError);
+        result.and(deassert)
+    }
+}
+
+impl<SPI, CS, D> WriteOnlyDataCommand for St7920SerialInterface<SPI, CS, D>
+where
+    SPI: SpiBus,
+    CS: OutputPin,
+    D: DelayNs,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result {
+        self.send(SYNC_COMMAND, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result {
+        self.send(SYNC_DATA, buf)
+    }
+}
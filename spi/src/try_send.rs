@@ -0,0 +1,55 @@
+//! Non-blocking transfers in chunks for superloops
+
+use display_interface::{DataFormat, DisplayError, TryWriteOnlyDataCommand};
+use embedded_hal::{digital::OutputPin, spi::SpiDevice};
+
+use crate::{send_u8_staged, Result, SpiInterface, BUFFER_SIZE};
+
+impl<SPI, DC> SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Write the next chunk of `words`, selecting the phase first if the transfer is new
+    fn try_send(&mut self, words: DataFormat<'_>, data: bool) -> nb::Result<(), DisplayError> {
+        let (new, words) = self.progress.next_format_chunk(data, words, BUFFER_SIZE)?;
+        let result = self.write_chunk(words, data, new);
+        self.progress.finish(result)
+    }
+
+    fn write_chunk(&mut self, words: DataFormat<'_>, data: bool, new: bool) -> Result {
+        if new {
+            // 1 = data, 0 = command
+            if data {
+                self.dc.set_high()
+            } else {
+                self.dc.set_low()
+            }
+            .map_err(|_| DisplayError::DCError)?;
+        }
+
+        let opts = if data {
+            self.data_options()
+        } else {
+            self.command_options()
+        };
+        let (spi, staging) = self.spi_and_staging();
+        send_u8_staged(spi, words, opts, 0, staging).map_err(DisplayError::from)
+    }
+}
+
+impl<SPI, DC> TryWriteOnlyDataCommand for SpiInterface<SPI, DC>
+where
+    SPI: SpiDevice,
+    DC: OutputPin,
+{
+    /// Send the next chunk of commands, of at most 64 bytes
+    fn try_send_commands(&mut self, cmds: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(cmds, false)
+    }
+
+    /// Send the next chunk of data, of at most 64 bytes
+    fn try_send_data(&mut self, buf: DataFormat<'_>) -> nb::Result<(), DisplayError> {
+        self.try_send(buf, true)
+    }
+}
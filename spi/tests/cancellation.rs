@@ -0,0 +1,239 @@
+//! State left behind by `async` sends dropped at their first await point
+
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    convert::Infallible,
+    rc::Rc,
+};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat};
+use display_interface_spi::{CascadedSpiInterface, Half, SpiBusInterface, SpiInterface};
+use embedded_hal::{
+    digital::{ErrorType as PinErrorType, OutputPin},
+    spi::{ErrorType, Operation},
+};
+
+use common::{block_on, poll_once};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Event {
+    /// Level of the data/command pin
+    Dc(bool),
+    /// Level of the first or second chip select
+    Cs(usize, bool),
+    Write(Vec<u8>),
+    Flush,
+}
+
+/// Events of the bus and pins in order, and whether writes stall
+#[derive(Clone, Default)]
+struct Log {
+    events: Rc<RefCell<Vec<Event>>>,
+    stall: Rc<Cell<bool>>,
+}
+
+impl Log {
+    fn push(&self, event: Event) {
+        self.events.borrow_mut().push(event);
+    }
+
+    fn take(&self) -> Vec<Event> {
+        self.events.take()
+    }
+
+    /// Record the write, then never complete it while stalling
+    async fn write(&self, words: &[u8]) {
+        self.push(Event::Write(words.to_vec()));
+        if self.stall.get() {
+            core::future::pending::<()>().await;
+        }
+    }
+}
+
+struct Bus(Log);
+
+impl ErrorType for Bus {
+    type Error = Infallible;
+}
+
+impl embedded_hal_async::spi::SpiBus for Bus {
+    async fn read(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+        unimplemented!()
+    }
+
+    async fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+        self.0.write(words).await;
+        Ok(())
+    }
+
+    async fn transfer(&mut self, _read: &mut [u8], _write: &[u8]) -> Result<(), Infallible> {
+        unimplemented!()
+    }
+
+    async fn transfer_in_place(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+        unimplemented!()
+    }
+
+    async fn flush(&mut self) -> Result<(), Infallible> {
+        self.0.push(Event::Flush);
+        Ok(())
+    }
+}
+
+struct Device(Log);
+
+impl ErrorType for Device {
+    type Error = Infallible;
+}
+
+impl embedded_hal_async::spi::SpiDevice for Device {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        for op in operations {
+            match op {
+                Operation::Write(words) => self.0.write(words).await,
+                _ => unimplemented!(),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Data/command pin, or chip select number `.1` if given
+struct Pin(Log, Option<usize>);
+
+impl PinErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.push(match self.1 {
+            Some(n) => Event::Cs(n, false),
+            None => Event::Dc(false),
+        });
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.push(match self.1 {
+            Some(n) => Event::Cs(n, true),
+            None => Event::Dc(true),
+        });
+        Ok(())
+    }
+}
+
+/// Start sending 200 bytes of pixels from an iterator and drop the future in the first write,
+/// returning how many bytes were taken from the iterator
+///
+/// The second chunk is staged while the first one is written, so that is 128 bytes.
+fn cancel_pixels(iface: &mut impl AsyncWriteOnlyDataCommand, log: &Log) -> usize {
+    log.stall.set(true);
+    let mut pixels = (0..200).map(|i| i as u8);
+    assert!(!poll_once(iface.send_data(DataFormat::U8Iter(&mut pixels))));
+    log.stall.set(false);
+    200 - pixels.count()
+}
+
+/// A memory write command sent after the cancelled call
+async fn memory_write(iface: &mut impl AsyncWriteOnlyDataCommand) {
+    iface
+        .send_commands(DataFormat::U8Iter(&mut [0x2c].into_iter()))
+        .await
+        .unwrap();
+}
+
+fn first_chunk() -> Event {
+    Event::Write((0..64).collect())
+}
+
+#[test]
+fn spi_device_interface() {
+    let log = Log::default();
+    let mut iface = SpiInterface::new(Device(log.clone()), Pin(log.clone(), None));
+
+    assert_eq!(cancel_pixels(&mut iface, &log), 128);
+    assert_eq!(log.take(), [Event::Dc(true), first_chunk()]);
+
+    // Only the command goes out, in the command phase
+    block_on(memory_write(&mut iface));
+    assert_eq!(log.take(), [Event::Dc(false), Event::Write(vec![0x2c])]);
+}
+
+#[test]
+fn spi_bus_interface() {
+    let log = Log::default();
+    let mut iface = SpiBusInterface::new(
+        Bus(log.clone()),
+        Pin(log.clone(), None),
+        Pin(log.clone(), Some(0)),
+    );
+
+    assert_eq!(cancel_pixels(&mut iface, &log), 128);
+    // Chip select is deasserted when the future is dropped
+    assert_eq!(
+        log.take(),
+        [
+            Event::Dc(true),
+            Event::Cs(0, false),
+            first_chunk(),
+            Event::Cs(0, true)
+        ]
+    );
+
+    block_on(memory_write(&mut iface));
+    assert_eq!(
+        log.take(),
+        [
+            Event::Dc(false),
+            Event::Cs(0, false),
+            Event::Write(vec![0x2c]),
+            Event::Flush,
+            Event::Cs(0, true),
+        ]
+    );
+}
+
+#[test]
+fn cascaded_interface() {
+    let log = Log::default();
+    let mut iface = CascadedSpiInterface::new(
+        Bus(log.clone()),
+        Pin(log.clone(), None),
+        Pin(log.clone(), Some(0)),
+        Pin(log.clone(), Some(1)),
+    );
+    iface.select(Half::Both);
+
+    assert_eq!(cancel_pixels(&mut iface, &log), 128);
+    // Both chip selects are deasserted when the future is dropped
+    assert_eq!(
+        log.take(),
+        [
+            Event::Dc(true),
+            Event::Cs(0, false),
+            Event::Cs(1, false),
+            first_chunk(),
+            Event::Cs(0, true),
+            Event::Cs(1, true),
+        ]
+    );
+
+    iface.select(Half::Right);
+    block_on(memory_write(&mut iface));
+    assert_eq!(
+        log.take(),
+        [
+            Event::Dc(false),
+            Event::Cs(1, false),
+            Event::Write(vec![0x2c]),
+            Event::Flush,
+            Event::Cs(1, true),
+        ]
+    );
+}
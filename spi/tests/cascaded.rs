@@ -0,0 +1,116 @@
+//! Chip select handling of the interface for cascaded controllers
+
+mod common;
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+use display_interface_spi::{CascadedSpiInterface, Half};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+    MockError,
+};
+
+use common::block_on;
+
+/// Chip select transactions for `count` calls addressing the controller
+fn framed(count: usize) -> Vec<PinTransaction> {
+    (0..count)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::Low),
+                PinTransaction::set(State::High),
+            ]
+        })
+        .collect()
+}
+
+fn write(bytes: &[u8]) -> [SpiTransaction<u8>; 2] {
+    [
+        SpiTransaction::write_vec(bytes.to_vec()),
+        SpiTransaction::flush(),
+    ]
+}
+
+#[test]
+fn calls_go_to_the_selected_halves() {
+    let spi: Vec<_> = [&[0x24][..], &[0xff, 0x00], &[0x26], &[0x12]]
+        .into_iter()
+        .flat_map(write)
+        .collect();
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::Low),
+    ];
+    let mut iface = CascadedSpiInterface::new(
+        SpiMock::new(&spi),
+        PinMock::new(&dc),
+        PinMock::new(&framed(3)),
+        PinMock::new(&framed(2)),
+    );
+
+    assert_eq!(iface.selected(), Half::Left);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x24])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0xff, 0x00])).unwrap();
+    iface.select(Half::Right);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x26])).unwrap();
+    iface.select(Half::Both);
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&[0x12]),
+    ))
+    .unwrap();
+
+    let (mut spi, mut dc, mut cs1, mut cs2) = iface.release();
+    spi.done();
+    dc.done();
+    cs1.done();
+    cs2.done();
+}
+
+#[test]
+fn first_chip_select_is_released_if_the_second_fails() {
+    let dc = [PinTransaction::set(State::High)];
+    let cs2 =
+        [PinTransaction::set(State::Low).with_error(MockError::Io(std::io::ErrorKind::Other))];
+    let mut iface = CascadedSpiInterface::new(
+        SpiMock::new(&[]),
+        PinMock::new(&dc),
+        PinMock::new(&framed(1)),
+        PinMock::new(&cs2),
+    );
+    iface.select(Half::Both);
+
+    let result = WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0xff]));
+
+    assert!(matches!(result, Err(DisplayError::CSError)));
+    let (mut spi, mut dc, mut cs1, mut cs2) = iface.release();
+    spi.done();
+    dc.done();
+    cs1.done();
+    cs2.done();
+}
+
+#[test]
+fn resync_deselects_both_halves() {
+    let high = [PinTransaction::set(State::High)];
+    let mut iface = CascadedSpiInterface::new(
+        SpiMock::<u8>::new(&[]),
+        PinMock::new(&[PinTransaction::set(State::Low)]),
+        PinMock::new(&high),
+        PinMock::new(&high),
+    );
+    // Whatever the selection, both chip selects are deasserted
+    iface.select(Half::Left);
+
+    iface.resync().unwrap();
+
+    let (mut spi, mut dc, mut cs1, mut cs2) = iface.release();
+    spi.done();
+    dc.done();
+    cs1.done();
+    cs2.done();
+}
@@ -0,0 +1,49 @@
+//! Helpers shared by the integration tests
+
+use core::{
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+/// Waker which does nothing, the tests poll until done anyway
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(clone(core::ptr::null())) }
+}
+
+/// Poll `future` to completion without an executor
+#[allow(dead_code)]
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    count_pending(future).0
+}
+
+/// Poll `future` to completion, also returning how often it returned `Pending`
+pub fn count_pending<F: Future>(future: F) -> (F::Output, usize) {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut pending = 0;
+
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return (output, pending),
+            Poll::Pending => pending += 1,
+        }
+    }
+}
+
+/// Poll `future` once and drop it, like a caller cancelling it at its first await point
+///
+/// Returns whether it completed.
+#[allow(dead_code)]
+pub fn poll_once<F: Future>(future: F) -> bool {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    pin!(future).poll(&mut cx).is_ready()
+}
@@ -0,0 +1,78 @@
+//! Interfaces built from embedded-hal 0.2 implementations
+
+#![cfg(feature = "eh02")]
+
+use display_interface::{DataFormat, DisplayError, ReadData, WriteOnlyDataCommand};
+use display_interface_spi::{SpiBusInterface, SpiInterface};
+use embedded_hal_mock::eh0::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+    MockError,
+};
+
+#[test]
+fn device_interface_writes() {
+    let spi = [
+        SpiTransaction::write(vec![0x2c]),
+        SpiTransaction::write(vec![0x12, 0x34, 0x56, 0x78]),
+    ];
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiInterface::new_eh02(SpiMock::new(&spi), PinMock::new(&dc));
+
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    iface
+        .send_data(DataFormat::U16BE(&mut [0x1234, 0x5678]))
+        .unwrap();
+
+    let (mut spi, mut dc) = iface.release();
+    spi.0.done();
+    dc.0.done();
+}
+
+#[test]
+fn bus_interface_drives_chip_select() {
+    let spi = [SpiTransaction::write(vec![0x2a])];
+    let dc = [PinTransaction::set(State::Low)];
+    let cs = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface =
+        SpiBusInterface::new_eh02(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+
+    iface.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+
+    let (mut spi, mut dc, mut cs) = iface.release();
+    spi.0.done();
+    dc.0.done();
+    cs.0.done();
+}
+
+#[test]
+fn pin_errors_are_mapped() {
+    let dc = [PinTransaction::set(State::Low).with_error(MockError::Io(std::io::ErrorKind::Other))];
+    let mut iface = SpiInterface::new_eh02(SpiMock::new(&[]), PinMock::new(&dc));
+
+    let result = iface.send_commands(DataFormat::U8(&[0x2c]));
+
+    assert!(matches!(result, Err(DisplayError::DCError)));
+    let (mut spi, mut dc) = iface.release();
+    spi.0.done();
+    dc.0.done();
+}
+
+#[test]
+fn reads_are_unsupported() {
+    let dc = [PinTransaction::set(State::High)];
+    let mut iface = SpiInterface::new_eh02(SpiMock::new(&[]), PinMock::new(&dc));
+
+    let result = iface.read_data(&mut [0; 2]);
+
+    assert!(matches!(result, Err(DisplayError::BusReadError)));
+    let (mut spi, mut dc) = iface.release();
+    spi.0.done();
+    dc.0.done();
+}
@@ -0,0 +1,94 @@
+//! Phase of the writes of the interface for peripherals driving D/C in hardware
+
+use core::convert::Infallible;
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::{DcxSpiDevice, SpiDcxInterface};
+use embedded_hal::{
+    digital::PinState,
+    spi::{ErrorType, Operation, SpiDevice},
+};
+
+#[derive(Debug, PartialEq)]
+enum Write {
+    Command(Vec<u8>),
+    Data(Vec<u8>),
+    /// Written without a D/C level
+    Plain(Vec<u8>),
+}
+
+/// Peripheral keeping every write with the D/C level it was sent with
+#[derive(Default)]
+struct Dcx(Vec<Write>);
+
+impl ErrorType for Dcx {
+    type Error = Infallible;
+}
+
+impl SpiDevice for Dcx {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        for op in operations {
+            if let Operation::Write(words) = op {
+                self.0.push(Write::Plain(words.to_vec()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl DcxSpiDevice for Dcx {
+    fn write_with_dc(&mut self, dc: PinState, words: &[u8]) -> Result<(), Infallible> {
+        self.0.push(match dc {
+            PinState::Low => Write::Command(words.to_vec()),
+            PinState::High => Write::Data(words.to_vec()),
+        });
+        Ok(())
+    }
+}
+
+#[test]
+fn commands_and_data_go_out_in_their_phase() {
+    let mut iface = SpiDcxInterface::new(Dcx::default());
+
+    iface.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    iface
+        .send_data(DataFormat::U8(&[0x00, 0x00, 0x00, 0xef]))
+        .unwrap();
+    iface
+        .send_commands(DataFormat::U8Iter(&mut [0x2c].into_iter()))
+        .unwrap();
+    iface
+        .send_data(DataFormat::U16BE(&mut [0x1234, 0x5678]))
+        .unwrap();
+    iface
+        .send_data(DataFormat::U16LEIter(&mut (0..100)))
+        .unwrap();
+    iface.send_commands(DataFormat::U8(&[0x29])).unwrap();
+
+    let pixels: Vec<u8> = (0..100u16).flat_map(u16::to_le_bytes).collect();
+    assert_eq!(
+        iface.release().0,
+        [
+            Write::Command(vec![0x2a]),
+            Write::Data(vec![0x00, 0x00, 0x00, 0xef]),
+            Write::Command(vec![0x2c]),
+            Write::Data(vec![0x12, 0x34, 0x56, 0x78]),
+            // Iterators are staged in chunks of 64 words, each written with D/C high
+            Write::Data(pixels[..128].to_vec()),
+            Write::Data(pixels[128..].to_vec()),
+            Write::Command(vec![0x29]),
+        ]
+    );
+}
+
+#[test]
+fn empty_calls_write_nothing() {
+    let mut iface = SpiDcxInterface::new(Dcx::default());
+
+    iface
+        .send_commands(DataFormat::U8Iter(&mut [].into_iter()))
+        .unwrap();
+    iface.send_data(DataFormat::U16BEIter(&mut (0..0))).unwrap();
+
+    assert!(iface.release().0.is_empty());
+}
@@ -0,0 +1,140 @@
+//! Owned buffer transfers through a mocked DMA capable device
+
+#![cfg(feature = "embedded-dma")]
+
+use display_interface::{DisplayError, DmaTransfer, DmaWriteOnlyDataCommand};
+use display_interface_spi::{DmaSpiTransfer, DmaSpiWrite, SpiInterface};
+use embedded_dma::ReadBuffer;
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiDevice};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    MockError,
+};
+
+/// Device completing a transfer after `polls` calls to `is_done`, failing it if `fail` is set
+#[derive(Default)]
+struct Dma {
+    polls: usize,
+    fail: bool,
+    written: Vec<u8>,
+}
+
+impl ErrorType for Dma {
+    type Error = ErrorKind;
+}
+
+impl SpiDevice for Dma {
+    fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+        unreachable!("owned buffers don't go through transactions")
+    }
+}
+
+impl DmaSpiWrite for Dma {
+    type Transfer<'a, B>
+        = Transfer<'a, B>
+    where
+        B: ReadBuffer<Word = u8> + 'static;
+
+    fn write_owned<B>(&mut self, buf: B) -> Result<Transfer<'_, B>, (B, ErrorKind)>
+    where
+        B: ReadBuffer<Word = u8> + 'static,
+    {
+        Ok(Transfer { dma: self, buf })
+    }
+}
+
+struct Transfer<'a, B> {
+    dma: &'a mut Dma,
+    buf: B,
+}
+
+impl<B> ErrorType for Transfer<'_, B> {
+    type Error = ErrorKind;
+}
+
+impl<B: ReadBuffer<Word = u8>> DmaSpiTransfer<B> for Transfer<'_, B> {
+    fn is_done(&mut self) -> bool {
+        if self.dma.polls > 0 {
+            self.dma.polls -= 1;
+        }
+        self.dma.polls == 0
+    }
+
+    fn wait(mut self) -> Result<B, (B, ErrorKind)> {
+        while !self.is_done() {}
+        if self.dma.fail {
+            return Err((self.buf, ErrorKind::Other));
+        }
+        // SAFETY: the buffer is owned by the transfer and not touched elsewhere
+        let (ptr, len) = unsafe { self.buf.read_buffer() };
+        self.dma
+            .written
+            .extend_from_slice(unsafe { core::slice::from_raw_parts(ptr, len) });
+        Ok(self.buf)
+    }
+}
+
+fn buffer() -> &'static mut [u8; 4] {
+    Box::leak(Box::new([0x12, 0x34, 0x56, 0x78]))
+}
+
+#[test]
+fn buffer_is_handed_back_after_completion() {
+    let buf = buffer();
+    let addr = buf.as_ptr();
+    let dma = Dma {
+        polls: 2,
+        ..Dma::default()
+    };
+    let mut iface = SpiInterface::new(dma, PinMock::new(&[PinTransaction::set(State::High)]));
+
+    let mut transfer = iface.send_data_owned(buf).ok().unwrap();
+    // Returned before the transfer completed
+    assert!(!transfer.is_done());
+    let buf = transfer.wait().unwrap();
+    assert_eq!(buf.as_ptr(), addr);
+
+    let (dma, mut dc) = iface.release();
+    assert_eq!(dma.written, [0x12, 0x34, 0x56, 0x78]);
+    dc.done();
+}
+
+#[test]
+fn buffer_is_handed_back_on_dc_error() {
+    let buf = buffer();
+    let addr = buf.as_ptr();
+    let dc =
+        [PinTransaction::set(State::High).with_error(MockError::Io(std::io::ErrorKind::Other))];
+    let mut iface = SpiInterface::new(Dma::default(), PinMock::new(&dc));
+
+    let Err((buf, err)) = iface.send_data_owned(buf) else {
+        panic!("the transfer must not start");
+    };
+    assert!(matches!(err, DisplayError::DCError));
+    assert_eq!(buf.as_ptr(), addr);
+
+    let (dma, mut dc) = iface.release();
+    assert!(dma.written.is_empty());
+    dc.done();
+}
+
+#[test]
+fn buffer_is_handed_back_on_bus_error() {
+    let buf = buffer();
+    let addr = buf.as_ptr();
+    let dma = Dma {
+        polls: 1,
+        fail: true,
+        ..Dma::default()
+    };
+    let mut iface = SpiInterface::new(dma, PinMock::new(&[PinTransaction::set(State::High)]));
+
+    let transfer = iface.send_data_owned(buf).ok().unwrap();
+    let (buf, err) = transfer.wait().unwrap_err();
+    assert!(matches!(err, DisplayError::BusWriteError));
+    assert_eq!(buf.as_ptr(), addr);
+
+    let (dma, mut dc) = iface.release();
+    assert!(dma.written.is_empty());
+    dc.done();
+}
@@ -0,0 +1,276 @@
+//! Slice formats and their iterator counterparts put identical bytes on the wire
+//!
+//! The slice formats are written in place or swapped in the caller's buffer, the iterator formats
+//! are serialized through a staging buffer. Only the bytes and the D/C level they are sent with
+//! have to match, the transfers may be split differently.
+
+mod common;
+
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::{BitOrder, SpiBusInterface, SpiInterface};
+use embedded_hal::{
+    digital::{ErrorType as PinErrorType, OutputPin},
+    spi::{ErrorType, Operation, SpiBus},
+};
+use proptest::{collection::vec, prelude::*};
+
+use common::block_on;
+
+/// What ended up on the wire: every byte with the D/C level it was sent with
+#[derive(Default)]
+struct Wire {
+    dc: bool,
+    bytes: Vec<(bool, u8)>,
+}
+
+type Shared = Rc<RefCell<Wire>>;
+
+/// SPI device and bus recording every written byte on the wire
+struct Spi(Shared);
+
+impl Spi {
+    fn write(&mut self, buf: &[u8]) {
+        let mut wire = self.0.borrow_mut();
+        let dc = wire.dc;
+        wire.bytes.extend(buf.iter().map(|&b| (dc, b)));
+    }
+
+    fn record(&mut self, operations: &mut [Operation<'_, u8>]) {
+        for op in operations {
+            if let Operation::Write(buf) = op {
+                self.write(buf);
+            }
+        }
+    }
+}
+
+impl ErrorType for Spi {
+    type Error = Infallible;
+}
+
+impl embedded_hal::spi::SpiDevice for Spi {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::spi::SpiDevice for Spi {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+impl SpiBus for Spi {
+    fn read(&mut self, _words: &mut [u8]) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Infallible> {
+        Spi::write(self, words);
+        Ok(())
+    }
+
+    fn transfer(&mut self, _read: &mut [u8], write: &[u8]) -> Result<(), Infallible> {
+        Spi::write(self, write);
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Infallible> {
+        Spi::write(self, words);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// D/C pin of the recorded wire
+struct Dc(Shared);
+
+impl PinErrorType for Dc {
+    type Error = Infallible;
+}
+
+impl OutputPin for Dc {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().dc = false;
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.0.borrow_mut().dc = true;
+        Ok(())
+    }
+}
+
+/// The interfaces and settings every pair of formats is checked with
+#[derive(Clone, Copy, Debug)]
+enum Setup {
+    Device,
+    DeviceAsync,
+    LsbFirst,
+    /// Transfers split into an odd number of bytes, cutting through words
+    MaxTransferLen,
+    /// A staging buffer for iterators smaller than a single slice transfer
+    #[cfg(feature = "static-buffer")]
+    StagingBuffer,
+    Bus,
+}
+
+const SETUPS: &[Setup] = &[
+    Setup::Device,
+    Setup::DeviceAsync,
+    Setup::LsbFirst,
+    Setup::MaxTransferLen,
+    #[cfg(feature = "static-buffer")]
+    Setup::StagingBuffer,
+    Setup::Bus,
+];
+
+/// The outcome of a call, with the error as text since `DisplayError` can't be compared
+type Outcome = (Result<(), String>, Vec<(bool, u8)>);
+
+/// Send `format` as commands or data, with the error as text
+fn call(
+    iface: &mut impl WriteOnlyDataCommand,
+    commands: bool,
+    format: DataFormat<'_>,
+) -> Result<(), String> {
+    let result = if commands {
+        iface.send_commands(format)
+    } else {
+        iface.send_data(format)
+    };
+    result.map_err(|e| format!("{e:?}"))
+}
+
+/// Send `format` as commands or data through the interface of `setup` and capture the wire
+fn send(setup: Setup, commands: bool, format: DataFormat<'_>) -> Outcome {
+    let wire = Shared::default();
+    let spi = Spi(wire.clone());
+    let dc = Dc(wire.clone());
+
+    let result = match setup {
+        Setup::Device => call(&mut SpiInterface::new(spi, dc), commands, format),
+        Setup::DeviceAsync => {
+            let mut iface = SpiInterface::new(spi, dc).with_yield_every(1);
+            let result = if commands {
+                block_on(AsyncWriteOnlyDataCommand::send_commands(&mut iface, format))
+            } else {
+                block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, format))
+            };
+            result.map_err(|e| format!("{e:?}"))
+        }
+        Setup::LsbFirst => {
+            let mut iface = SpiInterface::new(spi, dc).with_bit_order(BitOrder::LsbFirst);
+            call(&mut iface, commands, format)
+        }
+        Setup::MaxTransferLen => {
+            let mut iface = SpiInterface::new(spi, dc).with_max_transfer_len(7);
+            call(&mut iface, commands, format)
+        }
+        #[cfg(feature = "static-buffer")]
+        Setup::StagingBuffer => {
+            let buffer = Box::leak(vec![0; 6].into_boxed_slice());
+            call(
+                &mut SpiInterface::new_with_buffer(spi, dc, buffer),
+                commands,
+                format,
+            )
+        }
+        Setup::Bus => call(
+            &mut SpiBusInterface::new_without_cs(spi, dc),
+            commands,
+            format,
+        ),
+    };
+
+    let bytes = core::mem::take(&mut wire.borrow_mut().bytes);
+    (result, bytes)
+}
+
+/// Run `check` with every setup, for commands and for data
+fn each_setup(
+    mut check: impl FnMut(Setup, bool) -> Result<(), TestCaseError>,
+) -> Result<(), TestCaseError> {
+    for &setup in SETUPS {
+        for commands in [false, true] {
+            check(setup, commands)?;
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn u8_slice_and_iter_match(bytes in vec(any::<u8>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U8(&bytes));
+            let iter = send(setup, commands, DataFormat::U8Iter(&mut bytes.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16be_slice_and_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16BE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16BEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    #[test]
+    fn u16le_slice_and_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16LE(&mut words.clone()));
+            let iter = send(setup, commands, DataFormat::U16LEIter(&mut words.iter().copied()));
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+
+    /// `U16` is in native byte order, so it matches the iterator of the native order
+    #[test]
+    fn u16_slice_and_native_iter_match(words in vec(any::<u16>(), 0..300)) {
+        each_setup(|setup, commands| {
+            let slice = send(setup, commands, DataFormat::U16(&words));
+            let mut iter = words.iter().copied();
+            let iter = if cfg!(target_endian = "big") {
+                send(setup, commands, DataFormat::U16BEIter(&mut iter))
+            } else {
+                send(setup, commands, DataFormat::U16LEIter(&mut iter))
+            };
+            prop_assert_eq!(slice, iter, "{:?}, commands: {}", setup, commands);
+            Ok(())
+        })?;
+    }
+}
+
+/// 24-bit words aren't supported by any SPI interface, whether in a slice or an iterator
+#[test]
+fn u24_is_unsupported_either_way() {
+    let unsupported = (Err("DataFormatNotImplemented".into()), vec![]);
+    each_setup(|setup, commands| {
+        let slice = send(setup, commands, DataFormat::U24(&[0x123456]));
+        let iter = send(
+            setup,
+            commands,
+            DataFormat::U24Iter(&mut [0x123456].into_iter()),
+        );
+        prop_assert_eq!(&slice, &unsupported, "{:?}", setup);
+        prop_assert_eq!(&iter, &unsupported, "{:?}", setup);
+        Ok(())
+    })
+    .unwrap();
+}
@@ -0,0 +1,119 @@
+//! Checks that the blocking and `async` implementations put the same bytes on the bus
+
+mod common;
+
+use core::convert::Infallible;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::SpiInterface;
+use embedded_hal::{
+    digital::{ErrorType as PinErrorType, OutputPin},
+    spi::{ErrorType, Operation},
+};
+
+use common::block_on;
+
+/// SPI device recording every written byte
+#[derive(Default)]
+struct Recorder(Vec<u8>);
+
+impl ErrorType for Recorder {
+    type Error = Infallible;
+}
+
+impl Recorder {
+    fn record(&mut self, operations: &mut [Operation<'_, u8>]) {
+        for op in operations {
+            if let Operation::Write(buf) = op {
+                self.0.extend_from_slice(buf);
+            }
+        }
+    }
+}
+
+impl embedded_hal::spi::SpiDevice for Recorder {
+    fn transaction(&mut self, operations: &mut [Operation<'_, u8>]) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::spi::SpiDevice for Recorder {
+    async fn transaction(
+        &mut self,
+        operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), Infallible> {
+        self.record(operations);
+        Ok(())
+    }
+}
+
+struct Dc;
+
+impl PinErrorType for Dc {
+    type Error = Infallible;
+}
+
+impl OutputPin for Dc {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+fn words() -> Vec<u16> {
+    (0..200u16)
+        .map(|i| i.wrapping_mul(0x0101) ^ 0x1234)
+        .collect()
+}
+
+/// Send the format built from `buf` through both implementations and return the bytes of each
+macro_rules! send_both {
+    ($buf:ident => $format:expr) => {{
+        let mut blocking = SpiInterface::new(Recorder::default(), Dc);
+        #[allow(unused_mut)]
+        let mut $buf = words();
+        WriteOnlyDataCommand::send_data(&mut blocking, $format).unwrap();
+
+        let mut asynch = SpiInterface::new(Recorder::default(), Dc).with_yield_every(1);
+        #[allow(unused_mut)]
+        let mut $buf = words();
+        block_on(AsyncWriteOnlyDataCommand::send_data(&mut asynch, $format)).unwrap();
+
+        (blocking.release().0 .0, asynch.release().0 .0)
+    }};
+}
+
+#[test]
+fn u16_formats_match() {
+    let (blocking, asynch) = send_both!(buf => DataFormat::U16BE(&mut buf));
+    assert_eq!(blocking, asynch);
+    assert_eq!(&blocking[..2], &0x1234u16.to_be_bytes());
+
+    let (blocking, asynch) = send_both!(buf => DataFormat::U16LE(&mut buf));
+    assert_eq!(blocking, asynch);
+    assert_eq!(&blocking[..2], &0x1234u16.to_le_bytes());
+}
+
+#[test]
+fn iterator_formats_match() {
+    let (blocking, asynch) =
+        send_both!(buf => DataFormat::U8Iter(&mut buf.iter().map(|v| *v as u8)));
+    assert_eq!(blocking.len(), 200);
+    assert_eq!(blocking, asynch);
+
+    let (blocking, asynch) = send_both!(buf => DataFormat::U16BEIter(&mut buf.iter().copied()));
+    assert_eq!(blocking.len(), 400);
+    assert_eq!(blocking, asynch);
+    assert_eq!(&blocking[..2], &0x1234u16.to_be_bytes());
+}
+
+#[test]
+fn slices_and_iterators_match() {
+    let (slice, _) = send_both!(buf => DataFormat::U16LE(&mut buf));
+    let (iter, _) = send_both!(buf => DataFormat::U16LEIter(&mut buf.iter().copied()));
+    assert_eq!(slice, iter);
+}
@@ -0,0 +1,950 @@
+//! Exact transactions put on the wire for every `DataFormat`, checked with `embedded-hal-mock`
+
+mod common;
+
+use display_interface::{
+    AsyncWriteOnlyDataCommand, DataFormat, DisplayError, TypedWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+use display_interface_spi::{SpiDataInterface, SpiInterface, SpiInterfaceError};
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation, SpiDevice};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+    MockError,
+};
+
+use common::block_on;
+
+/// Payload sizes covering the empty case and the boundaries of the staging buffer
+const SIZES: [usize; 6] = [0, 1, 63, 64, 65, 1000];
+
+/// Number of words staged per write for iterator formats
+const CHUNK: usize = 64;
+
+#[derive(Clone, Copy, Debug)]
+enum Kind {
+    U8,
+    U16,
+    U16LE,
+    U16BE,
+    U8Iter,
+    U16LEIter,
+    U16BEIter,
+}
+
+const KINDS: [Kind; 7] = [
+    Kind::U8,
+    Kind::U16,
+    Kind::U16LE,
+    Kind::U16BE,
+    Kind::U8Iter,
+    Kind::U16LEIter,
+    Kind::U16BEIter,
+];
+
+fn words(len: usize) -> Vec<u16> {
+    (0..len as u16)
+        .map(|i| i.wrapping_mul(0x0102) ^ 0xa55a)
+        .collect()
+}
+
+/// Bytes of `words` in the order `kind` puts them on the wire
+fn bytes(kind: Kind, words: &[u16]) -> Vec<u8> {
+    match kind {
+        Kind::U8 | Kind::U8Iter => words.iter().map(|w| *w as u8).collect(),
+        Kind::U16 => words.iter().flat_map(|w| w.to_ne_bytes()).collect(),
+        Kind::U16LE | Kind::U16LEIter => words.iter().flat_map(|w| w.to_le_bytes()).collect(),
+        Kind::U16BE | Kind::U16BEIter => words.iter().flat_map(|w| w.to_be_bytes()).collect(),
+    }
+}
+
+fn transaction(bytes: Vec<u8>) -> [SpiTransaction<u8>; 3] {
+    [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(bytes),
+        SpiTransaction::transaction_end(),
+    ]
+}
+
+/// Slices are written in one transaction, iterators in one transaction per staging buffer
+fn expected(kind: Kind, words: &[u16]) -> Vec<SpiTransaction<u8>> {
+    match kind {
+        Kind::U8 | Kind::U16 | Kind::U16LE | Kind::U16BE => {
+            transaction(bytes(kind, words)).to_vec()
+        }
+        Kind::U8Iter | Kind::U16LEIter | Kind::U16BEIter => words
+            .chunks(CHUNK)
+            .flat_map(|chunk| transaction(bytes(kind, chunk)))
+            .collect(),
+    }
+}
+
+/// Build the `DataFormat` of `kind` holding `words` and pass it to `f`
+fn with_format<R>(kind: Kind, words: &[u16], f: impl FnOnce(DataFormat<'_>) -> R) -> R {
+    let bytes: Vec<u8> = words.iter().map(|w| *w as u8).collect();
+    let mut buf = words.to_vec();
+
+    match kind {
+        Kind::U8 => f(DataFormat::U8(&bytes)),
+        Kind::U16 => f(DataFormat::U16(&buf)),
+        Kind::U16LE => f(DataFormat::U16LE(&mut buf)),
+        Kind::U16BE => f(DataFormat::U16BE(&mut buf)),
+        Kind::U8Iter => f(DataFormat::U8Iter(&mut bytes.iter().copied())),
+        Kind::U16LEIter => f(DataFormat::U16LEIter(&mut buf.iter().copied())),
+        Kind::U16BEIter => f(DataFormat::U16BEIter(&mut buf.iter().copied())),
+    }
+}
+
+fn interface(
+    spi: &[SpiTransaction<u8>],
+    dc: &[PinTransaction],
+) -> SpiInterface<SpiMock<u8>, PinMock> {
+    SpiInterface::new(SpiMock::new(spi), PinMock::new(dc))
+}
+
+fn done(iface: SpiInterface<SpiMock<u8>, PinMock>) {
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+/// Send `len` words of `kind` through the blocking and `async` implementations
+fn check(kind: Kind, len: usize, data: bool) {
+    let words = words(len);
+    let spi = expected(kind, &words);
+    let state = if data { State::High } else { State::Low };
+    let dc = [PinTransaction::set(state)];
+
+    let mut iface = interface(&spi, &dc);
+    with_format(kind, &words, |format| {
+        if data {
+            WriteOnlyDataCommand::send_data(&mut iface, format)
+        } else {
+            WriteOnlyDataCommand::send_commands(&mut iface, format)
+        }
+    })
+    .unwrap_or_else(|e| panic!("{kind:?} with {len} words: {e:?}"));
+    done(iface);
+
+    let mut iface = interface(&spi, &dc);
+    with_format(kind, &words, |format| {
+        block_on(async {
+            if data {
+                AsyncWriteOnlyDataCommand::send_data(&mut iface, format).await
+            } else {
+                AsyncWriteOnlyDataCommand::send_commands(&mut iface, format).await
+            }
+        })
+    })
+    .unwrap_or_else(|e| panic!("async {kind:?} with {len} words: {e:?}"));
+    done(iface);
+}
+
+#[test]
+fn data() {
+    for kind in KINDS {
+        for len in SIZES {
+            check(kind, len, true);
+        }
+    }
+}
+
+#[test]
+fn commands() {
+    for kind in KINDS {
+        for len in SIZES {
+            check(kind, len, false);
+        }
+    }
+}
+
+#[test]
+fn command_then_data() {
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+    ];
+    let spi: Vec<_> = [
+        transaction(vec![0x2c]),
+        transaction(vec![0x12, 0x34, 0x56, 0x78]),
+        transaction(vec![0x29]),
+    ]
+    .concat();
+
+    let mut iface = interface(&spi, &dc);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut [0x1234, 0x5678])).unwrap();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x29])).unwrap();
+    done(iface);
+
+    let mut iface = interface(&spi, &dc);
+    block_on(async {
+        AsyncWriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).await?;
+        AsyncWriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut [0x1234, 0x5678]))
+            .await?;
+        AsyncWriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x29])).await
+    })
+    .unwrap();
+    done(iface);
+}
+
+#[test]
+fn u16_is_native_byte_order() {
+    #[cfg(target_endian = "little")]
+    let bytes = vec![0x34, 0x12, 0x78, 0x56];
+    #[cfg(target_endian = "big")]
+    let bytes = vec![0x12, 0x34, 0x56, 0x78];
+
+    let spi = transaction(bytes);
+    let dc = [PinTransaction::set(State::High)];
+
+    let mut iface = interface(&spi, &dc);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16(&[0x1234, 0x5678])).unwrap();
+    done(iface);
+}
+
+#[test]
+fn data_only_interface() {
+    let spi = transaction(vec![0x80, 0x01, 0xff]);
+
+    let mut iface = SpiDataInterface::new(SpiMock::new(&[]));
+    assert!(matches!(
+        WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x80])),
+        Err(DisplayError::Unsupported)
+    ));
+    iface.release().done();
+
+    let mut iface = SpiDataInterface::new(SpiMock::new(&spi)).with_commands_as_data();
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x80, 0x01, 0xff])).unwrap();
+    iface.release().done();
+
+    let mut iface = SpiDataInterface::new(SpiMock::new(&spi));
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U8(&[0x80, 0x01, 0xff]),
+    ))
+    .unwrap();
+    iface.release().done();
+}
+
+#[cfg(feature = "static-buffer")]
+#[test]
+fn static_buffer_bounds_iterator_transfers() {
+    let words = words(5);
+    let spi: Vec<_> = words
+        .chunks(2)
+        .flat_map(|chunk| transaction(bytes(Kind::U16BEIter, chunk)))
+        .collect();
+    let dc = [PinTransaction::set(State::High)];
+
+    // Holds two words, the odd byte is left unused
+    let interface = || {
+        let buffer: &'static mut [u8] = Box::leak(Box::new([0; 5]));
+        SpiInterface::new_with_buffer(SpiMock::new(&spi), PinMock::new(&dc), buffer)
+    };
+
+    let mut iface = interface();
+    with_format(Kind::U16BEIter, &words, |format| {
+        WriteOnlyDataCommand::send_data(&mut iface, format)
+    })
+    .unwrap();
+    done(iface);
+
+    let mut iface = interface();
+    with_format(Kind::U16BEIter, &words, |format| {
+        block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, format))
+    })
+    .unwrap();
+    done(iface);
+}
+
+#[test]
+fn lsb_first_reverses_every_byte() {
+    use display_interface_spi::BitOrder;
+
+    let reversed = [0x80, 0x01, 0x48, 0x0f, 0x48, 0x2c];
+    let spi: Vec<_> = [
+        transaction(reversed[..4].to_vec()),
+        transaction(reversed[4..].to_vec()),
+        transaction(reversed[4..].to_vec()),
+    ]
+    .concat();
+    let dc = [
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::High),
+    ];
+
+    let interface = || interface(&spi, &dc).with_bit_order(BitOrder::LsbFirst);
+
+    let mut iface = interface();
+    let mut words = [0x1234];
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x01, 0x80, 0x12, 0xf0])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut words)).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BEIter(&mut [0x1234].into_iter()))
+        .unwrap();
+    assert_eq!(words, [0x1234]);
+    done(iface);
+
+    let mut iface = interface();
+    block_on(async {
+        AsyncWriteOnlyDataCommand::send_data(
+            &mut iface,
+            DataFormat::U8Iter(&mut [0x01, 0x80, 0x12, 0xf0].into_iter()),
+        )
+        .await?;
+        AsyncWriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16LE(&mut [0x3412])).await?;
+        AsyncWriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16(&[0x1234u16.to_be()]))
+            .await
+    })
+    .unwrap();
+    done(iface);
+}
+
+#[test]
+fn write_format_with_caller_chunk() {
+    use display_interface_spi::{write_format, write_format_async};
+
+    let spi: Vec<_> = [
+        transaction(vec![0x12, 0x34, 0x56, 0x78]),
+        transaction(vec![0x12, 0x34]),
+        transaction(vec![0x56, 0x78]),
+    ]
+    .concat();
+    let mut chunk = [0; 3];
+
+    let mut device = SpiMock::new(&spi);
+    write_format(
+        &mut device,
+        DataFormat::U16BE(&mut [0x1234, 0x5678]),
+        &mut chunk,
+    )
+    .unwrap();
+    write_format(
+        &mut device,
+        DataFormat::U16BEIter(&mut [0x1234, 0x5678].into_iter()),
+        &mut chunk,
+    )
+    .unwrap();
+    assert!(matches!(
+        write_format(
+            &mut device,
+            DataFormat::U16LEIter(&mut [0].into_iter()),
+            &mut [0; 1]
+        ),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    device.done();
+
+    let mut device = SpiMock::new(&spi);
+    block_on(async {
+        write_format_async(
+            &mut device,
+            DataFormat::U8(&[0x12, 0x34, 0x56, 0x78]),
+            &mut chunk,
+        )
+        .await?;
+        write_format_async(
+            &mut device,
+            DataFormat::U8Iter(&mut [0x12, 0x34, 0x56, 0x78].into_iter()),
+            &mut [0; 2],
+        )
+        .await
+    })
+    .unwrap();
+    device.done();
+}
+
+#[test]
+fn bounced_slices_are_written_like_iterators() {
+    for (kind, iter_kind) in [
+        (Kind::U8, Kind::U8Iter),
+        (Kind::U16LE, Kind::U16LEIter),
+        (Kind::U16BE, Kind::U16BEIter),
+    ] {
+        let words = words(130);
+        let spi = expected(iter_kind, &words);
+        let dc = [PinTransaction::set(State::High)];
+
+        let mut iface = interface(&spi, &dc).with_bounce_const_data(true);
+        with_format(kind, &words, |format| {
+            WriteOnlyDataCommand::send_data(&mut iface, format)
+        })
+        .unwrap();
+        done(iface);
+
+        let mut iface = interface(&spi, &dc).with_bounce_const_data(true);
+        with_format(kind, &words, |format| {
+            block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, format))
+        })
+        .unwrap();
+        done(iface);
+    }
+
+    let mut buf = [0x1234, 0x5678];
+    let spi = transaction(vec![0x12, 0x34, 0x56, 0x78]);
+    let mut iface =
+        interface(&spi, &[PinTransaction::set(State::High)]).with_bounce_const_data(true);
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut buf)).unwrap();
+    done(iface);
+    assert_eq!(buf, [0x1234, 0x5678]);
+}
+
+#[test]
+fn delays_stay_within_the_transaction() {
+    use display_interface_spi::SequenceStep;
+
+    let spi = [
+        // Sleep out without parameters
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write(0x11),
+        SpiTransaction::delay(120_000),
+        SpiTransaction::transaction_end(),
+        // FRMCTR1, pausing between the command and its parameters, with the transaction and
+        // therefore chip select ending after the pause
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write(0xb1),
+        SpiTransaction::delay(500),
+        SpiTransaction::transaction_end(),
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(vec![0x01, 0x2c, 0x2d]),
+        SpiTransaction::transaction_end(),
+        // The same as a sequence, followed by another command
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write(0xb1),
+        SpiTransaction::delay(500),
+        SpiTransaction::transaction_end(),
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(vec![0x01, 0x2c, 0x2d]),
+        SpiTransaction::delay(10),
+        SpiTransaction::transaction_end(),
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write(0x29),
+        SpiTransaction::transaction_end(),
+    ];
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+    ];
+    let sequence = [
+        SequenceStep::Command(&[0xb1]),
+        SequenceStep::DelayNs(500),
+        SequenceStep::Data(&[0x01, 0x2c, 0x2d]),
+        SequenceStep::DelayNs(10),
+        SequenceStep::Command(&[0x29]),
+    ];
+
+    let mut iface = interface(&spi, &dc);
+    iface.send_command_then_wait(0x11, &[], 120_000).unwrap();
+    iface
+        .send_command_then_wait(0xb1, &[0x01, 0x2c, 0x2d], 500)
+        .unwrap();
+    iface.send_sequence(&sequence).unwrap();
+    done(iface);
+
+    let mut iface = interface(&spi, &dc);
+    block_on(async {
+        iface
+            .send_command_then_wait_async(0x11, &[], 120_000)
+            .await?;
+        iface
+            .send_command_then_wait_async(0xb1, &[0x01, 0x2c, 0x2d], 500)
+            .await?;
+        iface.send_sequence_async(&sequence).await
+    })
+    .unwrap();
+    done(iface);
+}
+
+#[test]
+fn bus_sequences_keep_chip_select_asserted() {
+    use display_interface_spi::{SequenceStep, SpiBusInterface};
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+    // The bus is flushed before every pause and before the D/C pin changes
+    let spi = [
+        SpiTransaction::write_vec(vec![0xb1]),
+        SpiTransaction::flush(),
+        SpiTransaction::write_vec(vec![0x01, 0x2c, 0x2d]),
+        SpiTransaction::flush(),
+        SpiTransaction::write_vec(vec![0x29]),
+        SpiTransaction::flush(),
+    ];
+    // Chip select is asserted once around the whole sequence
+    let cs = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+    ];
+    let sequence = [
+        SequenceStep::Command(&[0xb1]),
+        SequenceStep::DelayNs(500),
+        SequenceStep::Data(&[0x01, 0x2c, 0x2d]),
+        SequenceStep::DelayNs(10),
+        SequenceStep::Command(&[0x29]),
+    ];
+
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::blocking_delay_ns(500),
+        DelayTransaction::blocking_delay_ns(10),
+    ]);
+    let mut iface = SpiBusInterface::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+    iface.send_sequence(&sequence, &mut delay).unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+    delay.done();
+
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::async_delay_ns(500),
+        DelayTransaction::async_delay_ns(10),
+    ]);
+    let mut iface = SpiBusInterface::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+    block_on(iface.send_sequence_async(&sequence, &mut delay)).unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+    delay.done();
+
+    // Without parameters the command and its pause are all there is
+    let spi = [
+        SpiTransaction::write_vec(vec![0x11]),
+        SpiTransaction::flush(),
+    ];
+    let mut delay = CheckedDelay::new(&[DelayTransaction::blocking_delay_ns(120_000)]);
+    let mut iface = SpiBusInterface::new(
+        SpiMock::new(&spi),
+        PinMock::new(&dc[..1]),
+        PinMock::new(&cs),
+    );
+    iface
+        .send_command_then_wait(0x11, &[], 120_000, &mut delay)
+        .unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+    delay.done();
+}
+
+#[test]
+fn chip_select_toggles_around_every_call() {
+    use display_interface_spi::SpiInterfaceWithCs;
+
+    let spi = [
+        SpiTransaction::write_vec(vec![0x2c]),
+        SpiTransaction::flush(),
+        SpiTransaction::write_vec(vec![0x12, 0x34]),
+        SpiTransaction::flush(),
+    ];
+    // Deasserted between the command and its data
+    let cs = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+
+    let mut iface =
+        SpiInterfaceWithCs::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).unwrap();
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x12, 0x34])).unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+
+    let mut iface =
+        SpiInterfaceWithCs::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+    block_on(async {
+        AsyncWriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).await?;
+        AsyncWriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0x12, 0x34])).await
+    })
+    .unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+}
+
+#[test]
+fn sequences_reject_changed_writes() {
+    use display_interface_spi::{BitOrder, SequenceStep};
+
+    // Reversed bits or split transfers can't keep a run in one transaction, so nothing is sent
+    let sequence = [SequenceStep::Command(&[0xb1]), SequenceStep::DelayNs(500)];
+    let interfaces = [
+        interface(&[], &[]).with_bit_order(BitOrder::LsbFirst),
+        interface(&[], &[]).with_max_transfer_len(64),
+    ];
+    for mut iface in interfaces {
+        assert!(matches!(
+            iface.send_sequence(&sequence),
+            Err(DisplayError::Unsupported)
+        ));
+        assert!(matches!(
+            block_on(iface.send_command_then_wait_async(0x11, &[], 120_000)),
+            Err(DisplayError::Unsupported)
+        ));
+        done(iface);
+    }
+}
+
+#[test]
+fn long_sequences_are_rejected_up_front() {
+    use display_interface_spi::{SequenceStep, MAX_SEQUENCE_STEPS};
+
+    let steps = [SequenceStep::Command(&[0x00]); MAX_SEQUENCE_STEPS + 1];
+    let mut iface = interface(&[], &[]);
+    assert!(matches!(
+        iface.send_sequence(&steps),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    done(iface);
+}
+
+#[test]
+fn cs_timing_waits_around_chip_select() {
+    use display_interface_spi::{CsTiming, SpiBusInterface};
+    use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+
+    let spi = [
+        SpiTransaction::write_vec(vec![0x2c]),
+        SpiTransaction::flush(),
+    ];
+    let cs = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let dc = [PinTransaction::set(State::Low)];
+    let timing = CsTiming {
+        setup_ns: 50,
+        hold_ns: 2_000,
+    };
+
+    let delay = CheckedDelay::new(&[
+        DelayTransaction::blocking_delay_ns(50),
+        DelayTransaction::blocking_delay_ns(2_000),
+    ]);
+    let mut iface = SpiBusInterface::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs))
+        .with_cs_timing(timing, delay);
+    WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(&[0x2c])).unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock, mut delay) = iface.release_with_delay();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+    delay.done();
+
+    let delay = CheckedDelay::new(&[
+        DelayTransaction::async_delay_ns(50),
+        DelayTransaction::async_delay_ns(2_000),
+    ]);
+    let mut iface = SpiBusInterface::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs))
+        .with_cs_timing(timing, delay);
+    block_on(AsyncWriteOnlyDataCommand::send_commands(
+        &mut iface,
+        DataFormat::U8(&[0x2c]),
+    ))
+    .unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock, mut delay) = iface.release_with_delay();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+    delay.done();
+}
+
+#[test]
+fn suspend_parks_pins() {
+    use display_interface::LowPower;
+    use display_interface_spi::SpiBusInterface;
+
+    let dc = [PinTransaction::set(State::Low)];
+    let mut iface = interface(&[], &dc);
+    iface.suspend().unwrap();
+    iface.resume().unwrap();
+    done(iface);
+
+    let spi = [SpiTransaction::flush()];
+    let cs = [
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiBusInterface::new(SpiMock::new(&spi), PinMock::new(&dc), PinMock::new(&cs));
+    iface.suspend().unwrap();
+    iface.resume().unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+}
+
+#[test]
+fn resync_drives_idle_levels() {
+    use display_interface_spi::SpiBusInterface;
+
+    let dc = [PinTransaction::set(State::Low)];
+    let mut iface = interface(&[], &dc);
+    iface.resync().unwrap();
+    done(iface);
+
+    let cs = [PinTransaction::set(State::High)];
+    let mut iface = SpiBusInterface::new(
+        SpiMock::<u8>::new(&[]),
+        PinMock::new(&dc),
+        PinMock::new(&cs),
+    );
+    iface.resync().unwrap();
+    let (mut spi_mock, mut dc_mock, mut cs_mock) = iface.release();
+    spi_mock.done();
+    dc_mock.done();
+    cs_mock.done();
+}
+
+/// Send `words` of `kind` as data with transfers of at most `limit` bytes, through the blocking
+/// and `async` implementations, expecting `spi` on the wire
+fn send_limited(
+    limit: usize,
+    kind: Kind,
+    words: &[u16],
+    spi: &[SpiTransaction<u8>],
+) -> [Result<(), DisplayError>; 2] {
+    let dc = [PinTransaction::set(State::High)];
+
+    let mut iface = interface(spi, &dc).with_max_transfer_len(limit);
+    let blocking = with_format(kind, words, |format| {
+        WriteOnlyDataCommand::send_data(&mut iface, format)
+    });
+    done(iface);
+
+    let mut iface = interface(spi, &dc).with_max_transfer_len(limit);
+    let asynch = with_format(kind, words, |format| {
+        block_on(AsyncWriteOnlyDataCommand::send_data(&mut iface, format))
+    });
+    done(iface);
+
+    [blocking, asynch]
+}
+
+/// A transaction of its own for each of `chunks`, with a single write
+fn chunked(chunks: &[&[u8]]) -> Vec<SpiTransaction<u8>> {
+    chunks
+        .iter()
+        .flat_map(|c| transaction(c.to_vec()))
+        .collect()
+}
+
+#[test]
+fn odd_transfer_limit_keeps_words_whole() {
+    let words = words(70);
+
+    // Bytes are split right at the limit
+    let b = bytes(Kind::U8, &words[..12]);
+    let spi = chunked(&[&b[..5], &b[5..10], &b[10..]]);
+    for result in send_limited(5, Kind::U8, &words[..12], &spi) {
+        result.unwrap();
+    }
+
+    // 16-bit words only fit twice into five bytes
+    let b = bytes(Kind::U16BE, &words[..6]);
+    let spi = chunked(&[&b[..4], &b[4..8], &b[8..]]);
+    for result in send_limited(5, Kind::U16BE, &words[..6], &spi) {
+        result.unwrap();
+    }
+
+    // Iterators are split per staging buffer of 64 words
+    let b = bytes(Kind::U16BEIter, &words);
+    let mut spi = Vec::new();
+    for staged in [&b[..128], &b[128..]] {
+        let chunks: Vec<_> = staged.chunks(4).collect();
+        spi.extend(chunked(&chunks));
+    }
+    assert_eq!(spi.len(), 3 * (32 + 3));
+    for result in send_limited(5, Kind::U16BEIter, &words, &spi) {
+        result.unwrap();
+    }
+}
+
+#[test]
+fn transfer_limit_below_one_word() {
+    let words = words(3);
+
+    // Single bytes are fine
+    let b = bytes(Kind::U8, &words);
+    let spi = chunked(&[&b[..1], &b[1..2], &b[2..]]);
+    for result in send_limited(1, Kind::U8, &words, &spi) {
+        result.unwrap();
+    }
+
+    // 16-bit words can't be sent without splitting them, so nothing is written
+    for kind in [Kind::U16, Kind::U16LE, Kind::U16BE, Kind::U16BEIter] {
+        for result in send_limited(1, kind, &words, &[]) {
+            assert!(
+                matches!(result, Err(DisplayError::InvalidFormatError)),
+                "{kind:?}: {result:?}"
+            );
+        }
+    }
+}
+
+/// One transaction writing each of `chunks` followed by a pause of `ns`
+fn paced(chunks: &[&[u8]], ns: u32) -> Vec<SpiTransaction<u8>> {
+    let writes = chunks.iter().flat_map(|c| {
+        [
+            SpiTransaction::write_vec(c.to_vec()),
+            SpiTransaction::delay(ns),
+        ]
+    });
+    [SpiTransaction::transaction_start()]
+        .into_iter()
+        .chain(writes)
+        .chain([SpiTransaction::transaction_end()])
+        .collect()
+}
+
+/// Send `cmds` as commands, then `data` as data and `pixels` from an iterator with `pacing`,
+/// through the blocking and `async` implementations
+fn check_pacing(
+    pacing: display_interface_spi::Pacing,
+    max_transfer_len: Option<usize>,
+    calls: (&[u8], &[u8], &[u8]),
+    spi: &[SpiTransaction<u8>],
+) {
+    let (cmds, data, pixels) = calls;
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::High),
+    ];
+    let iface = |spi| {
+        let iface = interface(spi, &dc).with_pacing(pacing);
+        match max_transfer_len {
+            Some(len) => iface.with_max_transfer_len(len),
+            None => iface,
+        }
+    };
+
+    let mut blocking = iface(spi);
+    WriteOnlyDataCommand::send_commands(&mut blocking, DataFormat::U8(cmds)).unwrap();
+    WriteOnlyDataCommand::send_data(&mut blocking, DataFormat::U8(data)).unwrap();
+    let iter = &mut pixels.iter().copied();
+    WriteOnlyDataCommand::send_data(&mut blocking, DataFormat::U8Iter(iter)).unwrap();
+    done(blocking);
+
+    let mut asynch = iface(spi);
+    block_on(async {
+        AsyncWriteOnlyDataCommand::send_commands(&mut asynch, DataFormat::U8(cmds)).await?;
+        AsyncWriteOnlyDataCommand::send_data(&mut asynch, DataFormat::U8(data)).await?;
+        let iter = &mut pixels.iter().copied();
+        AsyncWriteOnlyDataCommand::send_data(&mut asynch, DataFormat::U8Iter(iter)).await
+    })
+    .unwrap();
+    done(asynch);
+}
+
+#[test]
+fn pacing_pauses_between_writes() {
+    use display_interface_spi::Pacing;
+
+    let cmds = [0x2a, 0x00, 0x00, 0x00, 0xef];
+    let data = [0x55; 4];
+    let pixels: Vec<u8> = (0..100).collect();
+
+    // Every command byte is followed by its pause, data chunks by theirs
+    let pacing = Pacing {
+        command_byte_gap_us: 10,
+        chunk_gap_us: 50,
+    };
+    let single: Vec<&[u8]> = cmds.iter().map(core::slice::from_ref).collect();
+    let mut spi = paced(&single, 10_000);
+    spi.extend(paced(&[&data], 50_000));
+    spi.extend(paced(&[&pixels[..64]], 50_000));
+    spi.extend(paced(&[&pixels[64..]], 50_000));
+    check_pacing(pacing, None, (&cmds, &data, &pixels), &spi);
+
+    // Commands fall back to the chunk gap, and a limit splits the chunks further, each with its
+    // pause in a transaction of its own
+    let pacing = Pacing {
+        command_byte_gap_us: 0,
+        chunk_gap_us: 2,
+    };
+    let spi: Vec<_> = [&cmds[..], &data, &pixels[..64], &pixels[64..]]
+        .into_iter()
+        .flat_map(|bytes| bytes.chunks(3))
+        .flat_map(|chunk| paced(&[chunk], 2_000))
+        .collect();
+    check_pacing(pacing, Some(3), (&cmds, &data, &pixels), &spi);
+}
+
+#[test]
+fn paced_command_bytes_beyond_a_transaction() {
+    use display_interface_spi::Pacing;
+
+    // At most eight writes with their pauses share a transaction
+    let cmds: Vec<u8> = (0..10).collect();
+    let pacing = Pacing {
+        command_byte_gap_us: 1,
+        chunk_gap_us: 0,
+    };
+    let single: Vec<&[u8]> = cmds.iter().map(core::slice::from_ref).collect();
+    let mut spi = paced(&single[..8], 1_000);
+    spi.extend(paced(&single[8..], 1_000));
+    spi.extend(transaction(vec![0x00]));
+    spi.extend(transaction(vec![0x01]));
+    check_pacing(pacing, None, (&cmds, &[0x00], &[0x01]), &spi);
+}
+
+/// Device failing every transaction with `ErrorKind::Overrun`
+struct Overrun;
+
+impl ErrorType for Overrun {
+    type Error = ErrorKind;
+}
+
+impl SpiDevice for Overrun {
+    fn transaction(&mut self, _: &mut [Operation<'_, u8>]) -> Result<(), ErrorKind> {
+        Err(ErrorKind::Overrun)
+    }
+}
+
+#[test]
+fn typed_errors_keep_the_device_and_pin_errors() {
+    let dc = [
+        PinTransaction::set(State::Low).with_error(MockError::Io(std::io::ErrorKind::Other)),
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiInterface::new(Overrun, PinMock::new(&dc));
+
+    let result = iface.send_commands_typed(DataFormat::U8(&[0x2c]));
+    assert!(matches!(
+        result,
+        Err(SpiInterfaceError::Dc(MockError::Io(_)))
+    ));
+
+    let result = iface.send_data_typed(DataFormat::U8(&[0]));
+    assert!(matches!(
+        result,
+        Err(SpiInterfaceError::Spi(ErrorKind::Overrun))
+    ));
+
+    let result = WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(&[0]));
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+
+    iface.release().1.done();
+}
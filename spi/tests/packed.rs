@@ -0,0 +1,113 @@
+//! Bit exact packing of 9-bit words by the 3-wire interface
+
+mod common;
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::Spi3WirePackedInterface;
+use embedded_hal_mock::eh1::spi::{Mock as SpiMock, Transaction as SpiTransaction};
+
+use common::block_on;
+
+fn transaction(bytes: Vec<u8>) -> [SpiTransaction<u8>; 3] {
+    [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(bytes),
+        SpiTransaction::transaction_end(),
+    ]
+}
+
+/// Check the bytes sent for `cmds` and `data` by the blocking and `async` implementations
+fn check(cmds: &[u8], data: &[u8], expected: &[Vec<u8>]) {
+    let spi: Vec<_> = expected
+        .iter()
+        .flat_map(|bytes| transaction(bytes.clone()))
+        .collect();
+
+    let mut iface = Spi3WirePackedInterface::new(SpiMock::new(&spi));
+    if !cmds.is_empty() {
+        WriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(cmds)).unwrap();
+    }
+    if !data.is_empty() {
+        WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8Iter(&mut data.iter().copied()))
+            .unwrap();
+    }
+    iface.release().done();
+
+    let mut iface = Spi3WirePackedInterface::new(SpiMock::new(&spi));
+    block_on(async {
+        if !cmds.is_empty() {
+            AsyncWriteOnlyDataCommand::send_commands(&mut iface, DataFormat::U8(cmds)).await?;
+        }
+        if !data.is_empty() {
+            AsyncWriteOnlyDataCommand::send_data(&mut iface, DataFormat::U8(data)).await?;
+        }
+        Ok::<_, display_interface::DisplayError>(())
+    })
+    .unwrap();
+    iface.release().done();
+}
+
+#[test]
+fn single_command_is_padded() {
+    // 0_00101010 + 7 padding bits
+    check(&[0x2a], &[], &[vec![0b0001_0101, 0b0000_0000]]);
+}
+
+#[test]
+fn data_bit_precedes_every_byte() {
+    // 1_00000000 1_10000000 + 6 padding bits
+    check(
+        &[],
+        &[0x00, 0x80],
+        &[vec![0b1000_0000, 0b0110_0000, 0b0000_0000]],
+    );
+}
+
+#[test]
+fn eight_words_fill_nine_bytes() {
+    check(&[], &[0xff; 8], &[vec![0xff; 9]]);
+
+    // 0_10101010 0_01010101 ... alternating
+    check(
+        &[0xaa, 0x55, 0xaa, 0x55, 0xaa, 0x55, 0xaa, 0x55],
+        &[],
+        &[vec![
+            0b0101_0101,
+            0b0001_0101,
+            0b0101_0101,
+            0b0100_0101,
+            0b0101_0101,
+            0b0101_0001,
+            0b0101_0101,
+            0b0101_0100,
+            0b0101_0101,
+        ]],
+    );
+}
+
+#[test]
+fn calls_are_split_into_chunks_of_64_words() {
+    let mut expected = vec![vec![0xff; 72]];
+    // 1_11111111 + 7 padding bits
+    expected.push(vec![0xff, 0x80]);
+
+    check(&[], &[0xff; 65], &expected);
+}
+
+#[test]
+fn u16_formats_send_both_bytes() {
+    // 1_00010010 1_00110100
+    let expected = transaction(vec![0b1000_1001, 0b0100_1101, 0b0000_0000]);
+
+    let mut iface = Spi3WirePackedInterface::new(SpiMock::new(&expected));
+    WriteOnlyDataCommand::send_data(&mut iface, DataFormat::U16BE(&mut [0x1234])).unwrap();
+    iface.release().done();
+
+    let mut iface = Spi3WirePackedInterface::new(SpiMock::new(&expected));
+    block_on(AsyncWriteOnlyDataCommand::send_data(
+        &mut iface,
+        DataFormat::U16LEIter(&mut [0x3412].into_iter()),
+    ))
+    .unwrap();
+    iface.release().done();
+}
@@ -0,0 +1,127 @@
+//! Staging, writing and yielding of the `async` implementation, seen by polling its futures by
+//! hand
+
+mod common;
+
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use display_interface::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError};
+use display_interface_spi::{NoCs, NullSpiDevice, SpiInterface};
+use embedded_hal::spi::{ErrorKind, ErrorType, Operation};
+
+use common::{block_on, count_pending};
+
+/// Future returning `Pending` once, like a DMA transfer started on the first poll
+struct Busy(bool);
+
+impl Future for Busy {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            Poll::Ready(())
+        } else {
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+/// Device noting how many words were taken from the iterator when each write started and ended
+struct Dma {
+    pulled: Rc<Cell<usize>>,
+    writes: Rc<RefCell<Vec<(usize, usize)>>>,
+    fail: bool,
+}
+
+impl ErrorType for Dma {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal_async::spi::SpiDevice for Dma {
+    async fn transaction(
+        &mut self,
+        _operations: &mut [Operation<'_, u8>],
+    ) -> Result<(), ErrorKind> {
+        if self.fail {
+            return Err(ErrorKind::Other);
+        }
+
+        let started = self.pulled.get();
+        Busy(false).await;
+        self.writes.borrow_mut().push((started, self.pulled.get()));
+        Ok(())
+    }
+}
+
+/// Send 200 counted bytes from an iterator to a [Dma] device
+fn send(fail: bool) -> (Result<(), DisplayError>, usize, Vec<(usize, usize)>) {
+    let pulled = Rc::new(Cell::new(0));
+    let writes = Rc::new(RefCell::new(Vec::new()));
+    let dma = Dma {
+        pulled: pulled.clone(),
+        writes: writes.clone(),
+        fail,
+    };
+    let mut iface = SpiInterface::new(dma, NoCs);
+
+    let mut bytes = (0..200).map(|i| {
+        pulled.set(pulled.get() + 1);
+        i as u8
+    });
+    let result = block_on(iface.send_data(DataFormat::U8Iter(&mut bytes)));
+    (result, pulled.get(), writes.take())
+}
+
+#[test]
+fn next_chunk_is_staged_while_writing() {
+    let (result, pulled, writes) = send(false);
+    result.unwrap();
+    assert_eq!(pulled, 200);
+    // Every write but the last ends with the following chunk of 64 bytes staged
+    assert_eq!(writes, [(64, 128), (128, 192), (192, 200), (200, 200)]);
+}
+
+#[test]
+fn nothing_is_staged_after_a_failed_write() {
+    let (result, pulled, writes) = send(true);
+    assert!(matches!(result, Err(DisplayError::BusWriteError)));
+    assert_eq!(pulled, 64);
+    assert!(writes.is_empty());
+}
+
+/// How often sending 6 staging buffers of bytes returns `Pending` when yielding every `chunks`
+fn yields(chunks: usize, iterator: bool) -> usize {
+    let mut iface = SpiInterface::new(NullSpiDevice::new(), NoCs).with_yield_every(chunks);
+    let bytes = [0x5a; 6 * 64];
+
+    let (result, pending) = if iterator {
+        count_pending(iface.send_data(DataFormat::U8Iter(&mut bytes.into_iter())))
+    } else {
+        count_pending(iface.send_data(DataFormat::U8(&bytes)))
+    };
+    result.unwrap();
+    pending
+}
+
+#[test]
+fn iterators_yield_every_few_chunks() {
+    // The null device never suspends, so every `Pending` is a yield
+    assert_eq!(yields(0, true), 0);
+    assert_eq!(yields(1, true), 6);
+    assert_eq!(yields(2, true), 3);
+    assert_eq!(yields(4, true), 1);
+    assert_eq!(yields(7, true), 0);
+}
+
+#[test]
+fn slices_never_yield() {
+    assert_eq!(yields(1, false), 0);
+}
@@ -0,0 +1,135 @@
+//! Reading registers and data back, with and without dummy bits before the response
+
+mod common;
+
+use display_interface::{AsyncReadData, ReadData};
+use display_interface_spi::SpiInterface;
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+use common::block_on;
+
+/// RDDID response of an ILI9341
+const ID: [u8; 3] = [0x00, 0x93, 0x41];
+
+/// The bytes clocked in for `data` following `bits` dummy bits, which read as ones
+///
+/// Up to 7 bits shift the data into one more byte, 8 make up a whole byte of their own.
+fn wire(data: &[u8], bits: u8) -> Vec<u8> {
+    match bits {
+        0 => data.to_vec(),
+        8 => [&[0xff][..], data].concat(),
+        _ => {
+            let mut stream = Vec::new();
+            let mut carry = 0xffu8 << (8 - bits);
+            for &byte in data {
+                stream.push(carry | (byte >> bits));
+                carry = byte << (8 - bits);
+            }
+            stream.push(carry);
+            stream
+        }
+    }
+}
+
+/// Transaction of a read of `ID` after `bits` dummy bits, preceded by `prefix`
+fn read(prefix: &[u8], bits: u8) -> Vec<SpiTransaction<u8>> {
+    let mut wire = wire(&ID, bits);
+    let reads = match bits {
+        0 => vec![SpiTransaction::read_vec(wire)],
+        8 => {
+            let data = wire.split_off(1);
+            vec![
+                SpiTransaction::read_vec(wire),
+                SpiTransaction::read_vec(data),
+            ]
+        }
+        _ => {
+            let extra = wire.split_off(ID.len());
+            vec![
+                SpiTransaction::read_vec(wire),
+                SpiTransaction::read_vec(extra),
+            ]
+        }
+    };
+
+    [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(prefix.to_vec()),
+    ]
+    .into_iter()
+    .chain(reads)
+    .chain([SpiTransaction::transaction_end()])
+    .collect()
+}
+
+fn interface(
+    spi: &[SpiTransaction<u8>],
+    dc: State,
+    bits: u8,
+) -> SpiInterface<SpiMock<u8>, PinMock> {
+    SpiInterface::new(SpiMock::new(spi), PinMock::new(&[PinTransaction::set(dc)]))
+        .with_read_dummy_bits(bits)
+}
+
+fn done(iface: SpiInterface<SpiMock<u8>, PinMock>) {
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+/// Read `ID` as the response of RDDID and as plain data, through the blocking and `async`
+/// implementations, with `bits` dummy bits configured and `wire_bits` on the wire
+fn check(bits: u8, wire_bits: u8) {
+    let register = read(&[0x04], wire_bits);
+    let data = read(&[], wire_bits);
+
+    let mut buf = [0; 3];
+    let mut iface = interface(&register, State::Low, bits);
+    iface.read_register(0x04, &mut buf).unwrap();
+    assert_eq!(buf, ID, "register after {bits} dummy bits");
+    done(iface);
+
+    let mut buf = [0; 3];
+    let mut iface = interface(&register, State::Low, bits);
+    block_on(iface.read_register_async(0x04, &mut buf)).unwrap();
+    assert_eq!(buf, ID, "async register after {bits} dummy bits");
+    done(iface);
+
+    let mut buf = [0; 3];
+    let mut iface = interface(&data, State::High, bits);
+    ReadData::read_data(&mut iface, &mut buf).unwrap();
+    assert_eq!(buf, ID, "data after {bits} dummy bits");
+    done(iface);
+
+    let mut buf = [0; 3];
+    let mut iface = interface(&data, State::High, bits);
+    block_on(AsyncReadData::read_data(&mut iface, &mut buf)).unwrap();
+    assert_eq!(buf, ID, "async data after {bits} dummy bits");
+    done(iface);
+}
+
+#[test]
+fn without_dummy_bits() {
+    check(0, 0);
+}
+
+#[test]
+fn dummy_byte() {
+    check(8, 8);
+}
+
+#[test]
+fn dummy_bits_short_of_a_byte() {
+    for bits in 1..8 {
+        check(bits, bits);
+    }
+}
+
+#[test]
+fn more_than_eight_dummy_bits_skip_a_byte() {
+    check(9, 8);
+    check(u8::MAX, 8);
+}
@@ -0,0 +1,114 @@
+//! Framing of Sharp memory LCD transfers
+
+use display_interface::{DataFormat, DisplayError, WriteOnlyDataCommand};
+use display_interface_spi::{BitOrder, SharpMemoryInterface};
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+/// Chip select transactions for `count` transfers, asserted high
+fn framed(count: usize) -> Vec<PinTransaction> {
+    (0..count)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::High),
+                PinTransaction::set(State::Low),
+            ]
+        })
+        .collect()
+}
+
+#[test]
+fn clear_is_sent_right_away() {
+    let spi = [
+        SpiTransaction::write_vec(vec![0x04]),
+        SpiTransaction::write_vec(vec![0x00]),
+        SpiTransaction::flush(),
+    ];
+    let mut iface = SharpMemoryInterface::new(SpiMock::new(&spi), PinMock::new(&framed(1)));
+
+    iface.send_commands(DataFormat::U8(&[0x04])).unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn lines_follow_the_update_mode() {
+    let lines = [1, 0xaa, 0x55, 0x00, 2, 0xff, 0x00, 0x00];
+    let spi = [
+        SpiTransaction::write_vec(vec![0x03]),
+        SpiTransaction::write_vec(lines.to_vec()),
+        SpiTransaction::write_vec(vec![0x00]),
+        SpiTransaction::flush(),
+    ];
+    let mut iface = SharpMemoryInterface::new(SpiMock::new(&spi), PinMock::new(&framed(1)));
+
+    iface.send_commands(DataFormat::U8(&[0x03])).unwrap();
+    iface.send_data(DataFormat::U8(&lines)).unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn bits_are_reversed_in_software() {
+    let spi = [
+        SpiTransaction::write_vec(vec![0x80]),
+        SpiTransaction::write_vec(vec![0x80, 0x0f, 0x00]),
+        SpiTransaction::write_vec(vec![0x00]),
+        SpiTransaction::flush(),
+    ];
+    let mut iface = SharpMemoryInterface::new(SpiMock::new(&spi), PinMock::new(&framed(1)))
+        .with_bit_order(BitOrder::LsbFirst);
+
+    iface.send_commands(DataFormat::U8(&[0x01])).unwrap();
+    iface
+        .send_data(DataFormat::U8Iter(&mut [0x01, 0xf0, 0x00].into_iter()))
+        .unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn data_needs_an_update_mode() {
+    let mut iface = SharpMemoryInterface::new(SpiMock::new(&[]), PinMock::new(&[]));
+
+    let result = iface.send_data(DataFormat::U8(&[1, 0xff, 0x00]));
+    assert!(matches!(result, Err(DisplayError::InvalidFormatError)));
+    let result = iface.send_commands(DataFormat::U8(&[0x01, 0x00]));
+    assert!(matches!(result, Err(DisplayError::InvalidFormatError)));
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn resync_deasserts_chip_select_and_keeps_the_update_mode() {
+    let lines = [1, 0xaa, 0x00];
+    let spi = [
+        SpiTransaction::write_vec(vec![0x01]),
+        SpiTransaction::write_vec(lines.to_vec()),
+        SpiTransaction::write_vec(vec![0x00]),
+        SpiTransaction::flush(),
+    ];
+    let cs = [PinTransaction::set(State::Low)]
+        .into_iter()
+        .chain(framed(1))
+        .collect::<Vec<_>>();
+    let mut iface = SharpMemoryInterface::new(SpiMock::new(&spi), PinMock::new(&cs));
+
+    iface.send_commands(DataFormat::U8(&[0x01])).unwrap();
+    iface.resync().unwrap();
+    iface.send_data(DataFormat::U8(&lines)).unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
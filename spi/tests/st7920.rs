@@ -0,0 +1,106 @@
+//! Serial framing of the ST7920, checked with `embedded-hal-mock`
+
+use display_interface::{DataFormat, WriteOnlyDataCommand};
+use display_interface_spi::St7920SerialInterface;
+use embedded_hal_mock::eh1::{
+    delay::{CheckedDelay, Transaction as DelayTransaction},
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+/// Chip select transactions for `count` calls, asserted high
+fn framed(count: usize) -> Vec<PinTransaction> {
+    (0..count)
+        .flat_map(|_| {
+            [
+                PinTransaction::set(State::High),
+                PinTransaction::set(State::Low),
+            ]
+        })
+        .collect()
+}
+
+fn write(bytes: &[u8]) -> [SpiTransaction<u8>; 2] {
+    [
+        SpiTransaction::write_vec(bytes.to_vec()),
+        SpiTransaction::flush(),
+    ]
+}
+
+#[test]
+fn commands_and_data_are_split_into_nibbles() {
+    let spi: Vec<_> = [
+        // Function set 0x30, display on 0x0c
+        &[0xf8, 0x30, 0x00, 0x00, 0xc0][..],
+        &[0xfa, 0xa0, 0x50],
+        &[0xfa, 0x10, 0x20, 0x30, 0x40],
+    ]
+    .into_iter()
+    .flat_map(write)
+    .collect();
+    let mut iface = St7920SerialInterface::new(SpiMock::new(&spi), PinMock::new(&framed(3)));
+
+    iface.send_commands(DataFormat::U8(&[0x30, 0x0c])).unwrap();
+    iface.send_data(DataFormat::U8(&[0xa5])).unwrap();
+    iface.send_data(DataFormat::U16BE(&mut [0x1234])).unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn every_byte_is_followed_by_the_delay() {
+    let spi: Vec<_> = [&[0xf8, 0x00, 0x10][..], &[0x30, 0x00]]
+        .into_iter()
+        .flat_map(write)
+        .collect();
+    let delay = CheckedDelay::new(&[
+        DelayTransaction::blocking_delay_ns(72_000),
+        DelayTransaction::blocking_delay_ns(72_000),
+    ]);
+    let mut iface = St7920SerialInterface::new(SpiMock::new(&spi), PinMock::new(&framed(1)))
+        .with_byte_delay(delay, 72_000);
+
+    iface.send_commands(DataFormat::U8(&[0x01, 0x30])).unwrap();
+
+    let (mut spi, mut cs, mut delay) = iface.release_with_delay();
+    spi.done();
+    cs.done();
+    delay.done();
+}
+
+#[test]
+fn long_transfers_keep_a_single_sync_byte() {
+    let data: Vec<u8> = (0..40).collect();
+    let nibbles: Vec<u8> = data.iter().flat_map(|&b| [b & 0xf0, b << 4]).collect();
+    let mut first = vec![0xfa];
+    first.extend_from_slice(&nibbles[..62]);
+    let spi: Vec<_> = [&first[..], &nibbles[62..]]
+        .into_iter()
+        .flat_map(write)
+        .collect();
+    let mut iface = St7920SerialInterface::new(SpiMock::new(&spi), PinMock::new(&framed(1)));
+
+    iface
+        .send_data(DataFormat::U8Iter(&mut data.iter().copied()))
+        .unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
+
+#[test]
+fn resync_deasserts_chip_select() {
+    let mut iface = St7920SerialInterface::new(
+        SpiMock::<u8>::new(&[]),
+        PinMock::new(&[PinTransaction::set(State::Low)]),
+    );
+
+    iface.resync().unwrap();
+
+    let (mut spi, mut cs) = iface.release();
+    spi.done();
+    cs.done();
+}
@@ -0,0 +1,146 @@
+//! Non-blocking transfers in chunks
+
+#![cfg(feature = "nb")]
+
+use display_interface::{DataFormat, DisplayError, TryWriteOnlyDataCommand};
+use display_interface_spi::SpiInterface;
+use embedded_hal_mock::eh1::{
+    digital::{Mock as PinMock, State, Transaction as PinTransaction},
+    spi::{Mock as SpiMock, Transaction as SpiTransaction},
+};
+
+/// Call `f` until it no longer blocks, returning the result and the number of calls
+fn poll(mut f: impl FnMut() -> nb::Result<(), DisplayError>) -> (Result<(), DisplayError>, usize) {
+    let mut calls = 1;
+    loop {
+        match f() {
+            Err(nb::Error::WouldBlock) => calls += 1,
+            Err(nb::Error::Other(e)) => return (Err(e), calls),
+            Ok(()) => return (Ok(()), calls),
+        }
+    }
+}
+
+fn write(bytes: &[u8]) -> [SpiTransaction<u8>; 3] {
+    [
+        SpiTransaction::transaction_start(),
+        SpiTransaction::write_vec(bytes.to_vec()),
+        SpiTransaction::transaction_end(),
+    ]
+}
+
+#[test]
+fn data_is_sent_in_chunks() {
+    let data: Vec<u8> = (0..150).collect();
+    let spi: Vec<_> = [
+        write(&[0x2c]),
+        write(&data[..64]),
+        write(&data[64..128]),
+        write(&data[128..]),
+    ]
+    .concat();
+    let dc = [
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiInterface::new(SpiMock::new(&spi), PinMock::new(&dc));
+
+    let (result, calls) = poll(|| iface.try_send_commands(DataFormat::U8(&[0x2c])));
+    assert!(result.is_ok());
+    assert_eq!(calls, 1);
+
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U8(&data)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 3);
+
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+#[test]
+fn u16_chunks_keep_whole_words() {
+    let mut pixels: Vec<u16> = (0..40).map(|n| 0x0100 | n).collect();
+    let bytes: Vec<u8> = pixels.iter().flat_map(|p| p.to_be_bytes()).collect();
+    let spi: Vec<_> = [write(&bytes[..64]), write(&bytes[64..])].concat();
+    let dc = [PinTransaction::set(State::High)];
+    let mut iface = SpiInterface::new(SpiMock::new(&spi), PinMock::new(&dc));
+
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U16BE(&mut pixels)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 2);
+
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+#[test]
+fn another_phase_starts_over() {
+    let data = [0xaa; 100];
+    let spi: Vec<_> = [
+        write(&data[..64]),
+        write(&[0x29]),
+        write(&data[..64]),
+        write(&data[64..]),
+    ]
+    .concat();
+    let dc = [
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiInterface::new(SpiMock::new(&spi), PinMock::new(&dc));
+
+    assert!(matches!(
+        iface.try_send_data(DataFormat::U8(&data)),
+        Err(nb::Error::WouldBlock)
+    ));
+    assert!(iface.try_send_commands(DataFormat::U8(&[0x29])).is_ok());
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U8(&data)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 2);
+
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+#[test]
+fn resync_abandons_the_transfer() {
+    let data = [0x55; 100];
+    let spi: Vec<_> = [write(&data[..64]), write(&data[..64]), write(&data[64..])].concat();
+    let dc = [
+        PinTransaction::set(State::High),
+        PinTransaction::set(State::Low),
+        PinTransaction::set(State::High),
+    ];
+    let mut iface = SpiInterface::new(SpiMock::new(&spi), PinMock::new(&dc));
+
+    assert!(matches!(
+        iface.try_send_data(DataFormat::U8(&data)),
+        Err(nb::Error::WouldBlock)
+    ));
+    iface.resync().unwrap();
+    let (result, calls) = poll(|| iface.try_send_data(DataFormat::U8(&data)));
+    assert!(result.is_ok());
+    assert_eq!(calls, 2);
+
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
+
+#[test]
+fn iterators_are_rejected() {
+    let mut iface = SpiInterface::new(SpiMock::new(&[]), PinMock::new(&[]));
+
+    assert!(matches!(
+        iface.try_send_data(DataFormat::U8Iter(&mut [0u8; 4].into_iter())),
+        Err(nb::Error::Other(DisplayError::DataFormatNotImplemented))
+    ));
+
+    let (mut spi, mut dc) = iface.release();
+    spi.done();
+    dc.done();
+}
@@ -0,0 +1,329 @@
+//! Always-on log of the most recent calls, for post-mortem analysis
+//!
+//! A [DebugTap] wraps any interface and notes every call in a [TapLog]: the first command word
+//! and the number of bytes of commands, the number of bytes of data, and whether the call
+//! succeeded. Payloads aren't kept, so the log stays small and recording costs next to nothing;
+//! once full, the oldest entries are overwritten. Keep the log in a `static` to read it from a
+//! panic or hard fault handler:
+//!
+//! ```
+//! use display_interface::{
+//!     debug_tap::{DebugTap, TapEntry, TapLog},
+//!     queue::CommandQueue,
+//!     DataFormat, WriteOnlyDataCommand,
+//! };
+//!
+//! static TAP: TapLog<16> = TapLog::new();
+//!
+//! let mut di = DebugTap::new(CommandQueue::<64>::new(), &TAP);
+//! di.send_commands(DataFormat::U8(&[0x2a, 0x00, 0x00, 0x00, 0xef])).unwrap();
+//! di.send_data(DataFormat::U16BEIter(&mut (0..10))).unwrap();
+//!
+//! // e.g. in the panic handler
+//! let last: Vec<_> = TAP.entries().collect();
+//! assert_eq!(
+//!     last,
+//!     [
+//!         TapEntry::Commands { first: Some(0x2a), bytes: 5, ok: true },
+//!         TapEntry::Data { bytes: 20, ok: true },
+//!     ]
+//! );
+//! ```
+
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Flag of data entries in the first word of a slot
+const DATA: u32 = 1 << 0;
+/// Flag of successful calls in the first word of a slot
+const OK: u32 = 1 << 1;
+/// Flag of a present first command word in the first word of a slot
+const FIRST: u32 = 1 << 2;
+/// Position of the first command word in the first word of a slot
+const FIRST_SHIFT: u32 = 8;
+
+/// One call noted by a [DebugTap]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum TapEntry {
+    /// `send_commands` with its first word, if any, and its length in bytes
+    ///
+    /// The first word is the command itself on most controllers, the remaining bytes are its
+    /// parameters. Only the low 24 bits of the word are kept.
+    Commands {
+        /// First word sent
+        first: Option<u32>,
+        /// Number of bytes sent, 2 per word for 16-bit and 3 for 24-bit formats
+        bytes: u32,
+        /// Whether the interface returned `Ok`
+        ok: bool,
+    },
+    /// `send_data` with its length in bytes
+    Data {
+        /// Number of bytes sent, 2 per word for 16-bit and 3 for 24-bit formats
+        bytes: u32,
+        /// Whether the interface returned `Ok`
+        ok: bool,
+    },
+}
+
+impl TapEntry {
+    fn encode(self) -> [u32; 2] {
+        match self {
+            TapEntry::Commands { first, bytes, ok } => {
+                let first = first.map_or(0, |word| FIRST | (word << FIRST_SHIFT));
+                [first | if ok { OK } else { 0 }, bytes]
+            }
+            TapEntry::Data { bytes, ok } => [DATA | if ok { OK } else { 0 }, bytes],
+        }
+    }
+
+    fn decode([flags, bytes]: [u32; 2]) -> Self {
+        let ok = flags & OK != 0;
+        if flags & DATA != 0 {
+            TapEntry::Data { bytes, ok }
+        } else {
+            let first = (flags & FIRST != 0).then_some(flags >> FIRST_SHIFT);
+            TapEntry::Commands { first, bytes, ok }
+        }
+    }
+}
+
+/// Ring buffer of the last `N` calls noted by a [DebugTap]
+///
+/// The log takes eight bytes per entry and can be read through a shared reference at any time,
+/// in particular from a `static` in a panic handler. It is written with plain 32-bit atomic loads
+/// and stores, without compare-and-swap, so it works on the Cortex-M0 as well. An entry being
+/// written while the log is read, e.g. when the fault interrupted the tap, may come out garbled;
+/// all other entries are intact.
+pub struct TapLog<const N: usize> {
+    slots: [[AtomicU32; 2]; N],
+    count: AtomicU32,
+}
+
+impl<const N: usize> Default for TapLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> TapLog<N> {
+    /// Create an empty log
+    pub const fn new() -> Self {
+        #[allow(clippy::declare_interior_mutable_const)]
+        const EMPTY: [AtomicU32; 2] = [AtomicU32::new(0), AtomicU32::new(0)];
+        Self {
+            slots: [EMPTY; N],
+            count: AtomicU32::new(0),
+        }
+    }
+
+    /// Number of calls noted since creation or the last [clear](Self::clear), including the ones
+    /// already overwritten
+    pub fn count(&self) -> u32 {
+        self.count.load(Ordering::Acquire)
+    }
+
+    /// The last up to `N` calls, oldest first
+    pub fn entries(&self) -> impl Iterator<Item = TapEntry> + '_ {
+        let count = self.count() as usize;
+        let len = count.min(N);
+        (count - len..count).map(move |i| {
+            let slot = &self.slots[i % N];
+            TapEntry::decode([
+                slot[0].load(Ordering::Relaxed),
+                slot[1].load(Ordering::Relaxed),
+            ])
+        })
+    }
+
+    /// The most recent call, if any
+    pub fn last(&self) -> Option<TapEntry> {
+        self.entries().last()
+    }
+
+    /// Forget all entries
+    pub fn clear(&self) {
+        self.count.store(0, Ordering::Release);
+    }
+
+    fn push(&self, entry: TapEntry) {
+        if N == 0 {
+            return;
+        }
+        let count = self.count.load(Ordering::Relaxed);
+        let slot = &self.slots[count as usize % N];
+        let [flags, bytes] = entry.encode();
+        slot[0].store(flags, Ordering::Relaxed);
+        slot[1].store(bytes, Ordering::Relaxed);
+        self.count.store(count.wrapping_add(1), Ordering::Release);
+    }
+}
+
+/// Interface wrapper noting every call in a [TapLog] before passing on its result
+///
+/// Slices are measured without touching them. Iterators are passed on through a counting adapter,
+/// which adds one call per word.
+pub struct DebugTap<'a, DI, const N: usize> {
+    di: DI,
+    log: &'a TapLog<N>,
+}
+
+impl<'a, DI, const N: usize> DebugTap<'a, DI, N> {
+    /// Wrap `di`, noting its calls in `log`
+    pub fn new(di: DI, log: &'a TapLog<N>) -> Self {
+        Self { di, log }
+    }
+
+    /// The log the calls are noted in
+    pub fn log(&self) -> &'a TapLog<N> {
+        self.log
+    }
+
+    /// Consume the tap and return the wrapped interface
+    pub fn release(self) -> DI {
+        self.di
+    }
+}
+
+/// Iterator counting the words passed on and keeping the first one
+struct Counting<'i, T> {
+    iter: &'i mut dyn Iterator<Item = T>,
+    first: Option<T>,
+    count: usize,
+}
+
+impl<'i, T: Copy> Counting<'i, T> {
+    fn new(iter: &'i mut dyn Iterator<Item = T>) -> Self {
+        Self {
+            iter,
+            first: None,
+            count: 0,
+        }
+    }
+}
+
+impl<T: Copy> Iterator for Counting<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        let word = self.iter.next()?;
+        if self.count == 0 {
+            self.first = Some(word);
+        }
+        self.count += 1;
+        Some(word)
+    }
+}
+
+/// Pass `$format` on as `$f` to `$send`, evaluating to the first word, the number of bytes and
+/// the result of `$send`
+macro_rules! tapped {
+    ($format:expr, |$f:ident| $send:expr) => {
+        match $format {
+            DataFormat::U8(slice) => {
+                let $f = DataFormat::U8(slice);
+                (slice.first().map(|&w| w.into()), slice.len(), $send)
+            }
+            DataFormat::U16(slice) => {
+                let (first, bytes) = (slice.first().map(|&w| w.into()), slice.len() * 2);
+                let $f = DataFormat::U16(slice);
+                (first, bytes, $send)
+            }
+            DataFormat::U16BE(slice) => {
+                let (first, bytes) = (slice.first().map(|&w| w.into()), slice.len() * 2);
+                let $f = DataFormat::U16BE(slice);
+                (first, bytes, $send)
+            }
+            DataFormat::U16LE(slice) => {
+                let (first, bytes) = (slice.first().map(|&w| w.into()), slice.len() * 2);
+                let $f = DataFormat::U16LE(slice);
+                (first, bytes, $send)
+            }
+            DataFormat::U24(slice) => {
+                let $f = DataFormat::U24(slice);
+                (slice.first().copied(), slice.len() * 3, $send)
+            }
+            DataFormat::U8Iter(iter) => {
+                let mut counting = Counting::new(iter);
+                let result = {
+                    let $f = DataFormat::U8Iter(&mut counting);
+                    $send
+                };
+                (counting.first.map(u32::from), counting.count, result)
+            }
+            DataFormat::U16BEIter(iter) => {
+                let mut counting = Counting::new(iter);
+                let result = {
+                    let $f = DataFormat::U16BEIter(&mut counting);
+                    $send
+                };
+                (counting.first.map(u32::from), counting.count * 2, result)
+            }
+            DataFormat::U16LEIter(iter) => {
+                let mut counting = Counting::new(iter);
+                let result = {
+                    let $f = DataFormat::U16LEIter(&mut counting);
+                    $send
+                };
+                (counting.first.map(u32::from), counting.count * 2, result)
+            }
+            DataFormat::U24Iter(iter) => {
+                let mut counting = Counting::new(iter);
+                let result = {
+                    let $f = DataFormat::U24Iter(&mut counting);
+                    $send
+                };
+                (counting.first, counting.count * 3, result)
+            }
+        }
+    };
+}
+
+impl<DI, const N: usize> DebugTap<'_, DI, N> {
+    fn note(
+        &self,
+        data: bool,
+        (first, bytes, result): (Option<u32>, usize, Result<(), DisplayError>),
+    ) -> Result<(), DisplayError> {
+        let bytes = u32::try_from(bytes).unwrap_or(u32::MAX);
+        let ok = result.is_ok();
+        self.log.push(if data {
+            TapEntry::Data { bytes, ok }
+        } else {
+            TapEntry::Commands {
+                first: first.map(|word| word & 0x00ff_ffff),
+                bytes,
+                ok,
+            }
+        });
+        result
+    }
+}
+
+impl<DI: WriteOnlyDataCommand, const N: usize> WriteOnlyDataCommand for DebugTap<'_, DI, N> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let tapped = tapped!(cmd, |f| self.di.send_commands(f));
+        self.note(false, tapped)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let tapped = tapped!(buf, |f| self.di.send_data(f));
+        self.note(true, tapped)
+    }
+}
+
+impl<DI: AsyncWriteOnlyDataCommand, const N: usize> AsyncWriteOnlyDataCommand
+    for DebugTap<'_, DI, N>
+{
+    async fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        let tapped = tapped!(cmd, |f| self.di.send_commands(f).await);
+        self.note(false, tapped)
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let tapped = tapped!(buf, |f| self.di.send_data(f).await);
+        self.note(true, tapped)
+    }
+}
@@ -0,0 +1,13 @@
+/// Placeholder delay for interfaces which don't need to wait
+///
+/// Implements the `async` `DelayNs` as well with the `embedded-hal-async` feature.
+pub struct NoDelay;
+
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "embedded-hal-async")]
+impl embedded_hal_async::delay::DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
@@ -0,0 +1,238 @@
+//! Patching the commands of a driver for the quirks of a panel variant
+//!
+//! A [FilterInterface] shows every command call to a callback before passing it on. The callback
+//! gets the first byte of the call, the command on most controllers, together with a copy of its
+//! parameters: the remaining bytes of the call or, for drivers sending them separately, the bytes
+//! of the following data call. It can change the parameters and decides whether they are sent
+//! or suppressed. Other data is passed on untouched.
+//!
+//! ```
+//! use display_interface::{
+//!     filter::{FilterInterface, Verdict},
+//!     DataFormat, WriteOnlyDataCommand,
+//! };
+//! # use display_interface::DisplayError;
+//! # /// Interface keeping the bytes of every call
+//! # #[derive(Default)]
+//! # struct Calls(Vec<Vec<u8>>);
+//! # impl WriteOnlyDataCommand for Calls {
+//! #     fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         self.send_data(cmds)
+//! #     }
+//! #     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+//! #         match buf {
+//! #             DataFormat::U8(slice) => self.0.push(slice.to_vec()),
+//! #             _ => unreachable!(),
+//! #         }
+//! #         Ok(())
+//! #     }
+//! # }
+//!
+//! let mut awake = false;
+//! let mut di = FilterInterface::new(Calls::default(), move |cmd, params: &mut [u8]| {
+//!     match cmd {
+//!         // The panel locks up if sleep out is sent twice without sleep in in between
+//!         0x10 => awake = false,
+//!         0x11 if awake => return Verdict::Suppress,
+//!         0x11 => awake = true,
+//!         // The panel needs BGR order, whatever the orientation
+//!         0x36 => params.iter_mut().for_each(|p| *p |= 0x08),
+//!         _ => {}
+//!     }
+//!     Verdict::Send
+//! });
+//!
+//! di.send_commands(DataFormat::U8(&[0x11])).unwrap();
+//! di.send_commands(DataFormat::U8(&[0x11])).unwrap();
+//! // MADCTL as sent by most drivers, with its parameter as data
+//! di.send_commands(DataFormat::U8(&[0x36])).unwrap();
+//! di.send_data(DataFormat::U8(&[0x40])).unwrap();
+//!
+//! assert_eq!(di.release().0, [vec![0x11], vec![0x36], vec![0x48]]);
+//! ```
+
+use crate::{AsyncWriteOnlyDataCommand, DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Decision of the callback of a [FilterInterface] on a command call
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Send the call, including any changes made to the parameters
+    Send,
+    /// Drop the call; it reports success without reaching the wrapped interface
+    Suppress,
+}
+
+/// Interface wrapper passing every command call through a callback
+///
+/// `filter` is called with the first byte of every [U8](DataFormat::U8) and
+/// [U8Iter](DataFormat::U8Iter) command call and a copy of the remaining bytes. The bytes of a
+/// call are copied into a stack buffer of `N` bytes first, 32 unless changed with
+/// [with_capacity](Self::with_capacity). Longer calls fail with
+/// [CapacityExceeded](DisplayError::CapacityExceeded) without sending anything, with iterators
+/// consumed up to that point. Calls without bytes are passed on without calling `filter`, as
+/// are commands in other formats, e.g. the 16-bit commands of some controllers.
+///
+/// Drivers for MIPI DCS controllers send the command alone and its parameters with `send_data`.
+/// So after a command call without parameters, the next data call is shown to `filter` again,
+/// with the same command byte and a copy of the data as parameters, if it is a `U8` slice of at
+/// most `N` bytes. Larger data, e.g. pixels, iterators and other formats are passed on untouched.
+/// If the command was suppressed, the next data call is dropped as well, whatever its format.
+///
+/// Every call is shown to `filter` as a whole, so drivers sending several commands in one call
+/// need the callback to split them up itself.
+pub struct FilterInterface<DI, F, const N: usize = 32> {
+    di: DI,
+    filter: F,
+    /// Command sent without parameters and what became of it, applied to the next data call
+    after: Option<(u8, Verdict)>,
+}
+
+/// Outcome of filtering one call
+enum Filtered<'a> {
+    /// The format isn't filtered, pass it on as it is
+    Unfiltered(DataFormat<'a>),
+    /// Send the first bytes of the buffer
+    Send(usize),
+    /// Drop the call
+    Suppressed,
+}
+
+impl<DI, F> FilterInterface<DI, F>
+where
+    F: FnMut(u8, &mut [u8]) -> Verdict,
+{
+    /// Wrap `di`, passing its command calls through `filter`
+    ///
+    /// Command calls of up to 32 bytes are filtered, see [with_capacity](Self::with_capacity).
+    pub fn new(di: DI, filter: F) -> Self {
+        Self {
+            di,
+            filter,
+            after: None,
+        }
+    }
+}
+
+impl<DI, F, const N: usize> FilterInterface<DI, F, N>
+where
+    F: FnMut(u8, &mut [u8]) -> Verdict,
+{
+    /// Filter command calls of up to `M` bytes, e.g. for long gamma or waveform tables
+    ///
+    /// The buffer lives on the stack during every command call.
+    pub fn with_capacity<const M: usize>(self) -> FilterInterface<DI, F, M> {
+        FilterInterface {
+            di: self.di,
+            filter: self.filter,
+            after: self.after,
+        }
+    }
+
+    /// Consume the wrapper and return the wrapped interface
+    pub fn release(self) -> DI {
+        self.di
+    }
+
+    /// Copy `cmds` into `buf` and let the callback decide on them
+    fn filter_commands<'a>(
+        &mut self,
+        cmds: DataFormat<'a>,
+        buf: &mut [u8; N],
+    ) -> Result<Filtered<'a>, DisplayError> {
+        self.after = None;
+        let len = match cmds {
+            DataFormat::U8(slice) => {
+                buf.get_mut(..slice.len())
+                    .ok_or(DisplayError::CapacityExceeded)?
+                    .copy_from_slice(slice);
+                slice.len()
+            }
+            DataFormat::U8Iter(iter) => {
+                let mut len = 0;
+                for byte in iter {
+                    *buf.get_mut(len).ok_or(DisplayError::CapacityExceeded)? = byte;
+                    len += 1;
+                }
+                len
+            }
+            cmds => return Ok(Filtered::Unfiltered(cmds)),
+        };
+
+        let Some((&mut cmd, params)) = buf[..len].split_first_mut() else {
+            return Ok(Filtered::Send(0));
+        };
+        let verdict = (self.filter)(cmd, params);
+        if params.is_empty() {
+            self.after = Some((cmd, verdict));
+        }
+        Ok(match verdict {
+            Verdict::Send => Filtered::Send(len),
+            Verdict::Suppress => Filtered::Suppressed,
+        })
+    }
+
+    /// Copy `data` into `buf` and let the callback decide on it if it holds the parameters of
+    /// the last command
+    fn filter_data<'a>(&mut self, data: DataFormat<'a>, buf: &mut [u8; N]) -> Filtered<'a> {
+        match (self.after.take(), data) {
+            (Some((_, Verdict::Suppress)), _) => Filtered::Suppressed,
+            (Some((cmd, Verdict::Send)), DataFormat::U8(slice)) if slice.len() <= N => {
+                let params = &mut buf[..slice.len()];
+                params.copy_from_slice(slice);
+                match (self.filter)(cmd, params) {
+                    Verdict::Send => Filtered::Send(slice.len()),
+                    Verdict::Suppress => Filtered::Suppressed,
+                }
+            }
+            (_, data) => Filtered::Unfiltered(data),
+        }
+    }
+}
+
+impl<DI, F, const N: usize> WriteOnlyDataCommand for FilterInterface<DI, F, N>
+where
+    DI: WriteOnlyDataCommand,
+    F: FnMut(u8, &mut [u8]) -> Verdict,
+{
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        let mut buf = [0; N];
+        match self.filter_commands(cmds, &mut buf)? {
+            Filtered::Unfiltered(cmds) => self.di.send_commands(cmds),
+            Filtered::Send(len) => self.di.send_commands(DataFormat::U8(&buf[..len])),
+            Filtered::Suppressed => Ok(()),
+        }
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let mut copy = [0; N];
+        match self.filter_data(buf, &mut copy) {
+            Filtered::Unfiltered(buf) => self.di.send_data(buf),
+            Filtered::Send(len) => self.di.send_data(DataFormat::U8(&copy[..len])),
+            Filtered::Suppressed => Ok(()),
+        }
+    }
+}
+
+impl<DI, F, const N: usize> AsyncWriteOnlyDataCommand for FilterInterface<DI, F, N>
+where
+    DI: AsyncWriteOnlyDataCommand,
+    F: FnMut(u8, &mut [u8]) -> Verdict,
+{
+    async fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        let mut buf = [0; N];
+        match self.filter_commands(cmds, &mut buf)? {
+            Filtered::Unfiltered(cmds) => self.di.send_commands(cmds).await,
+            Filtered::Send(len) => self.di.send_commands(DataFormat::U8(&buf[..len])).await,
+            Filtered::Suppressed => Ok(()),
+        }
+    }
+
+    async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        let mut copy = [0; N];
+        match self.filter_data(buf, &mut copy) {
+            Filtered::Unfiltered(buf) => self.di.send_data(buf).await,
+            Filtered::Send(len) => self.di.send_data(DataFormat::U8(&copy[..len])).await,
+            Filtered::Suppressed => Ok(()),
+        }
+    }
+}
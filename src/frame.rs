@@ -0,0 +1,470 @@
+//! Framing of command and data phases for forwarding them over a byte stream
+//!
+//! Every [send_commands](WriteOnlyDataCommand::send_commands) or
+//! [send_data](WriteOnlyDataCommand::send_data) call is turned into one or more frames, each
+//! made of a header and up to [MAX_PAYLOAD] bytes of payload:
+//!
+//! | Offset | Size     | Content                                                         |
+//! |--------|----------|-----------------------------------------------------------------|
+//! | 0      | 1        | [Phase], `0x00` for commands, `0x01` for data, `0x02` for delays |
+//! | 1      | 2        | Length of the payload, big endian                               |
+//! | 3      | length   | Payload                                                         |
+//!
+//! The payload of a delay frame is the pause in nanoseconds as a big endian 32-bit value.
+//!
+//! [encode] produces the frames on the sending side, e.g. in a host side interface forwarding
+//! a driver's output to a target board, while [FrameDecoder] reassembles them on the receiving
+//! side and replays them into a local interface. Captures of a whole stream, e.g. taken with a
+//! [LoopbackInterface](crate::loopback::LoopbackInterface), are read back with [frames], or with
+//! [groups] to see every command together with the data following it. None of them needs an
+//! allocator.
+//!
+//! Captures stored for later, like serialized recordings of the `recording` feature, start with
+//! the magic bytes [MAGIC] and the [VERSION] of the format. [frames] and [groups] skip this
+//! header if it is there, so they read both kinds of captures.
+
+use crate::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Maximum number of payload bytes of one frame
+pub const MAX_PAYLOAD: usize = 256;
+
+/// Size of the frame header
+pub const HEADER_SIZE: usize = 3;
+
+/// Magic bytes at the start of a stored capture
+pub const MAGIC: [u8; 4] = *b"DIRC";
+
+/// Version of the format following [MAGIC] in a stored capture
+pub const VERSION: u8 = 1;
+
+/// Size of the header of a stored capture
+pub const CAPTURE_HEADER_SIZE: usize = MAGIC.len() + 1;
+
+/// Size of the payload of a delay frame
+const DELAY_SIZE: usize = 4;
+
+/// Phase of a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Phase {
+    /// Bytes sent with [send_commands](WriteOnlyDataCommand::send_commands)
+    Command,
+    /// Bytes sent with [send_data](WriteOnlyDataCommand::send_data)
+    Data,
+    /// A pause, see [encode_delay]
+    Delay,
+}
+
+impl Phase {
+    /// The byte identifying the phase in the frame header
+    pub const fn code(self) -> u8 {
+        match self {
+            Phase::Command => 0x00,
+            Phase::Data => 0x01,
+            Phase::Delay => 0x02,
+        }
+    }
+
+    /// The phase identified by `code`, if any
+    pub const fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0x00 => Some(Phase::Command),
+            0x01 => Some(Phase::Data),
+            0x02 => Some(Phase::Delay),
+            _ => None,
+        }
+    }
+}
+
+/// Error decoding a stream of frames
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum FrameError {
+    /// The header holds an unknown phase
+    UnknownPhase(u8),
+    /// The header announces more than [MAX_PAYLOAD] bytes of payload
+    TooLong(u16),
+    /// The header of a delay frame announces a payload other than four bytes
+    InvalidDelay(u16),
+    /// The capture was stored by a later, unknown version of the format
+    UnsupportedVersion(u8),
+    /// The capture ends within the frame starting at `offset`
+    Truncated {
+        /// Position of the incomplete frame in the capture
+        offset: usize,
+    },
+    /// The interface failed to replay a frame
+    Interface(DisplayError),
+}
+
+impl From<DisplayError> for FrameError {
+    fn from(e: DisplayError) -> Self {
+        FrameError::Interface(e)
+    }
+}
+
+/// Call `f` with the bytes of `data`, as a byte oriented interface would send them
+///
+/// [DataFormat::U16] values are taken in native byte order, the other 16-bit formats in the
+/// order they name. 24-bit formats are not implemented.
+pub(crate) fn for_each_byte(
+    data: DataFormat<'_>,
+    mut f: impl FnMut(u8) -> Result<(), DisplayError>,
+) -> Result<(), DisplayError> {
+    fn all<T: IntoIterator<Item = [u8; 2]>>(
+        words: T,
+        f: &mut impl FnMut(u8) -> Result<(), DisplayError>,
+    ) -> Result<(), DisplayError> {
+        words.into_iter().flatten().try_for_each(f)
+    }
+
+    match data {
+        DataFormat::U8(slice) => slice.iter().copied().try_for_each(f),
+        DataFormat::U8Iter(mut iter) => (&mut iter).try_for_each(f),
+        DataFormat::U16(slice) => all(slice.iter().map(|w| w.to_ne_bytes()), &mut f),
+        DataFormat::U16BE(slice) => all(slice.iter().map(|w| w.to_be_bytes()), &mut f),
+        DataFormat::U16LE(slice) => all(slice.iter().map(|w| w.to_le_bytes()), &mut f),
+        DataFormat::U16BEIter(iter) => all(iter.map(u16::to_be_bytes), &mut f),
+        DataFormat::U16LEIter(iter) => all(iter.map(u16::to_le_bytes), &mut f),
+        _ => Err(DisplayError::DataFormatNotImplemented),
+    }
+}
+
+/// Encode `data` as frames of `phase`, passing every complete frame to `write`
+///
+/// The payload is split into frames of at most [MAX_PAYLOAD] bytes; empty `data` results in no
+/// frame at all. Errors of `write` are passed on as they are. Delays are encoded with
+/// [encode_delay] instead, [Phase::Delay] fails with
+/// [InvalidFormatError](DisplayError::InvalidFormatError).
+pub fn encode(
+    phase: Phase,
+    data: DataFormat<'_>,
+    mut write: impl FnMut(&[u8]) -> Result<(), DisplayError>,
+) -> Result<(), DisplayError> {
+    if phase == Phase::Delay {
+        return Err(DisplayError::InvalidFormatError);
+    }
+
+    let mut frame = [0; HEADER_SIZE + MAX_PAYLOAD];
+    let mut len = 0;
+
+    let mut flush = |frame: &mut [u8; HEADER_SIZE + MAX_PAYLOAD], len: usize| {
+        frame[0] = phase.code();
+        frame[1..HEADER_SIZE].copy_from_slice(&(len as u16).to_be_bytes());
+        write(&frame[..HEADER_SIZE + len])
+    };
+
+    for_each_byte(data, |byte| {
+        if len == MAX_PAYLOAD {
+            flush(&mut frame, len)?;
+            len = 0;
+        }
+        frame[HEADER_SIZE + len] = byte;
+        len += 1;
+        Ok(())
+    })?;
+
+    if len > 0 {
+        flush(&mut frame, len)?;
+    }
+    Ok(())
+}
+
+/// Encode a pause of `ns` nanoseconds as a delay frame, passing it to `write`
+pub fn encode_delay(
+    ns: u32,
+    write: impl FnOnce(&[u8]) -> Result<(), DisplayError>,
+) -> Result<(), DisplayError> {
+    let mut frame = [0; HEADER_SIZE + DELAY_SIZE];
+    frame[0] = Phase::Delay.code();
+    frame[1..HEADER_SIZE].copy_from_slice(&(DELAY_SIZE as u16).to_be_bytes());
+    frame[HEADER_SIZE..].copy_from_slice(&ns.to_be_bytes());
+    write(&frame)
+}
+
+/// Check the frame header made of `code` and `len`, returning the phase and payload length
+fn check_header(code: u8, len: [u8; 2]) -> Result<(Phase, usize), FrameError> {
+    let phase = Phase::from_code(code).ok_or(FrameError::UnknownPhase(code))?;
+    let len = u16::from_be_bytes(len);
+    if usize::from(len) > MAX_PAYLOAD {
+        return Err(FrameError::TooLong(len));
+    }
+    if phase == Phase::Delay && usize::from(len) != DELAY_SIZE {
+        return Err(FrameError::InvalidDelay(len));
+    }
+    Ok((phase, usize::from(len)))
+}
+
+/// The size of the capture header at the start of `bytes`, zero if there is none
+fn capture_header(bytes: &[u8]) -> Result<usize, FrameError> {
+    if !bytes.starts_with(&MAGIC) {
+        return Ok(0);
+    }
+    match bytes.get(MAGIC.len()) {
+        Some(&VERSION) => Ok(CAPTURE_HEADER_SIZE),
+        Some(&version) => Err(FrameError::UnsupportedVersion(version)),
+        None => Err(FrameError::Truncated { offset: 0 }),
+    }
+}
+
+/// The pause in nanoseconds held by the `payload` of a delay frame
+pub fn delay_ns(payload: &[u8]) -> Option<u32> {
+    payload.try_into().ok().map(u32::from_be_bytes)
+}
+
+/// Reassembles frames from a byte stream
+///
+/// The bytes can be pushed in pieces of any size, e.g. as they arrive on a socket or UART; the
+/// decoder keeps the partial frame in between.
+pub struct FrameDecoder {
+    header: [u8; HEADER_SIZE],
+    payload: [u8; MAX_PAYLOAD],
+    phase: Phase,
+    len: usize,
+    filled: usize,
+}
+
+impl Default for FrameDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameDecoder {
+    /// Create a decoder expecting the start of a frame
+    pub const fn new() -> Self {
+        Self {
+            header: [0; HEADER_SIZE],
+            payload: [0; MAX_PAYLOAD],
+            phase: Phase::Command,
+            len: 0,
+            filled: 0,
+        }
+    }
+
+    /// Whether the decoder is between frames, i.e. the bytes pushed so far were complete frames
+    pub fn is_idle(&self) -> bool {
+        self.filled == 0
+    }
+
+    /// Drop the partial frame, e.g. after an error or a reconnect
+    pub fn reset(&mut self) {
+        self.filled = 0;
+    }
+
+    /// Push the next `byte` of the stream, returning the frame it completes
+    ///
+    /// After an error the decoder is [reset](Self::reset), so the stream has to be resynchronized
+    /// by the caller, usually by starting over with a new connection.
+    pub fn push(&mut self, byte: u8) -> Result<Option<(Phase, &[u8])>, FrameError> {
+        if self.filled < HEADER_SIZE {
+            self.header[self.filled] = byte;
+            self.filled += 1;
+            if self.filled < HEADER_SIZE {
+                return Ok(None);
+            }
+
+            let [code, len @ ..] = self.header;
+            (self.phase, self.len) = match check_header(code, len) {
+                Ok(header) => header,
+                Err(e) => {
+                    self.reset();
+                    return Err(e);
+                }
+            };
+        } else {
+            self.payload[self.filled - HEADER_SIZE] = byte;
+            self.filled += 1;
+        }
+
+        if self.filled == HEADER_SIZE + self.len {
+            self.filled = 0;
+            Ok(Some((self.phase, &self.payload[..self.len])))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Push `bytes`, replaying every frame they complete into `di`
+    ///
+    /// Delay frames are skipped, as there is nothing to pause with.
+    pub fn replay(
+        &mut self,
+        bytes: &[u8],
+        di: &mut impl WriteOnlyDataCommand,
+    ) -> Result<(), FrameError> {
+        for &byte in bytes {
+            if let Some((phase, payload)) = self.push(byte)? {
+                let payload = DataFormat::U8(payload);
+                match phase {
+                    Phase::Command => di.send_commands(payload)?,
+                    Phase::Data => di.send_data(payload)?,
+                    Phase::Delay => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iterator over the frames of a capture, see [frames]
+#[derive(Clone, Debug)]
+pub struct Frames<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    header: Option<FrameError>,
+    failed: bool,
+}
+
+/// Iterate over the frames in the capture `bytes`, as phase and payload
+///
+/// The header of a stored capture is skipped; a header of an unknown version results in an
+/// [UnsupportedVersion](FrameError::UnsupportedVersion) error. A malformed or incomplete frame,
+/// e.g. at the end of a capture cut short, results in one error, after which the iterator ends;
+/// the frames before it are returned as usual and the bytes from the broken frame on are left in
+/// [remainder](Frames::remainder).
+pub fn frames(bytes: &[u8]) -> Frames<'_> {
+    let (offset, header) = match capture_header(bytes) {
+        Ok(offset) => (offset, None),
+        Err(e) => (0, Some(e)),
+    };
+    Frames {
+        bytes,
+        offset,
+        header,
+        failed: false,
+    }
+}
+
+impl<'a> Frames<'a> {
+    /// The bytes not decoded yet
+    pub fn remainder(&self) -> &'a [u8] {
+        &self.bytes[self.offset..]
+    }
+
+    /// Decode the frame at the start of the remainder, returning it and its size
+    fn decode(&self) -> Result<(Phase, &'a [u8], usize), FrameError> {
+        let truncated = FrameError::Truncated {
+            offset: self.offset,
+        };
+        let rest = self.remainder();
+        let [code, l0, l1, ..] = *rest else {
+            return Err(truncated);
+        };
+
+        let (phase, len) = check_header(code, [l0, l1])?;
+        let size = HEADER_SIZE + len;
+        let payload = rest.get(HEADER_SIZE..size).ok_or(truncated)?;
+        Ok((phase, payload, size))
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Result<(Phase, &'a [u8]), FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.header.take() {
+            self.failed = true;
+            return Some(Err(e));
+        }
+        if self.failed || self.remainder().is_empty() {
+            return None;
+        }
+
+        match self.decode() {
+            Ok((phase, payload, size)) => {
+                self.offset += size;
+                Some(Ok((phase, payload)))
+            }
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+/// A command frame together with the data frames following it, see [groups]
+#[derive(Clone, Debug)]
+pub struct Group<'a> {
+    command: &'a [u8],
+    data: &'a [u8],
+}
+
+impl<'a> Group<'a> {
+    /// The payload of the command frame, empty for data at the start of a capture
+    pub fn command(&self) -> &'a [u8] {
+        self.command
+    }
+
+    /// The payloads of the data frames
+    pub fn data(&self) -> impl Iterator<Item = &'a [u8]> + 'a {
+        // The frames were checked when the group was made
+        frames(self.data).filter_map(|frame| match frame {
+            Ok((Phase::Data, payload)) => Some(payload),
+            _ => None,
+        })
+    }
+
+    /// The bytes of all data frames
+    pub fn data_bytes(&self) -> impl Iterator<Item = u8> + 'a {
+        self.data().flatten().copied()
+    }
+}
+
+/// Iterator over the groups of a capture, see [groups]
+#[derive(Clone, Debug)]
+pub struct Groups<'a> {
+    frames: Frames<'a>,
+}
+
+/// Iterate over the command frames in the capture `bytes`, each with the data frames following it
+///
+/// This turns a capture into entries like "command `0x2a`, data `[0, 0, 0, 239]`" without any
+/// knowledge of the controller. Data at the start of the capture is returned in a group with an
+/// empty command. Delays are skipped, so a command keeps the data sent after a pause following
+/// it. Like with [frames], a malformed or incomplete frame ends the groups with an error, after
+/// the group holding the frames before it.
+pub fn groups(bytes: &[u8]) -> Groups<'_> {
+    Groups {
+        frames: frames(bytes),
+    }
+}
+
+impl<'a> Iterator for Groups<'a> {
+    type Item = Result<Group<'a>, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut command = &[][..];
+        loop {
+            let mut peek = self.frames.clone();
+            match peek.next()? {
+                Err(e) => {
+                    self.frames = peek;
+                    return Some(Err(e));
+                }
+                Ok((Phase::Command, payload)) => {
+                    command = payload;
+                    self.frames = peek;
+                    break;
+                }
+                Ok((Phase::Data, _)) => break,
+                Ok((Phase::Delay, _)) => self.frames = peek,
+            }
+        }
+
+        let start = self.frames.offset;
+        loop {
+            let mut peek = self.frames.clone();
+            match peek.next() {
+                Some(Ok((Phase::Data | Phase::Delay, _))) => self.frames = peek,
+                _ => break,
+            }
+        }
+
+        Some(Ok(Group {
+            command,
+            data: &self.frames.bytes[start..self.frames.offset],
+        }))
+    }
+}
@@ -8,7 +8,30 @@
 #![no_std]
 #![allow(async_fn_in_trait)]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "debug-tap")]
+pub mod debug_tap;
+#[cfg(feature = "embedded-hal")]
+mod delay;
+pub mod filter;
+pub mod frame;
+pub mod loopback;
+#[cfg(feature = "power")]
+pub mod power;
 pub mod prelude;
+pub mod queue;
+#[cfg(feature = "recording")]
+pub mod recording;
+pub mod rle;
+#[cfg(feature = "std")]
+pub mod tcp;
+#[cfg(feature = "u8g2")]
+pub mod u8g2;
+
+#[cfg(feature = "embedded-hal")]
+pub use delay::NoDelay;
 
 /// A ubiquitous error type for all kinds of problems which could happen when communicating with a
 /// display
@@ -30,6 +53,14 @@ pub enum DisplayError {
     RSError,
     /// Attempted to write to a non-existing pixel outside the display's bounds
     OutOfBoundsError,
+    /// Unable to read from bus
+    BusReadError,
+    /// The operation is not supported by this display interface implementation
+    Unsupported,
+    /// The data doesn't fit into the buffer or queue of the display interface implementation
+    CapacityExceeded,
+    /// Unable to switch a power rail of the display
+    PowerError,
 }
 
 /// DI specific data format wrapper around slices of various widths
@@ -39,7 +70,13 @@ pub enum DisplayError {
 pub enum DataFormat<'a> {
     /// Slice of unsigned bytes
     U8(&'a [u8]),
-    /// Slice of unsigned 16bit values with the same endianness as the system, not recommended
+    /// Slice of unsigned 16bit values to be sent in the native byte order of the target
+    ///
+    /// Each value is sent as its in-memory bytes, i.e. little endian on little endian targets and
+    /// big endian on big endian targets. This is meant for buffers which were already swapped into
+    /// the order the display expects; byte oriented interfaces don't convert anything, while 16-bit
+    /// buses transfer each value as a whole. Use [U16BE](DataFormat::U16BE) or
+    /// [U16LE](DataFormat::U16LE) to get the same byte order on every target.
     U16(&'a [u16]),
     /// Slice of unsigned 16bit values to be sent in big endian byte order
     U16BE(&'a mut [u16]),
@@ -51,6 +88,38 @@ pub enum DataFormat<'a> {
     U16BEIter(&'a mut dyn Iterator<Item = u16>),
     /// Iterator over unsigned 16bit values to be sent in little endian byte order
     U16LEIter(&'a mut dyn Iterator<Item = u16>),
+    /// Slice of unsigned 24bit values, e.g. RGB888 pixels, held in the low 24 bits of each value
+    ///
+    /// The top byte is ignored. This is meant for buses which transfer 24 bits at a time.
+    U24(&'a [u32]),
+    /// Iterator over unsigned 24bit values, held in the low 24 bits of each value like in
+    /// [U24](DataFormat::U24)
+    U24Iter(&'a mut dyn Iterator<Item = u32>),
+}
+
+/// Levels of the data/command selection line
+///
+/// Interfaces with a D/C pin drive it high for data and low for commands by default; boards which
+/// invert the line, e.g. through a transistor, need [DcPolarity::LowData] instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum DcPolarity {
+    /// High for data, low for commands
+    #[default]
+    HighData,
+    /// Low for data, high for commands
+    LowData,
+}
+
+impl DcPolarity {
+    /// Return whether the D/C line has to be high to select data if `data` is set, or commands
+    /// otherwise
+    pub const fn is_high(self, data: bool) -> bool {
+        match self {
+            DcPolarity::HighData => data,
+            DcPolarity::LowData => !data,
+        }
+    }
 }
 
 /// This trait implements a write-only interface for a display which has separate data and command
@@ -64,6 +133,299 @@ pub trait WriteOnlyDataCommand {
     fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError>;
 }
 
+/// This trait implements a write-only interface like [WriteOnlyDataCommand], reporting errors in
+/// the interface's own error type.
+///
+/// Implementations wrapping e.g. a vendor SDK can surface its native errors, including codes
+/// [DisplayError] has no room for, to callers which bound on this trait. The interface crates of
+/// this project implement both traits, with [WriteOnlyDataCommand] converting the errors of this
+/// one with [Into].
+///
+/// # Migration
+///
+/// The next breaking release is planned to fold the associated error type into
+/// [WriteOnlyDataCommand] itself. Until then:
+///
+/// - Drivers keep bounding on [WriteOnlyDataCommand] and returning [DisplayError]; nothing changes
+///   for them now or after the merge, as the error will still convert with [Into].
+/// - Interfaces which only implement this trait are wrapped in [TypedCompat] to be passed to such
+///   drivers, or implement [WriteOnlyDataCommand] as well by delegating to this trait. After the
+///   merge, the methods of this trait only need to be renamed.
+pub trait TypedWriteOnlyDataCommand {
+    /// The error of the interface
+    type Error: Into<DisplayError>;
+
+    /// Send a batch of commands to display
+    fn send_commands_typed(&mut self, cmd: DataFormat<'_>) -> Result<(), Self::Error>;
+
+    /// Send pixel data to display
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> Result<(), Self::Error>;
+}
+
+impl<T: WriteOnlyDataCommand + ?Sized> WriteOnlyDataCommand for &mut T {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        T::send_commands(self, cmd)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        T::send_data(self, buf)
+    }
+}
+
+impl<T: TypedWriteOnlyDataCommand + ?Sized> TypedWriteOnlyDataCommand for &mut T {
+    type Error = T::Error;
+
+    fn send_commands_typed(&mut self, cmd: DataFormat<'_>) -> Result<(), T::Error> {
+        T::send_commands_typed(self, cmd)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> Result<(), T::Error> {
+        T::send_data_typed(self, buf)
+    }
+}
+
+/// Adapter implementing [WriteOnlyDataCommand] for an interface which only implements
+/// [TypedWriteOnlyDataCommand], converting its errors with [Into]
+///
+/// The typed methods are passed through unchanged.
+pub struct TypedCompat<T>(T);
+
+impl<T> TypedCompat<T> {
+    /// Wrap `iface`
+    pub fn new(iface: T) -> Self {
+        Self(iface)
+    }
+
+    /// Consume the adapter and return the wrapped interface
+    pub fn release(self) -> T {
+        self.0
+    }
+}
+
+impl<T: TypedWriteOnlyDataCommand> WriteOnlyDataCommand for TypedCompat<T> {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.send_commands_typed(cmd).map_err(Into::into)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.send_data_typed(buf).map_err(Into::into)
+    }
+}
+
+impl<T: TypedWriteOnlyDataCommand> TypedWriteOnlyDataCommand for TypedCompat<T> {
+    type Error = T::Error;
+
+    fn send_commands_typed(&mut self, cmd: DataFormat<'_>) -> Result<(), T::Error> {
+        self.0.send_commands_typed(cmd)
+    }
+
+    fn send_data_typed(&mut self, buf: DataFormat<'_>) -> Result<(), T::Error> {
+        self.0.send_data_typed(buf)
+    }
+}
+
+/// This trait parks the pins of an interface for low-power modes of the microcontroller.
+///
+/// [suspend](Self::suspend) puts the control and data pins into a defined state which doesn't
+/// leak current into an idle display: chip selects deasserted, write strobes at their idle level,
+/// D/C and data pins driven low. [resume](Self::resume) restores the idle levels and forgets any
+/// pin state the interface cached, so the next call drives every pin anew even if the pins were
+/// reconfigured in between, e.g. into analog mode for deep sleep. Nothing may be sent between the
+/// two calls.
+pub trait LowPower {
+    /// Park the pins of the interface before entering a low-power mode
+    fn suspend(&mut self) -> Result<(), DisplayError>;
+
+    /// Restore the pins of the interface after leaving a low-power mode
+    fn resume(&mut self) -> Result<(), DisplayError>;
+}
+
+/// This trait implements a non-blocking variant of [WriteOnlyDataCommand] for superloops without
+/// an executor.
+///
+/// Every call transfers at most one chunk and returns [WouldBlock](nb::Error::WouldBlock) until
+/// the whole buffer has been sent, so a large transfer doesn't stall the loop. The interface keeps
+/// track of the progress between calls, so the same buffer has to be passed on every call until
+/// it returns `Ok` or an error; passing a buffer of another phase or length starts a new transfer,
+/// abandoning the previous one. Only slice formats are supported, as iterators can't be resumed
+/// safely; they result in [DataFormatNotImplemented](DisplayError::DataFormatNotImplemented).
+#[cfg(feature = "nb")]
+pub trait TryWriteOnlyDataCommand {
+    /// Send the next chunk of a batch of commands to display
+    fn try_send_commands(&mut self, cmd: DataFormat<'_>) -> nb::Result<(), DisplayError>;
+
+    /// Send the next chunk of pixel data to display
+    fn try_send_data(&mut self, buf: DataFormat<'_>) -> nb::Result<(), DisplayError>;
+}
+
+/// Progress of a chunked transfer, for implementations of [TryWriteOnlyDataCommand]
+///
+/// Implementations call [next_format_chunk](Self::next_format_chunk) to get the words to write,
+/// select the phase if the transfer is new, write the chunk and pass the result to
+/// [finish](Self::finish), which returns what the call should return.
+#[cfg(feature = "nb")]
+#[derive(Clone, Debug, Default)]
+pub struct TryProgress {
+    transfer: Option<(bool, usize, usize)>,
+}
+
+#[cfg(feature = "nb")]
+impl TryProgress {
+    /// Create the progress of no transfer
+    pub const fn new() -> Self {
+        Self { transfer: None }
+    }
+
+    /// Whether no transfer is in progress
+    pub fn is_idle(&self) -> bool {
+        self.transfer.is_none()
+    }
+
+    /// Abandon the transfer in progress
+    pub fn reset(&mut self) {
+        self.transfer = None;
+    }
+
+    /// Continue the transfer of `len` words of data if `data` is set or commands otherwise, or
+    /// start a new one, returning whether it is new and the range of the next `chunk_len` words
+    pub fn next_chunk(
+        &mut self,
+        data: bool,
+        len: usize,
+        chunk_len: usize,
+    ) -> (bool, core::ops::Range<usize>) {
+        let (new, offset) = match self.transfer {
+            Some((d, l, offset)) if d == data && l == len => (false, offset),
+            _ => (true, 0),
+        };
+        let end = len.min(offset + chunk_len.max(1));
+        self.transfer = Some((data, len, end));
+        (new, offset..end)
+    }
+
+    /// Like [next_chunk](Self::next_chunk) for the words of the slice format `words`, returning
+    /// the next chunk of at most `max_bytes` bytes as a format of the same kind
+    ///
+    /// A chunk holds at least one word. Iterator formats result in
+    /// [DataFormatNotImplemented](DisplayError::DataFormatNotImplemented).
+    pub fn next_format_chunk<'a>(
+        &mut self,
+        data: bool,
+        words: DataFormat<'a>,
+        max_bytes: usize,
+    ) -> Result<(bool, DataFormat<'a>), DisplayError> {
+        Ok(match words {
+            DataFormat::U8(slice) => {
+                let (new, chunk) = self.next_chunk(data, slice.len(), max_bytes);
+                (new, DataFormat::U8(&slice[chunk]))
+            }
+            DataFormat::U16(slice) => {
+                let (new, chunk) = self.next_chunk(data, slice.len(), max_bytes / 2);
+                (new, DataFormat::U16(&slice[chunk]))
+            }
+            DataFormat::U16BE(slice) => {
+                let (new, chunk) = self.next_chunk(data, slice.len(), max_bytes / 2);
+                (new, DataFormat::U16BE(&mut slice[chunk]))
+            }
+            DataFormat::U16LE(slice) => {
+                let (new, chunk) = self.next_chunk(data, slice.len(), max_bytes / 2);
+                (new, DataFormat::U16LE(&mut slice[chunk]))
+            }
+            DataFormat::U24(slice) => {
+                let (new, chunk) = self.next_chunk(data, slice.len(), max_bytes / 3);
+                (new, DataFormat::U24(&slice[chunk]))
+            }
+            _ => return Err(DisplayError::DataFormatNotImplemented),
+        })
+    }
+
+    /// Finish the call which wrote the chunk returned by [next_chunk](Self::next_chunk)
+    ///
+    /// Returns `Ok` once the last chunk was written, [WouldBlock](nb::Error::WouldBlock) before,
+    /// and the error of a failed chunk, which ends the transfer.
+    pub fn finish(&mut self, result: Result<(), DisplayError>) -> nb::Result<(), DisplayError> {
+        match (result, self.transfer) {
+            (Err(e), _) => {
+                self.reset();
+                Err(nb::Error::Other(e))
+            }
+            (Ok(()), Some((_, len, offset))) if offset < len => Err(nb::Error::WouldBlock),
+            (Ok(()), _) => {
+                self.reset();
+                Ok(())
+            }
+        }
+    }
+}
+
+/// This trait implements a write-only interface like [WriteOnlyDataCommand], generic over the
+/// bus word instead of taking a [DataFormat]. Drivers pass words of the interface's native width,
+/// so there is no format which the interface might not implement.
+pub trait WordWriteOnlyDataCommand {
+    /// The word transferred by the interface, e.g. `u8` for byte oriented buses
+    type Word: Copy;
+
+    /// Send the commands from `cmds` to display
+    fn send_command_iter(
+        &mut self,
+        cmds: impl IntoIterator<Item = Self::Word>,
+    ) -> Result<(), DisplayError>;
+
+    /// Send the pixel data from `data` to display
+    fn send_data_iter(
+        &mut self,
+        data: impl IntoIterator<Item = Self::Word>,
+    ) -> Result<(), DisplayError>;
+
+    /// Send a slice of commands to display
+    ///
+    /// Implementations may override this with a faster path for contiguous buffers.
+    fn send_command_slice(&mut self, cmds: &[Self::Word]) -> Result<(), DisplayError> {
+        self.send_command_iter(cmds.iter().copied())
+    }
+
+    /// Send a slice of pixel data to display
+    ///
+    /// Implementations may override this with a faster path for contiguous buffers.
+    fn send_data_slice(&mut self, data: &[Self::Word]) -> Result<(), DisplayError> {
+        self.send_data_iter(data.iter().copied())
+    }
+}
+
+/// This trait implements reading data back from a display, e.g. identification or status
+/// registers. It is the responsibility of implementations to activate data mode when reading.
+pub trait ReadData {
+    /// Read data from display into `buf`
+    fn read_data(&mut self, buf: &mut [u8]) -> Result<(), DisplayError>;
+}
+
+/// This trait extends [WriteOnlyDataCommand] for interfaces which can hand an owned buffer to a
+/// DMA engine. The buffer type `B` is usually a `'static` buffer as required by the HAL's DMA API.
+///
+/// Sending returns as soon as the transfer was started, leaving the caller free to prepare the
+/// next frame in another buffer. The returned [DmaTransfer] owns the buffer until the transfer
+/// completed and hands it back from [DmaTransfer::wait], or together with the error if starting
+/// the transfer failed.
+pub trait DmaWriteOnlyDataCommand<B>: WriteOnlyDataCommand {
+    /// Transfer in flight, borrowing the interface until it completed
+    type Transfer<'a>: DmaTransfer<B>
+    where
+        Self: 'a;
+
+    /// Start sending pixel data from an owned buffer to display
+    fn send_data_owned(&mut self, buf: B) -> Result<Self::Transfer<'_>, (B, DisplayError)>;
+}
+
+/// A DMA transfer started by [DmaWriteOnlyDataCommand::send_data_owned], owning its buffer
+pub trait DmaTransfer<B> {
+    /// Whether the transfer completed, successfully or not
+    fn is_done(&mut self) -> bool;
+
+    /// Wait for the transfer to complete and hand back the buffer, together with the error if
+    /// it failed
+    fn wait(self) -> Result<B, (B, DisplayError)>;
+}
+
 /// This trait implements a write-only interface for a display which has separate data and command
 /// modes. It is the responsibility of implementations to activate the correct mode in their
 /// implementation when corresponding method is called.
@@ -74,3 +436,43 @@ pub trait AsyncWriteOnlyDataCommand {
     /// Send pixel data to display
     async fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError>;
 }
+
+/// This trait implements a write-only interface like [AsyncWriteOnlyDataCommand], generic over
+/// the bus word instead of taking a [DataFormat].
+pub trait AsyncWordWriteOnlyDataCommand {
+    /// The word transferred by the interface, e.g. `u8` for byte oriented buses
+    type Word: Copy;
+
+    /// Send the commands from `cmds` to display
+    async fn send_command_iter(
+        &mut self,
+        cmds: impl IntoIterator<Item = Self::Word>,
+    ) -> Result<(), DisplayError>;
+
+    /// Send the pixel data from `data` to display
+    async fn send_data_iter(
+        &mut self,
+        data: impl IntoIterator<Item = Self::Word>,
+    ) -> Result<(), DisplayError>;
+
+    /// Send a slice of commands to display
+    ///
+    /// Implementations may override this with a faster path for contiguous buffers.
+    async fn send_command_slice(&mut self, cmds: &[Self::Word]) -> Result<(), DisplayError> {
+        self.send_command_iter(cmds.iter().copied()).await
+    }
+
+    /// Send a slice of pixel data to display
+    ///
+    /// Implementations may override this with a faster path for contiguous buffers.
+    async fn send_data_slice(&mut self, data: &[Self::Word]) -> Result<(), DisplayError> {
+        self.send_data_iter(data.iter().copied()).await
+    }
+}
+
+/// This trait implements reading data back from a display, e.g. identification or status
+/// registers. It is the responsibility of implementations to activate data mode when reading.
+pub trait AsyncReadData {
+    /// Read data from display into `buf`
+    async fn read_data(&mut self, buf: &mut [u8]) -> Result<(), DisplayError>;
+}
@@ -0,0 +1,178 @@
+//! In-memory interface for testing drivers without hardware
+
+use crate::{
+    frame::{encode, Phase, HEADER_SIZE},
+    DataFormat, DisplayError, ReadData, WriteOnlyDataCommand,
+};
+
+/// Size of the header of a queued response: the command byte and the big endian length
+const RESPONSE_HEADER: usize = 3;
+
+/// Interface capturing everything written to it and answering reads from a queue of responses
+///
+/// Commands and data are appended to the `stream` buffer as [frames](crate::frame), which can be
+/// decoded with [FrameDecoder](crate::frame::FrameDecoder). Reads are answered with the
+/// responses set up with [expect_read](Self::expect_read) for the last command written:
+///
+/// ```
+/// use display_interface::{loopback::LoopbackInterface, DataFormat, ReadData, WriteOnlyDataCommand};
+///
+/// let mut stream = [0; 64];
+/// let mut responses = [0; 16];
+/// let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+/// di.expect_read(0x04, &[0x00, 0x85, 0x85, 0x52]);
+///
+/// // Read the display ID, skipping the dummy byte
+/// di.send_commands(DataFormat::U8(&[0x04])).unwrap();
+/// let mut id = [0; 4];
+/// di.read_data(&mut id).unwrap();
+/// assert_eq!(id[1..], [0x85, 0x85, 0x52]);
+/// assert!(!di.has_pending_reads());
+/// ```
+///
+/// Both buffers are provided by the caller, so the interface works without an allocator. Writes
+/// which don't fit into `stream` fail with [BusWriteError](DisplayError::BusWriteError).
+pub struct LoopbackInterface<'a> {
+    stream: &'a mut [u8],
+    written: usize,
+    responses: &'a mut [u8],
+    queued: usize,
+    last_command: Option<u8>,
+}
+
+impl<'a> LoopbackInterface<'a> {
+    /// Create a new interface capturing into `stream` and queueing responses in `responses`
+    pub fn new(stream: &'a mut [u8], responses: &'a mut [u8]) -> Self {
+        Self {
+            stream,
+            written: 0,
+            responses,
+            queued: 0,
+            last_command: None,
+        }
+    }
+
+    /// Queue `response` to be returned by reads following the command byte `cmd`
+    ///
+    /// Responses for the same command are returned in the order they were queued. A read takes
+    /// as many bytes as it asks for, so a response can also be consumed by several smaller reads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the response doesn't fit into the `responses` buffer, which needs three bytes
+    /// per response in addition to the response itself.
+    pub fn expect_read(&mut self, cmd: u8, response: &[u8]) -> &mut Self {
+        let start = self.queued;
+        let end = start + RESPONSE_HEADER + response.len();
+        assert!(
+            end <= self.responses.len() && response.len() <= usize::from(u16::MAX),
+            "response buffer too small"
+        );
+
+        self.responses[start] = cmd;
+        self.responses[start + 1..start + RESPONSE_HEADER]
+            .copy_from_slice(&(response.len() as u16).to_be_bytes());
+        self.responses[start + RESPONSE_HEADER..end].copy_from_slice(response);
+        self.queued = end;
+        self
+    }
+
+    /// Whether there are responses which haven't been read yet
+    pub fn has_pending_reads(&self) -> bool {
+        self.queued > 0
+    }
+
+    /// The first byte of the last command written, which selects the response of the next read
+    pub fn last_command(&self) -> Option<u8> {
+        self.last_command
+    }
+
+    /// The frames captured so far
+    pub fn written(&self) -> &[u8] {
+        &self.stream[..self.written]
+    }
+
+    /// Drop the frames captured so far
+    pub fn clear(&mut self) {
+        self.written = 0;
+    }
+
+    /// Find the first queued response for `cmd`, returning its start and length
+    fn find_response(&self, cmd: u8) -> Option<(usize, usize)> {
+        let mut start = 0;
+        while start < self.queued {
+            let len = u16::from_be_bytes([self.responses[start + 1], self.responses[start + 2]]);
+            let len = usize::from(len);
+            if self.responses[start] == cmd {
+                return Some((start, len));
+            }
+            start += RESPONSE_HEADER + len;
+        }
+        None
+    }
+
+    fn capture(&mut self, phase: Phase, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        let Self {
+            stream, written, ..
+        } = self;
+        let mut first = None;
+        encode(phase, data, |frame| {
+            let end = *written + frame.len();
+            stream
+                .get_mut(*written..end)
+                .ok_or(DisplayError::BusWriteError)?
+                .copy_from_slice(frame);
+            *written = end;
+            if first.is_none() {
+                first = frame.get(HEADER_SIZE).copied();
+            }
+            Ok(())
+        })?;
+
+        // The command byte leads parameters sent in the same call
+        if phase == Phase::Command && first.is_some() {
+            self.last_command = first;
+        }
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for LoopbackInterface<'_> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.capture(Phase::Command, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.capture(Phase::Data, buf)
+    }
+}
+
+impl ReadData for LoopbackInterface<'_> {
+    /// Read the next bytes of the response queued for the last command
+    ///
+    /// Fails with [BusReadError](DisplayError::BusReadError) if no command was written yet or
+    /// the queued response is shorter than `buf`, without consuming anything.
+    fn read_data(&mut self, buf: &mut [u8]) -> Result<(), DisplayError> {
+        let cmd = self.last_command.ok_or(DisplayError::BusReadError)?;
+        let (start, len) = self
+            .find_response(cmd)
+            .filter(|&(_, len)| len >= buf.len())
+            .ok_or(DisplayError::BusReadError)?;
+
+        let bytes = start + RESPONSE_HEADER;
+        buf.copy_from_slice(&self.responses[bytes..bytes + buf.len()]);
+
+        // Drop the bytes read, and the whole response once it is used up
+        let (removed, from) = if len == buf.len() {
+            (RESPONSE_HEADER + len, start)
+        } else {
+            let rest = (len - buf.len()) as u16;
+            self.responses[start + 1..bytes].copy_from_slice(&rest.to_be_bytes());
+            (buf.len(), bytes)
+        };
+        self.responses
+            .copy_within(from + removed..self.queued, from);
+        self.queued -= removed;
+        Ok(())
+    }
+}
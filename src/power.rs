@@ -0,0 +1,163 @@
+//! Power sequencing of display panels
+//!
+//! AMOLED and many TFT modules need their supplies switched on in a strict order with settle
+//! times in between, e.g. IOVCC, then VCI, then releasing the reset line; getting the order wrong
+//! shortens the life of the panel. Drivers require a [PowerControl] next to the write interface,
+//! so board support crates encode the sequence once, e.g. with [GpioPowerRails]:
+//!
+//! ```
+//! # use core::convert::Infallible;
+//! # use embedded_hal::{delay::DelayNs, digital::{ErrorType, OutputPin}};
+//! # struct Pin;
+//! # impl ErrorType for Pin { type Error = Infallible; }
+//! # impl OutputPin for Pin {
+//! #     fn set_low(&mut self) -> Result<(), Infallible> { Ok(()) }
+//! #     fn set_high(&mut self) -> Result<(), Infallible> { Ok(()) }
+//! # }
+//! # struct Delay;
+//! # impl DelayNs for Delay { fn delay_ns(&mut self, _: u32) {} }
+//! # let (iovcc, vci, reset, mut delay) = (Pin, Pin, Pin, Delay);
+//! use display_interface::power::{GpioPowerRails, PowerControl};
+//!
+//! let mut power = GpioPowerRails::new()
+//!     .with_step(iovcc, 1_000)
+//!     .with_step(vci, 10_000)
+//!     .with_step(reset, 120_000);
+//! power.power_on(&mut delay).unwrap();
+//! ```
+
+use embedded_hal::{delay::DelayNs, digital::OutputPin};
+
+use crate::DisplayError;
+
+/// This trait switches the supplies of a display panel in the order the panel requires.
+pub trait PowerControl {
+    /// Switch the panel on, waiting with `delay` for every supply to settle
+    fn power_on(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError>;
+
+    /// Switch the panel off, waiting with `delay` for every supply to settle
+    fn power_off(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError>;
+}
+
+/// One step of [GpioPowerRails]: a pin enabling a supply or releasing a reset line
+pub struct Step<P> {
+    pin: P,
+    settle_us: u32,
+    active_high: bool,
+}
+
+impl<P: OutputPin> Step<P> {
+    fn set(&mut self, on: bool) -> Result<(), DisplayError> {
+        if on == self.active_high {
+            self.pin.set_high()
+        } else {
+            self.pin.set_low()
+        }
+        .map_err(|_| DisplayError::PowerError)
+    }
+}
+
+/// Ordered steps of [GpioPowerRails], built with its `with_step` methods
+///
+/// Implemented for `()`, no steps, and for `(S, Step<P>)`, the steps of `S` followed by one more.
+pub trait PowerSteps {
+    /// Switch every step on in order, each followed by its settle time
+    fn on(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError>;
+
+    /// Switch every step off in reverse order, each followed by its settle time
+    fn off(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError>;
+}
+
+impl PowerSteps for () {
+    fn on(&mut self, _: &mut impl DelayNs) -> Result<(), DisplayError> {
+        Ok(())
+    }
+
+    fn off(&mut self, _: &mut impl DelayNs) -> Result<(), DisplayError> {
+        Ok(())
+    }
+}
+
+impl<S: PowerSteps, P: OutputPin> PowerSteps for (S, Step<P>) {
+    fn on(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError> {
+        self.0.on(delay)?;
+        self.1.set(true)?;
+        delay.delay_us(self.1.settle_us);
+        Ok(())
+    }
+
+    fn off(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError> {
+        self.1.set(false)?;
+        delay.delay_us(self.1.settle_us);
+        self.0.off(delay)
+    }
+}
+
+/// Power sequencing with GPIO pins switching the supplies of a panel
+///
+/// Every step switches one pin and then waits for its settle time in microseconds. Powering on
+/// runs the steps in the order they were added, powering off runs them in reverse, so e.g. the
+/// reset line is asserted again before the supplies are removed. The pins may be of different
+/// types, e.g. from different GPIO ports or an I/O expander. A pin failing to switch aborts the
+/// sequence with [PowerError](DisplayError::PowerError).
+pub struct GpioPowerRails<S = ()> {
+    steps: S,
+}
+
+impl Default for GpioPowerRails {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GpioPowerRails {
+    /// Create a sequence without any steps
+    pub fn new() -> Self {
+        Self { steps: () }
+    }
+}
+
+impl<S: PowerSteps> GpioPowerRails<S> {
+    /// Add a step driving `pin` high to switch it on, followed by `settle_us` microseconds
+    pub fn with_step<P: OutputPin>(self, pin: P, settle_us: u32) -> GpioPowerRails<(S, Step<P>)> {
+        self.push(pin, settle_us, true)
+    }
+
+    /// Add a step driving `pin` low to switch it on, e.g. for a P-channel load switch,
+    /// followed by `settle_us` microseconds
+    pub fn with_step_active_low<P: OutputPin>(
+        self,
+        pin: P,
+        settle_us: u32,
+    ) -> GpioPowerRails<(S, Step<P>)> {
+        self.push(pin, settle_us, false)
+    }
+
+    fn push<P: OutputPin>(
+        self,
+        pin: P,
+        settle_us: u32,
+        active_high: bool,
+    ) -> GpioPowerRails<(S, Step<P>)> {
+        GpioPowerRails {
+            steps: (
+                self.steps,
+                Step {
+                    pin,
+                    settle_us,
+                    active_high,
+                },
+            ),
+        }
+    }
+}
+
+impl<S: PowerSteps> PowerControl for GpioPowerRails<S> {
+    fn power_on(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError> {
+        self.steps.on(delay)
+    }
+
+    fn power_off(&mut self, delay: &mut impl DelayNs) -> Result<(), DisplayError> {
+        self.steps.off(delay)
+    }
+}
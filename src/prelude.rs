@@ -1,3 +1,13 @@
+#[cfg(feature = "power")]
+pub use crate::power::PowerControl as _display_interface_PowerControl;
+pub use crate::AsyncReadData as _display_interface_AsyncReadData;
+pub use crate::AsyncWordWriteOnlyDataCommand as _display_interface_AsyncWordWriteOnlyDataCommand;
 pub use crate::AsyncWriteOnlyDataCommand as _display_interface_AsyncWriteOnlyDataCommand;
 pub use crate::DisplayError as _display_interface_DisplayError;
+pub use crate::DmaTransfer as _display_interface_DmaTransfer;
+pub use crate::DmaWriteOnlyDataCommand as _display_interface_DmaWriteOnlyDataCommand;
+pub use crate::LowPower as _display_interface_LowPower;
+pub use crate::ReadData as _display_interface_ReadData;
+pub use crate::TypedWriteOnlyDataCommand as _display_interface_TypedWriteOnlyDataCommand;
+pub use crate::WordWriteOnlyDataCommand as _display_interface_WordWriteOnlyDataCommand;
 pub use crate::WriteOnlyDataCommand as _display_interface_WriteOnlyDataCommand;
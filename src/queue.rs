@@ -0,0 +1,215 @@
+//! Deferred commands for handing transfers to another execution context
+
+use crate::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Size of the header of an entry: the tag and the little endian length of the payload
+const HEADER_SIZE: usize = 5;
+
+/// Tag bit of data entries
+const DATA: u8 = 0x80;
+
+/// Kinds of payload, replayed with a format of the same width and byte order
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    U8 = 0,
+    U16 = 1,
+    U16BE = 2,
+    U16LE = 3,
+    U24 = 4,
+}
+
+impl Kind {
+    fn from_tag(tag: u8) -> Self {
+        match tag & !DATA {
+            1 => Kind::U16,
+            2 => Kind::U16BE,
+            3 => Kind::U16LE,
+            4 => Kind::U24,
+            _ => Kind::U8,
+        }
+    }
+}
+
+/// Queue of commands and data recorded in one execution context and sent from another
+///
+/// The queue implements [WriteOnlyDataCommand] by copying every call into its `N` bytes of
+/// storage, so a driver can run in a high priority task without touching the bus. A lower
+/// priority task then sends everything with [drain_into](Self::drain_into). The queue doesn't
+/// allocate and holds no references, so it can be kept in a `static` or an RTIC resource and
+/// shared like any other data.
+///
+/// Every call takes five bytes of storage in addition to its payload. 16-bit and 24-bit words
+/// are replayed as iterator formats of the same byte order, so they reach the interface as the
+/// same words. A call which doesn't fit into the remaining storage fails with
+/// [CapacityExceeded](DisplayError::CapacityExceeded) and leaves the queue as it was.
+pub struct CommandQueue<const N: usize> {
+    storage: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for CommandQueue<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> CommandQueue<N> {
+    /// Create an empty queue
+    pub const fn new() -> Self {
+        Self {
+            storage: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Whether there is nothing queued
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Number of bytes of storage used
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Drop everything queued
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Send everything queued to `di`, in the order it was recorded
+    ///
+    /// If `di` fails, the calls up to and including the failed one are removed from the queue
+    /// and the error is returned; the remaining ones stay queued.
+    pub fn drain_into(&mut self, di: &mut impl WriteOnlyDataCommand) -> Result<(), DisplayError> {
+        let mut offset = 0;
+        let mut result = Ok(());
+        while offset < self.len && result.is_ok() {
+            let tag = self.storage[offset];
+            let mut len = [0; 4];
+            len.copy_from_slice(&self.storage[offset + 1..offset + HEADER_SIZE]);
+            let start = offset + HEADER_SIZE;
+            offset = start + u32::from_le_bytes(len) as usize;
+
+            result = replay(di, tag, &self.storage[start..offset]);
+        }
+
+        self.storage.copy_within(offset..self.len, 0);
+        self.len -= offset;
+        result
+    }
+
+    /// Append an entry of `kind`, with the payload written by `write`
+    fn push(
+        &mut self,
+        data: bool,
+        kind: Kind,
+        write: impl FnOnce(&mut Writer<'_>) -> Result<(), DisplayError>,
+    ) -> Result<(), DisplayError> {
+        let start = self.len + HEADER_SIZE;
+        if start > N {
+            return Err(DisplayError::CapacityExceeded);
+        }
+
+        let mut writer = Writer {
+            buf: &mut self.storage[start..],
+            len: 0,
+        };
+        write(&mut writer)?;
+        let len = writer.len;
+
+        self.storage[self.len] = kind as u8 | if data { DATA } else { 0 };
+        self.storage[self.len + 1..start].copy_from_slice(&(len as u32).to_le_bytes());
+        self.len = start + len;
+        Ok(())
+    }
+
+    fn record(&mut self, data: bool, words: DataFormat<'_>) -> Result<(), DisplayError> {
+        match words {
+            DataFormat::U8(slice) => self.push(data, Kind::U8, |w| w.bytes(slice)),
+            DataFormat::U8Iter(iter) => self.push(data, Kind::U8, |w| w.bytes_iter(iter)),
+            DataFormat::U16(slice) => {
+                self.push(data, Kind::U16, |w| w.words(slice.iter().copied()))
+            }
+            DataFormat::U16BE(slice) => {
+                self.push(data, Kind::U16BE, |w| w.words(slice.iter().copied()))
+            }
+            DataFormat::U16LE(slice) => {
+                self.push(data, Kind::U16LE, |w| w.words(slice.iter().copied()))
+            }
+            DataFormat::U16BEIter(iter) => self.push(data, Kind::U16BE, |w| w.words(iter)),
+            DataFormat::U16LEIter(iter) => self.push(data, Kind::U16LE, |w| w.words(iter)),
+            DataFormat::U24(slice) => {
+                self.push(data, Kind::U24, |w| w.words24(slice.iter().copied()))
+            }
+            DataFormat::U24Iter(iter) => self.push(data, Kind::U24, |w| w.words24(iter)),
+        }
+    }
+}
+
+/// Appends the payload of an entry to the free storage
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) -> Result<(), DisplayError> {
+        let end = self.len + bytes.len();
+        self.buf
+            .get_mut(self.len..end)
+            .ok_or(DisplayError::CapacityExceeded)?
+            .copy_from_slice(bytes);
+        self.len = end;
+        Ok(())
+    }
+
+    fn bytes_iter(&mut self, mut iter: impl Iterator<Item = u8>) -> Result<(), DisplayError> {
+        iter.try_for_each(|byte| self.bytes(&[byte]))
+    }
+
+    fn words(&mut self, mut iter: impl Iterator<Item = u16>) -> Result<(), DisplayError> {
+        iter.try_for_each(|word| self.bytes(&word.to_le_bytes()))
+    }
+
+    fn words24(&mut self, mut iter: impl Iterator<Item = u32>) -> Result<(), DisplayError> {
+        iter.try_for_each(|word| self.bytes(&word.to_le_bytes()[..3]))
+    }
+}
+
+/// Send the entry with `tag` and `payload` to `di`
+fn replay(di: &mut impl WriteOnlyDataCommand, tag: u8, payload: &[u8]) -> Result<(), DisplayError> {
+    let mut words = payload
+        .chunks_exact(2)
+        .map(|word| u16::from_le_bytes([word[0], word[1]]));
+    let mut words24 = payload
+        .chunks_exact(3)
+        .map(|word| u32::from_le_bytes([word[0], word[1], word[2], 0]));
+
+    let format = match Kind::from_tag(tag) {
+        Kind::U8 => DataFormat::U8(payload),
+        #[cfg(target_endian = "little")]
+        Kind::U16 => DataFormat::U16LEIter(&mut words),
+        #[cfg(target_endian = "big")]
+        Kind::U16 => DataFormat::U16BEIter(&mut words),
+        Kind::U16BE => DataFormat::U16BEIter(&mut words),
+        Kind::U16LE => DataFormat::U16LEIter(&mut words),
+        Kind::U24 => DataFormat::U24Iter(&mut words24),
+    };
+
+    if tag & DATA != 0 {
+        di.send_data(format)
+    } else {
+        di.send_commands(format)
+    }
+}
+
+impl<const N: usize> WriteOnlyDataCommand for CommandQueue<N> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(false, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(true, buf)
+    }
+}
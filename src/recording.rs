@@ -0,0 +1,210 @@
+//! Recording, persisting and replaying command streams
+//!
+//! A [Recording] captures the commands, data and delays a driver produces, e.g. while
+//! initializing a working board. It can be saved with [Recording::serialize], loaded again with
+//! [Recording::deserialize] and sent to another interface with [replay], which is handy to
+//! compare a misbehaving board against a known-good capture or two driver versions against each
+//! other.
+//!
+//! The serialized form is a stored capture of the [frame] module: the magic bytes
+//! `DIRC` and a version byte, followed by command, data and delay frames. So it can be inspected
+//! with [frames](crate::frame::frames) and [groups](crate::frame::groups) as well. Captures of
+//! older versions stay readable by later versions of this module.
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{
+    frame::{self, encode, encode_delay, Phase},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+pub use crate::frame::{MAGIC, VERSION};
+
+/// Size of the header of a serialized recording
+pub const HEADER_SIZE: usize = frame::CAPTURE_HEADER_SIZE;
+
+/// One entry of a [Recording]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum Entry<'a> {
+    /// Bytes sent with [send_commands](WriteOnlyDataCommand::send_commands)
+    Commands(&'a [u8]),
+    /// Bytes sent with [send_data](WriteOnlyDataCommand::send_data)
+    Data(&'a [u8]),
+    /// A pause of the given number of nanoseconds
+    DelayNs(u32),
+}
+
+/// Error loading a serialized recording
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum RecordingError {
+    /// The input doesn't start with [MAGIC]
+    BadMagic,
+    /// The input was written by a later, unknown version of the format
+    UnsupportedVersion(u8),
+    /// The entry at `offset` of the input is cut short or has an unknown tag
+    Malformed {
+        /// Position of the entry in the input
+        offset: usize,
+    },
+    /// The entries don't fit into the recording
+    CapacityExceeded,
+}
+
+/// Commands, data and delays captured in `N` bytes of storage
+///
+/// The recording implements [WriteOnlyDataCommand] and [DelayNs], so it can stand in for both
+/// the interface and the delay of a driver. Commands and data are kept as the bytes a byte
+/// oriented interface sends, so 16-bit formats are converted according to their byte order;
+/// 24-bit formats aren't supported. Every call is stored as frames of up to
+/// [MAX_PAYLOAD](frame::MAX_PAYLOAD) bytes, each taking three bytes of storage in addition to
+/// its payload, and every delay as a frame of seven bytes. Calls which don't fit fail with
+/// [CapacityExceeded](DisplayError::CapacityExceeded), leaving the recording as it was.
+pub struct Recording<const N: usize> {
+    storage: [u8; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for Recording<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Recording<N> {
+    /// Create an empty recording
+    pub const fn new() -> Self {
+        Self {
+            storage: [0; N],
+            len: 0,
+        }
+    }
+
+    /// Whether nothing has been recorded
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Drop everything recorded
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Iterate over the entries recorded, one per frame
+    pub fn entries(&self) -> impl Iterator<Item = Entry<'_>> + '_ {
+        // The frames were checked when they were recorded or loaded
+        frame::frames(&self.storage[..self.len]).filter_map(|frame| {
+            Some(match frame.ok()? {
+                (Phase::Command, bytes) => Entry::Commands(bytes),
+                (Phase::Data, bytes) => Entry::Data(bytes),
+                (Phase::Delay, ns) => Entry::DelayNs(frame::delay_ns(ns)?),
+            })
+        })
+    }
+
+    /// Number of bytes [serialize](Self::serialize) writes
+    pub fn serialized_len(&self) -> usize {
+        HEADER_SIZE + self.len
+    }
+
+    /// Write the recording to `out`, returning the number of bytes written
+    ///
+    /// Fails with [CapacityExceeded](DisplayError::CapacityExceeded) if `out` is shorter than
+    /// [serialized_len](Self::serialized_len).
+    pub fn serialize(&self, out: &mut [u8]) -> Result<usize, DisplayError> {
+        let out = out
+            .get_mut(..self.serialized_len())
+            .ok_or(DisplayError::CapacityExceeded)?;
+        out[..MAGIC.len()].copy_from_slice(&MAGIC);
+        out[MAGIC.len()] = VERSION;
+        out[HEADER_SIZE..].copy_from_slice(&self.storage[..self.len]);
+        Ok(out.len())
+    }
+
+    /// Load a recording written by [serialize](Self::serialize)
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, RecordingError> {
+        if bytes.len() < HEADER_SIZE || bytes[..MAGIC.len()] != MAGIC {
+            return Err(RecordingError::BadMagic);
+        }
+        match bytes[MAGIC.len()] {
+            1 => {}
+            version => return Err(RecordingError::UnsupportedVersion(version)),
+        }
+
+        // Check every frame, so replaying never meets a broken one
+        let mut frames = frame::frames(bytes);
+        while let Some(frame) = frames.next() {
+            if frame.is_err() {
+                let offset = bytes.len() - frames.remainder().len();
+                return Err(RecordingError::Malformed { offset });
+            }
+        }
+
+        let entries = &bytes[HEADER_SIZE..];
+        let mut recording = Self::new();
+        recording
+            .storage
+            .get_mut(..entries.len())
+            .ok_or(RecordingError::CapacityExceeded)?
+            .copy_from_slice(entries);
+        recording.len = entries.len();
+        Ok(recording)
+    }
+
+    /// Append `frame`
+    fn push(&mut self, frame: &[u8]) -> Result<(), DisplayError> {
+        let end = self.len + frame.len();
+        self.storage
+            .get_mut(self.len..end)
+            .ok_or(DisplayError::CapacityExceeded)?
+            .copy_from_slice(frame);
+        self.len = end;
+        Ok(())
+    }
+
+    /// Append the bytes of `words` as frames of `phase`
+    fn record(&mut self, phase: Phase, words: DataFormat<'_>) -> Result<(), DisplayError> {
+        let start = self.len;
+        let result = encode(phase, words, |frame| self.push(frame));
+        if result.is_err() {
+            self.len = start;
+        }
+        result
+    }
+}
+
+impl<const N: usize> WriteOnlyDataCommand for Recording<N> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(Phase::Command, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(Phase::Data, buf)
+    }
+}
+
+impl<const N: usize> DelayNs for Recording<N> {
+    /// Record a delay of `ns` nanoseconds
+    ///
+    /// Delays can't fail, so a delay which doesn't fit into the recording is dropped.
+    fn delay_ns(&mut self, ns: u32) {
+        let _ = encode_delay(ns, |frame| self.push(frame));
+    }
+}
+
+/// Send the entries of `recording` to `di`, pausing with `delay` for the recorded delays
+pub fn replay<const N: usize>(
+    recording: &Recording<N>,
+    di: &mut impl WriteOnlyDataCommand,
+    delay: &mut impl DelayNs,
+) -> Result<(), DisplayError> {
+    for entry in recording.entries() {
+        match entry {
+            Entry::Commands(bytes) => di.send_commands(DataFormat::U8(bytes))?,
+            Entry::Data(bytes) => di.send_data(DataFormat::U8(bytes))?,
+            Entry::DelayNs(ns) => delay.delay_ns(ns),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,117 @@
+//! Run-length encoded pixel data
+//!
+//! Icons, fonts and background fills are mostly made of long runs of the same color, so they are
+//! stored much more compactly as runs than as raw pixels. The format is a sequence of runs of
+//! four bytes each:
+//!
+//! | Offset | Size | Content                                   |
+//! |--------|------|-------------------------------------------|
+//! | 0      | 2    | Number of pixels in the run, big endian   |
+//! | 2      | 2    | 16-bit pixel value, big endian            |
+//!
+//! Runs hold at least one pixel; longer sequences of the same color are split into several runs.
+//! [encode] produces this format, e.g. in a build script, and [RleInterface] expands it into an
+//! interface's [U16BEIter](DataFormat::U16BEIter) path without materializing the pixels.
+
+use crate::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Size of one run
+pub const RUN_SIZE: usize = 4;
+
+/// Check that `rle` is made of whole runs of at least one pixel each
+fn validate(rle: &[u8]) -> Result<(), DisplayError> {
+    if rle.len() % RUN_SIZE != 0 {
+        return Err(DisplayError::InvalidFormatError);
+    }
+
+    if rle
+        .chunks_exact(RUN_SIZE)
+        .any(|run| run[0] == 0 && run[1] == 0)
+    {
+        return Err(DisplayError::InvalidFormatError);
+    }
+
+    Ok(())
+}
+
+/// Iterate over the pixels of the runs in `rle`, which has to be [validated](validate)
+fn pixels(rle: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    rle.chunks_exact(RUN_SIZE).flat_map(|run| {
+        let count = u16::from_be_bytes([run[0], run[1]]);
+        let value = u16::from_be_bytes([run[2], run[3]]);
+        core::iter::repeat(value).take(usize::from(count))
+    })
+}
+
+/// Return the number of bytes [encode] needs for `pixels`
+pub fn encoded_len(pixels: &[u16]) -> usize {
+    runs(pixels).count() * RUN_SIZE
+}
+
+/// Split `pixels` into runs of at most `u16::MAX` pixels
+fn runs(pixels: &[u16]) -> impl Iterator<Item = (u16, u16)> + '_ {
+    let mut rest = pixels;
+    core::iter::from_fn(move || {
+        let (&value, _) = rest.split_first()?;
+        let len = rest
+            .iter()
+            .take(usize::from(u16::MAX))
+            .take_while(|&&v| v == value)
+            .count();
+        rest = &rest[len..];
+        Some((len as u16, value))
+    })
+}
+
+/// Encode `pixels` into `out`, returning the number of bytes used
+///
+/// Returns `None` if `out` is too small, see [encoded_len].
+pub fn encode(pixels: &[u16], out: &mut [u8]) -> Option<usize> {
+    let mut len = 0;
+    for (count, value) in runs(pixels) {
+        let run = out.get_mut(len..len + RUN_SIZE)?;
+        run[..2].copy_from_slice(&count.to_be_bytes());
+        run[2..].copy_from_slice(&value.to_be_bytes());
+        len += RUN_SIZE;
+    }
+    Some(len)
+}
+
+/// Wrapper adding run-length encoded pixel data to the interface `DI`
+///
+/// Commands and plain data are passed on as they are.
+pub struct RleInterface<DI> {
+    di: DI,
+}
+
+impl<DI: WriteOnlyDataCommand> RleInterface<DI> {
+    /// Create a new wrapper around `di`
+    pub fn new(di: DI) -> Self {
+        Self { di }
+    }
+
+    /// Send the pixels of the runs in `rle` as [U16BEIter](DataFormat::U16BEIter) data
+    ///
+    /// The whole input is checked before anything is sent: input which isn't made of whole runs,
+    /// e.g. because it was cut short, or which contains a run of zero pixels results in an
+    /// [InvalidFormatError](DisplayError::InvalidFormatError).
+    pub fn send_rle_data(&mut self, rle: &[u8]) -> Result<(), DisplayError> {
+        validate(rle)?;
+        self.di.send_data(DataFormat::U16BEIter(&mut pixels(rle)))
+    }
+
+    /// Consume the wrapper and return the wrapped interface
+    pub fn release(self) -> DI {
+        self.di
+    }
+}
+
+impl<DI: WriteOnlyDataCommand> WriteOnlyDataCommand for RleInterface<DI> {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.di.send_commands(cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.di.send_data(buf)
+    }
+}
@@ -0,0 +1,68 @@
+//! Forwarding a driver's output to another machine over TCP
+
+use std::{
+    io::{self, Write},
+    net::TcpStream,
+};
+
+use crate::{
+    frame::{encode, Phase},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// Interface sending commands and data as [frames](crate::frame) over a [TcpStream]
+///
+/// This is meant for hardware-in-the-loop tests: the driver runs on the host, while an agent on
+/// the target board receives the frames and replays them onto the physical bus with a
+/// [FrameDecoder](crate::frame::FrameDecoder). Every call is written to the stream before it
+/// returns, and the frames carry the bytes as a byte oriented interface would send them.
+///
+/// Errors of the stream, including timeouts configured with
+/// [TcpStream::set_write_timeout] and disconnects, are returned as
+/// [BusWriteError](DisplayError::BusWriteError); the underlying [io::Error] can be retrieved
+/// with [take_error](Self::take_error).
+pub struct TcpBridgeInterface {
+    stream: TcpStream,
+    error: Option<io::Error>,
+}
+
+impl TcpBridgeInterface {
+    /// Create a new interface writing to `stream`
+    pub fn new(stream: TcpStream) -> Self {
+        Self {
+            stream,
+            error: None,
+        }
+    }
+
+    /// Return the error of the stream which caused the last
+    /// [BusWriteError](DisplayError::BusWriteError), if any, and clear it
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    /// Consume the interface and return the stream used by it
+    pub fn release(self) -> TcpStream {
+        self.stream
+    }
+
+    fn send(&mut self, phase: Phase, data: DataFormat<'_>) -> Result<(), DisplayError> {
+        let Self { stream, error } = self;
+        encode(phase, data, |frame| {
+            stream.write_all(frame).map_err(|e| {
+                *error = Some(e);
+                DisplayError::BusWriteError
+            })
+        })
+    }
+}
+
+impl WriteOnlyDataCommand for TcpBridgeInterface {
+    fn send_commands(&mut self, cmds: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(Phase::Command, cmds)
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.send(Phase::Data, buf)
+    }
+}
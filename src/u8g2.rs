@@ -0,0 +1,237 @@
+//! Replaying u8g2 initialization sequences
+//!
+//! The [u8g2](https://github.com/olikraus/u8g2) library describes the initialization of its
+//! displays as byte arrays built with the `U8X8_*` macros from `u8x8.h`. [replay_sequence]
+//! interprets the following subset of that format, which covers the init and power save
+//! sequences of the controller drivers:
+//!
+//! | Macro                    | Bytes                | Meaning                              |
+//! |--------------------------|----------------------|--------------------------------------|
+//! | `U8X8_START_TRANSFER()`  | `0x18`               | [START_TRANSFER]                     |
+//! | `U8X8_END_TRANSFER()`    | `0x19`               | [END_TRANSFER]                       |
+//! | `U8X8_C(c)`              | `0x15, c`            | [SEND_CMD], send `c` as a command    |
+//! | `U8X8_A(a)`              | `0x16, a`            | [SEND_ARG], send `a` as an argument  |
+//! | `U8X8_CA(c, a)`          | `0x15, c, 0x16, a`   | a command and its argument           |
+//! | `U8X8_D1(d)`             | `0x17, d`            | [SEND_DATA], send `d` as data        |
+//! | `U8X8_DLY(ms)`           | `0xfe, ms`           | [DELAY], wait for `ms` milliseconds  |
+//! | `U8X8_END()`             | `0xff`               | [END], end of the sequence           |
+//!
+//! Whether arguments are sent as commands or as data depends on the controller, u8g2 selects
+//! this with the CAD procedure of the display, see [ArgMode].
+
+use embedded_hal::delay::DelayNs;
+
+use crate::{DataFormat, DisplayError, WriteOnlyDataCommand};
+
+/// Code of `U8X8_C`, followed by the command byte
+pub const SEND_CMD: u8 = 0x15;
+/// Code of `U8X8_A`, followed by the argument byte
+pub const SEND_ARG: u8 = 0x16;
+/// Code of `U8X8_D1`, followed by the data byte
+pub const SEND_DATA: u8 = 0x17;
+/// Code of `U8X8_START_TRANSFER`
+pub const START_TRANSFER: u8 = 0x18;
+/// Code of `U8X8_END_TRANSFER`
+pub const END_TRANSFER: u8 = 0x19;
+/// Code of `U8X8_DLY`, followed by the delay in milliseconds
+pub const DELAY: u8 = 0xfe;
+/// Code of `U8X8_END`, terminating the sequence
+pub const END: u8 = 0xff;
+
+/// Number of bytes collected before they are handed to the interface
+const BATCH_SIZE: usize = 32;
+
+/// How the arguments of a sequence are sent
+///
+/// This corresponds to the CAD procedure u8g2 uses for the display: controllers like the SSD1306
+/// (`u8x8_cad_001`) take their arguments as commands, while controllers like the ST7735 or ILI9341
+/// (`u8x8_cad_011`) expect them as data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum ArgMode {
+    /// Send arguments with [send_commands](WriteOnlyDataCommand::send_commands)
+    #[default]
+    Command,
+    /// Send arguments with [send_data](WriteOnlyDataCommand::send_data)
+    Data,
+}
+
+/// Error replaying a u8g2 sequence
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "defmt-03", derive(defmt::Format))]
+pub enum SequenceError {
+    /// The byte at `offset` is not one of the supported codes
+    UnknownCode {
+        /// Position of the byte in the sequence
+        offset: usize,
+        /// The byte found
+        code: u8,
+    },
+    /// The code at `offset` is missing its operand
+    MissingOperand {
+        /// Position of the code in the sequence
+        offset: usize,
+    },
+    /// The transfer marker at `offset` doesn't match the transfers started before it, i.e. a
+    /// transfer is started twice or ended without being started
+    UnbalancedTransfer {
+        /// Position of the marker in the sequence
+        offset: usize,
+    },
+    /// The sequence isn't terminated by [END] or ends within a transfer
+    Unterminated,
+    /// The interface failed to send the sequence
+    Interface(DisplayError),
+}
+
+impl From<DisplayError> for SequenceError {
+    fn from(e: DisplayError) -> Self {
+        SequenceError::Interface(e)
+    }
+}
+
+/// One step of a sequence
+#[derive(Clone, Copy)]
+enum Step {
+    Start,
+    End,
+    Command(u8),
+    Arg(u8),
+    Data(u8),
+    DelayMs(u8),
+}
+
+/// Iterate over the steps of `seq` up to the [END] marker, checking the format on the way
+fn steps(seq: &[u8]) -> impl Iterator<Item = Result<Step, SequenceError>> + '_ {
+    let mut offset = 0;
+    let mut in_transfer = false;
+    let mut done = false;
+    core::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let at = offset;
+        let Some(&code) = seq.get(at) else {
+            done = true;
+            return Some(Err(SequenceError::Unterminated));
+        };
+        offset += 1;
+
+        let mut operand = || {
+            let byte = seq.get(offset).copied();
+            offset += 1;
+            byte.ok_or(SequenceError::MissingOperand { offset: at })
+        };
+
+        let step = match code {
+            END if in_transfer => Err(SequenceError::Unterminated),
+            END => {
+                done = true;
+                return None;
+            }
+            START_TRANSFER if in_transfer => Err(SequenceError::UnbalancedTransfer { offset: at }),
+            END_TRANSFER if !in_transfer => Err(SequenceError::UnbalancedTransfer { offset: at }),
+            START_TRANSFER | END_TRANSFER => {
+                in_transfer = code == START_TRANSFER;
+                Ok(if in_transfer { Step::Start } else { Step::End })
+            }
+            SEND_CMD => operand().map(Step::Command),
+            SEND_ARG => operand().map(Step::Arg),
+            SEND_DATA => operand().map(Step::Data),
+            DELAY => operand().map(Step::DelayMs),
+            code => Err(SequenceError::UnknownCode { offset: at, code }),
+        };
+
+        // Stop after the first error
+        done = step.is_err();
+        Some(step)
+    })
+}
+
+/// Check that `seq` is a well-formed sequence
+fn validate(seq: &[u8]) -> Result<(), SequenceError> {
+    for step in steps(seq) {
+        step?;
+    }
+    Ok(())
+}
+
+/// Bytes of the same kind waiting to be sent
+struct Batch {
+    buf: [u8; BATCH_SIZE],
+    len: usize,
+    data: bool,
+}
+
+impl Batch {
+    fn flush(&mut self, di: &mut impl WriteOnlyDataCommand) -> Result<(), DisplayError> {
+        if self.len == 0 {
+            return Ok(());
+        }
+        let bytes = DataFormat::U8(&self.buf[..self.len]);
+        self.len = 0;
+        if self.data {
+            di.send_data(bytes)
+        } else {
+            di.send_commands(bytes)
+        }
+    }
+
+    fn push(
+        &mut self,
+        di: &mut impl WriteOnlyDataCommand,
+        byte: u8,
+        data: bool,
+    ) -> Result<(), DisplayError> {
+        if self.len == BATCH_SIZE || self.data != data {
+            self.flush(di)?;
+        }
+        self.data = data;
+        self.buf[self.len] = byte;
+        self.len += 1;
+        Ok(())
+    }
+}
+
+/// Replay the u8g2 sequence `seq` through `di`, waiting with `delay`
+///
+/// The whole sequence is checked before anything is sent, so a malformed sequence results in a
+/// [SequenceError] without touching the display. It has to be terminated by [END]; anything after
+/// it is ignored, as in u8g2. Consecutive bytes of the same kind are combined into one
+/// [send_commands](WriteOnlyDataCommand::send_commands) or
+/// [send_data](WriteOnlyDataCommand::send_data) call, up to 32 bytes at a time. Delays and the end
+/// of a transfer always send the bytes collected so far.
+pub fn replay_sequence<DI, D>(
+    di: &mut DI,
+    delay: &mut D,
+    seq: &[u8],
+    args: ArgMode,
+) -> Result<(), SequenceError>
+where
+    DI: WriteOnlyDataCommand,
+    D: DelayNs,
+{
+    validate(seq)?;
+
+    let mut batch = Batch {
+        buf: [0; BATCH_SIZE],
+        len: 0,
+        data: false,
+    };
+    for step in steps(seq) {
+        match step? {
+            Step::Start => {}
+            Step::End => batch.flush(di)?,
+            Step::Command(byte) => batch.push(di, byte, false)?,
+            Step::Arg(byte) => batch.push(di, byte, args == ArgMode::Data)?,
+            Step::Data(byte) => batch.push(di, byte, true)?,
+            Step::DelayMs(ms) => {
+                batch.flush(di)?;
+                delay.delay_ms(u32::from(ms));
+            }
+        }
+    }
+    batch.flush(di)?;
+
+    Ok(())
+}
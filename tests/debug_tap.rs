@@ -0,0 +1,194 @@
+#![cfg(feature = "debug-tap")]
+//! Noting the most recent calls of an interface
+
+use display_interface::{
+    debug_tap::{DebugTap, TapEntry, TapLog},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// Interface consuming every format, failing data calls while `fail` is set
+#[derive(Default)]
+struct Sink {
+    words: usize,
+    fail: bool,
+}
+
+impl Sink {
+    fn consume(&mut self, buf: DataFormat<'_>) {
+        self.words += match buf {
+            DataFormat::U8(slice) => slice.len(),
+            DataFormat::U16(slice) => slice.len(),
+            DataFormat::U16BE(slice) | DataFormat::U16LE(slice) => slice.len(),
+            DataFormat::U24(slice) => slice.len(),
+            DataFormat::U8Iter(iter) => iter.count(),
+            DataFormat::U16BEIter(iter) | DataFormat::U16LEIter(iter) => iter.count(),
+            DataFormat::U24Iter(iter) => iter.count(),
+            _ => 0,
+        };
+    }
+}
+
+impl WriteOnlyDataCommand for Sink {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.consume(cmd);
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        if self.fail {
+            return Err(DisplayError::BusWriteError);
+        }
+        self.consume(buf);
+        Ok(())
+    }
+}
+
+#[test]
+fn notes_first_command_word_and_lengths() {
+    let log = TapLog::<8>::new();
+    let mut di = DebugTap::new(Sink::default(), &log);
+
+    di.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    di.send_data(DataFormat::U16BE(&mut [0; 3])).unwrap();
+    di.send_commands(DataFormat::U16(&[0x2a00, 0x0000]))
+        .unwrap();
+    di.send_data(DataFormat::U24(&[0; 2])).unwrap();
+    di.send_commands(DataFormat::U8(&[])).unwrap();
+
+    assert_eq!(
+        log.entries().collect::<Vec<_>>(),
+        [
+            TapEntry::Commands {
+                first: Some(0x2c),
+                bytes: 1,
+                ok: true
+            },
+            TapEntry::Data { bytes: 6, ok: true },
+            TapEntry::Commands {
+                first: Some(0x2a00),
+                bytes: 4,
+                ok: true
+            },
+            TapEntry::Data { bytes: 6, ok: true },
+            TapEntry::Commands {
+                first: None,
+                bytes: 0,
+                ok: true
+            },
+        ]
+    );
+    assert_eq!(di.release().words, 1 + 3 + 2 + 2);
+}
+
+#[test]
+fn iterators_are_counted_as_passed_on() {
+    let log = TapLog::<4>::new();
+    let mut di = DebugTap::new(Sink::default(), &log);
+
+    di.send_commands(DataFormat::U8Iter(&mut [0x36, 0x48].into_iter()))
+        .unwrap();
+    di.send_data(DataFormat::U16LEIter(&mut (0..100))).unwrap();
+    // Only the low 24 bits of a command word are kept
+    di.send_commands(DataFormat::U24Iter(&mut [0xff12_3456].into_iter()))
+        .unwrap();
+
+    assert_eq!(
+        log.entries().collect::<Vec<_>>(),
+        [
+            TapEntry::Commands {
+                first: Some(0x36),
+                bytes: 2,
+                ok: true
+            },
+            TapEntry::Data {
+                bytes: 200,
+                ok: true
+            },
+            TapEntry::Commands {
+                first: Some(0x12_3456),
+                bytes: 3,
+                ok: true
+            },
+        ]
+    );
+    assert_eq!(di.release().words, 103);
+}
+
+#[test]
+fn failures_are_noted_and_passed_on() {
+    let log = TapLog::<4>::new();
+    let mut di = DebugTap::new(
+        Sink {
+            fail: true,
+            ..Sink::default()
+        },
+        &log,
+    );
+
+    assert!(matches!(
+        di.send_data(DataFormat::U8(&[0; 10])),
+        Err(DisplayError::BusWriteError)
+    ));
+    // An iterator the interface gave up on before reading it
+    assert!(di
+        .send_data(DataFormat::U8Iter(&mut [0; 10].into_iter()))
+        .is_err());
+
+    assert_eq!(
+        log.entries().collect::<Vec<_>>(),
+        [
+            TapEntry::Data {
+                bytes: 10,
+                ok: false
+            },
+            TapEntry::Data {
+                bytes: 0,
+                ok: false
+            },
+        ]
+    );
+}
+
+#[test]
+fn keeps_the_most_recent_entries() {
+    let log = TapLog::<3>::new();
+    let mut di = DebugTap::new(Sink::default(), &log);
+    assert_eq!(log.last(), None);
+
+    for cmd in 0..5 {
+        di.send_commands(DataFormat::U8(&[cmd])).unwrap();
+    }
+
+    let firsts: Vec<_> = log
+        .entries()
+        .map(|entry| match entry {
+            TapEntry::Commands { first, .. } => first,
+            TapEntry::Data { .. } => None,
+        })
+        .collect();
+    assert_eq!(firsts, [Some(2), Some(3), Some(4)]);
+    assert_eq!(log.count(), 5);
+    assert_eq!(
+        log.last(),
+        Some(TapEntry::Commands {
+            first: Some(4),
+            bytes: 1,
+            ok: true
+        })
+    );
+
+    log.clear();
+    assert_eq!(log.entries().count(), 0);
+    assert_eq!(log.count(), 0);
+}
+
+/// The log can live in a `static`, e.g. to be read from a panic handler
+#[test]
+fn static_log() {
+    static LOG: TapLog<2> = TapLog::new();
+    let mut di = DebugTap::new(Sink::default(), &LOG);
+    di.send_data(DataFormat::U8(&[1, 2, 3])).unwrap();
+
+    assert!(core::ptr::eq(di.log(), &LOG));
+    assert_eq!(LOG.last(), Some(TapEntry::Data { bytes: 3, ok: true }));
+}
@@ -0,0 +1,233 @@
+//! Suppressing and rewriting commands with a callback
+
+use display_interface::{
+    filter::{FilterInterface, Verdict},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+#[derive(Debug, PartialEq)]
+enum Call {
+    Commands(Vec<u8>),
+    /// Commands in a format other than `U8`, with their number of words
+    OtherCommands(usize),
+    Data(Vec<u8>),
+    /// Data in a format other than `U8`, with its number of words
+    OtherData(usize),
+}
+
+/// Interface keeping every call it received
+#[derive(Default)]
+struct Calls(Vec<Call>);
+
+impl WriteOnlyDataCommand for Calls {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(match cmd {
+            DataFormat::U8(slice) => Call::Commands(slice.to_vec()),
+            DataFormat::U16(slice) => Call::OtherCommands(slice.len()),
+            DataFormat::U16BEIter(iter) => Call::OtherCommands(iter.count()),
+            _ => panic!("unexpected format"),
+        });
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(match buf {
+            DataFormat::U8(slice) => Call::Data(slice.to_vec()),
+            DataFormat::U16BEIter(iter) => Call::OtherData(iter.count()),
+            _ => panic!("unexpected format"),
+        });
+        Ok(())
+    }
+}
+
+/// Drop every sleep out but the first after a sleep in
+fn sleep_out_once() -> impl FnMut(u8, &mut [u8]) -> Verdict {
+    let mut awake = false;
+    move |cmd, _| {
+        match cmd {
+            0x10 => awake = false,
+            0x11 if awake => return Verdict::Suppress,
+            0x11 => awake = true,
+            _ => {}
+        }
+        Verdict::Send
+    }
+}
+
+#[test]
+fn suppressed_calls_are_dropped() {
+    let mut di = FilterInterface::new(Calls::default(), sleep_out_once());
+
+    for cmd in [0x11, 0x11, 0x29, 0x10, 0x11, 0x11] {
+        di.send_commands(DataFormat::U8(&[cmd])).unwrap();
+    }
+
+    assert_eq!(
+        di.release().0,
+        [
+            Call::Commands(vec![0x11]),
+            Call::Commands(vec![0x29]),
+            Call::Commands(vec![0x10]),
+            Call::Commands(vec![0x11]),
+        ]
+    );
+}
+
+#[test]
+fn parameters_are_rewritten() {
+    let mut seen = Vec::new();
+    let mut di = FilterInterface::new(Calls::default(), |cmd, params: &mut [u8]| {
+        seen.push((cmd, params.to_vec()));
+        if cmd == 0x36 {
+            params[0] |= 0x08;
+        }
+        Verdict::Send
+    });
+
+    let madctl = [0x36, 0x40];
+    di.send_commands(DataFormat::U8(&madctl)).unwrap();
+    di.send_commands(DataFormat::U8Iter(&mut [0x36, 0x80].into_iter()))
+        .unwrap();
+    di.send_commands(DataFormat::U8(&[0x3a, 0x55])).unwrap();
+
+    let calls = di.release().0;
+    assert_eq!(
+        calls,
+        [
+            Call::Commands(vec![0x36, 0x48]),
+            Call::Commands(vec![0x36, 0x88]),
+            Call::Commands(vec![0x3a, 0x55]),
+        ]
+    );
+    // The filter changes a copy, the caller's bytes are left alone
+    assert_eq!(madctl, [0x36, 0x40]);
+    assert_eq!(
+        seen,
+        [(0x36, vec![0x40]), (0x36, vec![0x80]), (0x3a, vec![0x55])]
+    );
+}
+
+#[test]
+fn data_and_other_formats_pass_untouched() {
+    let mut calls = 0;
+    let mut di = FilterInterface::new(Calls::default(), |_, _: &mut [u8]| {
+        calls += 1;
+        Verdict::Suppress
+    });
+
+    di.send_data(DataFormat::U8(&[0x11; 4])).unwrap();
+    di.send_data(DataFormat::U16BEIter(&mut (0..100))).unwrap();
+    di.send_commands(DataFormat::U16(&[0x2a00, 0x0000]))
+        .unwrap();
+    di.send_commands(DataFormat::U16BEIter(&mut (0..3)))
+        .unwrap();
+    di.send_commands(DataFormat::U8(&[])).unwrap();
+
+    assert_eq!(
+        di.release().0,
+        [
+            Call::Data(vec![0x11; 4]),
+            Call::OtherData(100),
+            Call::OtherCommands(2),
+            Call::OtherCommands(3),
+            Call::Commands(vec![]),
+        ]
+    );
+    assert_eq!(calls, 0);
+}
+
+#[test]
+fn calls_beyond_the_buffer_are_rejected() {
+    let mut di = FilterInterface::new(Calls::default(), |_, _: &mut [u8]| Verdict::Send)
+        .with_capacity::<4>();
+
+    di.send_commands(DataFormat::U8(&[0xb1, 0x00, 0x1b, 0x00]))
+        .unwrap();
+    assert!(matches!(
+        di.send_commands(DataFormat::U8(&[0xe0; 5])),
+        Err(DisplayError::CapacityExceeded)
+    ));
+    assert!(matches!(
+        di.send_commands(DataFormat::U8Iter(&mut [0xe1; 5].into_iter())),
+        Err(DisplayError::CapacityExceeded)
+    ));
+
+    assert_eq!(
+        di.release().0,
+        [Call::Commands(vec![0xb1, 0x00, 0x1b, 0x00])]
+    );
+}
+
+#[test]
+fn parameters_sent_as_data_are_rewritten() {
+    let mut seen = Vec::new();
+    let mut di = FilterInterface::new(Calls::default(), |cmd, params: &mut [u8]| {
+        seen.push((cmd, params.to_vec()));
+        if cmd == 0x36 {
+            params.iter_mut().for_each(|p| *p |= 0x08);
+        }
+        Verdict::Send
+    });
+
+    // MADCTL the way MIPI DCS drivers send it
+    di.send_commands(DataFormat::U8(&[0x36])).unwrap();
+    di.send_data(DataFormat::U8(&[0x40])).unwrap();
+    // Only the first data call after the command holds its parameters
+    di.send_data(DataFormat::U8(&[0x40])).unwrap();
+
+    assert_eq!(
+        di.release().0,
+        [
+            Call::Commands(vec![0x36]),
+            Call::Data(vec![0x48]),
+            Call::Data(vec![0x40]),
+        ]
+    );
+    assert_eq!(seen, [(0x36, vec![]), (0x36, vec![0x40])]);
+}
+
+#[test]
+fn data_of_suppressed_commands_is_dropped() {
+    let mut di = FilterInterface::new(Calls::default(), |cmd, _: &mut [u8]| {
+        if cmd == 0xb1 {
+            Verdict::Suppress
+        } else {
+            Verdict::Send
+        }
+    });
+
+    di.send_commands(DataFormat::U8(&[0xb1])).unwrap();
+    di.send_data(DataFormat::U16BEIter(&mut (0..3))).unwrap();
+    di.send_data(DataFormat::U8(&[0x05])).unwrap();
+
+    assert_eq!(di.release().0, [Call::Data(vec![0x05])]);
+}
+
+#[test]
+fn pixels_after_a_command_pass_untouched() {
+    let mut calls = 0;
+    let mut di = FilterInterface::new(Calls::default(), |_, params: &mut [u8]| {
+        calls += 1;
+        params.fill(0);
+        Verdict::Send
+    })
+    .with_capacity::<4>();
+
+    // Larger than the buffer
+    di.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    di.send_data(DataFormat::U8(&[0xff; 5])).unwrap();
+    // An iterator
+    di.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    di.send_data(DataFormat::U16BEIter(&mut (0..10))).unwrap();
+
+    assert_eq!(
+        di.release().0,
+        [
+            Call::Commands(vec![0x2c]),
+            Call::Data(vec![0xff; 5]),
+            Call::Commands(vec![0x2c]),
+            Call::OtherData(10),
+        ]
+    );
+    assert_eq!(calls, 2);
+}
@@ -0,0 +1,293 @@
+//! Framing of command and data phases
+
+use display_interface::{
+    frame::{self, encode, groups, FrameDecoder, FrameError, Phase, MAX_PAYLOAD},
+    loopback::LoopbackInterface,
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Commands(Vec<u8>),
+    Data(Vec<u8>),
+}
+
+/// Interface recording everything sent through it
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+fn bytes(buf: DataFormat<'_>) -> Vec<u8> {
+    match buf {
+        DataFormat::U8(slice) => slice.to_vec(),
+        _ => panic!("unexpected format"),
+    }
+}
+
+impl WriteOnlyDataCommand for Recorder {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(Event::Commands(bytes(cmd)));
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(Event::Data(bytes(buf)));
+        Ok(())
+    }
+}
+
+fn frames(phase: Phase, data: DataFormat<'_>) -> Vec<u8> {
+    let mut stream = Vec::new();
+    encode(phase, data, |frame| {
+        stream.extend_from_slice(frame);
+        Ok(())
+    })
+    .unwrap();
+    stream
+}
+
+#[test]
+fn header_holds_phase_and_length() {
+    assert_eq!(
+        frames(Phase::Command, DataFormat::U8(&[0x2a, 0x00])),
+        [0x00, 0x00, 0x02, 0x2a, 0x00]
+    );
+    assert_eq!(
+        frames(Phase::Data, DataFormat::U16BE(&mut [0x1234])),
+        [0x01, 0x00, 0x02, 0x12, 0x34]
+    );
+    assert_eq!(
+        frames(
+            Phase::Data,
+            DataFormat::U16LEIter(&mut [0x1234].into_iter())
+        ),
+        [0x01, 0x00, 0x02, 0x34, 0x12]
+    );
+    assert!(frames(Phase::Data, DataFormat::U8(&[])).is_empty());
+}
+
+#[test]
+fn long_payloads_are_split() {
+    let data: Vec<u8> = (0..=255).cycle().take(MAX_PAYLOAD + 10).collect();
+    let stream = frames(Phase::Data, DataFormat::U8(&data));
+
+    assert_eq!(stream.len(), 2 * 3 + data.len());
+    assert_eq!(stream[..3], [0x01, 0x01, 0x00]);
+    assert_eq!(stream[3 + MAX_PAYLOAD..6 + MAX_PAYLOAD], [0x01, 0x00, 0x0a]);
+}
+
+#[test]
+fn decoder_replays_frames_pushed_in_pieces() {
+    let data: Vec<u8> = (0..=255).cycle().take(MAX_PAYLOAD + 1).collect();
+    let mut stream = frames(Phase::Command, DataFormat::U8(&[0x2c]));
+    stream.extend(frames(Phase::Data, DataFormat::U8(&data)));
+
+    let mut decoder = FrameDecoder::new();
+    let mut recorder = Recorder::default();
+    for piece in stream.chunks(7) {
+        decoder.replay(piece, &mut recorder).unwrap();
+    }
+
+    assert!(decoder.is_idle());
+    assert_eq!(
+        recorder.0,
+        [
+            Event::Commands(vec![0x2c]),
+            Event::Data(data[..MAX_PAYLOAD].to_vec()),
+            Event::Data(data[MAX_PAYLOAD..].to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn decoder_rejects_malformed_headers() {
+    let mut decoder = FrameDecoder::new();
+    let mut recorder = Recorder::default();
+
+    assert!(matches!(
+        decoder.replay(&[0x03, 0x00, 0x01, 0xff], &mut recorder),
+        Err(FrameError::UnknownPhase(0x03))
+    ));
+    assert!(matches!(
+        decoder.replay(&[0x02, 0x00, 0x02], &mut recorder),
+        Err(FrameError::InvalidDelay(2))
+    ));
+    assert!(matches!(
+        decoder.replay(&[0x01, 0x01, 0x01], &mut recorder),
+        Err(FrameError::TooLong(0x101))
+    ));
+    assert!(decoder.is_idle());
+    assert!(recorder.0.is_empty());
+
+    decoder.replay(&[0x01, 0x00], &mut recorder).unwrap();
+    assert!(!decoder.is_idle());
+    decoder.reset();
+    // Delays are skipped
+    decoder
+        .replay(
+            &[0x02, 0x00, 0x04, 0, 0, 0x01, 0xf4, 0x00, 0x00, 0x01, 0x29],
+            &mut recorder,
+        )
+        .unwrap();
+    assert_eq!(recorder.0, [Event::Commands(vec![0x29])]);
+}
+
+#[test]
+fn u24_is_not_implemented() {
+    assert!(matches!(
+        encode(Phase::Data, DataFormat::U24(&[0x123456]), |_| Ok(())),
+        Err(DisplayError::DataFormatNotImplemented)
+    ));
+}
+
+/// Capture a short driver sequence with a loopback interface
+fn capture(stream: &mut [u8]) -> &[u8] {
+    let mut di = LoopbackInterface::new(stream, &mut []);
+    di.send_data(DataFormat::U8(&[0xaa])).unwrap();
+    di.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    di.send_data(DataFormat::U8(&[0x00, 0x00])).unwrap();
+    di.send_data(DataFormat::U16BE(&mut [0x00ef])).unwrap();
+    di.send_commands(DataFormat::U8(&[0x29])).unwrap();
+    let len = di.written().len();
+    &stream[..len]
+}
+
+#[test]
+fn frames_of_a_capture() {
+    let mut stream = [0; 64];
+    let capture = capture(&mut stream);
+
+    let decoded: Vec<_> = frame::frames(capture).map(Result::unwrap).collect();
+    assert_eq!(
+        decoded,
+        [
+            (Phase::Data, &[0xaa][..]),
+            (Phase::Command, &[0x2a]),
+            (Phase::Data, &[0x00, 0x00]),
+            (Phase::Data, &[0x00, 0xef]),
+            (Phase::Command, &[0x29]),
+        ]
+    );
+}
+
+#[test]
+fn groups_of_a_capture() {
+    let mut stream = [0; 64];
+    let capture = capture(&mut stream);
+
+    let decoded: Vec<_> = groups(capture)
+        .map(Result::unwrap)
+        .map(|group| (group.command(), group.data_bytes().collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        decoded,
+        [
+            (&[][..], vec![0xaa]),
+            (&[0x2a][..], vec![0x00, 0x00, 0x00, 0xef]),
+            (&[0x29][..], vec![]),
+        ]
+    );
+
+    let group = groups(capture).nth(1).unwrap().unwrap();
+    assert_eq!(group.data().count(), 2);
+}
+
+#[test]
+fn truncated_captures() {
+    let mut stream = [0; 64];
+    let capture = capture(&mut stream);
+
+    // Cut short within the payload of the second data frame of 0x2a
+    let cut = &capture[..capture.len() - 6];
+    let mut decoded = frame::frames(cut);
+    assert_eq!(decoded.by_ref().filter(Result::is_ok).count(), 3);
+    assert_eq!(decoded.remainder(), &cut[13..]);
+    assert!(decoded.next().is_none());
+
+    let mut decoded = groups(cut);
+    assert_eq!(decoded.next().unwrap().unwrap().command(), []);
+    let group = decoded.next().unwrap().unwrap();
+    assert_eq!(group.command(), [0x2a]);
+    assert_eq!(group.data_bytes().collect::<Vec<_>>(), [0x00, 0x00]);
+    assert!(matches!(
+        decoded.next(),
+        Some(Err(FrameError::Truncated { offset: 13 }))
+    ));
+    assert!(decoded.next().is_none());
+
+    // Cut short within a header
+    assert!(matches!(
+        frame::frames(&[0x00, 0x00]).next(),
+        Some(Err(FrameError::Truncated { offset: 0 }))
+    ));
+    assert!(matches!(
+        groups(&[0x07, 0x00, 0x00]).next(),
+        Some(Err(FrameError::UnknownPhase(0x07)))
+    ));
+    assert!(frame::frames(&[]).next().is_none());
+}
+
+/// A stored capture with a pause between a command and its data
+fn stored() -> Vec<u8> {
+    let mut capture = frame::MAGIC.to_vec();
+    capture.push(frame::VERSION);
+    let mut write = |frame: &[u8]| {
+        capture.extend_from_slice(frame);
+        Ok(())
+    };
+    encode(Phase::Command, DataFormat::U8(&[0xb1]), &mut write).unwrap();
+    frame::encode_delay(500, &mut write).unwrap();
+    encode(Phase::Data, DataFormat::U8(&[0x01, 0x2c]), &mut write).unwrap();
+    frame::encode_delay(120_000, &mut write).unwrap();
+    encode(Phase::Command, DataFormat::U8(&[0x29]), &mut write).unwrap();
+    capture
+}
+
+#[test]
+fn stored_captures_skip_the_header() {
+    let capture = stored();
+    assert_eq!(
+        capture[frame::CAPTURE_HEADER_SIZE..][..7],
+        [0x00, 0x00, 0x01, 0xb1, 0x02, 0x00, 0x04]
+    );
+
+    let decoded: Vec<_> = frame::frames(&capture).map(Result::unwrap).collect();
+    assert_eq!(
+        decoded,
+        [
+            (Phase::Command, &[0xb1][..]),
+            (Phase::Delay, &[0x00, 0x00, 0x01, 0xf4]),
+            (Phase::Data, &[0x01, 0x2c]),
+            (Phase::Delay, &[0x00, 0x01, 0xd4, 0xc0]),
+            (Phase::Command, &[0x29]),
+        ]
+    );
+    assert_eq!(frame::delay_ns(decoded[1].1), Some(500));
+
+    // The pause doesn't separate the command from its data
+    let decoded: Vec<_> = groups(&capture)
+        .map(Result::unwrap)
+        .map(|group| (group.command(), group.data_bytes().collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        decoded,
+        [(&[0xb1][..], vec![0x01, 0x2c]), (&[0x29][..], vec![])]
+    );
+}
+
+#[test]
+fn stored_captures_of_unknown_versions() {
+    let mut capture = stored();
+    capture[frame::MAGIC.len()] = 2;
+    let mut decoded = groups(&capture);
+    assert!(matches!(
+        decoded.next(),
+        Some(Err(FrameError::UnsupportedVersion(2)))
+    ));
+    assert!(decoded.next().is_none());
+
+    assert!(matches!(
+        frame::frames(b"DIRC").next(),
+        Some(Err(FrameError::Truncated { offset: 0 }))
+    ));
+    assert!(frame::frames(b"DIRC\x01").next().is_none());
+}
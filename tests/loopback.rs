@@ -0,0 +1,137 @@
+//! Driver style write/read round trips on the loopback interface
+
+use display_interface::{
+    frame::{FrameDecoder, Phase},
+    loopback::LoopbackInterface,
+    DataFormat, DisplayError, ReadData, WriteOnlyDataCommand,
+};
+
+/// Read the manufacturer, version and ID bytes like a driver would
+fn read_id(di: &mut (impl WriteOnlyDataCommand + ReadData)) -> Result<(u8, u8, u8), DisplayError> {
+    di.send_commands(DataFormat::U8(&[0x04]))?;
+    let mut id = [0; 3];
+    di.read_data(&mut id)?;
+    Ok((id[0], id[1], id[2]))
+}
+
+#[test]
+fn reads_follow_the_last_command() {
+    let mut stream = [0; 64];
+    let mut responses = [0; 32];
+    let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+    di.expect_read(0x0a, &[0x9c])
+        .expect_read(0x04, &[0x85, 0x85, 0x52]);
+
+    assert_eq!(read_id(&mut di).unwrap(), (0x85, 0x85, 0x52));
+
+    let mut status = [0; 1];
+    di.send_commands(DataFormat::U8(&[0x0a])).unwrap();
+    di.read_data(&mut status).unwrap();
+    assert_eq!(status, [0x9c]);
+    assert!(!di.has_pending_reads());
+}
+
+#[test]
+fn parameters_in_the_same_call_dont_select_the_response() {
+    let mut stream = [0; 64];
+    let mut responses = [0; 32];
+    let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+    di.expect_read(0xd3, &[0x00, 0x93, 0x41])
+        .expect_read(0x41, &[0xff]);
+
+    // Command with a parameter, e.g. selecting the register bank to read from
+    di.send_commands(DataFormat::U8(&[0xd3, 0x41])).unwrap();
+    assert_eq!(di.last_command(), Some(0xd3));
+
+    let mut id = [0; 3];
+    di.read_data(&mut id).unwrap();
+    assert_eq!(id, [0x00, 0x93, 0x41]);
+}
+
+#[test]
+fn responses_are_queued_per_command() {
+    let mut stream = [0; 64];
+    let mut responses = [0; 32];
+    let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+    di.expect_read(0x0a, &[0x01, 0x02])
+        .expect_read(0x04, &[0x85, 0x85, 0x52])
+        .expect_read(0x0a, &[0x03]);
+
+    di.send_commands(DataFormat::U8(&[0x0a])).unwrap();
+    let mut byte = [0; 1];
+    for expected in [0x01, 0x02, 0x03] {
+        di.read_data(&mut byte).unwrap();
+        assert_eq!(byte, [expected]);
+    }
+    assert!(matches!(
+        di.read_data(&mut byte),
+        Err(DisplayError::BusReadError)
+    ));
+
+    assert!(di.has_pending_reads());
+    assert_eq!(read_id(&mut di).unwrap(), (0x85, 0x85, 0x52));
+    assert!(!di.has_pending_reads());
+}
+
+#[test]
+fn short_or_missing_responses_fail() {
+    let mut stream = [0; 64];
+    let mut responses = [0; 32];
+    let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+    di.expect_read(0x04, &[0x85, 0x85]);
+
+    let mut byte = [0; 1];
+    assert!(matches!(
+        di.read_data(&mut byte),
+        Err(DisplayError::BusReadError)
+    ));
+    assert!(matches!(read_id(&mut di), Err(DisplayError::BusReadError)));
+
+    // Nothing was consumed by the failed read
+    let mut id = [0; 2];
+    di.read_data(&mut id).unwrap();
+    assert_eq!(id, [0x85, 0x85]);
+}
+
+#[test]
+fn writes_are_captured_as_frames() {
+    let mut stream = [0; 16];
+    let mut responses = [0; 0];
+    let mut di = LoopbackInterface::new(&mut stream, &mut responses);
+
+    di.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    di.send_data(DataFormat::U16BE(&mut [0x00ef])).unwrap();
+    assert_eq!(di.last_command(), Some(0x2a));
+
+    let mut frames = Vec::new();
+    let mut decoder = FrameDecoder::new();
+    for &byte in di.written() {
+        if let Some((phase, payload)) = decoder.push(byte).unwrap() {
+            frames.push((phase, payload.to_vec()));
+        }
+    }
+    assert_eq!(
+        frames,
+        [
+            (Phase::Command, vec![0x2a]),
+            (Phase::Data, vec![0x00, 0xef])
+        ]
+    );
+
+    // 9 of 16 bytes used, the next frame doesn't fit
+    assert!(matches!(
+        di.send_data(DataFormat::U8(&[0; 5])),
+        Err(DisplayError::BusWriteError)
+    ));
+    di.clear();
+    di.send_data(DataFormat::U8(&[0; 5])).unwrap();
+    assert_eq!(di.written().len(), 8);
+}
+
+#[test]
+#[should_panic(expected = "response buffer too small")]
+fn responses_must_fit() {
+    let mut stream = [0; 0];
+    let mut responses = [0; 4];
+    LoopbackInterface::new(&mut stream, &mut responses).expect_read(0x04, &[0; 2]);
+}
@@ -0,0 +1,142 @@
+#![cfg(feature = "power")]
+//! Power sequencing with GPIO pins
+
+use std::{cell::RefCell, convert::Infallible, rc::Rc};
+
+use display_interface::{
+    power::{GpioPowerRails, PowerControl},
+    DisplayError,
+};
+use embedded_hal::{
+    delay::DelayNs,
+    digital::{ErrorKind, ErrorType, OutputPin},
+};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Pin(&'static str, bool),
+    DelayUs(u32),
+}
+
+type Log = Rc<RefCell<Vec<Event>>>;
+
+/// Pin logging its level changes
+struct Pin {
+    name: &'static str,
+    log: Log,
+}
+
+impl ErrorType for Pin {
+    type Error = Infallible;
+}
+
+impl OutputPin for Pin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        self.log.borrow_mut().push(Event::Pin(self.name, false));
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        self.log.borrow_mut().push(Event::Pin(self.name, true));
+        Ok(())
+    }
+}
+
+/// Pin which can't be switched
+struct BrokenPin;
+
+impl ErrorType for BrokenPin {
+    type Error = ErrorKind;
+}
+
+impl OutputPin for BrokenPin {
+    fn set_low(&mut self) -> Result<(), ErrorKind> {
+        Err(ErrorKind::Other)
+    }
+
+    fn set_high(&mut self) -> Result<(), ErrorKind> {
+        Err(ErrorKind::Other)
+    }
+}
+
+/// Delay logging its duration in microseconds
+struct Delay(Log);
+
+impl DelayNs for Delay {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.borrow_mut().push(Event::DelayUs(ns / 1_000));
+    }
+}
+
+fn pin(name: &'static str, log: &Log) -> Pin {
+    Pin {
+        name,
+        log: log.clone(),
+    }
+}
+
+#[test]
+fn powers_on_in_order_and_off_in_reverse() {
+    let log = Log::default();
+    let mut power = GpioPowerRails::new()
+        .with_step(pin("iovcc", &log), 1_000)
+        .with_step_active_low(pin("vci", &log), 10_000)
+        .with_step(pin("reset", &log), 120_000);
+    let mut delay = Delay(log.clone());
+
+    power.power_on(&mut delay).unwrap();
+    assert_eq!(
+        log.take(),
+        [
+            Event::Pin("iovcc", true),
+            Event::DelayUs(1_000),
+            Event::Pin("vci", false),
+            Event::DelayUs(10_000),
+            Event::Pin("reset", true),
+            Event::DelayUs(120_000),
+        ]
+    );
+
+    power.power_off(&mut delay).unwrap();
+    assert_eq!(
+        log.take(),
+        [
+            Event::Pin("reset", false),
+            Event::DelayUs(120_000),
+            Event::Pin("vci", true),
+            Event::DelayUs(10_000),
+            Event::Pin("iovcc", false),
+            Event::DelayUs(1_000),
+        ]
+    );
+}
+
+#[test]
+fn failing_pin_aborts_sequence() {
+    let log = Log::default();
+    let mut power = GpioPowerRails::new()
+        .with_step(pin("iovcc", &log), 1_000)
+        .with_step(BrokenPin, 10_000)
+        .with_step(pin("reset", &log), 120_000);
+
+    assert!(matches!(
+        power.power_on(&mut Delay(log.clone())),
+        Err(DisplayError::PowerError)
+    ));
+    assert_eq!(
+        log.take(),
+        [Event::Pin("iovcc", true), Event::DelayUs(1_000)]
+    );
+}
+
+/// A driver requiring power control next to its interface
+fn init(power: &mut impl PowerControl, delay: &mut impl DelayNs) -> Result<(), DisplayError> {
+    power.power_on(delay)
+}
+
+#[test]
+fn empty_sequence_does_nothing() {
+    let log = Log::default();
+    init(&mut GpioPowerRails::default(), &mut Delay(log.clone())).unwrap();
+    assert!(log.borrow().is_empty());
+}
@@ -0,0 +1,140 @@
+//! Deferring commands and data to another context
+
+use display_interface::{queue::CommandQueue, DataFormat, DisplayError, WriteOnlyDataCommand};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Commands(String),
+    Data(String),
+}
+
+/// Interface recording the format and words of every call, failing the `fail_at`th call
+#[derive(Default)]
+struct Recorder {
+    events: Vec<Event>,
+    fail_at: Option<usize>,
+}
+
+fn describe(buf: DataFormat<'_>) -> String {
+    match buf {
+        DataFormat::U8(slice) => format!("U8{slice:02x?}"),
+        DataFormat::U16BEIter(iter) => format!("U16BE{:04x?}", iter.collect::<Vec<_>>()),
+        DataFormat::U16LEIter(iter) => format!("U16LE{:04x?}", iter.collect::<Vec<_>>()),
+        DataFormat::U24Iter(iter) => format!("U24{:06x?}", iter.collect::<Vec<_>>()),
+        _ => panic!("unexpected format"),
+    }
+}
+
+impl Recorder {
+    fn record(&mut self, event: Event) -> Result<(), DisplayError> {
+        if self.fail_at == Some(self.events.len()) {
+            self.fail_at = None;
+            return Err(DisplayError::BusWriteError);
+        }
+        self.events.push(event);
+        Ok(())
+    }
+}
+
+impl WriteOnlyDataCommand for Recorder {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(Event::Commands(describe(cmd)))
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.record(Event::Data(describe(buf)))
+    }
+}
+
+#[test]
+fn calls_are_replayed_in_order() {
+    let mut queue = CommandQueue::<128>::new();
+    queue.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    queue
+        .send_data(DataFormat::U16BE(&mut [0x0000, 0x00ef]))
+        .unwrap();
+    queue
+        .send_commands(DataFormat::U8Iter(&mut [0x2c].into_iter()))
+        .unwrap();
+    queue
+        .send_data(DataFormat::U16LEIter(&mut [0xf800].into_iter()))
+        .unwrap();
+    queue.send_data(DataFormat::U24(&[0x12_3456])).unwrap();
+    assert!(!queue.is_empty());
+
+    let mut di = Recorder::default();
+    queue.drain_into(&mut di).unwrap();
+    assert!(queue.is_empty());
+    assert_eq!(
+        di.events,
+        [
+            Event::Commands("U8[2a]".into()),
+            Event::Data("U16BE[0000, 00ef]".into()),
+            Event::Commands("U8[2c]".into()),
+            Event::Data("U16LE[f800]".into()),
+            Event::Data("U24[123456]".into()),
+        ]
+    );
+}
+
+#[test]
+fn native_u16_keeps_the_native_byte_order() {
+    let mut queue = CommandQueue::<16>::new();
+    queue.send_data(DataFormat::U16(&[0x1234])).unwrap();
+
+    let mut di = Recorder::default();
+    queue.drain_into(&mut di).unwrap();
+    let expected = if cfg!(target_endian = "little") {
+        "U16LE[1234]"
+    } else {
+        "U16BE[1234]"
+    };
+    assert_eq!(di.events, [Event::Data(expected.into())]);
+}
+
+#[test]
+fn calls_exceeding_the_capacity_are_rejected() {
+    let mut queue = CommandQueue::<16>::new();
+    queue.send_commands(DataFormat::U8(&[0x2c; 4])).unwrap();
+    assert_eq!(queue.len(), 9);
+
+    assert!(matches!(
+        queue.send_data(DataFormat::U8(&[0; 3])),
+        Err(DisplayError::CapacityExceeded)
+    ));
+    assert!(matches!(
+        queue.send_data(DataFormat::U16BEIter(&mut [0u16; 2].into_iter())),
+        Err(DisplayError::CapacityExceeded)
+    ));
+    assert_eq!(queue.len(), 9);
+
+    queue.send_data(DataFormat::U8(&[0; 2])).unwrap();
+    assert_eq!(queue.len(), 16);
+}
+
+#[test]
+fn failed_drain_keeps_the_rest() {
+    let mut queue = CommandQueue::<64>::new();
+    for cmd in [0x01, 0x02, 0x03] {
+        queue.send_commands(DataFormat::U8(&[cmd])).unwrap();
+    }
+
+    let mut di = Recorder {
+        fail_at: Some(1),
+        ..Default::default()
+    };
+    assert!(matches!(
+        queue.drain_into(&mut di),
+        Err(DisplayError::BusWriteError)
+    ));
+    assert_eq!(queue.len(), 6);
+
+    queue.drain_into(&mut di).unwrap();
+    assert_eq!(
+        di.events,
+        [
+            Event::Commands("U8[01]".into()),
+            Event::Commands("U8[03]".into())
+        ]
+    );
+}
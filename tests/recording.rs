@@ -0,0 +1,261 @@
+#![cfg(feature = "recording")]
+//! Recording, persisting and replaying command streams
+
+use display_interface::{
+    frame::{self, MAX_PAYLOAD},
+    recording::{replay, Entry, Recording, RecordingError, HEADER_SIZE, MAGIC, VERSION},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+use embedded_hal::delay::DelayNs;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Commands(Vec<u8>),
+    Data(Vec<u8>),
+    DelayNs(u32),
+}
+
+/// Interface and delay recording every call in one list
+#[derive(Default)]
+struct Recorder {
+    events: Vec<Event>,
+}
+
+impl WriteOnlyDataCommand for Recorder {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        match cmd {
+            DataFormat::U8(slice) => self.events.push(Event::Commands(slice.to_vec())),
+            _ => panic!("unexpected format"),
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        match buf {
+            DataFormat::U8(slice) => self.events.push(Event::Data(slice.to_vec())),
+            _ => panic!("unexpected format"),
+        }
+        Ok(())
+    }
+}
+
+impl DelayNs for Recorder {
+    fn delay_ns(&mut self, ns: u32) {
+        self.events.push(Event::DelayNs(ns));
+    }
+}
+
+/// A short init sequence as a driver would send it
+fn init(di: &mut (impl WriteOnlyDataCommand + DelayNs)) -> Result<(), DisplayError> {
+    di.send_commands(DataFormat::U8(&[0x01]))?;
+    di.delay_ms(120);
+    di.send_commands(DataFormat::U8(&[0x3a]))?;
+    di.send_data(DataFormat::U16BE(&mut [0x5500]))?;
+    di.send_data(DataFormat::U16LEIter(&mut [0x1234, 0x5678].into_iter()))
+}
+
+#[test]
+fn records_entries() {
+    let mut recording = Recording::<64>::new();
+    assert!(recording.is_empty());
+    init(&mut recording).unwrap();
+
+    assert_eq!(
+        recording.entries().collect::<Vec<_>>(),
+        [
+            Entry::Commands(&[0x01]),
+            Entry::DelayNs(120_000_000),
+            Entry::Commands(&[0x3a]),
+            Entry::Data(&[0x55, 0x00]),
+            Entry::Data(&[0x34, 0x12, 0x78, 0x56]),
+        ]
+    );
+
+    recording.clear();
+    assert!(recording.is_empty());
+}
+
+#[test]
+fn round_trip() {
+    let mut recording = Recording::<64>::new();
+    init(&mut recording).unwrap();
+
+    let mut out = [0; 64];
+    let len = recording.serialize(&mut out).unwrap();
+    assert_eq!(len, recording.serialized_len());
+    assert_eq!(out[..MAGIC.len()], MAGIC);
+    assert_eq!(out[MAGIC.len()], VERSION);
+
+    let loaded = Recording::<64>::deserialize(&out[..len]).unwrap();
+    assert!(loaded.entries().eq(recording.entries()));
+}
+
+#[test]
+fn serialize_needs_room_for_everything() {
+    let mut recording = Recording::<64>::new();
+    init(&mut recording).unwrap();
+
+    let mut out = vec![0; recording.serialized_len() - 1];
+    assert!(matches!(
+        recording.serialize(&mut out),
+        Err(DisplayError::CapacityExceeded)
+    ));
+}
+
+#[test]
+fn replay_honors_delays() {
+    let mut recording = Recording::<64>::new();
+    init(&mut recording).unwrap();
+
+    let mut di = Recorder::default();
+    let mut delay = Recorder::default();
+    replay(&recording, &mut di, &mut delay).unwrap();
+
+    assert_eq!(
+        di.events,
+        [
+            Event::Commands(vec![0x01]),
+            Event::Commands(vec![0x3a]),
+            Event::Data(vec![0x55, 0x00]),
+            Event::Data(vec![0x34, 0x12, 0x78, 0x56]),
+        ]
+    );
+    assert_eq!(delay.events, [Event::DelayNs(120_000_000)]);
+}
+
+#[test]
+fn reads_version_1_captures() {
+    // Captured with the first version of the format, has to stay readable
+    let capture = [
+        b'D', b'I', b'R', b'C', 0x01, // header
+        0x00, 0x00, 0x01, 0x11, // command 0x11
+        0x02, 0x00, 0x04, 0x00, 0x4c, 0x4b, 0x40, // delay of 5 ms
+        0x01, 0x00, 0x02, 0xaa, 0xbb, // data
+    ];
+    let recording = Recording::<32>::deserialize(&capture).unwrap();
+
+    let mut di = Recorder::default();
+    replay(&recording, &mut di, &mut Recorder::default()).unwrap();
+    assert_eq!(
+        di.events,
+        [Event::Commands(vec![0x11]), Event::Data(vec![0xaa, 0xbb])]
+    );
+    assert_eq!(recording.entries().nth(1), Some(Entry::DelayNs(5_000_000)));
+}
+
+#[test]
+fn rejects_foreign_input() {
+    assert_eq!(
+        Recording::<32>::deserialize(b"DIR").err(),
+        Some(RecordingError::BadMagic)
+    );
+    assert_eq!(
+        Recording::<32>::deserialize(b"RIFF\x01").err(),
+        Some(RecordingError::BadMagic)
+    );
+    assert_eq!(
+        Recording::<32>::deserialize(b"DIRC\x02").err(),
+        Some(RecordingError::UnsupportedVersion(2))
+    );
+}
+
+#[test]
+fn rejects_malformed_entries() {
+    // Cut short in the second entry
+    let truncated = b"DIRC\x01\x00\x00\x01\x11\x01\x00\x04\xaa";
+    assert_eq!(
+        Recording::<32>::deserialize(truncated).err(),
+        Some(RecordingError::Malformed { offset: 9 })
+    );
+
+    let unknown_tag = b"DIRC\x01\x07\x00\x00";
+    assert_eq!(
+        Recording::<32>::deserialize(unknown_tag).err(),
+        Some(RecordingError::Malformed {
+            offset: HEADER_SIZE
+        })
+    );
+
+    let short_delay = b"DIRC\x01\x02\x00\x02\x01\x02";
+    assert_eq!(
+        Recording::<32>::deserialize(short_delay).err(),
+        Some(RecordingError::Malformed {
+            offset: HEADER_SIZE
+        })
+    );
+}
+
+#[test]
+fn deserialize_checks_capacity() {
+    let capture = b"DIRC\x01\x01\x00\x04\x01\x02\x03\x04";
+    assert_eq!(
+        Recording::<6>::deserialize(capture).err(),
+        Some(RecordingError::CapacityExceeded)
+    );
+    assert!(Recording::<7>::deserialize(capture).is_ok());
+}
+
+#[test]
+fn overflow_leaves_recording_unchanged() {
+    let mut recording = Recording::<8>::new();
+    recording
+        .send_commands(DataFormat::U8(&[0x01, 0x02]))
+        .unwrap();
+
+    assert!(matches!(
+        recording.send_data(DataFormat::U8Iter(&mut (0..4))),
+        Err(DisplayError::CapacityExceeded)
+    ));
+    assert!(matches!(
+        recording.send_data(DataFormat::U24(&[0])),
+        Err(DisplayError::DataFormatNotImplemented)
+    ));
+    assert_eq!(
+        recording.entries().collect::<Vec<_>>(),
+        [Entry::Commands(&[0x01, 0x02])]
+    );
+}
+
+#[test]
+fn splits_long_calls() {
+    let mut recording = Recording::<1024>::new();
+    recording
+        .send_data(DataFormat::U8Iter(&mut (0..600).map(|i| i as u8)))
+        .unwrap();
+
+    let lengths: Vec<_> = recording
+        .entries()
+        .map(|entry| match entry {
+            Entry::Data(bytes) => bytes.len(),
+            _ => panic!("unexpected entry"),
+        })
+        .collect();
+    assert_eq!(lengths, [MAX_PAYLOAD, MAX_PAYLOAD, 600 - 2 * MAX_PAYLOAD]);
+}
+
+#[test]
+fn serialized_recordings_are_frames() {
+    let mut recording = Recording::<64>::new();
+    init(&mut recording).unwrap();
+    let mut out = [0; 64];
+    let len = recording.serialize(&mut out).unwrap();
+
+    let decoded: Vec<_> = frame::groups(&out[..len])
+        .map(Result::unwrap)
+        .map(|group| (group.command(), group.data_bytes().collect::<Vec<_>>()))
+        .collect();
+    assert_eq!(
+        decoded,
+        [
+            (&[0x01][..], vec![]),
+            (&[0x3a][..], vec![0x55, 0x00, 0x34, 0x12, 0x78, 0x56]),
+        ]
+    );
+
+    let delays: Vec<_> = frame::frames(&out[..len])
+        .map(Result::unwrap)
+        .filter(|&(phase, _)| phase == frame::Phase::Delay)
+        .map(|(_, payload)| frame::delay_ns(payload))
+        .collect();
+    assert_eq!(delays, [Some(120_000_000)]);
+}
@@ -0,0 +1,84 @@
+//! Expanding run-length encoded pixel data
+
+use display_interface::{
+    rle::{encode, encoded_len, RleInterface},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// Interface collecting the pixels of `U16BEIter` data, and the number of calls
+#[derive(Default)]
+struct Collector {
+    pixels: Vec<u16>,
+    calls: usize,
+}
+
+impl WriteOnlyDataCommand for Collector {
+    fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.calls += 1;
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.calls += 1;
+        match buf {
+            DataFormat::U16BEIter(iter) => self.pixels.extend(iter),
+            _ => panic!("unexpected format"),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn runs_are_expanded() {
+    let mut di = RleInterface::new(Collector::default());
+    di.send_rle_data(&[0x00, 0x03, 0xf8, 0x00, 0x00, 0x01, 0x07, 0xe0])
+        .unwrap();
+
+    let di = di.release();
+    assert_eq!(di.pixels, [0xf800, 0xf800, 0xf800, 0x07e0]);
+    assert_eq!(di.calls, 1);
+}
+
+#[test]
+fn long_runs_are_streamed() {
+    let mut di = RleInterface::new(Collector::default());
+    di.send_rle_data(&[0xff, 0xff, 0x12, 0x34, 0x00, 0x02, 0x00, 0x00])
+        .unwrap();
+
+    let di = di.release();
+    assert_eq!(di.pixels.len(), 0xffff + 2);
+    assert!(di.pixels[..0xffff].iter().all(|&p| p == 0x1234));
+    assert_eq!(di.pixels[0xffff..], [0, 0]);
+}
+
+#[test]
+fn encoder_round_trips() {
+    let mut pixels = vec![0xffff; 70000];
+    pixels.extend([1, 2, 2, 3]);
+
+    let mut rle = vec![0; encoded_len(&pixels)];
+    assert_eq!(encode(&pixels, &mut rle), Some(5 * 4));
+    assert_eq!(rle[..8], [0xff, 0xff, 0xff, 0xff, 0x11, 0x71, 0xff, 0xff]);
+    assert_eq!(encode(&pixels, &mut rle[..19]), None);
+
+    let mut di = RleInterface::new(Collector::default());
+    di.send_rle_data(&rle).unwrap();
+    assert_eq!(di.release().pixels, pixels);
+}
+
+#[test]
+fn malformed_input_is_rejected() {
+    let mut di = RleInterface::new(Collector::default());
+
+    // Truncated
+    assert!(matches!(
+        di.send_rle_data(&[0x00, 0x01, 0x12, 0x34, 0x00, 0x01, 0x12]),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    // Zero-length run
+    assert!(matches!(
+        di.send_rle_data(&[0x00, 0x01, 0x12, 0x34, 0x00, 0x00, 0x12, 0x34]),
+        Err(DisplayError::InvalidFormatError)
+    ));
+    assert_eq!(di.release().calls, 0);
+}
@@ -0,0 +1,83 @@
+//! Forwarding frames over TCP
+#![cfg(feature = "std")]
+
+use std::{
+    io::{ErrorKind, Read},
+    net::{Shutdown, TcpListener, TcpStream},
+    thread,
+};
+
+use display_interface::{
+    frame::FrameDecoder, tcp::TcpBridgeInterface, DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+
+/// Interface collecting the bytes sent through it, with a marker for every command
+#[derive(Default)]
+struct Collector(Vec<String>);
+
+impl WriteOnlyDataCommand for Collector {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        if let DataFormat::U8(slice) = cmd {
+            self.0.push(format!("C{slice:02x?}"));
+        }
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        if let DataFormat::U8(slice) = buf {
+            self.0.push(format!("D{slice:02x?}"));
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn frames_are_replayed_by_the_agent() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let agent = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut decoder = FrameDecoder::new();
+        let mut collector = Collector::default();
+        let mut buf = [0; 16];
+        loop {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            decoder.replay(&buf[..n], &mut collector).unwrap();
+        }
+        assert!(decoder.is_idle());
+        collector.0
+    });
+
+    let mut iface = TcpBridgeInterface::new(TcpStream::connect(addr).unwrap());
+    iface.send_commands(DataFormat::U8(&[0x2a])).unwrap();
+    iface
+        .send_data(DataFormat::U16BE(&mut [0x0000, 0x00ef]))
+        .unwrap();
+    iface.send_commands(DataFormat::U8(&[0x2c])).unwrap();
+    drop(iface.release());
+
+    assert_eq!(
+        agent.join().unwrap(),
+        ["C[2a]", "D[00, 00, 00, ef]", "C[2c]"]
+    );
+}
+
+#[test]
+fn stream_errors_are_kept() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    stream.shutdown(Shutdown::Write).unwrap();
+
+    let mut iface = TcpBridgeInterface::new(stream);
+    assert!(iface.take_error().is_none());
+    assert!(matches!(
+        iface.send_commands(DataFormat::U8(&[0x29])),
+        Err(DisplayError::BusWriteError)
+    ));
+    assert_eq!(iface.take_error().unwrap().kind(), ErrorKind::BrokenPipe);
+    assert!(iface.take_error().is_none());
+}
@@ -0,0 +1,92 @@
+//! Interfaces reporting errors in their own type
+
+use display_interface::{
+    rle::RleInterface, DataFormat, DisplayError, TypedCompat, TypedWriteOnlyDataCommand,
+    WriteOnlyDataCommand,
+};
+
+/// Error of a vendor SDK, carrying a code `DisplayError` has no room for
+#[derive(Debug, PartialEq)]
+struct SdkError(u32);
+
+impl From<SdkError> for DisplayError {
+    fn from(_: SdkError) -> Self {
+        DisplayError::BusWriteError
+    }
+}
+
+/// Interface failing data transfers with `code`, if set
+#[derive(Default)]
+struct SdkInterface {
+    code: Option<u32>,
+    commands: Vec<u8>,
+}
+
+impl TypedWriteOnlyDataCommand for SdkInterface {
+    type Error = SdkError;
+
+    fn send_commands_typed(&mut self, cmd: DataFormat<'_>) -> Result<(), SdkError> {
+        match cmd {
+            DataFormat::U8(slice) => self.commands.extend_from_slice(slice),
+            _ => panic!("unexpected format"),
+        }
+        Ok(())
+    }
+
+    fn send_data_typed(&mut self, _: DataFormat<'_>) -> Result<(), SdkError> {
+        self.code.map_or(Ok(()), |code| Err(SdkError(code)))
+    }
+}
+
+/// A driver written against the plain trait
+fn clear(di: &mut impl WriteOnlyDataCommand) -> Result<(), DisplayError> {
+    di.send_commands(DataFormat::U8(&[0x2c]))?;
+    di.send_data(DataFormat::U8(&[0; 4]))
+}
+
+#[test]
+fn native_errors_reach_typed_callers() {
+    let mut di = SdkInterface {
+        code: Some(0x8004_0001),
+        ..Default::default()
+    };
+    assert_eq!(
+        di.send_data_typed(DataFormat::U8(&[0])),
+        Err(SdkError(0x8004_0001))
+    );
+}
+
+#[test]
+fn plain_drivers_get_display_errors() {
+    let mut di = TypedCompat::new(SdkInterface::default());
+    clear(&mut di).unwrap();
+    assert_eq!(di.release().commands, [0x2c]);
+
+    let mut di = TypedCompat::new(SdkInterface {
+        code: Some(7),
+        ..Default::default()
+    });
+    assert!(matches!(clear(&mut di), Err(DisplayError::BusWriteError)));
+    // The wrapper passes the native errors through as well
+    assert_eq!(di.send_data_typed(DataFormat::U8(&[0])), Err(SdkError(7)));
+}
+
+#[test]
+fn drivers_take_borrowed_interfaces() {
+    let mut di = TypedCompat::new(SdkInterface::default());
+    clear(&mut &mut di).unwrap();
+    assert_eq!(di.release().commands, [0x2c]);
+}
+
+#[test]
+fn works_with_wrappers() {
+    let mut di = RleInterface::new(TypedCompat::new(SdkInterface {
+        code: Some(7),
+        ..Default::default()
+    }));
+    assert!(matches!(
+        di.send_rle_data(&[0x00, 0x02, 0xf8, 0x00]),
+        Err(DisplayError::BusWriteError)
+    ));
+    assert_eq!(di.release().release().code, Some(7));
+}
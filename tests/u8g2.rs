@@ -0,0 +1,194 @@
+//! Replaying u8g2 sequences
+#![cfg(feature = "u8g2")]
+
+use display_interface::{
+    u8g2::{replay_sequence, ArgMode, SequenceError},
+    DataFormat, DisplayError, WriteOnlyDataCommand,
+};
+use embedded_hal::delay::DelayNs;
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Commands(Vec<u8>),
+    Data(Vec<u8>),
+    DelayNs(u32),
+}
+
+/// Interface and delay recording everything sent through them
+#[derive(Default)]
+struct Recorder(Vec<Event>);
+
+fn bytes(buf: DataFormat<'_>) -> Vec<u8> {
+    match buf {
+        DataFormat::U8(slice) => slice.to_vec(),
+        _ => panic!("unexpected format"),
+    }
+}
+
+impl WriteOnlyDataCommand for Recorder {
+    fn send_commands(&mut self, cmd: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(Event::Commands(bytes(cmd)));
+        Ok(())
+    }
+
+    fn send_data(&mut self, buf: DataFormat<'_>) -> Result<(), DisplayError> {
+        self.0.push(Event::Data(bytes(buf)));
+        Ok(())
+    }
+}
+
+impl DelayNs for Recorder {
+    fn delay_ns(&mut self, ns: u32) {
+        self.0.push(Event::DelayNs(ns));
+    }
+}
+
+fn replay(seq: &[u8], args: ArgMode) -> Result<Vec<Event>, SequenceError> {
+    let mut di = Recorder::default();
+    let mut delay = Recorder::default();
+    replay_sequence(&mut di, &mut delay, seq, args)?;
+    di.0.extend(delay.0);
+    Ok(di.0)
+}
+
+/// `u8x8_d_ssd1306_128x64_noname_init_seq` from u8g2
+const SSD1306_INIT: &[u8] = &[
+    0x18, // U8X8_START_TRANSFER()
+    0x15, 0xae, // U8X8_C(0x0ae)
+    0x15, 0xd5, 0x16, 0x80, // U8X8_CA(0x0d5, 0x080)
+    0x15, 0xa8, 0x16, 0x3f, // U8X8_CA(0x0a8, 0x03f)
+    0x15, 0xd3, 0x16, 0x00, // U8X8_CA(0x0d3, 0x000)
+    0x15, 0x40, // U8X8_C(0x040)
+    0x15, 0x8d, 0x16, 0x14, // U8X8_CA(0x08d, 0x014)
+    0x15, 0x20, 0x16, 0x00, // U8X8_CA(0x020, 0x000)
+    0x15, 0xa1, // U8X8_C(0x0a1)
+    0x15, 0xc8, // U8X8_C(0x0c8)
+    0x15, 0xda, 0x16, 0x12, // U8X8_CA(0x0da, 0x012)
+    0x15, 0x81, 0x16, 0xcf, // U8X8_CA(0x081, 0x0cf)
+    0x15, 0xd9, 0x16, 0xf1, // U8X8_CA(0x0d9, 0x0f1)
+    0x15, 0xdb, 0x16, 0x40, // U8X8_CA(0x0db, 0x040)
+    0x15, 0x2e, // U8X8_C(0x02e)
+    0x15, 0xa4, // U8X8_C(0x0a4)
+    0x15, 0xa6, // U8X8_C(0x0a6)
+    0x19, // U8X8_END_TRANSFER()
+    0xff, // U8X8_END()
+];
+
+#[test]
+fn ssd1306_init() {
+    assert_eq!(
+        replay(SSD1306_INIT, ArgMode::Command).unwrap(),
+        [Event::Commands(vec![
+            0xae, 0xd5, 0x80, 0xa8, 0x3f, 0xd3, 0x00, 0x40, 0x8d, 0x14, 0x20, 0x00, 0xa1, 0xc8,
+            0xda, 0x12, 0x81, 0xcf, 0xd9, 0xf1, 0xdb, 0x40, 0x2e, 0xa4, 0xa6,
+        ])]
+    );
+}
+
+#[test]
+fn args_as_data_and_delays() {
+    let seq = [
+        0x18, // U8X8_START_TRANSFER()
+        0x15, 0x01, // U8X8_C(0x001)
+        0xfe, 0x96, // U8X8_DLY(150)
+        0x15, 0x3a, 0x16, 0x05, // U8X8_CA(0x03a, 0x005)
+        0x15, 0x2c, 0x17, 0xaa, 0x17, 0x55, // U8X8_C(0x02c), U8X8_D1(0x0aa), U8X8_D1(0x055)
+        0x19, // U8X8_END_TRANSFER()
+        0xff, // U8X8_END()
+    ];
+
+    assert_eq!(
+        replay(&seq, ArgMode::Data).unwrap(),
+        [
+            Event::Commands(vec![0x01]),
+            Event::Commands(vec![0x3a]),
+            Event::Data(vec![0x05]),
+            Event::Commands(vec![0x2c]),
+            Event::Data(vec![0xaa, 0x55]),
+            Event::DelayNs(150_000_000),
+        ]
+    );
+}
+
+#[test]
+fn transfers_are_sent_separately_and_long_runs_split() {
+    let mut seq = vec![0x18];
+    for cmd in 0..40 {
+        seq.extend([0x15, cmd]);
+    }
+    seq.extend([0x19, 0x18, 0x15, 0xaf, 0x19, 0xff, 0x42]);
+
+    assert_eq!(
+        replay(&seq, ArgMode::Command).unwrap(),
+        [
+            Event::Commands((0..32).collect()),
+            Event::Commands((32..40).collect()),
+            Event::Commands(vec![0xaf]),
+        ]
+    );
+}
+
+#[test]
+fn malformed_sequences_send_nothing() {
+    let mut di = Recorder::default();
+    let mut delay = Recorder::default();
+    let mut check = |seq: &[u8]| {
+        let result = replay_sequence(&mut di, &mut delay, seq, ArgMode::Command);
+        assert!(di.0.is_empty());
+        result.unwrap_err()
+    };
+
+    assert!(matches!(
+        check(&[0x18, 0x15, 0xae, 0x42, 0x19, 0xff]),
+        SequenceError::UnknownCode {
+            offset: 3,
+            code: 0x42
+        }
+    ));
+    assert!(matches!(
+        check(&[0x18, 0x15]),
+        SequenceError::MissingOperand { offset: 1 }
+    ));
+    assert!(matches!(
+        check(&[0x18, 0x15, 0xae, 0x18, 0x19, 0xff]),
+        SequenceError::UnbalancedTransfer { offset: 3 }
+    ));
+    assert!(matches!(
+        check(&[0x15, 0xae, 0x19, 0xff]),
+        SequenceError::UnbalancedTransfer { offset: 2 }
+    ));
+    assert!(matches!(
+        check(&[0x18, 0x15, 0xae, 0xff]),
+        SequenceError::Unterminated
+    ));
+    assert!(matches!(
+        check(&[0x18, 0x15, 0xae, 0x19]),
+        SequenceError::Unterminated
+    ));
+    assert!(matches!(check(&[]), SequenceError::Unterminated));
+}
+
+#[test]
+fn interface_errors_are_passed_on() {
+    struct Failing;
+
+    impl WriteOnlyDataCommand for Failing {
+        fn send_commands(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+            Err(DisplayError::BusWriteError)
+        }
+
+        fn send_data(&mut self, _: DataFormat<'_>) -> Result<(), DisplayError> {
+            Ok(())
+        }
+    }
+
+    assert!(matches!(
+        replay_sequence(
+            &mut Failing,
+            &mut Recorder::default(),
+            SSD1306_INIT,
+            ArgMode::Command
+        ),
+        Err(SequenceError::Interface(DisplayError::BusWriteError))
+    ));
+}